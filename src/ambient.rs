@@ -0,0 +1,291 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use rand::prelude::*;
+use std::f32::consts::PI;
+use crate::framebuffer::Framebuffer;
+use crate::renderer::clip_to_screen;
+use crate::scene::TrailParticle;
+use crate::skybox::SkyProjection;
+use crate::uniforms::FrameUniforms;
+
+// Seconds between meteors (uniformly random in this range)
+const SPAWN_INTERVAL: (f32, f32) = (5.0, 20.0);
+// Seconds the head takes to cross its arc, and how long the trail lingers afterwards
+const FLIGHT_TIME: f32 = 0.5;
+const AFTERGLOW_TIME: f32 = 0.35;
+// Arc length travelled by the head, in radians, and the tail length as a fraction of it
+const ARC_LENGTH: (f32, f32) = (0.15, 0.45);
+const TAIL_FRACTION: f32 = 0.4;
+const SEGMENTS: usize = 12;
+const METEOR_COLOR: u32 = 0xFFF6E8;
+
+// Comets crossing the view: frustum widths (2 in NDC) per second, and world units in front
+// of the camera, far enough that most bodies near the camera hide them
+pub const DEFAULT_COMET_RATE: f32 = 4.0; // per minute
+const COMET_SPEED: (f32, f32) = (1.5, 3.0);
+const COMET_DISTANCE: (f32, f32) = (60.0, 150.0);
+// The streak is the head's path over the last COMET_TAIL_TIME seconds, in this many pieces
+const COMET_TAIL_TIME: f32 = 0.12;
+const COMET_TAIL_SEGMENTS: usize = 8;
+const COMET_FADE_IN: f32 = 0.08;
+const COMET_COLOR: u32 = 0xFFD8ECFF;
+// Keeps the comet stream apart from the meteors' while sharing their seed
+const COMET_SEED_SALT: u64 = 0xC0_3E75;
+
+struct Meteor {
+    start: Vec3,   // unit direction where the head starts
+    tangent: Vec3, // unit direction of travel at `start`, perpendicular to it
+    arc: f32,
+    brightness: f32,
+    age: f32,
+}
+
+impl Meteor {
+    // Point on the meteor's great circle, `angle` radians from the start
+    fn point(&self, angle: f32) -> Vec3 {
+        self.start * angle.cos() + self.tangent * angle.sin()
+    }
+}
+
+// A fast streak across the view. It lives in normalized device coordinates, so it
+// crosses the frustum whatever the camera does, and is placed in the world only to draw it
+struct Comet {
+    start: Vec2,    // NDC, on an edge of the frustum
+    velocity: Vec2, // NDC per second, into the frustum
+    distance: f32,  // world units from the camera
+    brightness: f32,
+    age: f32,
+    exit_time: f32, // seconds until the head leaves the frustum
+}
+
+impl Comet {
+    fn head(&self, age: f32) -> Vec2 {
+        self.start + self.velocity * age
+    }
+
+    // Overall opacity: its brightness, after a short fade-in
+    fn strength(&self) -> f32 {
+        self.brightness * (self.age / COMET_FADE_IN).min(1.0)
+    }
+}
+
+// Occasional shooting stars drawn on the sky sphere, and comets crossing the view. Each
+// uses its own seeded RNG and only simulated time, so the same seed and frame times give
+// the same meteors and comets, and nothing spawns while the simulation is paused
+pub struct Meteors {
+    rng: StdRng,
+    next_spawn: f32,
+    active: Vec<Meteor>,
+    pub comet_rate: f32, // comets per minute on average; 0 turns them off
+    comet_rng: StdRng,
+    next_comet: f32, // seconds of simulated time until the next comet
+    comets: Vec<Comet>,
+}
+
+impl Meteors {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let next_spawn = rng.gen_range(SPAWN_INTERVAL.0..SPAWN_INTERVAL.1);
+        let mut comet_rng = StdRng::seed_from_u64(seed ^ COMET_SEED_SALT);
+        // Exponential waits make a steady random stream; drawn in full even while the rate
+        // is 0, so a scene that turns comets on later still gets the seed's sequence
+        let next_comet = -(1.0 - comet_rng.gen::<f32>()).ln();
+        Meteors {
+            rng,
+            next_spawn,
+            active: Vec::new(),
+            comet_rate: DEFAULT_COMET_RATE,
+            comet_rng,
+            next_comet,
+            comets: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return; // paused
+        }
+        self.update_comets(dt);
+
+        for meteor in &mut self.active {
+            meteor.age += dt;
+        }
+        self.active.retain(|m| m.age < FLIGHT_TIME + AFTERGLOW_TIME);
+
+        self.next_spawn -= dt;
+        if self.next_spawn <= 0.0 {
+            self.spawn();
+            self.next_spawn = self.rng.gen_range(SPAWN_INTERVAL.0..SPAWN_INTERVAL.1);
+        }
+    }
+
+    // `next_comet` counts in units of the mean wait, so a new rate applies at once
+    fn update_comets(&mut self, dt: f32) {
+        for comet in &mut self.comets {
+            comet.age += dt;
+        }
+        self.comets.retain(|comet| comet.age < comet.exit_time + COMET_TAIL_TIME);
+
+        if self.comet_rate <= 0.0 {
+            return;
+        }
+        self.next_comet -= dt * self.comet_rate / 60.0;
+        while self.next_comet <= 0.0 {
+            self.spawn_comet();
+            self.next_comet += -(1.0 - self.comet_rng.gen::<f32>()).ln();
+        }
+    }
+
+    fn spawn_comet(&mut self) {
+        let rng = &mut self.comet_rng;
+        // A point on one of the four edges, heading for a point in the middle of the view
+        let along = rng.gen_range(-1.0..1.0);
+        let start = match rng.gen_range(0..4) {
+            0 => Vec2::new(-1.0, along),
+            1 => Vec2::new(1.0, along),
+            2 => Vec2::new(along, -1.0),
+            _ => Vec2::new(along, 1.0),
+        };
+        let target = Vec2::new(rng.gen_range(-0.6..0.6), rng.gen_range(-0.6..0.6));
+        let velocity = (target - start).normalize() * rng.gen_range(COMET_SPEED.0..COMET_SPEED.1);
+
+        // Time to reach the opposite side, from whichever edge the path meets first
+        let exit = |p: f32, v: f32| if v > 0.0 { (1.0 - p) / v } else if v < 0.0 { (-1.0 - p) / v } else { f32::INFINITY };
+        let exit_time = exit(start.x, velocity.x).min(exit(start.y, velocity.y));
+
+        self.comets.push(Comet {
+            start,
+            velocity,
+            distance: rng.gen_range(COMET_DISTANCE.0..COMET_DISTANCE.1),
+            brightness: rng.gen_range(0.5..1.0),
+            age: 0.0,
+            exit_time,
+        });
+    }
+
+    fn spawn(&mut self) {
+        let theta = self.rng.gen::<f32>() * 2.0 * PI;
+        let phi = (1.0 - 2.0 * self.rng.gen::<f32>()).acos();
+        let start = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+
+        // Random direction of travel in the plane tangent to the sphere at `start`
+        let helper = if start.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let u = start.cross(&helper).normalize();
+        let v = start.cross(&u);
+        let heading = self.rng.gen::<f32>() * 2.0 * PI;
+
+        self.active.push(Meteor {
+            start,
+            tangent: u * heading.cos() + v * heading.sin(),
+            arc: self.rng.gen_range(ARC_LENGTH.0..ARC_LENGTH.1),
+            brightness: self.rng.gen_range(0.6..1.0),
+            age: 0.0,
+        });
+    }
+
+    // Draw active meteors; returns immediately when there are none
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        if self.active.is_empty() {
+            return;
+        }
+        let sky = SkyProjection::new(uniforms);
+
+        for meteor in &self.active {
+            let progress = (meteor.age / FLIGHT_TIME).min(1.0);
+            let head = meteor.arc * progress;
+            let tail = (head - meteor.arc * TAIL_FRACTION).max(0.0);
+            // Fades in quickly, then the whole streak fades out during the afterglow
+            let fade = if meteor.age < FLIGHT_TIME {
+                (meteor.age / (FLIGHT_TIME * 0.15)).min(1.0)
+            } else {
+                0.5 * (1.0 - (meteor.age - FLIGHT_TIME) / AFTERGLOW_TIME)
+            };
+
+            // Brightest at the head, fading to nothing at the tail
+            for i in 0..SEGMENTS {
+                let t0 = i as f32 / SEGMENTS as f32;
+                let t1 = (i + 1) as f32 / SEGMENTS as f32;
+                let a = meteor.point(tail + (head - tail) * t0);
+                let b = meteor.point(tail + (head - tail) * t1);
+                let alpha = meteor.brightness * fade * t1 * t1;
+                sky.line(framebuffer, a, b, METEOR_COLOR, alpha);
+            }
+        }
+    }
+
+    // Points along a comet's streak, head first, placed `distance` from the camera through
+    // their NDC position. `lifetime` is how much longer each point stays in the streak
+    fn comet_streak(comet: &Comet, uniforms: &FrameUniforms) -> Vec<TrailParticle> {
+        let Some(inverse) = (uniforms.projection_matrix * uniforms.view_matrix).try_inverse() else {
+            return Vec::new();
+        };
+        let eye = uniforms.camera_position;
+        (0..=COMET_TAIL_SEGMENTS)
+            .map(|i| i as f32 / COMET_TAIL_SEGMENTS as f32 * COMET_TAIL_TIME)
+            .take_while(|&delay| delay <= comet.age)
+            .map(|delay| {
+                let ndc = comet.head(comet.age - delay);
+                let far = inverse * Vec4::new(ndc.x, ndc.y, 1.0, 1.0);
+                let direction = (Vec3::new(far.x, far.y, far.z) / far.w - eye).normalize();
+                TrailParticle {
+                    position: eye + direction * comet.distance,
+                    color: COMET_COLOR,
+                    lifetime: COMET_TAIL_TIME - delay,
+                    size: 1.0,
+                }
+            })
+            .collect()
+    }
+
+    // Draw the comets as fading anti-aliased streaks. Call after the opaque geometry: the
+    // streaks are depth-tested against it but don't write depth
+    pub fn render_comets(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let to_screen = |particle: &TrailParticle| {
+            let p = particle.position;
+            clip_to_screen(uniforms, view_projection * Vec4::new(p.x, p.y, p.z, 1.0))
+        };
+        for comet in &self.comets {
+            let streak = Self::comet_streak(comet, uniforms);
+            for pair in streak.windows(2) {
+                // Brightest at the head, gone at the end of the tail
+                let alpha = comet.strength() * (pair[1].lifetime / COMET_TAIL_TIME).powi(2);
+                framebuffer.line_aa(to_screen(&pair[0]), to_screen(&pair[1]), pair[0].color & 0x00FFFFFF, alpha);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comet_starts(meteors: &Meteors) -> Vec<(f32, f32)> {
+        meteors.comets.iter().map(|comet| (comet.start.x, comet.start.y)).collect()
+    }
+
+    #[test]
+    fn comets_follow_the_seed() {
+        let mut a = Meteors::new(7);
+        let mut b = Meteors::new(7);
+        a.comet_rate = 600.0;
+        b.comet_rate = 600.0;
+        for _ in 0..60 {
+            a.update(1.0 / 30.0);
+            b.update(1.0 / 30.0);
+        }
+        assert!(!a.comets.is_empty());
+        assert_eq!(comet_starts(&a), comet_starts(&b));
+    }
+
+    #[test]
+    fn no_comets_spawn_while_paused() {
+        let mut meteors = Meteors::new(7);
+        meteors.comet_rate = 600.0;
+        let next_comet = meteors.next_comet;
+        for _ in 0..600 {
+            meteors.update(0.0);
+        }
+        assert!(meteors.comets.is_empty());
+        assert_eq!(meteors.next_comet, next_comet);
+    }
+}
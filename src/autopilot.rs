@@ -0,0 +1,253 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::info_panel::{format_distance, format_duration};
+use crate::scene::Scene;
+use crate::sprite::project;
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+use crate::uniforms::FrameUniforms;
+
+// The PD controller: natural frequency of the approach in radians per second, critically
+// damped, so it settles without circling the stop point
+const RESPONSE: f32 = 1.2;
+// Limits of the ship's engines: acceleration in world units per second squared, and speed
+// relative to the target in world units per second
+pub const MAX_THRUST: f32 = 15.0;
+pub const MAX_SPEED: f32 = 25.0;
+// Fraction of MAX_THRUST the approach counts on to brake, leaving the rest to correct with
+const BRAKING: f32 = 0.5;
+// How fast the nose swings to a new heading, per second
+const TURN_RATE: f32 = 3.0;
+// The target is led by the time left to reach it, but never by more than this many seconds
+const MAX_LEAD: f32 = 10.0;
+// Seconds over which the stop point's measured acceleration is smoothed: it's measured
+// from frame to frame and jumps where the drawn positions cross a simulation step, but
+// the moon's turns around the earth in a couple of seconds, so it can't lag much
+const ACCELERATION_SMOOTHING: f32 = 0.05;
+// Close enough and slow enough relative to the stop point to cut the engines
+pub const ARRIVAL_DISTANCE: f32 = 0.05;
+pub const ARRIVAL_SPEED: f32 = 0.2;
+// Below this closing speed there's no ETA to show
+const MIN_CLOSING_SPEED: f32 = 0.1;
+// The flown path: a point every PATH_INTERVAL seconds, at most PATH_POINTS of them
+const PATH_INTERVAL: f32 = 0.1;
+const PATH_POINTS: usize = 1200;
+const PATH_COLOR: u32 = 0x70FFA0; // the selection reticle's
+const PATH_ALPHA: f32 = 0.6;
+const STATUS_COLOR: u32 = 0x70FFA0;
+// Pixels from the top of the screen, under the help hint
+const STATUS_TOP: usize = 10 + 2 * LINE_ADVANCE;
+
+// What happened to a flight on this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutopilotEvent {
+    // At the stop point with the target's velocity: the engines are off and the camera
+    // can follow the body from there
+    Arrived(usize),
+    // Something else moved the camera (a warp, following a body) and the flight ended
+    Interrupted,
+}
+
+struct Flight {
+    target: usize,
+    offset: Vec3,              // from the body to the stop point, fixed when engaged
+    last_stop: Vec3,           // the stop point on the previous frame, to measure how it moves
+    target_velocity: Vec3,     // the stop point's, measured on the last update
+    target_acceleration: Vec3, // the stop point's, smoothed
+    eye: Vec3,                 // where the flight left the camera, to notice someone else moving it
+}
+
+// Flies the camera to a point `standoff` units from a body, on the side it started from:
+// a PD controller on the ship's velocity steers towards where the stop point will be by
+// the time the ship gets there (led by the body's motion), within MAX_THRUST and
+// MAX_SPEED, and brakes in time to reach it with the body's velocity. The way it flew is
+// kept as a path until the next flight, to see how the controller behaved
+pub struct Autopilot {
+    flight: Option<Flight>,
+    pub velocity: Vec3, // the ship's, in world units per second
+    path: Vec<Vec3>,
+    path_clock: f32, // seconds since the last path point
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Autopilot {
+    pub fn new() -> Self {
+        Autopilot { flight: None, velocity: Vec3::zeros(), path: Vec::new(), path_clock: 0.0 }
+    }
+
+    // The body being flown to, while a flight is on
+    pub fn target(&self) -> Option<usize> {
+        self.flight.as_ref().map(|flight| flight.target)
+    }
+
+    // Start flying to body `target` from the camera at `eye`, at rest, to stop `standoff`
+    // units from its center on this side of it
+    pub fn engage(&mut self, scene: &Scene, target: usize, eye: Vec3, standoff: f32) {
+        let body = scene.bodies[target].position;
+        let offset = (eye - body).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0)) * standoff;
+        self.flight = Some(Flight {
+            target,
+            offset,
+            last_stop: body + offset,
+            target_velocity: scene.velocity(target),
+            target_acceleration: Vec3::zeros(),
+            eye,
+        });
+        self.velocity = Vec3::zeros();
+        self.path = vec![eye];
+        self.path_clock = 0.0;
+    }
+
+    // Stop flying where the ship is; true if it was flying. The path stays
+    pub fn cancel(&mut self) -> bool {
+        self.velocity = Vec3::zeros();
+        self.flight.take().is_some()
+    }
+
+    // The point the flight ends at, on the current frame
+    fn stop_point(scene: &Scene, flight: &Flight) -> Vec3 {
+        scene.bodies[flight.target].position + flight.offset
+    }
+
+    // Distance left to the stop point from `eye`
+    pub fn distance(&self, scene: &Scene, eye: Vec3) -> Option<f32> {
+        let flight = self.flight.as_ref()?;
+        Some((Self::stop_point(scene, flight) - eye).magnitude())
+    }
+
+    // Seconds to the stop point at the speed the ship is closing in on it now; None when
+    // it isn't closing in (yet)
+    pub fn eta(&self, scene: &Scene, eye: Vec3) -> Option<f32> {
+        let flight = self.flight.as_ref()?;
+        let to_stop = Self::stop_point(scene, flight) - eye;
+        let distance = to_stop.magnitude();
+        if distance <= ARRIVAL_DISTANCE {
+            return Some(0.0);
+        }
+        let closing = (self.velocity - flight.target_velocity).dot(&(to_stop / distance));
+        (closing > MIN_CLOSING_SPEED).then(|| distance / closing)
+    }
+
+    // Fly the camera for `dt` seconds. Call it once a frame, after the scene moved and
+    // before anything else moves the camera; not in the bird's eye view
+    pub fn update(&mut self, scene: &Scene, camera: &mut Camera, dt: f32) -> Option<AutopilotEvent> {
+        let flight = self.flight.as_mut()?;
+        if flight.target >= scene.bodies.len() || camera.follow.is_some() || camera.eye != flight.eye {
+            self.cancel();
+            return Some(AutopilotEvent::Interrupted);
+        }
+        if dt <= 0.0 {
+            return None;
+        }
+
+        // The stop point's own velocity and acceleration: the body's orbital motion, as
+        // drawn this frame. Without the acceleration the ship would trail a body on a tight
+        // orbit by a little and never quite get there
+        let stop = Self::stop_point(scene, flight);
+        let target_velocity = (stop - flight.last_stop) / dt;
+        let acceleration = (target_velocity - flight.target_velocity) / dt;
+        let smoothing = 1.0 - (-dt / ACCELERATION_SMOOTHING).exp();
+        flight.target_acceleration += (acceleration - flight.target_acceleration) * smoothing;
+        flight.last_stop = stop;
+        flight.target_velocity = target_velocity;
+
+        // Lead the stop point by the time it would take to get there at the speed allowed,
+        // so the ship heads for where it will be instead of chasing where it is
+        let distance = (stop - camera.eye).magnitude();
+        let lead = (distance / MAX_SPEED).min(MAX_LEAD);
+        let aim = stop + target_velocity * lead;
+        let error = aim - camera.eye;
+
+        // PD: acceleration = kp * error - kd * (velocity relative to the target), plus the
+        // target's own acceleration. Written as a desired relative velocity (kp / kd *
+        // error), capped at MAX_SPEED and at the speed it can still brake from over the
+        // distance left, so long trips cruise and brake instead of overshooting
+        let (kp, kd) = (RESPONSE * RESPONSE, 2.0 * RESPONSE);
+        let braking_speed = (2.0 * MAX_THRUST * BRAKING * error.magnitude()).sqrt();
+        let desired = cap(error * (kp / kd), MAX_SPEED.min(braking_speed));
+        let thrust = cap((target_velocity + desired - self.velocity) * kd + flight.target_acceleration, MAX_THRUST);
+        self.velocity += thrust * dt;
+        let eye = camera.eye + self.velocity * dt;
+
+        // Nose towards where it's going, and towards the body once it's nearly there
+        let body = scene.bodies[flight.target].position;
+        let heading = if distance > flight.offset.magnitude() { aim - eye } else { body - eye };
+        let forward = match heading.try_normalize(f32::EPSILON) {
+            Some(heading) => {
+                let turn = 1.0 - (-TURN_RATE * dt).exp();
+                camera.look_direction().lerp(&heading, turn).try_normalize(f32::EPSILON).unwrap_or(heading)
+            }
+            None => camera.look_direction(),
+        };
+        camera.look_at(eye, eye + forward);
+        flight.eye = eye;
+
+        self.path_clock += dt;
+        if self.path_clock >= PATH_INTERVAL {
+            self.path_clock = 0.0;
+            if self.path.len() >= PATH_POINTS {
+                self.path.remove(0);
+            }
+            self.path.push(eye);
+        }
+
+        let relative_speed = (self.velocity - target_velocity).magnitude();
+        if (stop - eye).magnitude() < ARRIVAL_DISTANCE && relative_speed < ARRIVAL_SPEED {
+            let target = flight.target;
+            self.path.push(eye);
+            self.cancel();
+            return Some(AutopilotEvent::Arrived(target));
+        }
+        None
+    }
+
+    // The points the last flight went through, oldest first
+    pub fn path(&self) -> &[Vec3] {
+        &self.path
+    }
+
+    // Forget the last flight's path (the scene it was flown in is gone)
+    pub fn clear_path(&mut self) {
+        self.path.clear();
+    }
+
+    // The last flight's path as a line in the world, hidden behind the bodies in front of it
+    pub fn draw_path(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        for pair in self.path.windows(2) {
+            if let (Some(a), Some(b)) = (project(uniforms, pair[0]), project(uniforms, pair[1])) {
+                framebuffer.line_aa(a, b, PATH_COLOR, PATH_ALPHA);
+            }
+        }
+    }
+
+    // The target's name, the distance left and the ETA, at the top of the screen while flying
+    pub fn draw_status(&self, framebuffer: &mut Framebuffer, scene: &Scene, eye: Vec3) {
+        let (Some(target), Some(distance)) = (self.target(), self.distance(scene, eye)) else {
+            return;
+        };
+        let eta = self.eta(scene, eye).map_or("-".to_string(), format_duration);
+        let status = format!(
+            "Piloto automático: {}  {}  ETA {}",
+            scene.bodies[target].name,
+            format_distance(distance),
+            eta
+        );
+        let x = (framebuffer.width / 2).saturating_sub(text_width(&status, 1) / 2);
+        draw_text(framebuffer, x, STATUS_TOP, &status, STATUS_COLOR, 1);
+    }
+}
+
+// `vector` shortened to `limit` if it's longer
+fn cap(vector: Vec3, limit: f32) -> Vec3 {
+    let length = vector.magnitude();
+    if length > limit {
+        vector * (limit / length)
+    } else {
+        vector
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::{Duration, Instant};
+use nalgebra_glm::Vec3;
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::profile::FrameProfile;
+use space_travel::renderer::Renderer;
+use space_travel::scene::Scene;
+
+// Scripted camera for --bench: one steady orbit around the sun, outside the outermost
+// planet orbit and above the orbital plane, so nothing ever comes near the camera and
+// every run renders the same sequence of views
+const ORBIT_RADIUS: f32 = 50.0;
+const ORBIT_HEIGHT: f32 = 14.0;
+const ORBIT_PERIOD: f32 = 20.0; // simulated seconds per lap
+
+// Eye and center of the benchmark camera at simulated time `time`
+pub fn camera_pose(time: f32) -> (Vec3, Vec3) {
+    let angle = time / ORBIT_PERIOD * std::f32::consts::TAU;
+    let eye = Vec3::new(ORBIT_RADIUS * angle.cos(), ORBIT_HEIGHT, ORBIT_RADIUS * angle.sin());
+    (eye, Vec3::new(0.0, 0.0, 0.0))
+}
+
+// Views rendered per pass of the --threads comparison: one lap of the benchmark orbit
+const SPEEDUP_FRAMES: usize = 120;
+
+// Average render_scene time with one thread and with --threads bands
+pub struct ThreadSpeedup {
+    pub threads: usize,
+    pub single_ms: f32,
+    pub parallel_ms: f32,
+}
+
+impl ThreadSpeedup {
+    pub fn factor(&self) -> f32 {
+        if self.parallel_ms > 0.0 { self.single_ms / self.parallel_ms } else { 0.0 }
+    }
+}
+
+// Render the same lap of views once on one thread and once split into `threads` bands.
+// The scene isn't advanced, so both passes draw exactly the same work
+pub fn measure_speedup(
+    renderer: &mut Renderer,
+    framebuffer: &mut Framebuffer,
+    scene: &Scene,
+    camera: &mut Camera,
+    threads: usize,
+) -> ThreadSpeedup {
+    let mut pass = |threads: usize| {
+        renderer.threads = threads;
+        let start = Instant::now();
+        for i in 0..SPEEDUP_FRAMES {
+            let (eye, center) = camera_pose(i as f32 / SPEEDUP_FRAMES as f32 * ORBIT_PERIOD);
+            camera.look_at(eye, center);
+            camera.snap_chase();
+            renderer.render_scene(framebuffer, scene, camera);
+        }
+        start.elapsed().as_secs_f32() * 1000.0 / SPEEDUP_FRAMES as f32
+    };
+    let single_ms = pass(1);
+    let parallel_ms = pass(threads);
+    ThreadSpeedup { threads, single_ms, parallel_ms }
+}
+
+// Frame time distribution in milliseconds
+pub struct FrameStats {
+    pub min: f32,
+    pub avg: f32,
+    pub p95: f32,
+    pub max: f32,
+}
+
+impl FrameStats {
+    pub fn from_times(times: &[Duration]) -> Self {
+        let mut ms: Vec<f32> = times.iter().map(|t| t.as_secs_f32() * 1000.0).collect();
+        if ms.is_empty() {
+            return FrameStats { min: 0.0, avg: 0.0, p95: 0.0, max: 0.0 };
+        }
+        ms.sort_by(f32::total_cmp);
+        // Nearest-rank percentile
+        let rank = ((ms.len() as f32 * 0.95).ceil() as usize).clamp(1, ms.len());
+        FrameStats {
+            min: ms[0],
+            avg: ms.iter().sum::<f32>() / ms.len() as f32,
+            p95: ms[rank - 1],
+            max: ms[ms.len() - 1],
+        }
+    }
+}
+
+// Everything a benchmark run reports, collected once at the end
+pub struct BenchReport {
+    frames: usize,
+    width: usize,
+    height: usize,
+    stats: FrameStats,
+    stages: Vec<(&'static str, f32)>,  // ms per frame
+    counters: Vec<(&'static str, f64)>, // per frame
+    threads: usize,
+    speedup: Option<ThreadSpeedup>, // measured when threads > 1
+}
+
+impl BenchReport {
+    pub fn new(frame_times: &[Duration], width: usize, height: usize, threads: usize, profile: &FrameProfile) -> Self {
+        BenchReport {
+            frames: frame_times.len(),
+            width,
+            height,
+            stats: FrameStats::from_times(frame_times),
+            stages: profile.stage_averages(),
+            counters: profile.counter_averages(),
+            threads,
+            speedup: None,
+        }
+    }
+
+    pub fn with_speedup(mut self, speedup: ThreadSpeedup) -> Self {
+        self.speedup = Some(speedup);
+        self
+    }
+
+    pub fn text(&self) -> String {
+        let stats = &self.stats;
+        let mut lines = vec![
+            format!("Benchmark: {} cuadros a {}x{}, {} hilo(s)", self.frames, self.width, self.height, self.threads),
+            format!(
+                "Tiempo por cuadro (ms): min {:.3}  prom {:.3}  p95 {:.3}  max {:.3}",
+                stats.min, stats.avg, stats.p95, stats.max
+            ),
+            "Etapas (ms por cuadro):".to_string(),
+        ];
+        for (name, ms) in &self.stages {
+            lines.push(format!("  {:<10}{:>8.3}", name, ms));
+        }
+        lines.push("Promedio por cuadro:".to_string());
+        for (name, count) in &self.counters {
+            lines.push(format!("  {:<10}{:>10.0}", name, count));
+        }
+        if let Some(speedup) = &self.speedup {
+            lines.push(format!(
+                "Aceleración con {} hilos: {:.3} ms -> {:.3} ms por cuadro ({:.2}x)",
+                speedup.threads, speedup.single_ms, speedup.parallel_ms, speedup.factor()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    // Single line, so scripts can pick it out of the output with `grep '^{'`
+    pub fn json(&self) -> String {
+        let stats = &self.stats;
+        let stages = self.stages.iter()
+            .map(|(name, ms)| format!("\"{}\":{:.4}", name, ms))
+            .collect::<Vec<_>>()
+            .join(",");
+        let counters = self.counters.iter()
+            .map(|(name, count)| format!("\"{}\":{:.1}", name, count))
+            .collect::<Vec<_>>()
+            .join(",");
+        let speedup = self.speedup.as_ref()
+            .map(|speedup| format!(
+                ",\"speedup\":{{\"single_ms\":{:.4},\"parallel_ms\":{:.4},\"factor\":{:.3}}}",
+                speedup.single_ms, speedup.parallel_ms, speedup.factor()
+            ))
+            .unwrap_or_default();
+        format!(
+            "{{\"frames\":{},\"width\":{},\"height\":{},\"threads\":{},\"frame_ms\":{{\"min\":{:.4},\"avg\":{:.4},\"p95\":{:.4},\"max\":{:.4}}},\"stages_ms\":{{{}}},\"per_frame\":{{{}}}{}}}",
+            self.frames, self.width, self.height, self.threads, stats.min, stats.avg, stats.p95, stats.max, stages, counters, speedup
+        )
+    }
+}
@@ -0,0 +1,120 @@
+use std::ops::Range;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::scene::CelestialBody;
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+// Rows shown at once, one per number key (select_1 to select_9); longer scenes scroll
+pub const VISIBLE_ROWS: usize = 9;
+// Seconds the list stays up after it was last used, the last part of which it spends fading out
+const SHOW_TIME: f32 = 4.0;
+const FADE_TIME: f32 = 0.6;
+const TITLE_COLOR: u32 = 0xFFD700;
+const SELECTED_COLOR: u32 = 0x70FFA0; // the selection reticle's
+const TEXT_COLOR: u32 = 0xC0C0C0;
+const HIDDEN_COLOR: u32 = 0x606060;
+// Pixels from the top left corner of the screen
+const MARGIN: usize = 10;
+
+// The scene's bodies as a numbered list in the top left corner, to pick a warp target
+// from: the number keys select the body on their row, Tab / Shift+Tab step through the
+// bodies and Enter warps to the selected one. It shows up when used and fades out after
+// a few seconds; with more than VISIBLE_ROWS bodies it scrolls to keep the selection in view
+pub struct BodyList {
+    scroll: usize, // index of the body on the first row
+    age: f32,      // seconds since it was last used
+}
+
+impl Default for BodyList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BodyList {
+    pub fn new() -> Self {
+        BodyList { scroll: 0, age: SHOW_TIME }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.age < SHOW_TIME
+    }
+
+    // Bring the list up again, scrolled as little as possible to show `selected` among a
+    // scene of `len` bodies
+    pub fn reveal(&mut self, selected: Option<usize>, len: usize) {
+        self.age = 0.0;
+        if let Some(index) = selected {
+            if index < self.scroll {
+                self.scroll = index;
+            } else if index >= self.scroll + VISIBLE_ROWS {
+                self.scroll = index + 1 - VISIBLE_ROWS;
+            }
+        }
+        self.scroll = self.scroll.min(len.saturating_sub(VISIBLE_ROWS));
+    }
+
+    // Indices of the bodies on the rows shown
+    pub fn rows(&self, len: usize) -> Range<usize> {
+        self.scroll.min(len)..(self.scroll + VISIBLE_ROWS).min(len)
+    }
+
+    // The body on row `row` (0 for select_1) of those shown, if there is one
+    pub fn body_at_row(&self, row: usize, len: usize) -> Option<usize> {
+        (row < VISIBLE_ROWS).then(|| self.scroll + row).filter(|&index| index < len)
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.age = (self.age + dt).min(SHOW_TIME);
+    }
+
+    // Number, name and, on the selected row, a marker; hidden bodies are dimmed. Arrows
+    // above and below tell there are more rows to scroll to
+    pub fn draw(&self, framebuffer: &mut Framebuffer, bodies: &[CelestialBody], selected: Option<usize>) {
+        if !self.visible() {
+            return;
+        }
+        let fade = ((SHOW_TIME - self.age) / FADE_TIME).clamp(0.0, 1.0);
+        let faded = |color: u32| (Color::from_hex(color) * fade).to_hex();
+        let rows = self.rows(bodies.len());
+        let title = "Warp (Enter)";
+        let lines: Vec<String> = rows.clone()
+            .enumerate()
+            .map(|(row, index)| format!("{} {} {}", if selected == Some(index) { ">" } else { " " }, row + 1, bodies[index].name))
+            .collect();
+        let width = lines.iter().map(|line| text_width(line, 1)).chain([text_width(title, 1)]).max().unwrap_or(0) + 16;
+        let height = LINE_ADVANCE * (VISIBLE_ROWS.min(bodies.len()) + 3) + 10;
+        framebuffer.blend_rect(MARGIN, MARGIN, width, height, 0x000000, 0.6 * fade);
+
+        let x = MARGIN + 8;
+        draw_text(framebuffer, x, MARGIN + 8, title, faded(TITLE_COLOR), 1);
+        if rows.start > 0 {
+            draw_text(framebuffer, x, MARGIN + 8 + LINE_ADVANCE, "  ...", faded(TEXT_COLOR), 1);
+        }
+        for (row, (index, line)) in rows.clone().zip(&lines).enumerate() {
+            let color = match bodies[index].visibility.body {
+                _ if selected == Some(index) => SELECTED_COLOR,
+                true => TEXT_COLOR,
+                false => HIDDEN_COLOR,
+            };
+            draw_text(framebuffer, x, MARGIN + 8 + LINE_ADVANCE * (row + 2), line, faded(color), 1);
+        }
+        if rows.end < bodies.len() {
+            draw_text(framebuffer, x, MARGIN + 8 + LINE_ADVANCE * (rows.len() + 2), "  ...", faded(TEXT_COLOR), 1);
+        }
+    }
+}
+
+// The selection after `current` for Tab: the next body that isn't hidden, or none after
+// the last one
+pub fn next_selection(bodies: &[CelestialBody], current: Option<usize>) -> Option<usize> {
+    let start = current.map_or(0, |index| index + 1);
+    (start..bodies.len()).find(|&index| bodies[index].visibility.body)
+}
+
+// The selection before `current` for Shift+Tab: the one Tab would have come from. From
+// none it goes to the last body that isn't hidden
+pub fn previous_selection(bodies: &[CelestialBody], current: Option<usize>) -> Option<usize> {
+    let end = current.unwrap_or(bodies.len());
+    (0..end).rev().find(|&index| bodies[index].visibility.body)
+}
@@ -0,0 +1,125 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::framebuffer::{Framebuffer, OVERLAY_DEPTH};
+use crate::renderer::{clip_segment_near, clip_to_screen, is_in_frustum};
+use crate::scene::CelestialBody;
+use crate::uniforms::FrameUniforms;
+
+// Bodies the culling keeps, and the ones it throws away
+const KEPT_COLOR: u32 = 0x40FF40;
+const CULLED_COLOR: u32 = 0xFF4040;
+const FRUSTUM_COLOR: u32 = 0xFFD040;
+const CIRCLE_SEGMENTS: usize = 48;
+
+// Debug overlay for culling and picking. Each visible body gets the outline of its
+// bounding sphere (`CelestialBody::bounding_radius`, what a click has to hit), green if
+// `is_in_frustum` keeps it and red if it culls it. The first press tests against the
+// frame's own view; the second freezes that view and draws its frustum's edges, so moving
+// the camera away shows the volume and what it culls from outside
+pub struct BoundsOverlay {
+    pub enabled: bool,
+    frozen: Option<(Mat4, Mat4)>, // view and projection matrices of the frozen frustum
+}
+
+impl Default for BoundsOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundsOverlay {
+    pub fn new() -> Self {
+        BoundsOverlay { enabled: false, frozen: None }
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    // Off, then live, then frozen on `culling` (the uniforms the scene was culled with),
+    // then off again
+    pub fn cycle(&mut self, culling: &FrameUniforms) {
+        match (self.enabled, self.frozen) {
+            (false, _) => self.enabled = true,
+            (true, None) => self.frozen = Some((culling.view_matrix, culling.projection_matrix)),
+            (true, Some(_)) => *self = BoundsOverlay::new(),
+        }
+    }
+
+    // Draw over `framebuffer`, seen with `uniforms`. `culling` are the uniforms the scene
+    // was drawn with, the ones the live test uses
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, culling: &FrameUniforms, bodies: &[CelestialBody]) {
+        if !self.enabled {
+            return;
+        }
+        let (view, projection) = self.frozen.unwrap_or((culling.view_matrix, culling.projection_matrix));
+        for body in bodies.iter().filter(|body| body.visibility.body) {
+            let color = if is_in_frustum(body, &view, &projection) { KEPT_COLOR } else { CULLED_COLOR };
+            framebuffer.set_current_color(color);
+            draw_outline(framebuffer, uniforms, body.position, body.bounding_radius());
+        }
+        if let Some((view, projection)) = self.frozen {
+            framebuffer.set_current_color(FRUSTUM_COLOR);
+            draw_frustum(framebuffer, uniforms, &view, &projection);
+        }
+    }
+}
+
+// World-space segment, clipped at the near plane of `uniforms`
+fn draw_segment(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, a: Vec3, b: Vec3) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let a = view_projection * Vec4::new(a.x, a.y, a.z, 1.0);
+    let b = view_projection * Vec4::new(b.x, b.y, b.z, 1.0);
+    if let Some((a, b)) = clip_segment_near(a, b) {
+        let (mut a, mut b) = (clip_to_screen(uniforms, a), clip_to_screen(uniforms, b));
+        a.z = OVERLAY_DEPTH;
+        b.z = OVERLAY_DEPTH;
+        framebuffer.line(a, b);
+    }
+}
+
+// The sphere's outline as seen from the eye: the circle where the lines of sight touch it,
+// which faces the camera and projects exactly onto the sphere's silhouette. Nothing from
+// inside the sphere
+fn draw_outline(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, center: Vec3, radius: f32) {
+    let offset = uniforms.camera_position - center;
+    let distance = offset.magnitude();
+    if distance <= radius {
+        return;
+    }
+    let toward_eye = offset / distance;
+    let circle_center = center + toward_eye * (radius * radius / distance);
+    let circle_radius = radius * (1.0 - (radius / distance).powi(2)).sqrt();
+    // Any two axes across the line of sight
+    let helper = if toward_eye.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let u = toward_eye.cross(&helper).normalize();
+    let v = toward_eye.cross(&u);
+
+    let point = |i: usize| {
+        let (sin, cos) = (TAU * i as f32 / CIRCLE_SEGMENTS as f32).sin_cos();
+        circle_center + (u * cos + v * sin) * circle_radius
+    };
+    for i in 0..CIRCLE_SEGMENTS {
+        draw_segment(framebuffer, uniforms, point(i), point(i + 1));
+    }
+}
+
+// The twelve edges of the frustum of `view` and `projection`, near and far rectangles and
+// the four edges joining them
+fn draw_frustum(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, view: &Mat4, projection: &Mat4) {
+    let Some(inverse) = (projection * view).try_inverse() else {
+        return;
+    };
+    let corner = |x: f32, y: f32, z: f32| {
+        let world = inverse * Vec4::new(x, y, z, 1.0);
+        world.xyz() / world.w
+    };
+    let square = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    for (i, &(x, y)) in square.iter().enumerate() {
+        let (next_x, next_y) = square[(i + 1) % square.len()];
+        for z in [-1.0, 1.0] {
+            draw_segment(framebuffer, uniforms, corner(x, y, z), corner(next_x, next_y, z));
+        }
+        draw_segment(framebuffer, uniforms, corner(x, y, -1.0), corner(x, y, 1.0));
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Renderer;
+use crate::scene::Scene;
+use crate::uniforms::FrameUniforms;
+
+// Stops of each shot of a bracket, relative to the current exposure
+pub const BRACKET_STOPS: [f32; 3] = [-1.0, 0.0, 1.0];
+
+// One shot of a bracket: the stops it was taken at and the finished image
+pub struct Shot {
+    pub stops: f32,
+    pub framebuffer: Framebuffer,
+}
+
+// Render the scene as it is now once per BRACKET_STOPS, at the renderer's exposure times
+// 2^stops, into framebuffers like `template` (same size and background), which has to be
+// the size the renderer is set up for. Nothing advances between shots, so they differ only
+// in exposure. `post` runs on each shot right after the scene with the frame's uniforms,
+// for the same post-processing as the live view. The renderer's exposure is left as it was
+pub fn render_bracket(
+    renderer: &mut Renderer,
+    template: &Framebuffer,
+    scene: &Scene,
+    camera: &Camera,
+    mut post: impl FnMut(&mut Framebuffer, &FrameUniforms),
+) -> Vec<Shot> {
+    let exposure = renderer.exposure;
+    let shots = BRACKET_STOPS
+        .iter()
+        .map(|&stops| {
+            let mut framebuffer = template.with_same_background(template.width, template.height);
+            renderer.exposure = exposure * stops.exp2();
+            renderer.render_scene(&mut framebuffer, scene, camera);
+            post(&mut framebuffer, renderer.frame());
+            Shot { stops, framebuffer }
+        })
+        .collect();
+    renderer.exposure = exposure;
+    shots
+}
+
+// Save the shots as out_dir/bracket_NNN_<stops>ev.png, with the first NNN no earlier
+// bracket in `out_dir` used. Returns the files written
+pub fn save_bracket(shots: &[Shot], out_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(out_dir)?;
+    let path = |number: u32, stops: f32| out_dir.join(format!("bracket_{:03}_{:+}ev.png", number, stops));
+    let number = (1..)
+        .find(|&number| BRACKET_STOPS.iter().all(|&stops| !path(number, stops).exists()))
+        .unwrap_or(0);
+
+    let mut written = Vec::with_capacity(shots.len());
+    for shot in shots {
+        let file = path(number, shot.stops);
+        shot.framebuffer.save_png(&file)?;
+        written.push(file);
+    }
+    Ok(written)
+}
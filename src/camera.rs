@@ -1,31 +1,93 @@
 use nalgebra_glm::{Vec3};
 use std::f32::consts::PI;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+  FirstPerson,
+  ThirdPerson,
+}
+
+// Camera riding along with a moving body at a fixed offset
+#[derive(Debug, Clone, Copy)]
+pub struct Follow {
+  pub body: usize,
+  pub distance: f32,
+  direction: Vec3, // unit offset from the body to the eye
+  start_eye: Vec3,
+  start_center: Vec3,
+  blend: f32, // 0..1 progress of the lerp-in
+}
+
+// Seconds the camera takes to glide from its current pose to the follow offset
+const FOLLOW_BLEND_TIME: f32 = 0.8;
+
+// The bird's eye pose: above the orbital plane, looking down at the sun. The top of the
+// screen points along BIRD_EYE_HEADING, so the view never rolls however steep it is
+const BIRD_EYE_EYE: Vec3 = Vec3::new(0.0, 45.0, 45.0);
+const BIRD_EYE_CENTER: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+const BIRD_EYE_HEADING: Vec3 = Vec3::new(0.0, 0.0, -1.0);
+
+// Free-flight pose saved while the bird's eye view is held
+#[derive(Debug, Clone, Copy)]
+struct SavedPose {
+  eye: Vec3,
+  center: Vec3,
+  up: Vec3,
+  yaw: f32,
+  pitch: f32,
+  roll: f32,
+}
+
+// Up vector for a camera looking along `forward` whose screen top should point towards
+// `heading`. The side vector comes first, from forward x heading, and up is side x forward,
+// so it stays well defined looking straight down, where a fixed world up would be parallel
+// to forward. `heading` must not be parallel to `forward`
+pub fn stable_up(forward: Vec3, heading: Vec3) -> Vec3 {
+  let side = forward.cross(&heading).normalize();
+  side.cross(&forward).normalize()
+}
+
 pub struct Camera {
   pub eye: Vec3,
   pub center: Vec3,
   pub up: Vec3,
   pub has_changed: bool,
   pub bird_eye_active: bool,
-  pub previous_state: Option<(Vec3, Vec3, f32, f32, f32)>,
+  saved_pose: Option<SavedPose>,
   pub yaw: f32,
   pub roll: f32,
   pub pitch: f32,
+  pub mode: CameraMode,
+  pub chase_distance: f32,
+  pub chase_height: f32,
+  pub chase_stiffness: f32,
+  chase_eye: Vec3,
+  chase_center: Vec3,
+  pub follow: Option<Follow>,
 }
 
 impl Camera {
   pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-    Camera {
+    let mut camera = Camera {
       eye,
       center,
       up,
       has_changed: true,
       bird_eye_active: false,
-      previous_state: None,
+      saved_pose: None,
       yaw: 0.0,
       roll: 0.0,
       pitch: 0.0,
-    }
+      mode: CameraMode::ThirdPerson,
+      chase_distance: 6.0,
+      chase_height: 1.5,
+      chase_stiffness: 6.0,
+      chase_eye: eye,
+      chase_center: center,
+      follow: None,
+    };
+    camera.snap_chase();
+    camera
   }
 
   pub fn zoom(&mut self, delta: f32) {
@@ -49,14 +111,43 @@ impl Camera {
     self.update_center();
   }
 
-  pub fn set_bird_eye_view(&mut self) {
-    self.eye = Vec3::new(0.0, 1200.0, 800.0);
-    self.center = Vec3::new(0.0, 0.0, 0.0);
-    self.up = Vec3::new(0.0, 1.0, 0.0);
+  // Switch to the bird's eye view, saving the current pose for `exit_bird_eye_view`
+  pub fn enter_bird_eye_view(&mut self) {
+    if self.bird_eye_active {
+      return;
+    }
+    self.saved_pose = Some(SavedPose {
+      eye: self.eye,
+      center: self.center,
+      up: self.up,
+      yaw: self.yaw,
+      pitch: self.pitch,
+      roll: self.roll,
+    });
+    self.eye = BIRD_EYE_EYE;
+    self.center = BIRD_EYE_CENTER;
+    self.up = stable_up((BIRD_EYE_CENTER - BIRD_EYE_EYE).normalize(), BIRD_EYE_HEADING);
     self.bird_eye_active = true;
     self.has_changed = true;
   }
 
+  // Return to the pose the camera had before `enter_bird_eye_view`
+  pub fn exit_bird_eye_view(&mut self) {
+    if !self.bird_eye_active {
+      return;
+    }
+    if let Some(pose) = self.saved_pose.take() {
+      self.eye = pose.eye;
+      self.center = pose.center;
+      self.up = pose.up;
+      self.yaw = pose.yaw;
+      self.pitch = pose.pitch;
+      self.roll = pose.roll;
+    }
+    self.bird_eye_active = false;
+    self.has_changed = true;
+  }
+
   pub fn get_forward(&self) -> Vec3 {
     Vec3::new(
       self.yaw.cos() * self.pitch.cos(),
@@ -64,4 +155,136 @@ impl Camera {
       self.yaw.sin() * self.pitch.cos(),
     ).normalize()
   }
+
+  // Direction the ship (cockpit) is looking at, derived from eye/center
+  pub fn look_direction(&self) -> Vec3 {
+    let direction = self.center - self.eye;
+    if direction.magnitude() > f32::EPSILON {
+      direction.normalize()
+    } else {
+      self.get_forward()
+    }
+  }
+
+  // Right, up and forward axes of the view actually being rendered
+  pub fn get_local_axes(&self) -> (Vec3, Vec3, Vec3) {
+    let forward = (self.view_center() - self.view_eye()).normalize();
+    let right = forward.cross(&self.up).normalize();
+    let up = right.cross(&forward);
+    (right, up, forward)
+  }
+
+  pub fn toggle_mode(&mut self) {
+    self.mode = match self.mode {
+      CameraMode::FirstPerson => CameraMode::ThirdPerson,
+      CameraMode::ThirdPerson => CameraMode::FirstPerson,
+    };
+    // Start the chase rig from its resting pose so the switch doesn't swoop in
+    self.snap_chase();
+    self.has_changed = true;
+  }
+
+  pub fn adjust_chase_distance(&mut self, delta: f32) {
+    self.chase_distance = (self.chase_distance + delta).clamp(1.0, 50.0);
+  }
+
+  // Pose the chase camera wants to reach: behind the ship along its backward axis
+  fn chase_target(&self) -> (Vec3, Vec3) {
+    let forward = self.look_direction();
+    let eye = self.eye - forward * self.chase_distance + self.up * self.chase_height;
+    (eye, self.center)
+  }
+
+  pub fn snap_chase(&mut self) {
+    let (eye, center) = self.chase_target();
+    self.chase_eye = eye;
+    self.chase_center = center;
+  }
+
+  // Damp the chase camera toward its target pose (frame-rate independent)
+  pub fn update_chase(&mut self, dt: f32) {
+    let (target_eye, target_center) = self.chase_target();
+    let t = 1.0 - (-self.chase_stiffness * dt).exp();
+    self.chase_eye += (target_eye - self.chase_eye) * t;
+    self.chase_center += (target_center - self.chase_center) * t;
+  }
+
+  // Eye/center actually used to build the view matrix
+  pub fn view_eye(&self) -> Vec3 {
+    if self.mode == CameraMode::ThirdPerson && !self.bird_eye_active {
+      self.chase_eye
+    } else {
+      self.eye
+    }
+  }
+
+  pub fn view_center(&self) -> Vec3 {
+    if self.mode == CameraMode::ThirdPerson && !self.bird_eye_active {
+      self.chase_center
+    } else {
+      self.center
+    }
+  }
+
+  // Start following a body from the side the camera is currently on
+  pub fn start_follow(&mut self, body: usize, body_position: Vec3, distance: f32) {
+    let direction = (self.eye - body_position)
+      .try_normalize(f32::EPSILON)
+      .unwrap_or_else(|| -self.look_direction());
+    self.follow = Some(Follow {
+      body,
+      distance,
+      direction,
+      start_eye: self.eye,
+      start_center: self.center,
+      blend: 0.0,
+    });
+  }
+
+  // Stop following, keeping the current pose and syncing yaw/pitch with it so the
+  // next rotation doesn't snap back to the pre-follow heading
+  pub fn stop_follow(&mut self) {
+    if self.follow.take().is_some() {
+      self.sync_angles();
+    }
+  }
+
+  // Place the camera at `eye` looking at `center`, keeping yaw/pitch consistent with it
+  pub fn look_at(&mut self, eye: Vec3, center: Vec3) {
+    self.eye = eye;
+    self.center = center;
+    self.sync_angles();
+    self.has_changed = true;
+  }
+
+  // Derive yaw/pitch from eye/center after they were set directly
+  fn sync_angles(&mut self) {
+    let direction = self.look_direction();
+    self.yaw = direction.z.atan2(direction.x);
+    self.pitch = direction.y.clamp(-1.0, 1.0).asin().clamp(-PI/2.0 + 0.1, PI/2.0 - 0.1);
+  }
+
+  pub fn adjust_follow_distance(&mut self, factor: f32) {
+    if let Some(follow) = &mut self.follow {
+      follow.distance = (follow.distance * factor).clamp(0.5, 200.0);
+    }
+  }
+
+  // Keep the eye at the follow offset from the body's live position, easing in
+  // from the pose the camera had when following started
+  pub fn update_follow(&mut self, body_position: Vec3, dt: f32) {
+    if self.bird_eye_active {
+      return;
+    }
+    let Some(follow) = &mut self.follow else {
+      return;
+    };
+
+    follow.blend = (follow.blend + dt / FOLLOW_BLEND_TIME).min(1.0);
+    let t = follow.blend * follow.blend * (3.0 - 2.0 * follow.blend);
+    let target_eye = body_position + follow.direction * follow.distance;
+    self.eye = follow.start_eye.lerp(&target_eye, t);
+    self.center = follow.start_center.lerp(&body_position, t);
+    self.has_changed = true;
+  }
 }
@@ -0,0 +1,384 @@
+use std::fmt;
+use std::path::Path;
+use space_travel::dof;
+use space_travel::resolution::{Upscale, DEFAULT_TARGET_FPS};
+use space_travel::shaders::Shading;
+use space_travel::sprite::DEFAULT_PARTICLE_SOFTNESS;
+
+pub const USAGE: &str = "\
+Uso: SpaceTravel [opciones]
+
+Opciones:
+  --width <px>       Ancho de la ventana y del framebuffer (por defecto 800)
+  --height <px>      Alto de la ventana y del framebuffer (por defecto 600)
+  --fullscreen       Ventana sin bordes en la esquina superior izquierda; requiere
+                     --width y --height con el tamaño del monitor
+  --fps-cap <n>      Límite de cuadros por segundo, 0 = sin límite (por defecto 60)
+  --scene <ruta>     Archivo de escena a cargar
+  --seed <n>         Semilla raíz de la que salen todas las demás (cielo, meteoros,
+                     nebulosa, ruido de los shaders, asteroides y F6)
+  --deterministic    Paso fijo de 1/60 s también con ventana y tiempo de los shaders
+                     contado en cuadros: el cuadro N sale igual en cada ejecución
+  --shading <modo>   Sombreado inicial: phong (por defecto) o gouraud
+  --dof-strength <px> Radio del desenfoque de la profundidad de campo (;) en píxeles,
+                     entre 1 y 64 (por defecto 8)
+  --dof-range <u>    Distancia delante y detrás del cuerpo seleccionado que queda
+                     nítida con la profundidad de campo (por defecto 4)
+  --particle-softness <u> Distancia delante de una superficie en la que las partículas
+                     de las estelas se desvanecen al cruzarla, 0 = borde duro
+                     (por defecto 0.3)
+  --log-depth        Buffer de profundidad logarítmico: la misma precisión relativa a
+                     cualquier distancia, contra el parpadeo de superficies lejanas
+  --threads <n>      Dibujar los cuerpos en n franjas horizontales en paralelo, un hilo
+                     por franja (por defecto 1); la imagen es la misma con cualquier n
+  --dynamic-res      Renderizar la escena a entre 50% y 100% del tamaño de la ventana,
+                     ajustando la escala cada segundo para sostener --target-fps, y
+                     escalarla a la ventana antes de dibujar el HUD
+  --target-fps <n>   Con --dynamic-res: cuadros por segundo a sostener (por defecto 30)
+  --upscale <modo>   Con --dynamic-res: filtro del escalado, bilinear (por defecto) o
+                     nearest
+  --headless         Renderizar sin ventana a un paso fijo de 1/60 s y guardar PNGs;
+                     requiere --frames y --out
+  --bench            Medir el rendimiento: renderizar sin ventana con una cámara en
+                     órbita fija y un paso de 1/60 s, e imprimir estadísticas de
+                     tiempo por cuadro (texto y una línea JSON)
+  --thumbnails <dir> Renderizar sin ventana una miniatura de 256x256 encuadrada en cada
+                     cuerpo (<dir>/<cuerpo>.png) y una hoja con todas (sheet.png)
+  --frames <n>       Con --headless: cantidad de cuadros a simular; con --bench
+                     (por defecto 1000)
+  --out <dir>        Con --headless: carpeta de salida (frame_NNNN.png)
+  --save <lista>     Con --headless: guardar solo estos cuadros, separados por comas
+                     (p. ej. 60,120); por defecto se guardan todos
+  --no-intro         Empezar directamente con el control de la nave, sin el recorrido
+                     inicial de la cámara por el sistema
+  --dump-keys        Imprimir las teclas efectivas (por defecto + keybindings.toml) en
+                     formato keybindings.toml y salir
+  -h, --help         Mostrar esta ayuda";
+
+const MAX_DIMENSION: usize = 16384;
+const MAX_FPS_CAP: u32 = 1000;
+const MAX_THREADS: usize = 256;
+const MAX_HEADLESS_FRAMES: u32 = 100_000;
+const DEFAULT_BENCH_FRAMES: u32 = 1000;
+
+// Frames to render without a window; numbered from 1
+#[derive(Debug, Clone)]
+pub struct Headless {
+    pub frame_count: u32,
+    pub save_frames: Vec<u32>, // sorted, each in 1..=frame_count
+    pub out_dir: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub width: usize,
+    pub height: usize,
+    pub fullscreen: bool,
+    pub fps_cap: u32, // 0 = uncapped
+    pub scene: Option<String>,
+    pub seed: Option<u64>,
+    pub deterministic: bool,
+    pub shading: Shading,
+    pub log_depth: bool,
+    pub dof_strength: f32, // pixels
+    pub dof_range: f32,    // world units
+    pub particle_softness: f32, // world units, 0 = hard edge
+    pub threads: usize,
+    pub dynamic_resolution: bool,
+    pub target_fps: u32,
+    pub upscale: Upscale,
+    pub headless: Option<Headless>,
+    pub bench: Option<u32>, // frames to render in benchmark mode
+    pub thumbnails: Option<String>, // output folder
+    pub intro: bool, // fly-in over the system before handing over control (window only)
+    pub dump_keys: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 800,
+            height: 600,
+            fullscreen: false,
+            fps_cap: 60,
+            scene: None,
+            seed: None,
+            deterministic: false,
+            shading: Shading::Phong,
+            log_depth: false,
+            dof_strength: dof::DEFAULT_STRENGTH,
+            dof_range: dof::DEFAULT_FOCUS_RANGE,
+            particle_softness: DEFAULT_PARTICLE_SOFTNESS,
+            threads: 1,
+            dynamic_resolution: false,
+            target_fps: DEFAULT_TARGET_FPS,
+            upscale: Upscale::Bilinear,
+            headless: None,
+            bench: None,
+            thumbnails: None,
+            intro: true,
+            dump_keys: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Help,
+    Invalid(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Help => write!(f, "{}", USAGE),
+            CliError::Invalid(msg) => write!(f, "error: {}\n\n{}", msg, USAGE),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+fn invalid(msg: impl Into<String>) -> CliError {
+    CliError::Invalid(msg.into())
+}
+
+// Parse the arguments after the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, CliError> {
+    let mut options = Options::default();
+    let mut width = None;
+    let mut height = None;
+    let mut headless = false;
+    let mut bench = false;
+    let mut frames = None;
+    let mut out_dir = None;
+    let mut save = None;
+    let mut target_fps = None;
+    let mut upscale = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| invalid(format!("{} requiere un valor", name)))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "--width" => width = Some(parse_dimension("--width", &value("--width")?)?),
+            "--height" => height = Some(parse_dimension("--height", &value("--height")?)?),
+            "--fullscreen" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--fullscreen no acepta un valor"));
+                }
+                options.fullscreen = true;
+            }
+            "--fps-cap" => {
+                let raw = value("--fps-cap")?;
+                options.fps_cap = raw
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|fps| *fps <= MAX_FPS_CAP)
+                    .ok_or_else(|| invalid(format!("--fps-cap debe ser un entero entre 0 y {}, no '{}'", MAX_FPS_CAP, raw)))?;
+            }
+            "--scene" => {
+                let path = value("--scene")?;
+                if !Path::new(&path).is_file() {
+                    return Err(invalid(format!("no se encontró el archivo de escena '{}'", path)));
+                }
+                options.scene = Some(path);
+            }
+            "--seed" => {
+                let raw = value("--seed")?;
+                options.seed = Some(
+                    raw.parse::<u64>()
+                        .map_err(|_| invalid(format!("--seed debe ser un entero no negativo, no '{}'", raw)))?,
+                );
+            }
+            "--deterministic" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--deterministic no acepta un valor"));
+                }
+                options.deterministic = true;
+            }
+            "--dof-strength" => {
+                let raw = value("--dof-strength")?;
+                options.dof_strength = raw
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|strength| (1.0..=dof::MAX_STRENGTH).contains(strength))
+                    .ok_or_else(|| invalid(format!("--dof-strength debe ser un número entre 1 y {}, no '{}'", dof::MAX_STRENGTH, raw)))?;
+            }
+            "--dof-range" => {
+                let raw = value("--dof-range")?;
+                options.dof_range = raw
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|range| range.is_finite() && *range > 0.0)
+                    .ok_or_else(|| invalid(format!("--dof-range debe ser un número mayor que 0, no '{}'", raw)))?;
+            }
+            "--particle-softness" => {
+                let raw = value("--particle-softness")?;
+                options.particle_softness = raw
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|softness| softness.is_finite() && *softness >= 0.0)
+                    .ok_or_else(|| invalid(format!("--particle-softness debe ser un número mayor o igual que 0, no '{}'", raw)))?;
+            }
+            "--log-depth" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--log-depth no acepta un valor"));
+                }
+                options.log_depth = true;
+            }
+            "--headless" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--headless no acepta un valor"));
+                }
+                headless = true;
+            }
+            "--bench" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--bench no acepta un valor"));
+                }
+                bench = true;
+            }
+            "--shading" => {
+                let raw = value("--shading")?;
+                options.shading = match raw.to_ascii_lowercase().as_str() {
+                    "phong" => Shading::Phong,
+                    "gouraud" => Shading::Gouraud,
+                    _ => return Err(invalid(format!("--shading debe ser phong o gouraud, no '{}'", raw))),
+                };
+            }
+            "--threads" => {
+                let raw = value("--threads")?;
+                options.threads = raw
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|threads| (1..=MAX_THREADS).contains(threads))
+                    .ok_or_else(|| invalid(format!("--threads debe ser un entero entre 1 y {}, no '{}'", MAX_THREADS, raw)))?;
+            }
+            "--dynamic-res" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--dynamic-res no acepta un valor"));
+                }
+                options.dynamic_resolution = true;
+            }
+            "--target-fps" => {
+                let raw = value("--target-fps")?;
+                target_fps = Some(
+                    raw.parse::<u32>()
+                        .ok()
+                        .filter(|fps| (1..=MAX_FPS_CAP).contains(fps))
+                        .ok_or_else(|| invalid(format!("--target-fps debe ser un entero entre 1 y {}, no '{}'", MAX_FPS_CAP, raw)))?,
+                );
+            }
+            "--upscale" => {
+                let raw = value("--upscale")?;
+                upscale = Some(match raw.to_ascii_lowercase().as_str() {
+                    "bilinear" => Upscale::Bilinear,
+                    "nearest" => Upscale::Nearest,
+                    _ => return Err(invalid(format!("--upscale debe ser bilinear o nearest, no '{}'", raw))),
+                });
+            }
+            "--no-intro" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--no-intro no acepta un valor"));
+                }
+                options.intro = false;
+            }
+            "--dump-keys" => {
+                if inline_value.is_some() {
+                    return Err(invalid("--dump-keys no acepta un valor"));
+                }
+                options.dump_keys = true;
+            }
+            "--frames" => {
+                let raw = value("--frames")?;
+                frames = Some(parse_frame_number("--frames", &raw)?);
+            }
+            "--out" => out_dir = Some(value("--out")?),
+            "--thumbnails" => options.thumbnails = Some(value("--thumbnails")?),
+            "--save" => {
+                let raw = value("--save")?;
+                let mut list = raw
+                    .split(',')
+                    .map(|part| parse_frame_number("--save", part.trim()))
+                    .collect::<Result<Vec<u32>, CliError>>()?;
+                list.sort_unstable();
+                list.dedup();
+                save = Some(list);
+            }
+            _ => return Err(invalid(format!("opción desconocida '{}'", arg))),
+        }
+    }
+
+    // minifb can't query the monitor size, so a borderless fullscreen window needs it explicitly
+    if options.fullscreen && (width.is_none() || height.is_none()) {
+        return Err(invalid("--fullscreen requiere --width y --height con la resolución del monitor"));
+    }
+    if bench {
+        if headless || out_dir.is_some() || save.is_some() {
+            return Err(invalid("--bench no se puede usar con --headless, --out ni --save"));
+        }
+        if options.fullscreen {
+            return Err(invalid("--fullscreen no se puede usar con --bench"));
+        }
+        // The frame count belongs to the benchmark, not to a headless run
+        options.bench = Some(frames.take().unwrap_or(DEFAULT_BENCH_FRAMES));
+    }
+    if options.thumbnails.is_some() && (bench || headless || options.fullscreen) {
+        return Err(invalid("--thumbnails no se puede usar con --headless, --bench ni --fullscreen"));
+    }
+    // The scale follows the measured frame time, so it only makes sense with a window and
+    // would make deterministic frames depend on the machine
+    if options.dynamic_resolution && (bench || headless || options.thumbnails.is_some() || options.deterministic) {
+        return Err(invalid("--dynamic-res no se puede usar con --headless, --bench, --thumbnails ni --deterministic"));
+    }
+    if !options.dynamic_resolution && (target_fps.is_some() || upscale.is_some()) {
+        return Err(invalid("--target-fps y --upscale solo se usan con --dynamic-res"));
+    }
+    options.target_fps = target_fps.unwrap_or(options.target_fps);
+    options.upscale = upscale.unwrap_or(options.upscale);
+    options.headless = match (headless, frames, out_dir) {
+        (false, None, None) if save.is_none() => None,
+        (false, _, _) => return Err(invalid("--frames, --out y --save solo se usan con --headless")),
+        (true, Some(frame_count), Some(out_dir)) => {
+            let save_frames = save.unwrap_or_else(|| (1..=frame_count).collect());
+            if let Some(&last) = save_frames.last().filter(|&&last| last > frame_count) {
+                return Err(invalid(format!("--save pide el cuadro {} pero --frames es {}", last, frame_count)));
+            }
+            Some(Headless { frame_count, save_frames, out_dir })
+        }
+        (true, _, _) => return Err(invalid("--headless requiere --frames y --out")),
+    };
+    if options.headless.is_some() && options.fullscreen {
+        return Err(invalid("--fullscreen no se puede usar con --headless"));
+    }
+    options.width = width.unwrap_or(options.width);
+    options.height = height.unwrap_or(options.height);
+
+    Ok(options)
+}
+
+fn parse_dimension(name: &str, raw: &str) -> Result<usize, CliError> {
+    raw.parse::<usize>()
+        .ok()
+        .filter(|v| (1..=MAX_DIMENSION).contains(v))
+        .ok_or_else(|| invalid(format!("{} debe ser un entero entre 1 y {}, no '{}'", name, MAX_DIMENSION, raw)))
+}
+
+fn parse_frame_number(name: &str, raw: &str) -> Result<u32, CliError> {
+    raw.parse::<u32>()
+        .ok()
+        .filter(|n| (1..=MAX_HEADLESS_FRAMES).contains(n))
+        .ok_or_else(|| invalid(format!("{} debe ser un entero entre 1 y {}, no '{}'", name, MAX_HEADLESS_FRAMES, raw)))
+}
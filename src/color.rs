@@ -1,55 +1,188 @@
 use std::fmt;
 
+// 4x4 ordered-dither (Bayer) thresholds, in sixteenths
+const BAYER_4X4: [[u8; 4]; 4] = [
+  [ 0,  8,  2, 10],
+  [12,  4, 14,  6],
+  [ 3, 11,  1,  9],
+  [15,  7, 13,  5],
+];
+
+/// An RGB color. Channels are kept unquantized (0.0..=255.0) until the final pack to u32,
+/// so gradients don't lose precision in every intermediate lerp/multiply.
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
-  r: u8,
-  g: u8,
-  b: u8,
+  r: f32,
+  g: f32,
+  b: f32,
 }
 
 impl Color {
-  // Constructor to initialize the color using r, g, b values as u8
+  /// A color from 8-bit channels.
   pub fn new(r: u8, g: u8, b: u8) -> Self {
-    Color { r, g, b }
+    Color { r: r as f32, g: g as f32, b: b as f32 }
+  }
+
+  /// A color from 0xRRGGBB.
+  pub fn from_hex(hex: u32) -> Self {
+    Color {
+      r: ((hex >> 16) & 0xFF) as f32,
+      g: ((hex >> 8) & 0xFF) as f32,
+      b: (hex & 0xFF) as f32,
+    }
   }
 
-  // default color
+  /// Black, the default color.
   pub fn black() -> Self {
-    Color { r: 0, g: 0, b: 0 }
+    Color { r: 0.0, g: 0.0, b: 0.0 }
+  }
+
+  /// Unquantized red channel, 0.0..=255.0.
+  pub fn r(&self) -> f32 {
+    self.r
+  }
+
+  /// Unquantized green channel, 0.0..=255.0.
+  pub fn g(&self) -> f32 {
+    self.g
+  }
+
+  /// Unquantized blue channel, 0.0..=255.0.
+  pub fn b(&self) -> f32 {
+    self.b
+  }
+
+  /// Packed as 0xRRGGBB, each channel quantized to 8 bits.
+  pub fn to_hex(self) -> u32 {
+    ((quantize(self.r, 0.0) as u32) << 16) | ((quantize(self.g, 0.0) as u32) << 8) | (quantize(self.b, 0.0) as u32)
   }
 
-  // Function to return the color as a hex value
-  pub fn to_hex(&self) -> u32 {
-    ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+  /// Same as `to_hex`, but adds a per-pixel sub-LSB offset from a Bayer matrix before
+  /// quantizing. The pattern depends only on the pixel position, so it never flickers.
+  pub fn to_hex_dithered(self, x: usize, y: usize) -> u32 {
+    let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+    ((quantize(self.r, threshold) as u32) << 16) | ((quantize(self.g, threshold) as u32) << 8) | (quantize(self.b, threshold) as u32)
   }
 
-  // Linear interpolation between two colors
+  /// Linear interpolation between two colors.
   pub fn lerp(&self, other: &Color, t: f32) -> Self {
     let t = t.clamp(0.0, 1.0);
     Color {
-      r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-      g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-      b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+      r: self.r + (other.r - self.r) * t,
+      g: self.g + (other.g - self.g) * t,
+      b: self.b + (other.b - self.b) * t,
     }
   }
 
+  /// Interpolate in HSV, going the shorter way around the hue circle, so a blend between
+  /// two saturated hues stays saturated instead of crossing grey. A grey end (no hue) takes
+  /// the other end's hue.
+  pub fn lerp_hsv(&self, other: &Color, t: f32) -> Self {
+    let t = t.clamp(0.0, 1.0);
+    let (h1, s1, v1) = self.to_hsv();
+    let (h2, s2, v2) = other.to_hsv();
+    let (h1, h2) = match (s1 > 0.0, s2 > 0.0) {
+      (false, true) => (h2, h2),
+      (true, false) => (h1, h1),
+      _ => (h1, h2),
+    };
+    // Signed hue difference in -180..180
+    let delta = (h2 - h1 + 540.0).rem_euclid(360.0) - 180.0;
+    Color::from_hsv(h1 + delta * t, s1 + (s2 - s1) * t, v1 + (v2 - v1) * t)
+  }
+
+  /// Hue in degrees (0.0..360.0, 0 for greys), saturation and value in 0.0..=1.0.
+  pub fn to_hsv(&self) -> (f32, f32, f32) {
+    let max = self.r.max(self.g).max(self.b);
+    let min = self.r.min(self.g).min(self.b);
+    let chroma = max - min;
+    let hue = if chroma <= 0.0 {
+      0.0
+    } else if max == self.r {
+      60.0 * ((self.g - self.b) / chroma).rem_euclid(6.0)
+    } else if max == self.g {
+      60.0 * ((self.b - self.r) / chroma + 2.0)
+    } else {
+      60.0 * ((self.r - self.g) / chroma + 4.0)
+    };
+    let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+    (hue, saturation, max / 255.0)
+  }
+
+  /// Inverse of `to_hsv`. The hue wraps (-30 and 330 are the same), saturation and value clamp.
+  pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+    let hue = hue.rem_euclid(360.0);
+    let value = value.clamp(0.0, 1.0) * 255.0;
+    let chroma = value * saturation.clamp(0.0, 1.0);
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+      0 => (chroma, x, 0.0),
+      1 => (x, chroma, 0.0),
+      2 => (0.0, chroma, x),
+      3 => (0.0, x, chroma),
+      4 => (x, 0.0, chroma),
+      _ => (chroma, 0.0, x),
+    };
+    let min = value - chroma;
+    Color { r: r + min, g: g + min, b: b + min }
+  }
+
+  /// Multiply every channel, clamped to 0.0..=255.0. Negative or NaN factors give black.
+  pub fn scale(self, factor: f32) -> Self {
+    let factor = if factor.is_nan() { 0.0 } else { factor.max(0.0) };
+    let channel = |c: f32| if c <= 0.0 { 0.0 } else { (c * factor).min(255.0) };
+    Color { r: channel(self.r), g: channel(self.g), b: channel(self.b) }
+  }
+
+  /// `scale` with a separate factor per channel, e.g. to light a surface with colored light.
+  pub fn scale_rgb(self, r: f32, g: f32, b: f32) -> Self {
+    Color { r: self.scale(r).r, g: self.scale(g).g, b: self.scale(b).b }
+  }
+
+  /// Relative luminance (Rec. 709 weights) of the stored channels, 0.0..=1.0.
+  pub fn luminance(&self) -> f32 {
+    (0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b) / 255.0
+  }
+
+  /// Scale by `exposure` and tone map back into range (extended Reinhard with the white
+  /// point at the exposed maximum). Exposure 1.0 is the identity; above it highlights
+  /// are compressed instead of clipping, below it the color simply darkens.
+  pub fn exposed(self, exposure: f32) -> Self {
+    let white = exposure.max(1.0);
+    let map = |channel: f32| {
+      let x = channel / 255.0 * exposure;
+      x * (1.0 + x / (white * white)) / (1.0 + x) * 255.0
+    };
+    Color { r: map(self.r), g: map(self.g), b: map(self.b) }
+  }
+
 }
 
-// Implement addition for Color
-use std::ops::Add;
+fn quantize(channel: f32, offset: f32) -> u8 {
+  (channel + offset).round().clamp(0.0, 255.0) as u8
+}
+
+// Implement addition for Color (saturating at 255 per channel)
+use std::ops::{Add, AddAssign};
 
 impl Add for Color {
   type Output = Color;
 
   fn add(self, other: Color) -> Color {
     Color {
-      r: self.r.saturating_add(other.r),
-      g: self.g.saturating_add(other.g),
-      b: self.b.saturating_add(other.b),
+      r: (self.r + other.r).min(255.0),
+      g: (self.g + other.g).min(255.0),
+      b: (self.b + other.b).min(255.0),
     }
   }
 }
 
+impl AddAssign for Color {
+  fn add_assign(&mut self, other: Color) {
+    *self = *self + other;
+  }
+}
+
 // Implement multiplication by a constant for Color
 use std::ops::Mul;
 
@@ -57,17 +190,127 @@ impl Mul<f32> for Color {
   type Output = Color;
 
   fn mul(self, scalar: f32) -> Color {
-    Color {
-      r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-      g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-      b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
-    }
+    self.scale(scalar)
   }
 }
 
 // Implement display formatting for Color
 impl fmt::Display for Color {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+    write!(f, "Color(r: {}, g: {}, b: {})", quantize(self.r, 0.0), quantize(self.g, 0.0), quantize(self.b, 0.0))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EPSILON: f32 = 1e-3;
+
+  fn assert_channels(color: Color, r: f32, g: f32, b: f32) {
+    assert!(
+      (color.r() - r).abs() < EPSILON && (color.g() - g).abs() < EPSILON && (color.b() - b).abs() < EPSILON,
+      "expected ({}, {}, {}), got {:?}", r, g, b, color
+    );
+  }
+
+  #[test]
+  fn hex_round_trips() {
+    for hex in [0x000000, 0xFFFFFF, 0x123456, 0xFF00FF, 0x00FFFF, 0x808080, 0xA57E40] {
+      assert_eq!(Color::from_hex(hex).to_hex(), hex);
+    }
+    // Bits above the 24 color bits are ignored
+    assert_eq!(Color::from_hex(0xFF112233).to_hex(), 0x112233);
+  }
+
+  #[test]
+  fn hsv_of_primaries_and_greys() {
+    let hsv = |hex: u32| Color::from_hex(hex).to_hsv();
+    assert_eq!(hsv(0xFF0000), (0.0, 1.0, 1.0));
+    assert_eq!(hsv(0x00FF00), (120.0, 1.0, 1.0));
+    assert_eq!(hsv(0x0000FF), (240.0, 1.0, 1.0));
+    assert_eq!(hsv(0x000000), (0.0, 0.0, 0.0));
+    let (hue, saturation, value) = hsv(0x808080);
+    assert_eq!((hue, saturation), (0.0, 0.0));
+    assert!((value - 128.0 / 255.0).abs() < EPSILON);
+  }
+
+  #[test]
+  fn hsv_round_trips() {
+    for hex in [0xFF0000, 0x00FFFF, 0xFF00FF, 0x123456, 0xFF8C00, 0x7F7F7F, 0x010203] {
+      let (hue, saturation, value) = Color::from_hex(hex).to_hsv();
+      assert_eq!(Color::from_hsv(hue, saturation, value).to_hex(), hex, "{:06X}", hex);
+    }
+  }
+
+  #[test]
+  fn from_hsv_wraps_the_hue() {
+    assert_channels(Color::from_hsv(360.0, 1.0, 1.0), 255.0, 0.0, 0.0);
+    assert_channels(Color::from_hsv(-120.0, 1.0, 1.0), 0.0, 0.0, 255.0);
+    assert_channels(Color::from_hsv(720.0 + 120.0, 1.0, 1.0), 0.0, 255.0, 0.0);
+  }
+
+  #[test]
+  fn lerp_hsv_takes_the_short_way_across_zero() {
+    // Hue 350 to hue 10: the midpoint is red (0), not cyan (180)
+    let from = Color::from_hsv(350.0, 1.0, 1.0);
+    let to = Color::from_hsv(10.0, 1.0, 1.0);
+    let (hue, saturation, _) = from.lerp_hsv(&to, 0.5).to_hsv();
+    assert!(!(EPSILON..=360.0 - EPSILON).contains(&hue), "hue {}", hue);
+    assert!((saturation - 1.0).abs() < EPSILON);
+    assert_channels(to.lerp_hsv(&from, 0.5), 255.0, 0.0, 0.0);
+  }
+
+  #[test]
+  fn lerp_hsv_keeps_saturated_blends_vivid() {
+    let cyan = Color::new(0, 255, 255);
+    let magenta = Color::new(255, 0, 255);
+    // RGB lerp goes through a washed-out lavender; HSV goes through blue
+    let rgb = cyan.lerp(&magenta, 0.5).to_hsv();
+    let hsv = cyan.lerp_hsv(&magenta, 0.5).to_hsv();
+    assert!(rgb.1 < 0.6);
+    assert!((hsv.0 - 240.0).abs() < EPSILON && (hsv.1 - 1.0).abs() < EPSILON);
+    // The ends are exact and t is clamped
+    assert_eq!(cyan.lerp_hsv(&magenta, 0.0).to_hex(), 0x00FFFF);
+    assert_eq!(cyan.lerp_hsv(&magenta, 1.0).to_hex(), 0xFF00FF);
+    assert_eq!(cyan.lerp_hsv(&magenta, -2.0).to_hex(), 0x00FFFF);
+    assert_eq!(cyan.lerp_hsv(&magenta, 5.0).to_hex(), 0xFF00FF);
+  }
+
+  #[test]
+  fn lerp_hsv_from_grey_keeps_the_other_hue() {
+    let grey = Color::new(0, 0, 0);
+    let green = Color::new(0, 255, 0);
+    let (hue, _, _) = grey.lerp_hsv(&green, 0.5).to_hsv();
+    assert!((hue - 120.0).abs() < EPSILON);
+  }
+
+  #[test]
+  fn add_saturates() {
+    let mut color = Color::new(200, 100, 0);
+    color += Color::new(100, 100, 10);
+    assert_channels(color, 255.0, 200.0, 10.0);
+    assert_channels(Color::new(255, 255, 255) + Color::new(255, 255, 255), 255.0, 255.0, 255.0);
+  }
+
+  #[test]
+  fn scale_clamps_extreme_factors() {
+    let color = Color::new(100, 50, 0);
+    assert_channels(color.scale(2.0), 200.0, 100.0, 0.0);
+    assert_channels(color.scale(1e30), 255.0, 255.0, 0.0);
+    assert_channels(color.scale(f32::INFINITY), 255.0, 255.0, 0.0);
+    assert_channels(color.scale(0.0), 0.0, 0.0, 0.0);
+    assert_channels(color.scale(-3.0), 0.0, 0.0, 0.0);
+    assert_channels(color.scale(f32::NAN), 0.0, 0.0, 0.0);
+    assert_channels(color * 0.5, 50.0, 25.0, 0.0);
+  }
+
+  #[test]
+  fn luminance_weights_green_highest() {
+    assert!((Color::new(255, 255, 255).luminance() - 1.0).abs() < EPSILON);
+    assert_eq!(Color::black().luminance(), 0.0);
+    let (r, g, b) = (Color::new(255, 0, 0).luminance(), Color::new(0, 255, 0).luminance(), Color::new(0, 0, 255).luminance());
+    assert!(g > r && r > b);
+    assert!((r - 0.2126).abs() < EPSILON);
   }
 }
@@ -0,0 +1,102 @@
+use crate::framebuffer::Framebuffer;
+use crate::planet::PlanetType;
+use crate::renderer::Renderer;
+use crate::scene::{CelestialBody, Scene};
+use crate::shader_params::ShaderParamTable;
+use crate::text::{draw_text, text_width};
+use crate::thumbnails::{framing_camera, FILL};
+
+const DIVIDER_WIDTH: usize = 2;
+const DIVIDER_COLOR: u32 = 0xC0C0C0;
+const CAPTION_COLOR: u32 = 0xE0E0E0;
+// Pixels between the captions and the top of each half
+const CAPTION_TOP: usize = 10;
+
+// Side-by-side view for tuning a shader: a body of one type rendered twice from the same
+// camera in a vertical split, the left half with the parameters the shaders had when the
+// comparison started and the right half with the current ones (the ones the shader tweaker
+// changes). Each half is a sub-viewport of its own, so the body is framed whole in both
+pub struct ShaderComparison {
+    planet: Option<PlanetType>, // the type under comparison; None while off
+    baseline: ShaderParamTable, // the left half's parameters
+    half: Option<Framebuffer>,  // where each half is rendered before being copied in place
+}
+
+impl Default for ShaderComparison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShaderComparison {
+    pub fn new() -> Self {
+        ShaderComparison { planet: None, baseline: ShaderParamTable::new(), half: None }
+    }
+
+    pub fn planet(&self) -> Option<PlanetType> {
+        self.planet
+    }
+
+    pub fn active(&self) -> bool {
+        self.planet.is_some()
+    }
+
+    // Compare the next body type of the scene, in the order of its bodies, and after the
+    // last one turn the comparison off. Turning it on keeps a copy of `current` as the
+    // left half's parameters, so later changes show up against it
+    pub fn cycle(&mut self, bodies: &[CelestialBody], current: &ShaderParamTable) {
+        let mut types: Vec<PlanetType> = Vec::new();
+        for body in bodies {
+            if !types.contains(&body.shader_type) {
+                types.push(body.shader_type);
+            }
+        }
+        self.planet = match self.planet {
+            None => {
+                self.baseline = current.clone();
+                types.first().copied()
+            }
+            Some(planet) => types.iter().skip_while(|&&other| other != planet).nth(1).copied(),
+        };
+    }
+
+    // Draw both halves and the divider over the whole of `framebuffer`, framed on the first
+    // body of the compared type. `renderer` is sized for `framebuffer` and is left that way
+    pub fn render(&mut self, renderer: &mut Renderer, framebuffer: &mut Framebuffer, scene: &Scene) {
+        let Some(planet) = self.planet else {
+            return;
+        };
+        let Some(body) = scene.bodies.iter().find(|body| body.shader_type == planet) else {
+            return;
+        };
+        let camera = framing_camera(body, FILL);
+
+        let (width, height) = (framebuffer.width.saturating_sub(DIVIDER_WIDTH) / 2, framebuffer.height);
+        if width == 0 {
+            return;
+        }
+        let half = match &mut self.half {
+            Some(half) if (half.width, half.height) == (width, height) => half,
+            slot => slot.insert(framebuffer.with_same_background(width, height)),
+        };
+        renderer.resize(width, height);
+        renderer.render_scene_with(half, scene, &camera, &self.baseline);
+        framebuffer.blit(half, 0, 0);
+        renderer.render_scene(half, scene, &camera);
+        let right = framebuffer.width - width;
+        framebuffer.blit(half, right, 0);
+        renderer.resize(framebuffer.width, framebuffer.height);
+
+        for (left, caption) in [(0, "inicial"), (right, "actual")] {
+            let caption = format!("{}: {}", planet.name(), caption);
+            let x = left + width.saturating_sub(text_width(&caption, 1)) / 2;
+            draw_text(framebuffer, x, CAPTION_TOP, &caption, CAPTION_COLOR, 1);
+        }
+        // The divider last, so no caption crosses it
+        for y in 0..height {
+            for x in width..right {
+                framebuffer.set_pixel(x, y, DIVIDER_COLOR);
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use nalgebra_glm::Vec3;
+
+pub struct NamedStar {
+    pub name: &'static str,
+    pub direction: Vec3,
+}
+
+pub struct Constellation {
+    pub name: &'static str,
+    pub stars: Vec<NamedStar>,
+    pub lines: Vec<(usize, usize)>,
+}
+
+impl Constellation {
+    // Unit direction toward the middle of the figure, where its name is drawn
+    pub fn centroid(&self) -> Vec3 {
+        let sum = self.stars.iter().fold(Vec3::zeros(), |acc, s| acc + s.direction);
+        sum.normalize()
+    }
+}
+
+// Right ascension (hours) and declination (degrees) to a unit direction.
+// The celestial north pole maps to +Y, the sky's "up" in the scene
+pub fn equatorial(ra_hours: f32, dec_degrees: f32) -> Vec3 {
+    let ra = ra_hours / 24.0 * std::f32::consts::TAU;
+    let dec = dec_degrees.to_radians();
+    Vec3::new(dec.cos() * ra.cos(), dec.sin(), dec.cos() * ra.sin())
+}
+
+fn constellation(name: &'static str, stars: &[(&'static str, f32, f32)], lines: &[(usize, usize)]) -> Constellation {
+    Constellation {
+        name,
+        stars: stars
+            .iter()
+            .map(|&(star, ra, dec)| NamedStar { name: star, direction: equatorial(ra, dec) })
+            .collect(),
+        lines: lines.to_vec(),
+    }
+}
+
+// Small built-in table of well-known figures (J2000 positions, rounded)
+pub fn builtin() -> Vec<Constellation> {
+    vec![
+        constellation(
+            "Orión",
+            &[
+                ("Betelgeuse", 5.919, 7.41),
+                ("Bellatrix", 5.418, 6.35),
+                ("Alnitak", 5.679, -1.94),
+                ("Alnilam", 5.603, -1.20),
+                ("Mintaka", 5.533, -0.30),
+                ("Saiph", 5.796, -9.67),
+                ("Rigel", 5.242, -8.20),
+                ("Meissa", 5.585, 9.93),
+            ],
+            &[(7, 0), (7, 1), (0, 2), (1, 4), (2, 3), (3, 4), (2, 5), (4, 6)],
+        ),
+        constellation(
+            "Osa Mayor",
+            &[
+                ("Dubhe", 11.062, 61.75),
+                ("Merak", 11.031, 56.38),
+                ("Phecda", 11.897, 53.69),
+                ("Megrez", 12.257, 57.03),
+                ("Alioth", 12.900, 55.96),
+                ("Mizar", 13.399, 54.93),
+                ("Alkaid", 13.792, 49.31),
+            ],
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (3, 4), (4, 5), (5, 6)],
+        ),
+        constellation(
+            "Casiopea",
+            &[
+                ("Caph", 0.153, 59.15),
+                ("Schedar", 0.675, 56.54),
+                ("Navi", 0.945, 60.72),
+                ("Ruchbah", 1.430, 60.24),
+                ("Segin", 1.907, 63.67),
+            ],
+            &[(0, 1), (1, 2), (2, 3), (3, 4)],
+        ),
+        constellation(
+            "Cisne",
+            &[
+                ("Deneb", 20.690, 45.28),
+                ("Sadr", 20.370, 40.26),
+                ("Albireo", 19.512, 27.96),
+                ("Gienah", 20.770, 33.97),
+                ("Fawaris", 19.750, 45.13),
+            ],
+            &[(0, 1), (1, 2), (3, 1), (1, 4)],
+        ),
+        constellation(
+            "Cruz del Sur",
+            &[
+                ("Acrux", 12.443, -63.10),
+                ("Mimosa", 12.795, -59.69),
+                ("Gacrux", 12.519, -57.11),
+                ("Imai", 12.252, -58.75),
+            ],
+            &[(0, 2), (1, 3)],
+        ),
+        constellation(
+            "Escorpio",
+            &[
+                ("Antares", 16.490, -26.43),
+                ("Dschubba", 16.006, -22.62),
+                ("Acrab", 16.091, -19.81),
+                ("Fang", 15.981, -26.11),
+                ("Paikauhale", 16.598, -28.22),
+                ("Larawag", 16.836, -34.29),
+                ("Xamidimura", 16.864, -38.05),
+                ("Shaula", 17.560, -37.10),
+            ],
+            &[(2, 1), (3, 1), (1, 0), (0, 4), (4, 5), (5, 6), (6, 7)],
+        ),
+    ]
+}
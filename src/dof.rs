@@ -0,0 +1,141 @@
+use nalgebra_glm::{Vec3, Vec4};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::scene::CelestialBody;
+use crate::uniforms::FrameUniforms;
+
+pub const DEFAULT_STRENGTH: f32 = 8.0;
+pub const DEFAULT_FOCUS_RANGE: f32 = 4.0;
+pub const MAX_STRENGTH: f32 = 64.0;
+// The blur runs on a copy this many times smaller on each side
+pub(crate) const DOWNSAMPLE: usize = 2;
+
+// Cheap depth of field around a focused body. Pixels whose depth is within `focus_range`
+// of the body's stay sharp; farther in front or behind, they fade into a box-blurred copy
+// of the frame, fully at twice the range. The blurred copy is made at reduced resolution
+// with a separable (horizontal, then vertical) box filter and sampled back bilinearly.
+// Pixels nothing was drawn on (the sky, at infinity) are always fully blurred
+pub struct DepthOfField {
+    pub enabled: bool,
+    pub strength: f32,    // blur radius, in pixels of the full frame, of what's fully out of focus
+    pub focus_range: f32, // world units in front of and behind the body that stay sharp
+    small: Vec<Vec3>,
+    scratch: Vec<Vec3>,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DepthOfField {
+    pub fn new() -> Self {
+        DepthOfField {
+            enabled: false,
+            strength: DEFAULT_STRENGTH,
+            focus_range: DEFAULT_FOCUS_RANGE,
+            small: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    // Blur `framebuffer` around `body` as seen with `uniforms`, the frame's uniforms for
+    // this framebuffer's size. Depth is read but not changed. Does nothing while disabled
+    // or when the body is behind the camera
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, body: &CelestialBody) {
+        if !self.enabled || framebuffer.width == 0 || framebuffer.height == 0 {
+            return;
+        }
+        let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(body.position.x, body.position.y, body.position.z, 1.0);
+        if clip.w <= 0.0 {
+            return;
+        }
+        // The body's whole surface counts as in focus, not only its center
+        let focus = clip.w;
+        let range = self.focus_range.max(f32::EPSILON) + body.scale;
+
+        let (width, height) = (framebuffer.width.div_ceil(DOWNSAMPLE), framebuffer.height.div_ceil(DOWNSAMPLE));
+        self.downsample(framebuffer, width, height);
+        let radius = (self.strength / DOWNSAMPLE as f32).round().max(1.0) as usize;
+        box_blur(&mut self.small, &mut self.scratch, width, height, radius, 1);
+        box_blur(&mut self.small, &mut self.scratch, width, height, radius, width);
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let index = y * framebuffer.width + x;
+                let distance = uniforms.view_distance(framebuffer.zbuffer[index]);
+                let blur = ((distance - focus).abs() - range) / range;
+                if blur.is_nan() || blur <= 0.0 {
+                    continue; // in focus
+                }
+                let blurred = upsample(&self.small, width, height, x, y);
+                let sharp = to_vec(framebuffer.buffer[index]);
+                framebuffer.buffer[index] = to_hex(sharp.lerp(&blurred, blur.min(1.0)));
+            }
+        }
+    }
+
+    // Average each DOWNSAMPLE x DOWNSAMPLE block of the frame into `small`
+    fn downsample(&mut self, framebuffer: &Framebuffer, width: usize, height: usize) {
+        self.small.clear();
+        self.small.resize(width * height, Vec3::zeros());
+        for sy in 0..height {
+            for sx in 0..width {
+                let mut sum = Vec3::zeros();
+                let mut count = 0.0;
+                for y in (sy * DOWNSAMPLE..(sy + 1) * DOWNSAMPLE).take_while(|&y| y < framebuffer.height) {
+                    for x in (sx * DOWNSAMPLE..(sx + 1) * DOWNSAMPLE).take_while(|&x| x < framebuffer.width) {
+                        sum += to_vec(framebuffer.buffer[y * framebuffer.width + x]);
+                        count += 1.0;
+                    }
+                }
+                self.small[sy * width + sx] = sum / count;
+            }
+        }
+    }
+}
+
+// One pass of a box filter of `radius` over `pixels` (width x height), along rows when
+// `stride` is 1 and along columns when it's the width. Edges repeat the last pixel
+fn box_blur(pixels: &mut [Vec3], scratch: &mut Vec<Vec3>, width: usize, height: usize, radius: usize, stride: usize) {
+    let (lines, length, line_stride) = if stride == 1 { (height, width, width) } else { (width, height, 1) };
+    let window = (2 * radius + 1) as f32;
+    scratch.clear();
+    scratch.resize(length, Vec3::zeros());
+    for line in 0..lines {
+        let at = |i: isize| line * line_stride + i.clamp(0, length as isize - 1) as usize * stride;
+        let mut sum = (-(radius as isize)..=radius as isize).map(|i| pixels[at(i)]).sum::<Vec3>();
+        for (i, out) in scratch.iter_mut().enumerate() {
+            *out = sum / window;
+            let i = i as isize;
+            sum += pixels[at(i + radius as isize + 1)] - pixels[at(i - radius as isize)];
+        }
+        for (i, value) in scratch.iter().enumerate() {
+            pixels[line * line_stride + i * stride] = *value;
+        }
+    }
+}
+
+// A copy DOWNSAMPLE times smaller (width x height) at full-resolution pixel (x, y),
+// interpolated between its samples
+pub(crate) fn upsample(pixels: &[Vec3], width: usize, height: usize, x: usize, y: usize) -> Vec3 {
+    let u = ((x as f32 + 0.5) / DOWNSAMPLE as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+    let v = ((y as f32 + 0.5) / DOWNSAMPLE as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (u as usize, v as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (tx, ty) = (u - x0 as f32, v - y0 as f32);
+    let at = |x: usize, y: usize| pixels[y * width + x];
+    let top = at(x0, y0).lerp(&at(x1, y0), tx);
+    let bottom = at(x0, y1).lerp(&at(x1, y1), tx);
+    top.lerp(&bottom, ty)
+}
+
+pub(crate) fn to_vec(color: u32) -> Vec3 {
+    let color = Color::from_hex(color);
+    Vec3::new(color.r(), color.g(), color.b())
+}
+
+pub(crate) fn to_hex(color: Vec3) -> u32 {
+    Color::new(color.x.round() as u8, color.y.round() as u8, color.z.round() as u8).to_hex()
+}
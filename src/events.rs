@@ -0,0 +1,85 @@
+use nalgebra_glm::Vec3;
+use crate::scene::CelestialBody;
+
+// The camera is "in orbit" of a body within ENTER_RADIUS body scales of its center, and
+// only leaves once it is beyond LEAVE_RADIUS. The gap between the two is the hysteresis
+// band: hovering around either threshold doesn't toggle the state every frame
+const ENTER_RADIUS: f32 = 5.0;
+const LEAVE_RADIUS: f32 = 6.5;
+// The closest approach is reported once the camera has receded this fraction past its
+// minimum distance, and re-armed only after getting this much closer again
+const APPROACH_MARGIN: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpaceEvent {
+    EnteredOrbit(usize),         // body index
+    LeftOrbit(usize),            // body index
+    ClosestApproach(usize, f32), // body index, distance from its center
+}
+
+#[derive(Clone, Copy, Default)]
+struct Proximity {
+    inside: bool,
+    closest: f32,   // minimum distance since the last report (or since entering)
+    reported: bool, // `closest` has already been sent as a ClosestApproach
+}
+
+// Diffs the camera's distance to every body against the previous frame and turns the
+// changes into events
+pub struct ProximityTracker {
+    states: Vec<Proximity>,
+}
+
+impl Default for ProximityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProximityTracker {
+    pub fn new() -> Self {
+        ProximityTracker { states: Vec::new() }
+    }
+
+    pub fn update(&mut self, celestial_bodies: &[CelestialBody], camera_position: Vec3) -> Vec<SpaceEvent> {
+        self.states.resize(celestial_bodies.len(), Proximity::default());
+        let mut events = Vec::new();
+
+        for (index, (body, state)) in celestial_bodies.iter().zip(&mut self.states).enumerate() {
+            let distance = (body.position - camera_position).magnitude();
+            let scale = body.scale.max(0.01);
+
+            if !state.inside {
+                if distance < scale * ENTER_RADIUS {
+                    *state = Proximity { inside: true, closest: distance, reported: false };
+                    events.push(SpaceEvent::EnteredOrbit(index));
+                }
+                continue;
+            }
+
+            if distance > scale * LEAVE_RADIUS {
+                if !state.reported {
+                    events.push(SpaceEvent::ClosestApproach(index, state.closest));
+                }
+                state.inside = false;
+                events.push(SpaceEvent::LeftOrbit(index));
+                continue;
+            }
+
+            if state.reported {
+                // Coming in closer than the reported approach starts a new one
+                if distance < state.closest * (1.0 - APPROACH_MARGIN) {
+                    state.closest = distance;
+                    state.reported = false;
+                }
+            } else if distance < state.closest {
+                state.closest = distance;
+            } else if distance > state.closest * (1.0 + APPROACH_MARGIN) {
+                events.push(SpaceEvent::ClosestApproach(index, state.closest));
+                state.reported = true;
+            }
+        }
+
+        events
+    }
+}
@@ -0,0 +1,109 @@
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+// Manual exposure limits, in stops around 1.0
+const MIN_EXPOSURE: f32 = 0.125;
+const MAX_EXPOSURE: f32 = 8.0;
+const STEP_STOPS: f32 = 1.0 / 3.0;
+
+// Auto-exposure aims for this average luminance on lit geometry, within a narrower
+// range than manual so it never fully blacks out or washes out the scene
+const AUTO_TARGET: f32 = 0.35;
+const AUTO_MIN_EXPOSURE: f32 = 0.35;
+const AUTO_MAX_EXPOSURE: f32 = 4.0;
+// Time constant of the adaptation, in seconds: each one closes about 63 % of the gap in
+// stops, so a change of scene has mostly settled after a second. Easing in stops rather
+// than linearly keeps it from pumping
+const AUTO_ADAPT_TIME: f32 = 0.35;
+// The meter's first level averages blocks this many pixels on a side; each level after it
+// halves the size until one value is left
+const METER_BLOCK: usize = 4;
+
+pub struct Exposure {
+    pub value: f32,
+    pub auto: bool,
+    meter: Vec<(f32, f32)>, // luminance sum and lit pixel count per cell of the current level
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Exposure {
+    pub fn new() -> Self {
+        Exposure { value: 1.0, auto: true, meter: Vec::new() }
+    }
+
+    // Change exposure by a number of 1/3-stop steps (manual control)
+    pub fn step(&mut self, steps: f32) {
+        self.value = (self.value * (steps * STEP_STOPS).exp2()).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    }
+
+    pub fn stops(&self) -> f32 {
+        self.value.log2()
+    }
+
+    // Ease toward the exposure that brings the metered luminance to the target. Call it
+    // with the finished scene, before the HUD is drawn over it. The framebuffer is already
+    // exposed, so the meter divides the current exposure back out to estimate the scene
+    // luminance
+    pub fn update_auto(&mut self, framebuffer: &Framebuffer, dt: f32) {
+        if !self.auto {
+            return;
+        }
+        let Some(average) = self.average_lit_luminance(framebuffer) else {
+            return;
+        };
+
+        let scene = (average / self.value).max(1e-4);
+        let target = (AUTO_TARGET / scene).clamp(AUTO_MIN_EXPOSURE, AUTO_MAX_EXPOSURE);
+        let change = (target / self.value).log2() * (1.0 - (-dt / AUTO_ADAPT_TIME).exp());
+        self.value = (self.value * change.exp2()).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    }
+
+    // Average luminance (0..1) of the pixels covered by scene geometry, reduced level by
+    // level: METER_BLOCK-sized blocks first, then 2x2 groups of cells until one is left.
+    // The sky, stars and empty space are left out, otherwise the mostly black frame would
+    // always push the exposure to its maximum
+    fn average_lit_luminance(&mut self, framebuffer: &Framebuffer) -> Option<f32> {
+        let (mut width, mut height) = (framebuffer.width.div_ceil(METER_BLOCK), framebuffer.height.div_ceil(METER_BLOCK));
+        self.meter.clear();
+        self.meter.resize(width * height, (0.0, 0.0));
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let index = y * framebuffer.width + x;
+                // Geometry writes depths up to 1; sky layers use larger sentinel depths
+                if framebuffer.zbuffer[index] > 1.0 {
+                    continue;
+                }
+                let cell = &mut self.meter[(y / METER_BLOCK) * width + x / METER_BLOCK];
+                cell.0 += Color::from_hex(framebuffer.buffer[index]).luminance();
+                cell.1 += 1.0;
+            }
+        }
+
+        // Each pass writes a level half the size in place, over the start of the one before
+        while width > 1 || height > 1 {
+            let (next_width, next_height) = (width.div_ceil(2), height.div_ceil(2));
+            for y in 0..next_height {
+                for x in 0..next_width {
+                    let mut sum = (0.0, 0.0);
+                    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let (cx, cy) = (2 * x + dx, 2 * y + dy);
+                        if cx < width && cy < height {
+                            let cell = self.meter[cy * width + cx];
+                            sum = (sum.0 + cell.0, sum.1 + cell.1);
+                        }
+                    }
+                    self.meter[y * next_width + x] = sum;
+                }
+            }
+            (width, height) = (next_width, next_height);
+        }
+
+        let (sum, count) = self.meter.first().copied()?;
+        (count > 0.0).then(|| sum / count)
+    }
+}
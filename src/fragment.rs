@@ -1,33 +1,20 @@
 use nalgebra_glm::{Vec3, Vec2};
 use crate::color::Color;
 
+/// One covered pixel of a triangle, with the vertex attributes interpolated at its center.
+/// Interpolation is linear in screen space (barycentric weights of the projected triangle).
 pub struct Fragment {
-    pub position: Vec2,
-    pub color: Color,
-    pub depth: f32,
-    pub normal: Vec3,
-    pub intensity: f32,
-    pub vertex_position: Vec3,
-}
-
-impl Fragment {
-    pub fn new(
-        position: Vec2,
-        color: Color,
-        depth: f32,
-        normal: Vec3,
-        intensity: f32,
-        vertex_position: Vec3,
-    ) -> Self {  
-        Fragment {
-            position,
-            color,
-            depth,
-            normal,
-            intensity,
-            vertex_position,
-        }
-    }
-}
+    // Where it lands
+    pub position: Vec2, // pixel (x, y)
+    pub depth: f32,     // viewport depth, smaller is nearer
 
+    // Surface
+    pub vertex_position: Vec3, // model space: the mesh coordinates procedural shaders sample noise with
+    pub world_position: Vec3,  // world space, after the model matrix
+    pub normal: Vec3,          // world space, unit length (zero where it can't be normalized)
+    pub tex_coords: Vec2,      // the mesh's UVs
 
+    // Lighting
+    pub intensity: f32, // diffuse term: interpolated from the vertices with Gouraud, computed by fragment_shader with Phong
+    pub color: Color,
+}
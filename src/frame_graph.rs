@@ -0,0 +1,88 @@
+use std::time::Duration;
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+
+// Frames kept in the graph, one pixel column each
+const CAPACITY: usize = 120;
+const GRAPH_HEIGHT: usize = 60;
+// 60 FPS budget, drawn as a line at half the graph's height (the top is twice the budget)
+const BUDGET_MS: f32 = 1000.0 / 60.0;
+const SCALE_MS: f32 = BUDGET_MS * 2.0;
+const BAR_COLOR: u32 = 0x40C040;
+const OVER_BUDGET_COLOR: u32 = 0xFF4040;
+const BUDGET_LINE_COLOR: u32 = 0xC0C0C0;
+// In front of everything drawn before it, so the bars pass the depth test
+const HUD_DEPTH: f32 = f32::MIN;
+
+// Rolling plot of the most recent frame times, anchored to the bottom-right corner
+pub struct FrameGraph {
+    times: [f32; CAPACITY], // ms, ring buffer
+    next: usize,
+    len: usize,
+    pub visible: bool,
+}
+
+impl Default for FrameGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameGraph {
+    pub fn new() -> Self {
+        FrameGraph {
+            times: [0.0; CAPACITY],
+            next: 0,
+            len: 0,
+            visible: false,
+        }
+    }
+
+    pub fn push(&mut self, frame_time: Duration) {
+        self.times[self.next] = frame_time.as_secs_f32() * 1000.0;
+        self.next = (self.next + 1) % CAPACITY;
+        self.len = (self.len + 1).min(CAPACITY);
+    }
+
+    // Recorded times in ms, oldest first
+    pub fn times(&self) -> impl Iterator<Item = f32> + '_ {
+        let start = (self.next + CAPACITY - self.len) % CAPACITY;
+        (0..self.len).map(move |i| self.times[(start + i) % CAPACITY])
+    }
+
+    // One bar per frame with the newest on the right; bars over the budget are red and
+    // spikes past the top are clipped to it
+    pub fn draw(&self, framebuffer: &mut Framebuffer, bottom: usize) {
+        if !self.visible {
+            return;
+        }
+
+        let panel_width = CAPACITY + 16;
+        let panel_height = GRAPH_HEIGHT + GLYPH_HEIGHT + 14;
+        let left = framebuffer.width.saturating_sub(10 + panel_width);
+        let top = framebuffer.height.saturating_sub(bottom + panel_height);
+        framebuffer.blend_rect(left, top, panel_width, panel_height, 0x000000, 0.6);
+
+        let x0 = (left + 8) as f32;
+        let baseline = (top + panel_height - 6) as f32;
+        let first_column = CAPACITY - self.len;
+        for (i, ms) in self.times().enumerate() {
+            let x = x0 + (first_column + i) as f32;
+            let height = (ms / SCALE_MS).min(1.0) * GRAPH_HEIGHT as f32;
+            framebuffer.set_current_color(if ms > BUDGET_MS { OVER_BUDGET_COLOR } else { BAR_COLOR });
+            framebuffer.line(Vec3::new(x, baseline, HUD_DEPTH), Vec3::new(x, baseline - height, HUD_DEPTH));
+        }
+
+        // Over the bars, so it stays visible through spikes
+        let budget_y = (baseline - BUDGET_MS / SCALE_MS * GRAPH_HEIGHT as f32) as usize;
+        framebuffer.blend_rect(left + 8, budget_y, CAPACITY, 1, BUDGET_LINE_COLOR, 0.8);
+
+        let latest = self.times().last().unwrap_or(0.0);
+        let label = format!("{:.1} ms", latest);
+        draw_text(framebuffer, left + 8, top + 6, &label, 0xA0A0A0, 1);
+        let budget_label = "16.6 ms";
+        let budget_x = (left + panel_width).saturating_sub(8 + text_width(budget_label, 1));
+        draw_text(framebuffer, budget_x, top + 6, budget_label, BUDGET_LINE_COLOR, 1);
+    }
+}
@@ -1,4 +1,18 @@
-use nalgebra_glm::Vec3;
+use std::path::Path;
+use image::{ImageError, ImageFormat, Rgb, RgbImage};
+use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
+
+// What try_point did with a fragment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointTest {
+    Outside, // off the framebuffer (or the band), dropped
+    Hidden,  // behind what the pixel already holds, never shaded
+    Drawn,
+}
+
+// Depth overlay lines are drawn at: in front of anything, so nothing hides them
+pub const OVERLAY_DEPTH: f32 = f32::MIN;
 
 pub struct Framebuffer {
     pub width: usize,
@@ -6,6 +20,7 @@ pub struct Framebuffer {
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
     background_color: u32,
+    background_gradient: Option<(Color, Color)>,
     current_color: u32,
 }
 
@@ -17,13 +32,37 @@ impl Framebuffer {
             buffer: vec![0; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
             background_color: 0x000000,
+            background_gradient: None,
             current_color: 0xFFFFFF,
         }
     }
 
+    // Write the color buffer to `path` as an 8-bit RGB PNG
+    pub fn save_png(&self, path: &Path) -> std::io::Result<()> {
+        let image = RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = self.buffer[y as usize * self.width + x as usize];
+            Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        });
+        image.save_with_format(path, ImageFormat::Png).map_err(|err| match err {
+            ImageError::IoError(err) => err,
+            err => std::io::Error::other(err),
+        })
+    }
+
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
-            *pixel = self.background_color;
+        match self.background_gradient {
+            None => {
+                for pixel in self.buffer.iter_mut() {
+                    *pixel = self.background_color;
+                }
+            }
+            // One lerp per scanline, then a plain fill of the row
+            Some((top, bottom)) => {
+                let last_row = self.height.saturating_sub(1).max(1) as f32;
+                for (y, row) in self.buffer.chunks_mut(self.width.max(1)).enumerate() {
+                    row.fill(top.lerp(&bottom, y as f32 / last_row).to_hex());
+                }
+            }
         }
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
@@ -40,16 +79,300 @@ impl Framebuffer {
         }
     }
 
+    // Depth-tested write at a screen-space position straight from the rasterizer. Anything
+    // non-finite, negative or past the edge is rejected before it becomes an index, and
+    // `shade` (given the pixel) only runs for a fragment that passes the depth test
+    pub fn try_point(&mut self, x: f32, y: f32, depth: f32, shade: impl FnOnce(usize, usize) -> u32) -> PointTest {
+        let Some((x, y)) = screen_pixel(x, y, self.width, 0, self.height) else {
+            return PointTest::Outside;
+        };
+        let index = y * self.width + x;
+        if self.zbuffer[index] > depth {
+            self.buffer[index] = shade(x, y);
+            self.zbuffer[index] = depth;
+            PointTest::Drawn
+        } else {
+            PointTest::Hidden
+        }
+    }
+
+    // Split the buffers into `count` horizontal bands of whole rows, top to bottom. Each
+    // band borrows its own slice of the color and depth buffers, so the bands can be drawn
+    // from different threads without locking, and they already form the final image
+    pub fn bands(&mut self, count: usize) -> Vec<FramebufferBand<'_>> {
+        let width = self.width.max(1);
+        let rows_per_band = self.height.div_ceil(count.max(1)).max(1);
+        self.buffer
+            .chunks_mut(rows_per_band * width)
+            .zip(self.zbuffer.chunks_mut(rows_per_band * width))
+            .enumerate()
+            .map(|(i, (buffer, zbuffer))| FramebufferBand {
+                width: self.width,
+                first_row: i * rows_per_band,
+                rows: buffer.len() / width,
+                buffer,
+                zbuffer,
+            })
+            .collect()
+    }
+
+    // Alpha-blend a color over the current pixel; depth-tested but never writes depth
+    pub fn blend_point(&mut self, x: usize, y: usize, depth: f32, color: u32, alpha: f32) {
+        if x < self.width && y < self.height && self.zbuffer[y * self.width + x] > depth {
+            self.blend_pixel(x, y, color, alpha);
+        }
+    }
+
+    // Color at (x, y), or None outside the framebuffer
+    pub fn pixel(&self, x: usize, y: usize) -> Option<u32> {
+        (x < self.width && y < self.height).then(|| self.buffer[y * self.width + x])
+    }
+
+    // Depth at (x, y); INFINITY where nothing was drawn since the last clear
+    pub fn depth(&self, x: usize, y: usize) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.zbuffer[y * self.width + x])
+    }
+
+    // Overlay write (HUD, text): ignores and keeps the depth buffer
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u32) {
+        if x < self.width && y < self.height {
+            self.buffer[y * self.width + x] = color;
+        }
+    }
+
+    // Overlay alpha blend (HUD panels): ignores and keeps the depth buffer
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: u32, alpha: f32) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            let alpha = alpha.clamp(0.0, 1.0);
+            let dst = self.buffer[index];
+            let blend = |shift: u32| -> u32 {
+                let s = ((color >> shift) & 0xFF) as f32;
+                let d = ((dst >> shift) & 0xFF) as f32;
+                ((d + (s - d) * alpha).round() as u32) << shift
+            };
+            self.buffer[index] = blend(16) | blend(8) | blend(0);
+        }
+    }
+
+    pub fn blend_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32, alpha: f32) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.blend_pixel(px, py, color, alpha);
+            }
+        }
+    }
+
+    // Copy `source`, color and depth, with its top-left corner at (left, top); whatever
+    // falls outside this framebuffer is dropped
+    pub fn blit(&mut self, source: &Framebuffer, left: usize, top: usize) {
+        let columns = source.width.min(self.width.saturating_sub(left));
+        for y in 0..source.height.min(self.height.saturating_sub(top)) {
+            let from = y * source.width;
+            let to = (top + y) * self.width + left;
+            self.buffer[to..to + columns].copy_from_slice(&source.buffer[from..from + columns]);
+            self.zbuffer[to..to + columns].copy_from_slice(&source.zbuffer[from..from + columns]);
+        }
+    }
+
+    // A framebuffer of another size that clears to the same background as this one
+    pub fn with_same_background(&self, width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            background_color: self.background_color,
+            background_gradient: self.background_gradient,
+            ..Framebuffer::new(width, height)
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
 
+    // Clear to a vertical gradient (top row -> bottom row) instead of the solid
+    // background color; None goes back to the solid clear
+    pub fn set_background_gradient(&mut self, gradient: Option<(u32, u32)>) {
+        self.background_gradient = gradient.map(|(top, bottom)| (Color::from_hex(top), Color::from_hex(bottom)));
+    }
+
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
 
+    // Draw a depth-tested line between two screen-space points (x, y in pixels, z = depth).
+    // The segment is clipped to the framebuffer first so off-screen endpoints stay cheap
     pub fn line(&mut self, start: Vec3, end: Vec3) {
-        // Implementación del método para dibujar una línea entre start y end
+        let Some((start, end)) = self.clip_line(start, end) else {
+            return;
+        };
+
+        let delta = end - start;
+        let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let p = start + delta * t;
+            if p.x >= 0.0 && p.y >= 0.0 {
+                self.point(p.x as usize, p.y as usize, p.z);
+            }
+        }
+    }
+
+    // Anti-aliased (Wu) line blended over the buffer with the given color and opacity.
+    // Depth-tested like blend_point, never writes depth
+    pub fn line_aa(&mut self, start: Vec3, end: Vec3, color: u32, alpha: f32) {
+        let Some((start, end)) = self.clip_line(start, end) else {
+            return;
+        };
+
+        // Work in pixel-center coordinates and always step along the major axis
+        let (mut a, mut b) = (start - Vec3::new(0.5, 0.5, 0.0), end - Vec3::new(0.5, 0.5, 0.0));
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        if steep {
+            a = Vec3::new(a.y, a.x, a.z);
+            b = Vec3::new(b.y, b.x, b.z);
+        }
+        if a.x > b.x {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        let dx = b.x - a.x;
+        let gradient = if dx.abs() < f32::EPSILON { 0.0 } else { (b.y - a.y) / dx };
+        let depth_gradient = if dx.abs() < f32::EPSILON { 0.0 } else { (b.z - a.z) / dx };
+
+        let first = a.x.round() as i64;
+        let last = b.x.round() as i64;
+        for major in first..=last {
+            let offset = major as f32 - a.x;
+            let minor = a.y + gradient * offset;
+            let depth = a.z + depth_gradient * offset;
+            let base = minor.floor();
+            let coverage = minor - base;
+
+            for (minor, weight) in [(base as i64, 1.0 - coverage), (base as i64 + 1, coverage)] {
+                let (x, y) = if steep { (minor, major) } else { (major, minor) };
+                if x >= 0 && y >= 0 && weight > 0.0 {
+                    self.blend_point(x as usize, y as usize, depth, color, alpha * weight);
+                }
+            }
+        }
+    }
+
+    // Anti-aliased one-pixel outline of a circle (HUD markers). Overlay: ignores and keeps
+    // the depth buffer
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: u32) {
+        self.cover_circle(center, radius, color, |distance| 1.0 - (distance - radius).abs());
+    }
+
+    // Anti-aliased filled disc (radar blips), as an overlay like `circle`
+    pub fn filled_circle(&mut self, center: Vec2, radius: f32, color: u32) {
+        self.cover_circle(center, radius, color, |distance| radius + 0.5 - distance);
+    }
+
+    // Blend `color` over every pixel near the circle by `coverage` of the distance from the
+    // pixel's center to `center`, clamped to 0..1. A circle smaller than a pixel is a single
+    // pixel blended by the disc's area instead, so tiny markers fade rather than vanish
+    fn cover_circle(&mut self, center: Vec2, radius: f32, color: u32, coverage: impl Fn(f32) -> f32) {
+        if !(center.x.is_finite() && center.y.is_finite() && radius >= 0.0) {
+            return;
+        }
+        if radius < 0.5 {
+            if center.x >= 0.0 && center.y >= 0.0 {
+                self.blend_pixel(center.x as usize, center.y as usize, color, std::f32::consts::PI * radius * radius);
+            }
+            return;
+        }
+
+        let reach = radius + 1.0;
+        let (left, right) = ((center.x - reach).floor().max(0.0), (center.x + reach).ceil().min(self.width as f32));
+        let (top, bottom) = ((center.y - reach).floor().max(0.0), (center.y + reach).ceil().min(self.height as f32));
+        for y in top as usize..bottom as usize {
+            for x in left as usize..right as usize {
+                let distance = (Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - center).magnitude();
+                let alpha = coverage(distance).clamp(0.0, 1.0);
+                if alpha > 0.0 {
+                    self.blend_pixel(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    // Liang-Barsky clip of a screen-space segment against the framebuffer rectangle
+    fn clip_line(&self, start: Vec3, end: Vec3) -> Option<(Vec3, Vec3)> {
+        if !(start.x.is_finite() && start.y.is_finite() && end.x.is_finite() && end.y.is_finite()) {
+            return None;
+        }
+
+        let delta = end - start;
+        let max_x = self.width as f32 - 0.001;
+        let max_y = self.height as f32 - 0.001;
+        let mut t0: f32 = 0.0;
+        let mut t1: f32 = 1.0;
+
+        for (p, q) in [
+            (-delta.x, start.x),
+            (delta.x, max_x - start.x),
+            (-delta.y, start.y),
+            (delta.y, max_y - start.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let r = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(r);
+                } else {
+                    t1 = t1.min(r);
+                }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+        Some((start + delta * t0, start + delta * t1))
     }
 }
 
+
+// Rows [first_row, first_row + rows) of a Framebuffer, see `Framebuffer::bands`. Takes
+// framebuffer coordinates and ignores pixels outside the band
+pub struct FramebufferBand<'a> {
+    pub width: usize,
+    pub first_row: usize,
+    pub rows: usize,
+    buffer: &'a mut [u32],
+    zbuffer: &'a mut [f32],
+}
+
+impl FramebufferBand<'_> {
+    pub fn contains_row(&self, y: usize) -> bool {
+        y >= self.first_row && y < self.first_row + self.rows
+    }
+
+    // Framebuffer::try_point restricted to the band's rows
+    pub fn try_point(&mut self, x: f32, y: f32, depth: f32, shade: impl FnOnce(usize, usize) -> u32) -> PointTest {
+        let Some((x, y)) = screen_pixel(x, y, self.width, self.first_row, self.rows) else {
+            return PointTest::Outside;
+        };
+        let index = (y - self.first_row) * self.width + x;
+        if self.zbuffer[index] > depth {
+            self.buffer[index] = shade(x, y);
+            self.zbuffer[index] = depth;
+            PointTest::Drawn
+        } else {
+            PointTest::Hidden
+        }
+    }
+}
+
+// Pixel holding the screen-space point (x, y) when it falls inside columns 0..width and
+// rows first_row..first_row + rows. The float checks come first: `as usize` saturates, so
+// a negative or NaN coordinate would otherwise turn into column or row 0
+fn screen_pixel(x: f32, y: f32, width: usize, first_row: usize, rows: usize) -> Option<(usize, usize)> {
+    if !(x.is_finite() && y.is_finite() && x >= 0.0 && y >= 0.0) {
+        return None;
+    }
+    let (x, y) = (x as usize, y as usize);
+    (x < width && y >= first_row && y < first_row + rows).then_some((x, y))
+}
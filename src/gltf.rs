@@ -0,0 +1,581 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use nalgebra_glm::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+use crate::color::Color;
+use crate::mesh::{Mesh, Model, ModelPart};
+use crate::mtl::Material;
+use crate::vertex::Vertex;
+
+// Minimal glTF 2.0 reader: the first mesh of a .glb (binary, with its buffer inside) or a
+// .gltf (JSON, with its buffers in files next to it). Reads positions, normals, UVs,
+// indices and each primitive's base color; node transforms are baked into the vertices.
+// No animation, skins, morph targets, sparse accessors or embedded (data:) URIs
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+// Accessor component types
+const UNSIGNED_BYTE: u64 = 5121;
+const UNSIGNED_SHORT: u64 = 5123;
+const UNSIGNED_INT: u64 = 5125;
+const FLOAT: u64 = 5126;
+
+// Primitive mode for triangle lists, the default
+const TRIANGLES: u64 = 4;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io(String, std::io::Error),
+    Format(String, String), // path, message
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GltfError::Io(path, err) if err.kind() == std::io::ErrorKind::NotFound => write!(f, "no se encontró {}", path),
+            GltfError::Io(path, err) => write!(f, "no se pudo leer {}: {}", path, err),
+            GltfError::Format(path, msg) => write!(f, "{}: {}", path, msg),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+// Whether `path` names a glTF file rather than an OBJ
+pub fn is_gltf(path: &str) -> bool {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    extension.eq_ignore_ascii_case("glb") || extension.eq_ignore_ascii_case("gltf")
+}
+
+// The first mesh in the file, one part per primitive with the primitive's material
+pub fn load_gltf(filename: &str) -> Result<Model, GltfError> {
+    let bytes = fs::read(filename).map_err(|err| GltfError::Io(filename.to_string(), err))?;
+    let error = |msg: String| GltfError::Format(filename.to_string(), msg);
+
+    let (json, bin) = if read_u32(&bytes, 0) == Some(GLB_MAGIC) {
+        split_glb(&bytes).map_err(error)?
+    } else {
+        (std::str::from_utf8(&bytes).map_err(|_| error("el JSON no es UTF-8".to_string()))?, None)
+    };
+    let root = Json::parse(json).map_err(error)?;
+
+    // Buffers: the GLB's binary chunk for the first one without a URI, files for the rest
+    let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let mut buffers = Vec::new();
+    let mut bin = bin;
+    for buffer in root.get("buffers").map(Json::items).unwrap_or(&[]) {
+        match buffer.get("uri").and_then(Json::as_str) {
+            Some(uri) if uri.starts_with("data:") => return Err(error("los búferes data: no están soportados".to_string())),
+            Some(uri) => {
+                let path = directory.join(uri);
+                let path = path.to_string_lossy();
+                buffers.push(fs::read(path.as_ref()).map_err(|err| GltfError::Io(path.into_owned(), err))?);
+            }
+            None => buffers.push(bin.take().ok_or_else(|| error("falta el bloque binario del .glb".to_string()))?.to_vec()),
+        }
+    }
+
+    let document = Document { root: &root, buffers, directory };
+    document.first_mesh().map_err(error)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let word = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+}
+
+// The JSON text and the binary chunk (if any) of a .glb
+fn split_glb(bytes: &[u8]) -> Result<(&str, Option<&[u8]>), String> {
+    let version = read_u32(bytes, 4).ok_or("encabezado .glb incompleto")?;
+    if version != 2 {
+        return Err(format!("versión de glTF {} no soportada (solo 2)", version));
+    }
+    let length = (read_u32(bytes, 8).ok_or("encabezado .glb incompleto")? as usize).min(bytes.len());
+
+    let (mut json, mut bin) = (None, None);
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(bytes, offset).unwrap_or(0) as usize;
+        let chunk_type = read_u32(bytes, offset + 4).unwrap_or(0);
+        let data = bytes.get(offset + 8..offset + 8 + chunk_length).ok_or("bloque del .glb cortado")?;
+        match chunk_type {
+            CHUNK_JSON => json = Some(std::str::from_utf8(data).map_err(|_| "el JSON no es UTF-8")?),
+            CHUNK_BIN => bin = Some(data),
+            _ => {} // Extensions' chunks
+        }
+        offset += 8 + chunk_length;
+    }
+    Ok((json.ok_or("el .glb no tiene bloque JSON")?, bin))
+}
+
+// One accessor's elements, ready to be read component by component
+struct Accessor<'a> {
+    data: &'a [u8], // from the first element on
+    count: usize,
+    component_type: u64,
+    components: usize,
+    stride: usize,
+}
+
+impl Accessor<'_> {
+    // A FLOAT accessor's component
+    fn float(&self, element: usize, component: usize) -> f32 {
+        f32::from_bits(read_u32(self.data, element * self.stride + component * 4).unwrap_or(0))
+    }
+
+    fn index(&self, element: usize) -> u32 {
+        let at = element * self.stride;
+        match self.component_type {
+            UNSIGNED_BYTE => self.data[at] as u32,
+            UNSIGNED_SHORT => u16::from_le_bytes([self.data[at], self.data[at + 1]]) as u32,
+            _ => read_u32(self.data, at).unwrap_or(0),
+        }
+    }
+}
+
+struct Document<'a> {
+    root: &'a Json,
+    buffers: Vec<Vec<u8>>,
+    directory: &'a Path,
+}
+
+impl Document<'_> {
+    fn item(&self, list: &str, index: usize) -> Result<&Json, String> {
+        self.root.get(list).and_then(|items| items.items().get(index)).ok_or_else(|| format!("no existe {}[{}]", list, index))
+    }
+
+    fn accessor(&self, index: usize) -> Result<Accessor<'_>, String> {
+        let accessor = self.item("accessors", index)?;
+        if accessor.get("sparse").is_some() {
+            return Err(format!("accessors[{}]: los accessors dispersos no están soportados", index));
+        }
+        let count = accessor.get("count").and_then(Json::as_usize).ok_or_else(|| format!("accessors[{}] sin count", index))?;
+        let component_type = accessor.get("componentType").and_then(Json::as_u64).unwrap_or(0);
+        let component_size = match component_type {
+            UNSIGNED_BYTE => 1,
+            UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT => 4,
+            other => return Err(format!("accessors[{}]: tipo de componente {} no soportado", index, other)),
+        };
+        let components = match accessor.get("type").and_then(Json::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            other => return Err(format!("accessors[{}]: tipo {:?} no soportado", index, other)),
+        };
+
+        let view_index = accessor.get("bufferView").and_then(Json::as_usize).ok_or_else(|| format!("accessors[{}] sin bufferView", index))?;
+        let view = self.item("bufferViews", view_index)?;
+        let buffer = view.get("buffer").and_then(Json::as_usize).and_then(|buffer| self.buffers.get(buffer))
+            .ok_or_else(|| format!("bufferViews[{}] apunta a un búfer que no existe", view_index))?;
+        let view_offset = view.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+        let view_length = view.get("byteLength").and_then(Json::as_usize).unwrap_or(0);
+        let element_size = component_size * components;
+        let stride = view.get("byteStride").and_then(Json::as_usize).unwrap_or(element_size);
+        let offset = view_offset + accessor.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+
+        // Every element must lie inside both the view and the buffer
+        let end = if count == 0 { offset } else { offset + (count - 1) * stride + element_size };
+        if end > view_offset + view_length || end > buffer.len() {
+            return Err(format!("accessors[{}] se sale de su búfer", index));
+        }
+        Ok(Accessor { data: &buffer[offset..end], count, component_type, components, stride })
+    }
+
+    // Float vectors of N components (positions, normals, UVs)
+    fn vectors<const N: usize>(&self, index: usize) -> Result<Vec<[f32; N]>, String> {
+        let accessor = self.accessor(index)?;
+        if accessor.component_type != FLOAT || accessor.components != N {
+            return Err(format!("accessors[{}]: se esperaban {} floats por elemento", index, N));
+        }
+        Ok((0..accessor.count).map(|element| std::array::from_fn(|c| accessor.float(element, c))).collect())
+    }
+
+    fn indices(&self, index: usize) -> Result<Vec<u32>, String> {
+        let accessor = self.accessor(index)?;
+        if accessor.components != 1 || accessor.component_type == FLOAT {
+            return Err(format!("accessors[{}]: los índices deben ser enteros", index));
+        }
+        Ok((0..accessor.count).map(|element| accessor.index(element)).collect())
+    }
+
+    // Where mesh 0 sits in the world: the transform of the first node that uses it,
+    // walking the default scene (or every node, without scenes) depth first
+    fn mesh_transform(&self) -> Mat4 {
+        let nodes = self.root.get("nodes").map(Json::items).unwrap_or(&[]);
+        let scene = self.root.get("scene").and_then(Json::as_usize).unwrap_or(0);
+        let roots: Vec<usize> = match self.root.get("scenes").and_then(|scenes| scenes.items().get(scene)) {
+            Some(scene) => scene.get("nodes").map(Json::items).unwrap_or(&[]).iter().filter_map(Json::as_usize).collect(),
+            None => (0..nodes.len()).collect(),
+        };
+
+        let mut stack: Vec<(usize, Mat4)> = roots.into_iter().rev().map(|node| (node, Mat4::identity())).collect();
+        let mut visited = 0;
+        while let Some((index, parent)) = stack.pop() {
+            let Some(node) = nodes.get(index) else { continue };
+            visited += 1;
+            if visited > nodes.len() * 4 {
+                break; // a cycle in a malformed file
+            }
+            let world = parent * local_transform(node);
+            if node.get("mesh").and_then(Json::as_usize) == Some(0) {
+                return world;
+            }
+            let children = node.get("children").map(Json::items).unwrap_or(&[]);
+            stack.extend(children.iter().rev().filter_map(Json::as_usize).map(|child| (child, world)));
+        }
+        Mat4::identity()
+    }
+
+    fn material(&self, index: Option<usize>) -> Result<Material, String> {
+        let Some(index) = index else {
+            return Ok(Material::fallback());
+        };
+        let material = self.item("materials", index)?;
+        let pbr = material.get("pbrMetallicRoughness");
+        let factor = pbr.and_then(|pbr| pbr.get("baseColorFactor")).map(Json::items).unwrap_or(&[]);
+        let channel = |i: usize| factor.get(i).and_then(Json::as_f64).unwrap_or(1.0) as f32;
+        // glTF colors are linear; ours are stored gamma-encoded like the textures
+        let encode = |linear: f32| (linear.clamp(0.0, 1.0).powf(1.0 / 2.2) * 255.0).round() as u8;
+
+        // Only textures whose image is a file next to the model; images inside the .glb are skipped
+        let diffuse_map = pbr
+            .and_then(|pbr| pbr.get("baseColorTexture"))
+            .and_then(|texture| texture.get("index")).and_then(Json::as_usize)
+            .and_then(|texture| self.item("textures", texture).ok())
+            .and_then(|texture| texture.get("source")).and_then(Json::as_usize)
+            .and_then(|image| self.item("images", image).ok())
+            .and_then(|image| image.get("uri")).and_then(Json::as_str)
+            .filter(|uri| !uri.starts_with("data:"))
+            .map(|uri| self.directory.join(uri).to_string_lossy().into_owned());
+
+        Ok(Material {
+            name: material.get("name").and_then(Json::as_str).unwrap_or("").to_string(),
+            diffuse: Color::new(encode(channel(0)), encode(channel(1)), encode(channel(2))),
+            diffuse_map,
+        })
+    }
+
+    fn first_mesh(&self) -> Result<Model, String> {
+        let mesh = self.item("meshes", 0).map_err(|_| "el archivo no tiene mallas".to_string())?;
+        let transform = self.mesh_transform();
+        // Normals go through the inverse transpose, so non-uniform scales keep them perpendicular
+        let normal_matrix = Mat3::from_fn(|r, c| transform[(r, c)]).try_inverse().map(|m| m.transpose()).unwrap_or(Mat3::identity());
+
+        let mut parts = Vec::new();
+        for (number, primitive) in mesh.get("primitives").map(Json::items).unwrap_or(&[]).iter().enumerate() {
+            let mode = primitive.get("mode").and_then(Json::as_u64).unwrap_or(TRIANGLES);
+            if mode != TRIANGLES {
+                return Err(format!("primitiva {}: solo se admiten listas de triángulos (mode 4), no mode {}", number, mode));
+            }
+            let attributes = primitive.get("attributes");
+            let attribute = |name: &str| attributes.and_then(|a| a.get(name)).and_then(Json::as_usize);
+
+            let position_accessor = attribute("POSITION").ok_or_else(|| format!("primitiva {} sin POSITION", number))?;
+            let positions = self.vectors::<3>(position_accessor)?;
+            let normals = attribute("NORMAL").map(|index| self.vectors::<3>(index)).transpose()?;
+            let uvs = attribute("TEXCOORD_0").map(|index| self.vectors::<2>(index)).transpose()?;
+            if normals.as_ref().is_some_and(|n| n.len() != positions.len()) || uvs.as_ref().is_some_and(|t| t.len() != positions.len()) {
+                return Err(format!("primitiva {}: los atributos tienen distinta cantidad de elementos", number));
+            }
+
+            // Like OBJ corners without them: normal +Y and UV (0, 0)
+            let vertices = positions.iter().enumerate().map(|(i, &[x, y, z])| {
+                let position = transform * Vec4::new(x, y, z, 1.0);
+                let normal = normals.as_ref().map_or(Vec3::new(0.0, 1.0, 0.0), |n| {
+                    let normal = normal_matrix * Vec3::new(n[i][0], n[i][1], n[i][2]);
+                    if normal.magnitude() > 0.0 { normal.normalize() } else { normal }
+                });
+                let tex_coords = uvs.as_ref().map_or(Vec2::new(0.0, 0.0), |t| Vec2::new(t[i][0], t[i][1]));
+                Vertex::new(position.xyz() / position.w, normal, tex_coords)
+            }).collect::<Vec<_>>();
+
+            let indices = match primitive.get("indices").and_then(Json::as_usize) {
+                Some(index) => self.indices(index)?,
+                None => (0..vertices.len() as u32).collect(),
+            };
+            if indices.len() % 3 != 0 || indices.iter().any(|&index| index as usize >= vertices.len()) {
+                return Err(format!("primitiva {}: índices inválidos", number));
+            }
+
+            let material = self.material(primitive.get("material").and_then(Json::as_usize))?;
+            parts.push(ModelPart { mesh: Mesh { vertices, indices }, material });
+        }
+        if parts.is_empty() {
+            return Err("la malla no tiene primitivas".to_string());
+        }
+        Ok(Model { parts })
+    }
+}
+
+// A node's own transform: `matrix`, or translation * rotation * scale
+fn local_transform(node: &Json) -> Mat4 {
+    let numbers = |key: &str| -> Option<Vec<f32>> {
+        node.get(key).map(|value| value.items().iter().map(|n| n.as_f64().unwrap_or(0.0) as f32).collect())
+    };
+    if let Some(matrix) = numbers("matrix").filter(|m| m.len() == 16) {
+        return Mat4::from_column_slice(&matrix); // column-major, as glTF stores it
+    }
+    let translation = numbers("translation").filter(|t| t.len() == 3).map_or(Vec3::zeros(), |t| Vec3::new(t[0], t[1], t[2]));
+    let rotation = numbers("rotation").filter(|r| r.len() == 4).map_or(Quat::identity(), |r| Quat::new(r[3], r[0], r[1], r[2]));
+    let scale = numbers("scale").filter(|s| s.len() == 3).map_or(Vec3::repeat(1.0), |s| Vec3::new(s[0], s[1], s[2]));
+    nalgebra_glm::translation(&translation) * nalgebra_glm::quat_to_mat4(&rotation) * nalgebra_glm::scaling(&scale)
+}
+
+// A parsed JSON value. Objects keep their keys in file order
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser { bytes: text.as_bytes(), position: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.bytes.len() {
+            return Err(parser.error("texto sobrante después del JSON"));
+        }
+        Ok(value)
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    // An array's elements; empty for anything else
+    fn items(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        self.as_f64().filter(|n| *n >= 0.0 && n.fract() == 0.0).map(|n| n as u64)
+    }
+
+    fn as_usize(&self) -> Option<usize> {
+        self.as_u64().map(|n| n as usize)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+// Deeper nesting than any glTF file needs; stops malformed input from overflowing the stack
+const MAX_JSON_DEPTH: usize = 64;
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, msg: &str) -> String {
+        format!("JSON inválido en el byte {}: {}", self.position, msg)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.position).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.position) != Some(&byte) {
+            return Err(self.error(&format!("se esperaba '{}'", byte as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(self.error("demasiado anidado"));
+        }
+        self.skip_whitespace();
+        match self.bytes.get(self.position) {
+            Some(b'{') => {
+                self.position += 1;
+                let mut entries = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.position) == Some(&b'}') {
+                    self.position += 1;
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    entries.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.bytes.get(self.position) {
+                        Some(b',') => self.position += 1,
+                        Some(b'}') => {
+                            self.position += 1;
+                            return Ok(Json::Object(entries));
+                        }
+                        _ => return Err(self.error("se esperaba ',' o '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.position += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.position) == Some(&b']') {
+                    self.position += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.bytes.get(self.position) {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(self.error("se esperaba ',' o ']'")),
+                    }
+                }
+            }
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(_) => self.number(),
+            None => Err(self.error("fin inesperado")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if !self.bytes[self.position..].starts_with(word.as_bytes()) {
+            return Err(self.error("valor desconocido"));
+        }
+        self.position += word.len();
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        while self.bytes.get(self.position).is_some_and(|b| b.is_ascii_digit() || b"+-.eE".contains(b)) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|text| text.parse::<f64>().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("número inválido"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.bytes.get(self.position) != Some(&b'"') {
+            return Err(self.error("se esperaba un texto"));
+        }
+        self.position += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(&byte) = self.bytes.get(self.position) else {
+                return Err(self.error("texto sin cerrar"));
+            };
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = self.bytes.get(self.position).copied().ok_or_else(|| self.error("texto sin cerrar"))?;
+                    self.position += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("escape inválido")),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("texto que no es UTF-8"))
+    }
+
+    // After `\u`: four hex digits, and a second escape for the low half of a surrogate pair
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.bytes.get(self.position..self.position + 2) != Some(b"\\u") {
+                return Err(self.error("par sustituto incompleto"));
+            }
+            self.position += 2;
+            let low = self.hex4()?;
+            0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("\\u inválido"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.bytes.get(self.position..self.position + 4).and_then(|d| std::str::from_utf8(d).ok());
+        let value = digits.and_then(|d| u32::from_str_radix(d, 16).ok()).ok_or_else(|| self.error("\\u inválido"))?;
+        self.position += 4;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_json() {
+        let json = Json::parse(r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"é🚀"}} "#).unwrap();
+        assert_eq!(json.get("a").unwrap().items(), [Json::Number(1.0), Json::Number(-25.0), Json::Bool(true), Json::Null]);
+        assert_eq!(json.get("b").and_then(|b| b.get("c")).and_then(Json::as_str), Some("x\"é🚀"));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        for text in ["", "{", "[1,]", "{\"a\" 1}", "tru", "\"open", "[1] 2", &"[".repeat(200)] {
+            assert!(Json::parse(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn node_transforms_compose_translation_rotation_and_scale() {
+        let node = Json::parse(r#"{"translation": [1, 2, 3], "rotation": [0, 0.7071068, 0, 0.7071068], "scale": [2, 2, 2]}"#).unwrap();
+        let point = local_transform(&node) * Vec4::new(1.0, 0.0, 0.0, 1.0);
+        // Scaled to x = 2, turned 90 degrees about Y to z = -2, then moved
+        assert!((point.xyz() - Vec3::new(1.0, 2.0, 1.0)).magnitude() < 1e-5, "{:?}", point);
+    }
+}
@@ -0,0 +1,59 @@
+use nalgebra_glm::{Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::{clip_segment_near, clip_to_screen};
+use crate::uniforms::FrameUniforms;
+
+// World units between grid lines; the default orbit radii are multiples of it
+const SPACING: f32 = 5.0;
+// Half the size of the grid around the camera; the lines have faded out by this distance
+const EXTENT: f32 = 120.0;
+const COLOR: u32 = 0x3A5A8A;
+// The lines through the origin (the X and Z axes) stand out a little
+const AXIS_COLOR: u32 = 0x6A8AC0;
+const MAX_ALPHA: f32 = 0.35;
+
+// Faint grid on the orbital plane (Y = 0), centered under the camera so it never runs
+// out. Each line is drawn in SPACING-long pieces that fade with their horizontal distance
+// from the camera. Blended and depth-tested without writing depth, so bodies hide it
+// and the orbits drawn afterwards stay on top
+pub fn render_grid(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let camera = uniforms.camera_position;
+    let snap = |v: f32| (v / SPACING).round() * SPACING;
+    let (center_x, center_z) = (snap(camera.x), snap(camera.z));
+    let half_lines = (EXTENT / SPACING) as i32;
+
+    for line in -half_lines..=half_lines {
+        let offset = line as f32 * SPACING;
+        for piece in -half_lines..half_lines {
+            let (from, to) = (piece as f32 * SPACING, (piece + 1) as f32 * SPACING);
+            // A piece parallel to X at z = center_z + offset, and one parallel to Z
+            let along_x = (
+                Vec3::new(center_x + from, 0.0, center_z + offset),
+                Vec3::new(center_x + to, 0.0, center_z + offset),
+                center_z + offset == 0.0,
+            );
+            let along_z = (
+                Vec3::new(center_x + offset, 0.0, center_z + from),
+                Vec3::new(center_x + offset, 0.0, center_z + to),
+                center_x + offset == 0.0,
+            );
+            for (start, end, is_axis) in [along_x, along_z] {
+                let middle = (start + end) * 0.5;
+                let distance = (middle.x - camera.x).hypot(middle.z - camera.z);
+                let fade = (1.0 - distance / EXTENT).max(0.0);
+                let alpha = MAX_ALPHA * fade * fade;
+                if alpha < 0.01 {
+                    continue;
+                }
+
+                let clip_start = view_projection * Vec4::new(start.x, start.y, start.z, 1.0);
+                let clip_end = view_projection * Vec4::new(end.x, end.y, end.z, 1.0);
+                if let Some((a, b)) = clip_segment_near(clip_start, clip_end) {
+                    let color = if is_axis { AXIS_COLOR } else { COLOR };
+                    framebuffer.line_aa(clip_to_screen(uniforms, a), clip_to_screen(uniforms, b), color, alpha);
+                }
+            }
+        }
+    }
+}
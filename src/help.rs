@@ -0,0 +1,101 @@
+use crate::framebuffer::Framebuffer;
+use crate::input::{key_label, Action, InputMap};
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+// Seconds the "press H for help" hint stays up after startup
+const HINT_TIME: f32 = 10.0;
+const PADDING: usize = 10;
+const COLUMN_GAP: usize = 24;
+const TITLE_COLOR: u32 = 0xFFD700;
+const KEY_COLOR: u32 = 0xFFFFFF;
+const TEXT_COLOR: u32 = 0xA0A0A0;
+
+// Controls overlay listing every action with its current keys, drawn on top of the frame
+pub struct HelpOverlay {
+    pub visible: bool,
+    hint_time_left: f32,
+}
+
+impl Default for HelpOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HelpOverlay {
+    pub fn new() -> Self {
+        HelpOverlay { visible: false, hint_time_left: HINT_TIME }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.hint_time_left = (self.hint_time_left - dt).max(0.0);
+    }
+
+    // One-line hint at the top center, only during the first seconds and while hidden
+    pub fn draw_hint(&self, framebuffer: &mut Framebuffer, input: &InputMap) {
+        if self.visible || self.hint_time_left <= 0.0 {
+            return;
+        }
+        let Some(&key) = input.keys(Action::Help).first() else {
+            return;
+        };
+        let hint = format!("Pulsa {} para ver la ayuda", key_label(key));
+        let x = (framebuffer.width / 2).saturating_sub(text_width(&hint, 1) / 2);
+        draw_text(framebuffer, x, PADDING, &hint, TEXT_COLOR, 1);
+    }
+
+    // The binding list, in as many columns as needed to fit the screen height.
+    // `status` lines (camera mode, ...) are shown under the title
+    pub fn draw(&self, framebuffer: &mut Framebuffer, input: &InputMap, status: &[String]) {
+        if !self.visible {
+            return;
+        }
+
+        let rows: Vec<(String, &str)> = Action::ALL.iter()
+            .map(|&action| {
+                let keys = input.keys(action).iter().map(|&key| key_label(key)).collect::<Vec<_>>();
+                let keys = if keys.is_empty() { "-".to_string() } else { keys.join(" / ") };
+                (keys, action.description())
+            })
+            .collect();
+
+        let title = "Controles";
+        let header_lines = 1 + status.len() + 1; // title, status, blank line
+        // Leave PADDING around the panel plus PADDING inside it
+        let available = framebuffer.height.saturating_sub(4 * PADDING + header_lines * LINE_ADVANCE);
+        let per_column = (available / LINE_ADVANCE).max(1);
+        let columns = rows.chunks(per_column).collect::<Vec<_>>();
+
+        // Each column: keys left-aligned, descriptions aligned after the column's widest keys
+        let key_widths: Vec<usize> = columns.iter()
+            .map(|column| column.iter().map(|(keys, _)| text_width(keys, 1)).max().unwrap_or(0) + 8)
+            .collect();
+        let column_widths: Vec<usize> = columns.iter().zip(&key_widths)
+            .map(|(column, key_width)| key_width + column.iter().map(|(_, text)| text_width(text, 1)).max().unwrap_or(0))
+            .collect();
+        let content_width = column_widths.iter().sum::<usize>() + COLUMN_GAP * columns.len().saturating_sub(1);
+        let header_width = status.iter().map(|line| text_width(line, 1)).chain([text_width(title, 1)]).max().unwrap_or(0);
+        let width = content_width.max(header_width) + 2 * PADDING;
+        let height = (header_lines + per_column.min(rows.len())) * LINE_ADVANCE + 2 * PADDING;
+
+        let x = (framebuffer.width / 2).saturating_sub(width / 2);
+        let y = (framebuffer.height / 2).saturating_sub(height / 2);
+        framebuffer.blend_rect(x, y, width, height, 0x000000, 0.75);
+
+        draw_text(framebuffer, x + PADDING, y + PADDING, title, TITLE_COLOR, 1);
+        for (i, line) in status.iter().enumerate() {
+            draw_text(framebuffer, x + PADDING, y + PADDING + LINE_ADVANCE * (i + 1), line, TEXT_COLOR, 1);
+        }
+
+        let top = y + PADDING + header_lines * LINE_ADVANCE;
+        let mut column_x = x + PADDING;
+        for ((column, column_width), key_width) in columns.iter().zip(&column_widths).zip(&key_widths) {
+            for (row, (keys, text)) in column.iter().enumerate() {
+                let row_y = top + row * LINE_ADVANCE;
+                draw_text(framebuffer, column_x, row_y, keys, KEY_COLOR, 1);
+                draw_text(framebuffer, column_x + key_width, row_y, text, TEXT_COLOR, 1);
+            }
+            column_x += column_width + COLUMN_GAP;
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use crate::color::Color;
+use crate::dof::{to_hex, to_vec};
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::mesh::sphere_uv;
+use crate::shaders::fragment_shader;
+use crate::sprite::project;
+use crate::uniforms::{DrawUniforms, FrameUniforms};
+
+// Projected radius (pixels) below which a body is drawn only as an impostor, and above
+// which only the mesh is drawn. In between the impostor is blended over the mesh
+const IMPOSTOR_ONLY_RADIUS: f32 = 1.5;
+const MESH_ONLY_RADIUS: f32 = 4.0;
+// The impostor disc is never smaller than this (pixels)
+const MIN_DOT_RADIUS: f32 = 2.0;
+
+// Screen-space radius in pixels of a sphere of `radius` at `center`
+pub fn projected_radius(uniforms: &FrameUniforms, framebuffer: &Framebuffer, center: Vec3, radius: f32) -> f32 {
+    let distance = (center - uniforms.camera_position).magnitude();
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    // projection[(1, 1)] is 1 / tan(fov / 2), so this is the focal length in pixels
+    let focal = uniforms.projection_matrix[(1, 1)] * framebuffer.height as f32 * 0.5;
+    radius / distance * focal
+}
+
+// How much of the body is drawn by the impostor: 1.0 = impostor only, 0.0 = mesh only
+pub fn impostor_weight(projected_radius: f32) -> f32 {
+    let t = ((projected_radius - IMPOSTOR_ONLY_RADIUS) / (MESH_ONLY_RADIUS - IMPOSTOR_ONLY_RADIUS)).clamp(0.0, 1.0);
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
+
+// Side in texels of the image each body type's impostor is drawn from
+pub const SPRITE_SIZE: usize = 32;
+
+// What a body type looks like from afar: its shader evaluated once, at time 0, over the lit
+// face of a unit sphere seen straight down the light, kept as a small image with its
+// coarser mip levels, and the average of that face weighted by the diffuse term
+pub struct ImpostorSprite {
+    pub color: Color,
+    // SPRITE_SIZE texels square first, each next level half as wide, down to 1. Color is
+    // premultiplied by alpha (0.0..=255.0), alpha is how much of the texel the disc covers
+    levels: Vec<Vec<Vec4>>,
+}
+
+impl ImpostorSprite {
+    pub fn render(uniforms: &DrawUniforms) -> Self {
+        let light = uniforms.frame.light_direction();
+        // Axes of the image: two directions across the light
+        let helper = if light.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let right = helper.cross(&light).normalize();
+        let up = light.cross(&right);
+
+        let mut texels = Vec::with_capacity(SPRITE_SIZE * SPRITE_SIZE);
+        let (mut sum, mut weight) = (Vec3::zeros(), 0.0);
+        for y in 0..SPRITE_SIZE {
+            for x in 0..SPRITE_SIZE {
+                let u = (x as f32 + 0.5) / SPRITE_SIZE as f32 * 2.0 - 1.0;
+                let v = 1.0 - (y as f32 + 0.5) / SPRITE_SIZE as f32 * 2.0;
+                let distance = u.hypot(v);
+                // About a texel of antialiased edge
+                let coverage = ((1.0 - distance) * SPRITE_SIZE as f32 * 0.5 + 0.5).clamp(0.0, 1.0);
+                if coverage <= 0.0 {
+                    texels.push(Vec4::zeros());
+                    continue;
+                }
+                let facing = (1.0 - distance * distance).max(0.0).sqrt();
+                let normal = (right * u + up * v + light * facing).normalize();
+                let intensity = normal.dot(&light).max(0.0);
+                let fragment = Fragment {
+                    position: Vec2::new(x as f32, y as f32),
+                    depth: 0.0,
+                    vertex_position: normal,
+                    world_position: normal,
+                    normal,
+                    tex_coords: sphere_uv(normal),
+                    intensity,
+                    color: Color::black(),
+                };
+                let color = to_vec(fragment_shader(&fragment, uniforms).to_hex());
+                sum += color * intensity;
+                weight += intensity;
+                texels.push(Vec4::new(color.x, color.y, color.z, 1.0) * coverage);
+            }
+        }
+
+        let mut levels = vec![texels];
+        while levels.last().unwrap().len() > 1 {
+            levels.push(half_size(levels.last().unwrap()));
+        }
+        let color = if weight > 0.0 { Color::from_hex(to_hex(sum / weight)) } else { Color::black() };
+        ImpostorSprite { color, levels }
+    }
+
+    // Bilinear sample at (u, v), 0..1 across the image, from the level whose texels are
+    // about `texels_per_pixel` level-0 texels wide
+    pub fn sample(&self, u: f32, v: f32, texels_per_pixel: f32) -> Vec4 {
+        let level = (texels_per_pixel.max(1.0).log2().round() as usize).min(self.levels.len() - 1);
+        let texels = &self.levels[level];
+        let size = SPRITE_SIZE >> level;
+        let x = (u * size as f32 - 0.5).clamp(0.0, (size - 1) as f32);
+        let y = (v * size as f32 - 0.5).clamp(0.0, (size - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(size - 1), (y0 + 1).min(size - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let at = |x: usize, y: usize| texels[y * size + x];
+        at(x0, y0).lerp(&at(x1, y0), tx).lerp(&at(x0, y1).lerp(&at(x1, y1), tx), ty)
+    }
+}
+
+// The next mip level: each 2x2 block of `texels` averaged
+fn half_size(texels: &[Vec4]) -> Vec<Vec4> {
+    let size = (texels.len() as f32).sqrt() as usize;
+    let half = size / 2;
+    let mut next = Vec::with_capacity(half * half);
+    for y in 0..half {
+        for x in 0..half {
+            let at = |dx: usize, dy: usize| texels[(y * 2 + dy) * size + x * 2 + dx];
+            next.push((at(0, 0) + at(1, 0) + at(0, 1) + at(1, 1)) * 0.25);
+        }
+    }
+    next
+}
+
+// Disc standing in for a distant body: its impostor sprite facing the screen, never less
+// than MIN_DOT_RADIUS pixels in radius so far bodies stay a clean visible point
+pub struct Impostor {
+    pub center: Vec3,
+    pub projected_radius: f32,
+    pub brightness: f32, // the sprite's colors are scaled by this (the light's intensity)
+    pub alpha: f32,
+}
+
+pub fn render_impostor(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, impostor: &Impostor, sprite: &ImpostorSprite) {
+    if impostor.projected_radius <= 0.0 || impostor.alpha <= 0.0 {
+        return;
+    }
+    let Some(center) = project(uniforms, impostor.center) else {
+        return;
+    };
+    if !(center.x.is_finite() && center.y.is_finite() && center.z.is_finite()) {
+        return;
+    }
+    let radius = impostor.projected_radius.max(MIN_DOT_RADIUS);
+    let texels_per_pixel = SPRITE_SIZE as f32 / (radius * 2.0);
+    let start_x = (center.x - radius).floor().max(0.0) as usize;
+    let start_y = (center.y - radius).floor().max(0.0) as usize;
+    let end_x = ((center.x + radius).ceil().max(0.0) as usize).min(framebuffer.width);
+    let end_y = ((center.y + radius).ceil().max(0.0) as usize).min(framebuffer.height);
+
+    for y in start_y..end_y {
+        let v = (y as f32 + 0.5 - center.y) / (radius * 2.0) + 0.5;
+        for x in start_x..end_x {
+            let u = (x as f32 + 0.5 - center.x) / (radius * 2.0) + 0.5;
+            let texel = sprite.sample(u, v, texels_per_pixel);
+            if texel.w <= 0.0 {
+                continue;
+            }
+            let color = Color::from_hex(to_hex(texel.xyz() / texel.w)) * impostor.brightness;
+            framebuffer.blend_point(x, y, center.z, color.exposed(uniforms.exposure).to_hex(), texel.w * impostor.alpha);
+        }
+    }
+}
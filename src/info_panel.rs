@@ -0,0 +1,96 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::scene::{sun_position, Scene};
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+const TITLE_COLOR: u32 = 0x70FFA0; // the selection reticle's
+const TEXT_COLOR: u32 = 0xC0C0C0;
+const HINT_COLOR: u32 = 0x808080;
+// Pixels from the right edge of the screen
+const MARGIN: usize = 10;
+
+// What the info panel shows about one body, measured on the current frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyInfo {
+    pub name: String,
+    pub type_name: &'static str,
+    pub scale: f32,
+    pub sun_distance: f32,    // from the light's position (see sun_position)
+    pub camera_distance: f32, // from the eye to the body's center
+    pub speed: f32,           // world units per second
+    pub period: Option<f32>,  // seconds per orbit; None if it doesn't orbit
+    pub children: usize,      // bodies orbiting it
+}
+
+impl BodyInfo {
+    pub fn new(scene: &Scene, index: usize, camera_position: Vec3) -> Self {
+        let body = &scene.bodies[index];
+        BodyInfo {
+            name: body.name.clone(),
+            type_name: body.shader_type.name(),
+            scale: body.scale,
+            sun_distance: (body.position - sun_position(&scene.bodies)).magnitude(),
+            camera_distance: (body.position - camera_position).magnitude(),
+            speed: scene.orbital_speed(index),
+            period: scene.orbital_period(index),
+            children: scene.child_count(index),
+        }
+    }
+
+    // The panel's lines after the title
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Tipo: {}", self.type_name),
+            format!("Escala: {}", format_distance(self.scale)),
+            format!("Al sol: {}", format_distance(self.sun_distance)),
+            format!("A la cámara: {}", format_distance(self.camera_distance)),
+            format!("Velocidad: {} u/s", format_amount(self.speed)),
+            format!("Período: {}", self.period.map_or("-".to_string(), format_duration)),
+            format!("Satélites: {}", self.children),
+        ]
+    }
+}
+
+// Three significant digits at most, and no decimals from 100 up
+fn format_amount(value: f32) -> String {
+    let decimals = match value.abs() {
+        v if v < 10.0 => 2,
+        v if v < 100.0 => 1,
+        _ => 0,
+    };
+    format!("{:.*}", decimals, value)
+}
+
+// A distance in world units (the scene file's orbit_radius units)
+pub fn format_distance(distance: f32) -> String {
+    format!("{} u", format_amount(distance))
+}
+
+// Seconds, or minutes from two minutes up
+pub fn format_duration(seconds: f32) -> String {
+    if seconds < 120.0 {
+        format!("{} s", format_amount(seconds))
+    } else {
+        format!("{} min", format_amount(seconds / 60.0))
+    }
+}
+
+// The panel at the right edge of the screen, halfway down, with the Enter hint last
+pub fn draw_info_panel(framebuffer: &mut Framebuffer, info: &BodyInfo) {
+    let hint = "Enter: warp";
+    let lines = info.lines();
+    let width = lines.iter().map(|line| text_width(line, 1))
+        .chain([text_width(&info.name, 1), text_width(hint, 1)])
+        .max()
+        .unwrap_or(0) + 16;
+    let height = LINE_ADVANCE * (lines.len() + 2) + 10;
+    let x = framebuffer.width.saturating_sub(width + MARGIN);
+    let y = framebuffer.height.saturating_sub(height) / 2;
+    framebuffer.blend_rect(x, y, width, height, 0x000000, 0.6);
+
+    draw_text(framebuffer, x + 8, y + 8, &info.name, TITLE_COLOR, 1);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(framebuffer, x + 8, y + 8 + LINE_ADVANCE * (i + 1), line, TEXT_COLOR, 1);
+    }
+    draw_text(framebuffer, x + 8, y + 8 + LINE_ADVANCE * (lines.len() + 1), hint, HINT_COLOR, 1);
+}
@@ -0,0 +1,661 @@
+use minifb::{Key, KeyRepeat, Window};
+use crate::toml_lite::{key_value, list_items, strip_comment, unquote};
+
+// Every rebindable action, in the order they're listed by `--dump-keys`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    LookUp,
+    LookDown,
+    ZoomIn,
+    ZoomOut,
+    CameraMode,
+    BirdEye,
+    CameraCloser,
+    CameraFarther,
+    Follow,
+    SelectNext,
+    WarpSelected,
+    Autopilot,
+    Capture,
+    Select1,
+    Select2,
+    Select3,
+    Select4,
+    Select5,
+    Select6,
+    Select7,
+    Select8,
+    Select9,
+    Warp1,
+    Warp2,
+    Warp3,
+    Warp4,
+    Warp5,
+    Warp6,
+    Warp7,
+    Warp8,
+    Constellations,
+    SkyMode,
+    Grid,
+    DepthOfField,
+    SunShafts,
+    Minimap,
+    Labels,
+    Ruler,
+    HideBody,
+    HideOrbit,
+    HideTrail,
+    Orbits,
+    OrbitTicks,
+    Trails,
+    Shading,
+    Dither,
+    RegenerateSky,
+    ReloadScene,
+    Pause,
+    TimeSlower,
+    TimeFaster,
+    SpeedPreset,
+    LightOverride,
+    LightLeft,
+    LightRight,
+    LightUp,
+    LightDown,
+    AutoExposure,
+    ExposureDown,
+    ExposureUp,
+    ExposureBracket,
+    Profiler,
+    FrameGraph,
+    RenderStats,
+    DebugBounds,
+    TweakerToggle,
+    TweakerPrev,
+    TweakerNext,
+    TweakerDecrease,
+    TweakerIncrease,
+    CompareShaders,
+    Help,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 79] = [
+        Action::MoveForward, Action::MoveBack, Action::MoveLeft, Action::MoveRight,
+        Action::MoveUp, Action::MoveDown, Action::LookUp, Action::LookDown,
+        Action::ZoomIn, Action::ZoomOut, Action::CameraMode, Action::BirdEye,
+        Action::CameraCloser, Action::CameraFarther, Action::Follow, Action::SelectNext, Action::WarpSelected, Action::Autopilot, Action::Capture,
+        Action::Select1, Action::Select2, Action::Select3, Action::Select4, Action::Select5,
+        Action::Select6, Action::Select7, Action::Select8, Action::Select9,
+        Action::Warp1, Action::Warp2, Action::Warp3, Action::Warp4,
+        Action::Warp5, Action::Warp6, Action::Warp7, Action::Warp8,
+        Action::Constellations, Action::SkyMode, Action::Grid, Action::DepthOfField, Action::SunShafts, Action::Minimap, Action::Labels, Action::Ruler,
+        Action::HideBody, Action::HideOrbit, Action::HideTrail, Action::Orbits, Action::OrbitTicks, Action::Trails,
+        Action::Shading, Action::Dither, Action::RegenerateSky,
+        Action::ReloadScene, Action::Pause, Action::TimeSlower, Action::TimeFaster, Action::SpeedPreset,
+        Action::LightOverride, Action::LightLeft, Action::LightRight, Action::LightUp, Action::LightDown,
+        Action::AutoExposure, Action::ExposureDown, Action::ExposureUp, Action::ExposureBracket, Action::Profiler,
+        Action::FrameGraph, Action::RenderStats, Action::DebugBounds, Action::TweakerToggle, Action::TweakerPrev, Action::TweakerNext,
+        Action::TweakerDecrease, Action::TweakerIncrease, Action::CompareShaders, Action::Help, Action::Quit,
+    ];
+
+    // Selection of a row of the body list; the index is the row among those shown
+    pub const SELECT_ROWS: [Action; 9] = [
+        Action::Select1, Action::Select2, Action::Select3, Action::Select4, Action::Select5,
+        Action::Select6, Action::Select7, Action::Select8, Action::Select9,
+    ];
+
+    // The ship's manual controls; holding any of them takes over from the autopilot
+    pub const PILOTING: [Action; 8] = [
+        Action::MoveForward, Action::MoveBack, Action::MoveLeft, Action::MoveRight,
+        Action::MoveUp, Action::MoveDown, Action::LookUp, Action::LookDown,
+    ];
+
+    // Direct warp actions; the index is the target body's index in the scene
+    pub const WARPS: [Action; 8] = [
+        Action::Warp1, Action::Warp2, Action::Warp3, Action::Warp4,
+        Action::Warp5, Action::Warp6, Action::Warp7, Action::Warp8,
+    ];
+
+    // Name used in keybindings.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::LookUp => "look_up",
+            Action::LookDown => "look_down",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::CameraMode => "camera_mode",
+            Action::BirdEye => "bird_eye",
+            Action::CameraCloser => "camera_closer",
+            Action::CameraFarther => "camera_farther",
+            Action::Follow => "follow",
+            Action::SelectNext => "select_next",
+            Action::WarpSelected => "warp_selected",
+            Action::Autopilot => "autopilot",
+            Action::Capture => "capture",
+            Action::Select1 => "select_1",
+            Action::Select2 => "select_2",
+            Action::Select3 => "select_3",
+            Action::Select4 => "select_4",
+            Action::Select5 => "select_5",
+            Action::Select6 => "select_6",
+            Action::Select7 => "select_7",
+            Action::Select8 => "select_8",
+            Action::Select9 => "select_9",
+            Action::Warp1 => "warp_1",
+            Action::Warp2 => "warp_2",
+            Action::Warp3 => "warp_3",
+            Action::Warp4 => "warp_4",
+            Action::Warp5 => "warp_5",
+            Action::Warp6 => "warp_6",
+            Action::Warp7 => "warp_7",
+            Action::Warp8 => "warp_8",
+            Action::Constellations => "constellations",
+            Action::SkyMode => "sky_mode",
+            Action::Grid => "grid",
+            Action::DepthOfField => "depth_of_field",
+            Action::SunShafts => "sun_shafts",
+            Action::Minimap => "minimap",
+            Action::Labels => "labels",
+            Action::Ruler => "ruler",
+            Action::HideBody => "hide_body",
+            Action::HideOrbit => "hide_orbit",
+            Action::HideTrail => "hide_trail",
+            Action::Orbits => "orbits",
+            Action::OrbitTicks => "orbit_ticks",
+            Action::Trails => "trails",
+            Action::Shading => "shading",
+            Action::Dither => "dither",
+            Action::RegenerateSky => "regenerate_sky",
+            Action::ReloadScene => "reload_scene",
+            Action::Pause => "pause",
+            Action::TimeSlower => "time_slower",
+            Action::TimeFaster => "time_faster",
+            Action::SpeedPreset => "speed_preset",
+            Action::LightOverride => "light_override",
+            Action::LightLeft => "light_left",
+            Action::LightRight => "light_right",
+            Action::LightUp => "light_up",
+            Action::LightDown => "light_down",
+            Action::AutoExposure => "auto_exposure",
+            Action::ExposureDown => "exposure_down",
+            Action::ExposureUp => "exposure_up",
+            Action::ExposureBracket => "exposure_bracket",
+            Action::Profiler => "profiler",
+            Action::FrameGraph => "frame_graph",
+            Action::RenderStats => "render_stats",
+            Action::DebugBounds => "debug_bounds",
+            Action::TweakerToggle => "tweaker_toggle",
+            Action::TweakerPrev => "tweaker_prev",
+            Action::TweakerNext => "tweaker_next",
+            Action::TweakerDecrease => "tweaker_decrease",
+            Action::TweakerIncrease => "tweaker_increase",
+            Action::CompareShaders => "compare_shaders",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    // Short description for the help overlay
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Avanzar",
+            Action::MoveBack => "Retroceder",
+            Action::MoveLeft => "Izquierda",
+            Action::MoveRight => "Derecha",
+            Action::MoveUp => "Subir",
+            Action::MoveDown => "Bajar",
+            Action::LookUp => "Mirar arriba",
+            Action::LookDown => "Mirar abajo",
+            Action::ZoomIn => "Acercar zoom",
+            Action::ZoomOut => "Alejar zoom",
+            Action::CameraMode => "Cabina / persecución",
+            Action::BirdEye => "Vista de pájaro (mantener)",
+            Action::CameraCloser => "Cámara más cerca",
+            Action::CameraFarther => "Cámara más lejos",
+            Action::Follow => "Seguir cuerpo cercano",
+            Action::SelectNext => "Seleccionar el cuerpo siguiente (con Shift, el anterior)",
+            Action::WarpSelected => "Warp al cuerpo seleccionado",
+            Action::Autopilot => "Piloto automático al cuerpo seleccionado",
+            Action::Capture => "Capturar / soltar un asteroide o una luna cercanos",
+            Action::Select1 => "Seleccionar la fila 1 de la lista",
+            Action::Select2 => "Seleccionar la fila 2 de la lista",
+            Action::Select3 => "Seleccionar la fila 3 de la lista",
+            Action::Select4 => "Seleccionar la fila 4 de la lista",
+            Action::Select5 => "Seleccionar la fila 5 de la lista",
+            Action::Select6 => "Seleccionar la fila 6 de la lista",
+            Action::Select7 => "Seleccionar la fila 7 de la lista",
+            Action::Select8 => "Seleccionar la fila 8 de la lista",
+            Action::Select9 => "Seleccionar la fila 9 de la lista",
+            Action::Warp1 => "Warp directo al cuerpo 1",
+            Action::Warp2 => "Warp directo al cuerpo 2",
+            Action::Warp3 => "Warp directo al cuerpo 3",
+            Action::Warp4 => "Warp directo al cuerpo 4",
+            Action::Warp5 => "Warp directo al cuerpo 5",
+            Action::Warp6 => "Warp directo al cuerpo 6",
+            Action::Warp7 => "Warp directo al cuerpo 7",
+            Action::Warp8 => "Warp directo al cuerpo 8",
+            Action::Constellations => "Constelaciones",
+            Action::SkyMode => "Estrellas: infinitas / fijas al mundo",
+            Action::Grid => "Cuadrícula del plano orbital",
+            Action::DepthOfField => "Profundidad de campo en el cuerpo seleccionado",
+            Action::SunShafts => "Rayos de sol alrededor de lo que lo tapa",
+            Action::Minimap => "Minimapa",
+            Action::Labels => "Nombres de los cuerpos",
+            Action::Ruler => "Regla: clic en dos cuerpos para medir su distancia",
+            Action::HideBody => "Ocultar / mostrar el cuerpo enfocado",
+            Action::HideOrbit => "Ocultar / mostrar la órbita del cuerpo enfocado",
+            Action::HideTrail => "Ocultar / mostrar la estela del cuerpo enfocado",
+            Action::Orbits => "Todas las órbitas",
+            Action::OrbitTicks => "Marcas en las órbitas (vista de pájaro)",
+            Action::Trails => "Todas las estelas",
+            Action::Shading => "Sombreado Gouraud / Phong",
+            Action::Dither => "Tramado",
+            Action::RegenerateSky => "Regenerar el cielo",
+            Action::ReloadScene => "Recargar la escena",
+            Action::Pause => "Pausar / reanudar la simulación",
+            Action::TimeSlower => "Simulación a la mitad de velocidad",
+            Action::TimeFaster => "Simulación al doble de velocidad",
+            Action::SpeedPreset => "Velocidades orbitales clásicas / realistas",
+            Action::LightOverride => "Luz manual / volver a la luz normal",
+            Action::LightLeft => "Girar la luz manual a la izquierda",
+            Action::LightRight => "Girar la luz manual a la derecha",
+            Action::LightUp => "Subir la luz manual",
+            Action::LightDown => "Bajar la luz manual",
+            Action::AutoExposure => "Exposición automática",
+            Action::ExposureDown => "Bajar exposición",
+            Action::ExposureUp => "Subir exposición",
+            Action::ExposureBracket => "Capturas a -1, 0 y +1 EV",
+            Action::Profiler => "Perfil por etapas",
+            Action::FrameGraph => "Gráfico de tiempos",
+            Action::RenderStats => "Estadísticas de render",
+            Action::DebugBounds => "Esferas envolventes: en vivo / frustum fijo / apagar",
+            Action::TweakerToggle => "Panel de shaders",
+            Action::TweakerPrev => "Shaders: parámetro anterior",
+            Action::TweakerNext => "Shaders: parámetro siguiente",
+            Action::TweakerDecrease => "Shaders: disminuir",
+            Action::TweakerIncrease => "Shaders: aumentar",
+            Action::CompareShaders => "Comparar shaders: tipo siguiente / apagar",
+            Action::Help => "Ayuda",
+            Action::Quit => "Salir",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.name().eq_ignore_ascii_case(name))
+    }
+
+    fn default_keys(self) -> &'static [Key] {
+        match self {
+            Action::MoveForward => &[Key::W],
+            Action::MoveBack => &[Key::S],
+            Action::MoveLeft => &[Key::A],
+            Action::MoveRight => &[Key::D],
+            Action::MoveUp => &[Key::Q],
+            Action::MoveDown => &[Key::E],
+            Action::LookUp => &[Key::Up],
+            Action::LookDown => &[Key::Down],
+            // Moved off 1 and 2 when the number keys went to the body list
+            Action::ZoomIn => &[Key::F3],
+            Action::ZoomOut => &[Key::F2],
+            Action::CameraMode => &[Key::V],
+            Action::BirdEye => &[Key::B],
+            Action::CameraCloser => &[Key::PageUp],
+            Action::CameraFarther => &[Key::PageDown],
+            Action::Follow => &[Key::F],
+            Action::SelectNext => &[Key::Tab],
+            Action::WarpSelected => &[Key::Enter],
+            Action::Autopilot => &[Key::G],
+            Action::Capture => &[Key::F12],
+            Action::Select1 => &[Key::Key1],
+            Action::Select2 => &[Key::Key2],
+            Action::Select3 => &[Key::Key3],
+            Action::Select4 => &[Key::Key4],
+            Action::Select5 => &[Key::Key5],
+            Action::Select6 => &[Key::Key6],
+            Action::Select7 => &[Key::Key7],
+            Action::Select8 => &[Key::Key8],
+            Action::Select9 => &[Key::Key9],
+            // The old 1-8 warps, on the numeric keypad
+            Action::Warp1 => &[Key::NumPad1],
+            Action::Warp2 => &[Key::NumPad2],
+            Action::Warp3 => &[Key::NumPad3],
+            Action::Warp4 => &[Key::NumPad4],
+            Action::Warp5 => &[Key::NumPad5],
+            Action::Warp6 => &[Key::NumPad6],
+            Action::Warp7 => &[Key::NumPad7],
+            Action::Warp8 => &[Key::NumPad8],
+            Action::Constellations => &[Key::C],
+            Action::SkyMode => &[Key::K],
+            Action::Grid => &[Key::X],
+            Action::DepthOfField => &[Key::Semicolon],
+            Action::SunShafts => &[Key::Apostrophe],
+            Action::Minimap => &[Key::M],
+            Action::Labels => &[Key::L],
+            Action::Ruler => &[Key::U],
+            Action::HideBody => &[Key::N],
+            Action::HideOrbit => &[Key::J],
+            Action::HideTrail => &[Key::T],
+            Action::Orbits => &[Key::Y],
+            Action::OrbitTicks => &[Key::F4],
+            Action::Trails => &[Key::Z],
+            // Moved off G when it went to the autopilot
+            Action::Shading => &[Key::F11],
+            Action::Dither => &[Key::F5],
+            Action::RegenerateSky => &[Key::F6],
+            Action::ReloadScene => &[Key::R],
+            Action::Pause => &[Key::Space],
+            Action::TimeSlower => &[Key::NumPadSlash],
+            Action::TimeFaster => &[Key::NumPadAsterisk],
+            Action::SpeedPreset => &[Key::Backspace],
+            Action::LightOverride => &[Key::O],
+            Action::LightLeft => &[Key::Left],
+            Action::LightRight => &[Key::Right],
+            Action::LightUp => &[Key::Home],
+            Action::LightDown => &[Key::End],
+            Action::AutoExposure => &[Key::F7],
+            Action::ExposureDown => &[Key::Comma],
+            Action::ExposureUp => &[Key::Period],
+            Action::ExposureBracket => &[Key::Insert],
+            Action::Profiler => &[Key::F9],
+            Action::FrameGraph => &[Key::F8],
+            Action::RenderStats => &[Key::F10],
+            Action::DebugBounds => &[Key::Slash],
+            Action::TweakerToggle => &[Key::P],
+            Action::TweakerPrev => &[Key::LeftBracket],
+            Action::TweakerNext => &[Key::RightBracket],
+            Action::TweakerDecrease => &[Key::Minus, Key::NumPadMinus],
+            Action::TweakerIncrease => &[Key::Equal, Key::NumPadPlus],
+            Action::CompareShaders => &[Key::I],
+            Action::Help => &[Key::H, Key::F1],
+            Action::Quit => &[Key::Escape],
+        }
+    }
+}
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        const KEY_NAMES: &[(&str, Key)] = &[$((stringify!($key), Key::$key)),*];
+    };
+}
+
+// minifb key names as written in keybindings.toml
+key_names!(
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+    Down, Left, Right, Up,
+    Apostrophe, Backquote, Backslash, Comma, Equal, LeftBracket, Minus, Period, RightBracket, Semicolon, Slash,
+    Backspace, Delete, End, Enter, Escape, Home, Insert, Menu, PageDown, PageUp, Pause, Space, Tab,
+    NumLock, CapsLock, ScrollLock, LeftShift, RightShift, LeftCtrl, RightCtrl,
+    NumPad0, NumPad1, NumPad2, NumPad3, NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9,
+    NumPadDot, NumPadSlash, NumPadAsterisk, NumPadMinus, NumPadPlus, NumPadEnter,
+    LeftAlt, RightAlt, LeftSuper, RightSuper,
+);
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+    KEY_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|&(_, key)| key)
+}
+
+pub fn key_name(key: Key) -> &'static str {
+    KEY_NAMES.iter().find(|&&(_, k)| k == key).map(|&(n, _)| n).unwrap_or("Unknown")
+}
+
+// Short on-screen form of a key: "Key1" shows as "1", "Comma" as ","
+pub fn key_label(key: Key) -> String {
+    match key {
+        Key::Comma => ",".to_string(),
+        Key::Period => ".".to_string(),
+        Key::Minus => "-".to_string(),
+        Key::Equal => "=".to_string(),
+        Key::LeftBracket => "[".to_string(),
+        Key::RightBracket => "]".to_string(),
+        Key::Semicolon => ";".to_string(),
+        Key::Apostrophe => "'".to_string(),
+        Key::Slash => "/".to_string(),
+        Key::NumPadMinus => "Num -".to_string(),
+        Key::NumPadPlus => "Num +".to_string(),
+        _ => {
+            let name = key_name(key);
+            if let Some(digit) = name.strip_prefix("Key") {
+                digit.to_string()
+            } else if let Some(rest) = name.strip_prefix("NumPad") {
+                format!("Num {}", rest)
+            } else {
+                name.to_string()
+            }
+        }
+    }
+}
+
+/// Keys bound to each [`Action`]. An action may have several keys, or none.
+///
+/// [`InputMap::new`] has the default bindings; [`InputMap::load`] puts keybindings.toml
+/// over them, and [`InputMap::dump`] writes the result back in that format.
+pub struct InputMap {
+    bindings: Vec<Vec<Key>>, // indexed by `Action as usize`
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputMap {
+    /// The default bindings.
+    pub fn new() -> Self {
+        InputMap {
+            bindings: Action::ALL.iter().map(|action| action.default_keys().to_vec()).collect(),
+        }
+    }
+
+    /// Load `path` over the defaults. A missing file just means the defaults; problems
+    /// inside the file are printed as warnings and the affected lines ignored.
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                let (map, warnings) = InputMap::parse(&text);
+                for warning in warnings {
+                    eprintln!("{}: {}", path, warning);
+                }
+                map
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => InputMap::new(),
+            Err(err) => {
+                eprintln!("no se pudo leer '{}', se usan las teclas por defecto: {}", path, err);
+                InputMap::new()
+            }
+        }
+    }
+
+    /// Parse the `action = "Key"` / `action = ["Key", "Key"]` subset of TOML over the
+    /// defaults. Returns the resulting map and human-readable warnings.
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut map = InputMap::new();
+        let mut warnings = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            // Section headers are allowed but carry no meaning
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+            let warn = |msg: String| format!("línea {}: {}", number + 1, msg);
+
+            let Some((name, value)) = key_value(line) else {
+                warnings.push(warn(format!("se esperaba 'acción = \"Tecla\"', no '{}'", line)));
+                continue;
+            };
+            let name = unquote(name);
+            let Some(action) = Action::from_name(name) else {
+                warnings.push(warn(format!("acción desconocida '{}'", name)));
+                continue;
+            };
+
+            let names: Vec<&str> = match list_items(value) {
+                Some(items) => items.into_iter().map(unquote).collect(),
+                None => vec![unquote(value)],
+            };
+
+            let unbind = names.is_empty();
+            let mut keys = Vec::new();
+            for key_name in names {
+                match key_from_name(key_name) {
+                    Some(key) if !keys.contains(&key) => keys.push(key),
+                    Some(_) => {}
+                    None => warnings.push(warn(format!("tecla desconocida '{}' para {}", key_name, action.name()))),
+                }
+            }
+            // An explicit empty list unbinds the action; a list of only unknown keys keeps the default
+            if keys.is_empty() && !unbind {
+                continue;
+            }
+            map.bindings[action as usize] = keys;
+        }
+
+        warnings.extend(map.conflicts().into_iter().map(|(key, a, b)| {
+            format!("la tecla {} está asignada a {} y a {}", key_name(key), a.name(), b.name())
+        }));
+        (map, warnings)
+    }
+
+    /// Keys that trigger more than one action, with each pair of actions they trigger
+    /// (in `Action::ALL` order), whether the file or the defaults bound them.
+    pub fn conflicts(&self) -> Vec<(Key, Action, Action)> {
+        let mut conflicts = Vec::new();
+        for (i, &action) in Action::ALL.iter().enumerate() {
+            for &other in &Action::ALL[i + 1..] {
+                for &key in self.keys(action) {
+                    if self.keys(other).contains(&key) {
+                        conflicts.push((key, action, other));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The keys bound to `action`.
+    pub fn keys(&self, action: Action) -> &[Key] {
+        &self.bindings[action as usize]
+    }
+
+    /// Query actions against a window's keyboard state for this frame.
+    pub fn controls<'a>(&'a self, window: &'a Window) -> Controls<'a> {
+        Controls { window, map: self }
+    }
+
+    /// Effective bindings written back in keybindings.toml format.
+    pub fn dump(&self) -> String {
+        let mut out = String::from("# Teclas efectivas: acción = \"Tecla\" o [\"Tecla\", ...] (nombres de minifb::Key)\n");
+        for action in Action::ALL {
+            let keys: Vec<String> = self.keys(action).iter().map(|&key| format!("\"{}\"", key_name(key))).collect();
+            let value = if keys.len() == 1 { keys[0].clone() } else { format!("[{}]", keys.join(", ")) };
+            out.push_str(&format!("{} = {}\n", action.name(), value));
+        }
+        out
+    }
+}
+
+// Action queries for one frame: an InputMap paired with the window receiving the keys
+pub struct Controls<'a> {
+    window: &'a Window,
+    map: &'a InputMap,
+}
+
+impl Controls<'_> {
+    // Any of the action's keys is held down
+    pub fn down(&self, action: Action) -> bool {
+        self.map.keys(action).iter().any(|&key| self.window.is_key_down(key))
+    }
+
+    // Any of the action's keys went down this frame (no key repeat)
+    pub fn pressed(&self, action: Action) -> bool {
+        self.map.keys(action).iter().any(|&key| self.window.is_key_pressed(key, KeyRepeat::No))
+    }
+
+    // Like `pressed`, but also fires on key repeat while held
+    pub fn pressed_repeat(&self, action: Action) -> bool {
+        self.map.keys(action).iter().any(|&key| self.window.is_key_pressed(key, KeyRepeat::Yes))
+    }
+
+    // Either Shift is held, which turns some actions around (Shift+Tab selects backwards)
+    pub fn shift(&self) -> bool {
+        self.window.is_key_down(Key::LeftShift) || self.window.is_key_down(Key::RightShift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The actions `key` triggers
+    fn actions(map: &InputMap, key: Key) -> Vec<Action> {
+        Action::ALL.iter().copied().filter(|&action| map.keys(action).contains(&key)).collect()
+    }
+
+    #[test]
+    fn remapped_warp_and_movement_keys_replace_the_defaults() {
+        let (map, warnings) = InputMap::parse("warp_1 = \"numpad9\"\nmove_forward = [\"NumPad0\", \"Backquote\"]\n");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(map.keys(Action::Warp1), [Key::NumPad9]);
+        assert_eq!(actions(&map, Key::NumPad9), [Action::Warp1]);
+        assert_eq!(actions(&map, Key::NumPad0), [Action::MoveForward]);
+        assert_eq!(actions(&map, Key::Backquote), [Action::MoveForward]);
+        // The old keys are free now
+        assert!(actions(&map, Key::NumPad1).is_empty());
+        assert!(actions(&map, Key::W).is_empty());
+        // Everything the file doesn't mention keeps its default
+        assert_eq!(map.keys(Action::MoveBack), InputMap::new().keys(Action::MoveBack));
+    }
+
+    #[test]
+    fn unknown_names_are_warnings() {
+        let (map, warnings) = InputMap::parse("move_forward = \"Banana\"\nteleport = \"T\"\nwarp_2\n");
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].starts_with("línea 1:") && warnings[0].contains("'Banana'"), "{}", warnings[0]);
+        assert!(warnings[1].starts_with("línea 2:") && warnings[1].contains("'teleport'"), "{}", warnings[1]);
+        assert!(warnings[2].starts_with("línea 3:"), "{}", warnings[2]);
+        assert_eq!(map.keys(Action::MoveForward), [Key::W], "a line with no known key keeps the default");
+    }
+
+    #[test]
+    fn keys_bound_twice_are_reported() {
+        // Against an action the file left at its default (W moves forward)
+        let (map, warnings) = InputMap::parse("move_back = \"W\"\n");
+        assert_eq!(actions(&map, Key::W), [Action::MoveForward, Action::MoveBack]);
+        assert_eq!(warnings, ["la tecla W está asignada a move_forward y a move_back"]);
+
+        // Between two lines of the file, reported once
+        let (_, warnings) = InputMap::parse("warp_1 = \"NumPad9\"\nwarp_2 = [\"NumPad9\", \"NumPad9\"]\n");
+        assert_eq!(warnings, ["la tecla NumPad9 está asignada a warp_1 y a warp_2"]);
+
+        // The defaults don't collide with each other
+        assert!(InputMap::new().conflicts().is_empty(), "{:?}", InputMap::new().conflicts());
+    }
+
+    #[test]
+    fn dumped_keys_parse_back_to_the_same_map() {
+        let (map, warnings) = InputMap::parse("warp_1 = \"NumPad9\"\nmove_forward = [\"NumPad0\", \"Backquote\"]\nhelp = []\n");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert!(map.keys(Action::Help).is_empty());
+        let (again, warnings) = InputMap::parse(&map.dump());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        for action in Action::ALL {
+            assert_eq!(again.keys(action), map.keys(action), "{}", action.name());
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Seconds the fly-in takes from the far view to the player's starting pose
+pub const INTRO_DURATION: f32 = 8.0;
+// How far round the system the camera travels on its way in, in radians
+const ORBIT_TURN: f32 = 1.25 * PI;
+// Where the camera starts, in multiples of the system's extent: out from the sun, and above it
+const START_DISTANCE: f32 = 2.5;
+const START_HEIGHT: f32 = 1.2;
+// Points of the path from the far view to the starting pose, both included
+const KEYFRAMES: usize = 5;
+// Share of the intro at the end over which the view turns from the sun to the starting heading
+const HANDOFF: f32 = 0.3;
+
+// Point `t` (0..=1) along a Catmull-Rom spline through all of `points`, each segment taking
+// the same share of `t`. The curve passes through every point; the first and last are
+// repeated as the missing neighbours at the ends
+pub fn catmull_rom(points: &[Vec3], t: f32) -> Vec3 {
+    let segments = points.len() - 1;
+    if segments == 0 {
+        return points[0];
+    }
+    let position = t.clamp(0.0, 1.0) * segments as f32;
+    let segment = (position as usize).min(segments - 1);
+    let local = position - segment as f32;
+    let at = |i: isize| points[i.clamp(0, segments as isize) as usize];
+    let i = segment as isize;
+    let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+    let (t2, t3) = (local * local, local * local * local);
+    (p1 * 2.0
+        + (p2 - p0) * local
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+// The opening fly-in: the camera starts far out above the whole system looking at the sun,
+// circles it and closes in along a spline, easing to a stop exactly at the pose the player
+// starts from, so control is handed over without a jump
+pub struct Intro {
+    eyes: Vec<Vec3>,
+    focus: Vec3,
+    end_eye: Vec3,
+    end_center: Vec3,
+    elapsed: f32,
+}
+
+impl Intro {
+    // Ending at `end_eye` looking at `end_center`, around `focus` (the sun) in a system that
+    // reaches `extent` from it
+    pub fn new(end_eye: Vec3, end_center: Vec3, focus: Vec3, extent: f32) -> Self {
+        let offset = end_eye - focus;
+        let end_angle = offset.z.atan2(offset.x);
+        let end_radius = offset.x.hypot(offset.z);
+        let eyes = (0..KEYFRAMES)
+            .map(|k| {
+                let s = k as f32 / (KEYFRAMES - 1) as f32;
+                if k == KEYFRAMES - 1 {
+                    return end_eye;
+                }
+                let angle = end_angle - ORBIT_TURN * (1.0 - s);
+                let radius = extent * START_DISTANCE + (end_radius - extent * START_DISTANCE) * s;
+                let height = extent * START_HEIGHT + (offset.y - extent * START_HEIGHT) * s;
+                focus + Vec3::new(angle.cos() * radius, height, angle.sin() * radius)
+            })
+            .collect();
+        Intro { eyes, focus, end_eye, end_center, elapsed: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(INTRO_DURATION);
+    }
+
+    // Jump to the end: the next pose is the starting one
+    pub fn skip(&mut self) {
+        self.elapsed = INTRO_DURATION;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= INTRO_DURATION
+    }
+
+    // Eye and center of the camera at this point of the intro
+    pub fn pose(&self) -> (Vec3, Vec3) {
+        if self.finished() {
+            return (self.end_eye, self.end_center);
+        }
+        let t = self.elapsed / INTRO_DURATION;
+        let s = t * t * (3.0 - 2.0 * t);
+        let eye = catmull_rom(&self.eyes, s);
+
+        // Looking at the sun, then turning to the starting heading over the last stretch
+        let end_look = self.end_center - self.end_eye;
+        let end_direction = end_look.try_normalize(f32::EPSILON).unwrap_or_else(|| Vec3::new(0.0, 0.0, -1.0));
+        let to_focus = (self.focus - eye).try_normalize(f32::EPSILON).unwrap_or(end_direction);
+        let w = ((s - (1.0 - HANDOFF)) / HANDOFF).clamp(0.0, 1.0);
+        let w = w * w * (3.0 - 2.0 * w);
+        let direction = to_focus.lerp(&end_direction, w).try_normalize(f32::EPSILON).unwrap_or(end_direction);
+        (eye, eye + direction * end_look.magnitude().max(1.0))
+    }
+}
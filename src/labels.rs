@@ -0,0 +1,111 @@
+use nalgebra_glm::Vec4;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::impostor::projected_radius;
+use crate::renderer::clip_to_screen;
+use crate::scene::CelestialBody;
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT, LINE_ADVANCE};
+use crate::uniforms::FrameUniforms;
+
+const LABEL_COLOR: u32 = 0xD0D8E8;
+// Pixels between the top of the body's disc and the label
+const GAP: f32 = 4.0;
+// Projected radius (pixels) at which a label starts fading out, and where it's gone:
+// by then the body is big enough to recognize on its own
+const FADE_START_RADIUS: f32 = 40.0;
+const FADE_END_RADIUS: f32 = 80.0;
+// Seconds for a label to fade in or out when its body is hidden or revealed
+const OCCLUSION_FADE_TIME: f32 = 0.25;
+// Depth slack for the occlusion test, so the body's own surface never hides its label
+const DEPTH_EPSILON: f32 = 1e-4;
+
+struct Placed {
+    left: usize,
+    top: usize,
+    width: usize,
+}
+
+// Body names floating above their discs. A label fades out as its body grows on screen
+// and while something in the depth buffer covers the body; labels that would overlap
+// are nudged upwards
+pub struct Labels {
+    pub visible: bool,
+    occlusion_fades: Vec<f32>, // per body, 1 = fully shown
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Labels { visible: false, occlusion_fades: Vec::new() }
+    }
+
+    // Draw after the scene, before the rest of the HUD, so the depth buffer still holds the bodies
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, bodies: &[CelestialBody], dt: f32) {
+        if !self.visible {
+            return;
+        }
+        self.occlusion_fades.resize(bodies.len(), 1.0);
+
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let mut labels = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let clip = view_projection * Vec4::new(body.position.x, body.position.y, body.position.z, 1.0);
+            if clip.w <= 0.0 {
+                continue; // behind the camera
+            }
+            let center = clip_to_screen(uniforms, clip);
+            if center.x < 0.0 || center.y < 0.0 || center.x >= framebuffer.width as f32 || center.y >= framebuffer.height as f32 {
+                continue;
+            }
+
+            // Occluded when the depth buffer at the center holds something in front of the
+            // body's nearest point
+            let toward_camera = (uniforms.camera_position - body.position).normalize();
+            let nearest = body.position + toward_camera * body.scale;
+            let nearest_clip = view_projection * Vec4::new(nearest.x, nearest.y, nearest.z, 1.0);
+            let nearest_depth = clip_to_screen(uniforms, nearest_clip).z;
+            let occluded = framebuffer
+                .depth(center.x as usize, center.y as usize)
+                .is_some_and(|depth| depth < nearest_depth - DEPTH_EPSILON);
+            let step = dt / OCCLUSION_FADE_TIME;
+            let fade = &mut self.occlusion_fades[i];
+            *fade = if occluded { (*fade - step).max(0.0) } else { (*fade + step).min(1.0) };
+
+            let radius = projected_radius(uniforms, framebuffer, body.position, body.scale);
+            let size_fade = 1.0 - ((radius - FADE_START_RADIUS) / (FADE_END_RADIUS - FADE_START_RADIUS)).clamp(0.0, 1.0);
+            let alpha = *fade * size_fade;
+            if alpha <= 0.0 {
+                continue;
+            }
+
+            let width = text_width(&body.name, 1);
+            let left = (center.x as usize).saturating_sub(width / 2).min(framebuffer.width.saturating_sub(width));
+            let top = (center.y - radius.min(FADE_END_RADIUS) - GAP - GLYPH_HEIGHT as f32).max(0.0) as usize;
+            labels.push((top, left, width, &body.name, alpha));
+        }
+
+        // From the bottom of the screen up, each label moves above any already placed one it
+        // would overlap
+        labels.sort_by_key(|&(top, left, ..)| (std::cmp::Reverse(top), left));
+        let mut placed: Vec<Placed> = Vec::new();
+        for (mut top, left, width, name, alpha) in labels {
+            while let Some(other) = placed.iter().find(|other| {
+                left < other.left + other.width && other.left < left + width
+                    && top < other.top + LINE_ADVANCE && other.top < top + LINE_ADVANCE
+            }) {
+                if other.top < LINE_ADVANCE {
+                    break; // no room above; overlapping beats leaving the screen
+                }
+                top = other.top - LINE_ADVANCE;
+            }
+            let color = (Color::from_hex(LABEL_COLOR) * alpha).to_hex();
+            draw_text(framebuffer, left, top, name, color, 1);
+            placed.push(Placed { left, top, width });
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Software renderer and simulation behind SpaceTravel.
+//!
+//! A [`scene::Scene`] holds the solar system (bodies, orbits, trails, sky) and advances it
+//! with [`scene::Scene::update`]; a [`renderer::Renderer`] draws it from a
+//! [`camera::Camera`] into a [`framebuffer::Framebuffer`]. Nothing here needs a window, so
+//! frames can be rendered offscreen and inspected pixel by pixel:
+//!
+//! ```no_run
+//! use nalgebra_glm::Vec3;
+//! use space_travel::ambient::Meteors;
+//! use space_travel::camera::Camera;
+//! use space_travel::framebuffer::Framebuffer;
+//! use space_travel::mesh::MeshCache;
+//! use space_travel::renderer::Renderer;
+//! use space_travel::scene::{build_scene, SceneConfig};
+//! use space_travel::skybox::{Skybox, SkyboxConfig};
+//!
+//! let config = SceneConfig::default();
+//! let body_mesh = config.body_mesh(&mut MeshCache::new()).unwrap();
+//! let mut scene = build_scene(&config, body_mesh, Skybox::new(SkyboxConfig::default()), Meteors::new(7));
+//! scene.update(1.0 / 60.0);
+//!
+//! let camera = Camera::new(Vec3::new(0.0, 10.0, 50.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+//! let mut framebuffer = Framebuffer::new(800, 600);
+//! let mut renderer = Renderer::new(800, 600);
+//! renderer.set_shader_params(config.shader_params());
+//! renderer.render_scene(&mut framebuffer, &scene, &camera);
+//! println!("{:06X}", framebuffer.pixel(400, 300).unwrap());
+//! ```
+
+/// Color and depth buffers that everything is drawn into.
+pub mod framebuffer;
+/// Triangle rasterization with edge functions on a subpixel grid, and back-face tests.
+pub mod triangle;
+/// Mesh vertices ([`vertex::Vertex`]) and the checks models go through when loaded.
+pub mod vertex;
+/// Wavefront OBJ loading.
+pub mod obj;
+/// Wavefront MTL materials (the diffuse color and map) for OBJ models.
+pub mod mtl;
+/// A minimal glTF 2.0 (.glb / .gltf) mesh reader.
+pub mod gltf;
+/// Meshes and models: icospheres, UV spheres and rings, levels of detail and the mesh cache.
+pub mod mesh;
+/// [`color::Color`], RGB with unquantized channels, and its blending and dithering.
+pub mod color;
+/// [`fragment::Fragment`], one covered pixel of a triangle with its interpolated attributes.
+pub mod fragment;
+/// The vertex shader and each body type's fragment shader, with Gouraud or Phong lighting.
+pub mod shaders;
+/// The camera: first- and third-person (chase) modes, following a body, the bird's eye view.
+pub mod camera;
+/// The camera's fly-in from far outside the system at startup.
+pub mod intro;
+/// [`planet::PlanetType`], which shader and defaults each kind of body uses.
+pub mod planet;
+/// The sky: procedural stars, the milky way band and nebula, or a cubemap.
+pub mod skybox;
+/// Named stars and the constellation lines drawn between them on the sky.
+pub mod constellations;
+/// Camera-facing billboards for trail particles, and projecting points to the screen.
+pub mod sprite;
+/// Images (PNG or PPM) with mip pyramids, for the cubemap sky.
+pub mod texture;
+/// The 5x7 bitmap font the HUD is written in.
+pub mod text;
+/// The tunable numbers each planet shader reads, per body type.
+pub mod shader_params;
+/// The in-game panel for tuning the focused body's shader parameters.
+pub mod tweaker;
+/// Split-screen comparison of a shader's starting and current parameters.
+pub mod compare;
+/// Manual and automatic exposure.
+pub mod exposure;
+/// Exposure brackets: the frame rendered at -1, 0 and +1 EV and saved.
+pub mod bracket;
+/// Meteors and comets crossing the sky.
+pub mod ambient;
+/// Flat discs drawn in place of bodies too small on screen for their mesh.
+pub mod impostor;
+/// The frame clock, the fixed simulation timestep and the time scale.
+pub mod timing;
+/// The flash and star streaks of a warp.
+pub mod warp_effect;
+/// Depth of field around a focused body.
+pub mod dof;
+/// Light shafts from the sun when it is partly hidden.
+pub mod sun_shafts;
+/// Per-stage frame timings, shown on screen.
+pub mod profile;
+/// What the renderer drew on its last frame: triangles, fragments and what was culled.
+pub mod render_stats;
+/// A rolling graph of recent frame times.
+pub mod frame_graph;
+/// Debug overlay of the bodies' bounding spheres and what the culling kept.
+pub mod bounds;
+/// Velocity arrows and orbit ticks for the bird's eye view.
+pub mod motion_overlay;
+/// The TOML subset keybindings.toml and scene files are written in.
+pub mod toml_lite;
+/// Scene files ([`scene::SceneConfig`]), the simulated solar system ([`scene::Scene`]) and
+/// [`scene::build_scene`], which makes one from the other.
+pub mod scene;
+/// Actions, their default keys and keybindings.toml ([`input::InputMap`]).
+pub mod input;
+/// Entering and leaving orbit, and closest approaches, from the camera's distance to each body.
+pub mod events;
+/// Short-lived messages at the bottom of the screen.
+pub mod notices;
+/// The controls overlay and the startup hint.
+pub mod help;
+/// Per-frame and per-draw data the shaders read.
+pub mod uniforms;
+/// Colored point lights next to the sun.
+pub mod lights;
+/// [`renderer::Renderer`], which draws a scene from a camera.
+pub mod renderer;
+/// Model, view, projection and viewport matrices.
+pub mod transform;
+/// The seeds of every random source, derived from one root seed.
+pub mod seeds;
+/// A reference grid on the orbital plane.
+pub mod grid;
+/// A top-down map of the system in a corner of the screen.
+pub mod minimap;
+/// A thumbnail of each body and a contact sheet of them.
+pub mod thumbnails;
+/// Body names drawn over the bodies.
+pub mod labels;
+/// Measuring the distance between two bodies picked with the mouse.
+pub mod ruler;
+/// The bracket drawn around the selected body.
+pub mod reticle;
+/// The panel with the selected body's distances, speed and orbital period.
+pub mod info_panel;
+/// The numbered list of bodies to pick a warp target from.
+pub mod body_list;
+/// Flying the camera to a body and stopping next to it.
+pub mod autopilot;
+/// Dynamic render resolution and upscaling to the window.
+pub mod resolution;
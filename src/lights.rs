@@ -0,0 +1,88 @@
+use nalgebra_glm::Vec3;
+
+// Most point lights a scene may have; they travel in the frame uniforms as a fixed array
+pub const MAX_LIGHTS: usize = 8;
+// How far summed light may climb above the sun's full 1.0 before it's compressed away
+pub const LIGHT_HEADROOM: f32 = 1.0;
+
+// A colored light at a point, for stylized accents such as a blue rim light opposite
+// the sun. It lights the bodies' surfaces only; it's never drawn itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,  // world space
+    pub color: Vec3,     // RGB, 0.0..=1.0 per channel
+    pub intensity: f32,  // brightness at distance 1; falls off with the square of the distance
+    pub range: f32,      // distance where the light has faded out completely
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        PointLight { position: Vec3::zeros(), color: Vec3::repeat(1.0), intensity: 1.0, range: 50.0 }
+    }
+}
+
+impl PointLight {
+    // Inverse-square falloff, windowed so it reaches exactly 0 at `range` instead of
+    // lighting the whole scene faintly. Distances under 1 don't brighten it further
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        if distance >= self.range {
+            return 0.0;
+        }
+        let window = 1.0 - (distance / self.range).powi(4);
+        window * window / distance.max(1.0).powi(2)
+    }
+
+    // RGB light reaching a surface at `position` facing `normal` (unit length)
+    pub fn illuminate(&self, position: &Vec3, normal: &Vec3) -> Vec3 {
+        let to_light = self.position - position;
+        let distance = to_light.magnitude();
+        if distance <= f32::EPSILON {
+            return Vec3::zeros();
+        }
+        let facing = normal.dot(&(to_light / distance)).max(0.0);
+        self.color * (facing * self.intensity * self.attenuation(distance))
+    }
+}
+
+// Up to MAX_LIGHTS point lights, stored inline so the frame uniforms stay a plain copy
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PointLights {
+    lights: [PointLight; MAX_LIGHTS],
+    count: usize,
+}
+
+impl PointLights {
+    // The first MAX_LIGHTS of `lights`
+    pub fn new(lights: &[PointLight]) -> Self {
+        let mut result = PointLights::default();
+        for (slot, light) in result.lights.iter_mut().zip(lights) {
+            *slot = *light;
+        }
+        result.count = lights.len().min(MAX_LIGHTS);
+        result
+    }
+
+    pub fn as_slice(&self) -> &[PointLight] {
+        &self.lights[..self.count]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Sum of every light's contribution at a surface point
+    pub fn illuminate(&self, position: &Vec3, normal: &Vec3) -> Vec3 {
+        self.as_slice().iter().fold(Vec3::zeros(), |sum, light| sum + light.illuminate(position, normal))
+    }
+}
+
+// Roll off light above 1.0 (the sun at full strength) so it approaches 1 + LIGHT_HEADROOM
+// instead of growing without bound: several bright lights on one spot brighten it without
+// washing it out to white. Light at or below 1.0 is left as it is
+pub fn compress_light(light: f32) -> f32 {
+    if light <= 1.0 {
+        return light.max(0.0);
+    }
+    let excess = light - 1.0;
+    1.0 + LIGHT_HEADROOM * excess / (LIGHT_HEADROOM + excess)
+}
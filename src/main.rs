@@ -1,318 +1,273 @@
-use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::Vec3;
+use minifb::{KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::f32::consts::PI;
+use std::path::Path;
+use std::time::Instant;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+mod cli;
+mod bench;
+
+use space_travel::framebuffer::Framebuffer;
+use space_travel::mesh::{ship_placeholder, BodyMesh, MeshCache, Model};
+use space_travel::obj::Obj;
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::intro::Intro;
+use space_travel::planet::PlanetType;
+use space_travel::skybox::{Skybox, SkyboxConfig, NebulaConfig};
+use space_travel::sprite::project;
+use space_travel::tweaker::ShaderTweaker;
+use space_travel::compare::ShaderComparison;
+use space_travel::exposure::Exposure;
+use space_travel::bracket::{render_bracket, save_bracket};
+use space_travel::timing::{FixedTimestep, FrameClock};
+use space_travel::warp_effect::WarpEffect;
+use space_travel::dof::DepthOfField;
+use space_travel::sun_shafts::SunShafts;
+use space_travel::bounds::BoundsOverlay;
+use space_travel::motion_overlay::MotionOverlay;
+use space_travel::profile::FrameProfile;
+use space_travel::frame_graph::FrameGraph;
+use space_travel::minimap::Minimap;
+use space_travel::labels::Labels;
+use space_travel::ruler::Ruler;
+use space_travel::reticle::{draw_crosshair, Reticle};
+use space_travel::info_panel::{draw_info_panel, BodyInfo};
+use space_travel::autopilot::{Autopilot, AutopilotEvent};
+use space_travel::body_list::{next_selection, previous_selection, BodyList};
+use space_travel::resolution::{upscale, DynamicResolution};
+use space_travel::scene::{build_scene, sun_position, CelestialBody, OrbitSpeeds, Scene, SceneConfig};
+use space_travel::input::{Action, Controls, InputMap};
+use space_travel::events::{ProximityTracker, SpaceEvent};
+use space_travel::notices::Notices;
+use space_travel::help::HelpOverlay;
+use space_travel::ambient::Meteors;
+use space_travel::renderer::Renderer;
+use space_travel::seeds::{Seeds, DEFAULT_ROOT};
+use space_travel::uniforms::FrameUniforms;
+use space_travel::shaders::orbit_light;
+use space_travel::text::{draw_text, text_width, GLYPH_HEIGHT, LINE_ADVANCE};
+use space_travel::{thumbnails, transform};
+use bench::BenchReport;
+
+// Caras del cubemap opcional del skybox (+X, -X, +Y, -Y, +Z, -Z)
+const SKYBOX_DIR: &str = "assets/skybox";
+const SKYBOX_FACES: [&str; 6] = [
+    "assets/skybox/px.png",
+    "assets/skybox/nx.png",
+    "assets/skybox/py.png",
+    "assets/skybox/ny.png",
+    "assets/skybox/pz.png",
+    "assets/skybox/nz.png",
+];
 
-mod framebuffer;
-mod triangle;
-mod vertex;
-mod obj;
-mod color;
-mod fragment;
-mod shaders;
-mod camera;
-mod planet;
-//mod normal_map;
-mod skybox;
-
-use framebuffer::Framebuffer;
-use vertex::Vertex;
-use obj::Obj;
-use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader};
-use fastnoise_lite::{FastNoiseLite, NoiseType};
-use planet::PlanetType;
-//use normal_map::init_normal_map;
-use skybox::Skybox;
-
-pub struct Uniforms {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: FastNoiseLite
-}
-
-pub struct CelestialBody {
-    position: Vec3,
-    scale: f32,
-    rotation: Vec3,
-    shader_type: PlanetType,
-    trail: Trail,
-}
-
-pub struct Trail {
-    particles: Vec<TrailParticle>,
-    max_particles: usize,
-}
-
-pub struct TrailParticle {
-    position: Vec3,
-    color: u32,
-    lifetime: f32,
-    size: f32,
-}
-
-impl Trail {
-    fn new(max_particles: usize) -> Self {
-        Self {
-            particles: Vec::with_capacity(max_particles),
-            max_particles,
+// Archivo opcional de asignación de teclas (acción = "Tecla"), ver --dump-keys
+const KEYBINDINGS_PATH: &str = "keybindings.toml";
+
+// Carpeta (relativa al directorio de ejecución) de las capturas con exposiciones escalonadas
+const SCREENSHOT_DIR: &str = "screenshots";
+
+// Modelo de la nave de la cámara de persecución
+const SPACESHIP_MODEL: &str = "assets/models/spaceship.obj";
+
+// Paso fijo de la simulación (60 Hz): las órbitas, las estelas y el tiempo de los shaders
+// avanzan siempre de a un paso así, sea cual sea la velocidad de cuadros
+const SIM_STEP: f32 = 1.0 / 60.0;
+
+// Distancia inicial al seguir un cuerpo, en múltiplos de su escala
+const FOLLOW_DISTANCE_FACTOR: f32 = 6.0;
+
+// Distancia de llegada del warp en múltiplos de la escala del cuerpo destino
+const WARP_DISTANCE_FACTOR: f32 = 4.0;
+// Elevación sobre el plano orbital al llegar, para ver el cuerpo desde un poco arriba
+const WARP_ELEVATION: f32 = 0.35;
+// Margen mínimo respecto a la superficie de cualquier cuerpo, en múltiplos de su escala
+const WARP_CLEARANCE: f32 = 1.5;
+
+// Velocidad de giro de la luz manual, en radianes por segundo
+const LIGHT_TURN_SPEED: f32 = PI / 2.0;
+
+// Punto de llegada seguro frente a un cuerpo: del lado del Sol (o de la cámara, si el
+// destino es el Sol), a una distancia proporcional a su tamaño y fuera de cualquier otro cuerpo
+fn warp_arrival(celestial_bodies: &[CelestialBody], target: usize, camera_eye: Vec3) -> Vec3 {
+    let body = &celestial_bodies[target];
+    let sun = sun_position(celestial_bodies);
+
+    let toward = if body.shader_type == PlanetType::Sun { camera_eye } else { sun };
+    let horizontal = (toward - body.position).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+    let direction = (horizontal + Vec3::new(0.0, WARP_ELEVATION, 0.0)).normalize();
+    let mut arrival = body.position + direction * body.scale.max(0.1) * WARP_DISTANCE_FACTOR;
+
+    // Empujar el punto hacia afuera de cualquier cuerpo que lo contenga (unas pocas
+    // pasadas bastan: empujar fuera de uno puede meterlo en un vecino)
+    for _ in 0..4 {
+        let mut moved = false;
+        for other in celestial_bodies {
+            let safe_radius = other.scale * WARP_CLEARANCE;
+            let offset = arrival - other.position;
+            if offset.magnitude() < safe_radius {
+                let away = offset.try_normalize(f32::EPSILON).unwrap_or(direction);
+                arrival = other.position + away * safe_radius;
+                moved = true;
+            }
         }
-    }
-
-    fn update(&mut self, dt: f32) {
-        self.particles.retain_mut(|particle| {
-            particle.lifetime -= dt;
-            particle.size *= 0.999;
-            particle.lifetime > 0.0
-        });
-    }
-
-    fn add_particle(&mut self, position: Vec3, color: u32, is_moon: bool, planet_type: &PlanetType) {
-        if self.particles.len() >= self.max_particles {
-            self.particles.remove(0);
+        if !moved {
+            break;
         }
-
-        let lifetime = if is_moon { 2.0 } else { 200000.0 };
-        let size = if is_moon { 0.2 } else { 0.5 };
-
-        let trail_color = match planet_type {
-            PlanetType::Sun => 0xFFFFA500,       // Naranja brillante
-            PlanetType::RockyPlanet => 0xFFD2B48C, // Marrón claro (tono arena)
-            PlanetType::Earth => 0xFF32CD32,     // Verde limón
-            PlanetType::CrystalPlanet => 0xFFFF00FF, // Fucsia
-            PlanetType::FirePlanet => 0xFFFF4500,    // Rojo anaranjado (tono de fuego)
-            PlanetType::WaterPlanet => 0xFF40E0D0,   // Turquesa
-            PlanetType::CloudPlanet => 0xFFFFD700,   // Dorado
-            PlanetType::Moon => 0xFF9370DB,         // Morado
-            PlanetType::Asteroid => 0xFFFFA500,     // Naranja brillante (tono cercano a Sun)
-            PlanetType::Spaceship => 0xFFFFFFFF,    // Blanco
-            PlanetType::Trail => 0xFF888888,        // Gris
-            _ => 0xFFFFFFFF,
-        };
-
-        self.particles.push(TrailParticle {
-            position,
-            color: trail_color,
-            lifetime,
-            size,
-        });
     }
+    arrival
 }
 
-fn create_noise() -> FastNoiseLite {
-    create_cloud_noise() 
+// Función para realizar el warping al cuerpo `target` en su posición actual
+fn instant_warp(camera: &mut Camera, celestial_bodies: &[CelestialBody], target: usize) {
+    let arrival = warp_arrival(celestial_bodies, target, camera.eye);
+    camera.stop_follow(); // El salto cancela el seguimiento de un cuerpo
+    camera.look_at(arrival, celestial_bodies[target].position); // Enfocar en el nuevo destino
+    camera.snap_chase(); // La cámara de persecución no debe cruzar el sistema tras el salto
 }
 
-fn create_cloud_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise
+// Índice del cuerpo celeste más cercano a una posición (el "enfocado")
+fn focused_body_index(celestial_bodies: &[CelestialBody], position: Vec3) -> usize {
+    celestial_bodies.iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            let da = (a.position - position).magnitude() - a.scale;
+            let db = (b.position - position).magnitude() - b.scale;
+            da.total_cmp(&db)
+        })
+        .map(|(i, _)| i)
+        .expect("la escena debe tener al menos un cuerpo celeste")
 }
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
-
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
-
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
-
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
-    );
-
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
-
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
-    );
-
-    transform_matrix * rotation_matrix
-}
-
-
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
-
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 75.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
+fn focused_body(celestial_bodies: &[CelestialBody], position: Vec3) -> &CelestialBody {
+    &celestial_bodies[focused_body_index(celestial_bodies, position)]
 }
 
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0
-    )
+// Índice del cuerpo celeste bajo el punto (x, y) de la pantalla: el primero que corta el
+// rayo que sale de la cámara por ese píxel
+fn picked_body_index(celestial_bodies: &[CelestialBody], uniforms: &FrameUniforms, x: f32, y: f32, width: usize, height: usize) -> Option<usize> {
+    let (origin, direction) = transform::pick_ray(uniforms, x, y, width as f32, height as f32)?;
+    celestial_bodies.iter()
+        .enumerate()
+        .filter(|(_, body)| body.visibility.body)
+        .filter_map(|(i, body)| transform::ray_sphere(origin, direction, body.position, body.bounding_radius()).map(|t| (i, t)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], planet_type: &PlanetType) {
-    // Vertex Shader Stage
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    // Primitive Assembly Stage
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-
-    // Rasterization Stage
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    // Fragment Processing Stage
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            // Apply fragment shader
-            let shaded_color = fragment_shader(&fragment, &uniforms, planet_type);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+// Modo de cámara actual para el panel de ayuda
+fn camera_status(camera: &Camera, celestial_bodies: &[CelestialBody]) -> String {
+    let mode = if camera.bird_eye_active {
+        "vista de pájaro"
+    } else {
+        match camera.mode {
+            CameraMode::FirstPerson => "cabina",
+            CameraMode::ThirdPerson => "persecución",
         }
+    };
+    match camera.follow {
+        Some(follow) => format!("Cámara: {}, siguiendo {}", mode, celestial_bodies[follow.body].name),
+        None => format!("Cámara: {}", mode),
     }
 }
 
-fn render_trail(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    particle: &TrailParticle,
-) {
-    let model_matrix = create_model_matrix(
-        particle.position,
-        particle.size,
-        Vec3::new(0.0, 0.0, 0.0)
-    );
-
-    let position_clip = uniforms.projection_matrix * uniforms.view_matrix * model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
-    
-    let position_clip_vec4 = position_clip.data.as_slice(); // Accede a los datos de la matriz como un slice
-    if position_clip_vec4[3] <= 0.0 {
-        return;
+// Mensaje del HUD para un evento de proximidad
+fn describe_event(celestial_bodies: &[CelestialBody], event: SpaceEvent) -> String {
+    match event {
+        SpaceEvent::EnteredOrbit(body) => format!("Entrando en órbita: {}", celestial_bodies[body].name),
+        SpaceEvent::LeftOrbit(body) => format!("Saliendo de la órbita: {}", celestial_bodies[body].name),
+        SpaceEvent::ClosestApproach(body, distance) => format!(
+            "Máximo acercamiento a {}: {:.1}",
+            celestial_bodies[body].name,
+            distance
+        ),
     }
+}
 
-    let position_ndc = Vec3::new(
-        position_clip_vec4[0] / position_clip_vec4[3],
-        position_clip_vec4[1] / position_clip_vec4[3],
-        position_clip_vec4[2] / position_clip_vec4[3],
-    );
-
-    let position_screen = uniforms.viewport_matrix * Vec4::new(
-        position_ndc.x,
-        position_ndc.y,
-        position_ndc.z,
-        1.0,
-    );
-
-    let x = position_screen.x as usize;
-    let y = position_screen.y as usize;
+fn create_window(options: &cli::Options) -> Window {
+    // Pantalla completa: ventana sin bordes encima de todo, en la esquina del monitor
+    let window_options = WindowOptions {
+        borderless: options.fullscreen,
+        topmost: options.fullscreen,
+        ..WindowOptions::default()
+    };
+    let mut window = match Window::new("Rust Graphics - Renderer Example", options.width, options.height, window_options) {
+        Ok(window) => window,
+        Err(err) => {
+            eprintln!("no se pudo crear la ventana: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-    if x < framebuffer.width && y < framebuffer.height {
-        let alpha = (particle.lifetime * 255.0) as u32;
-        let color = (particle.color & 0x00FFFFFF) | (alpha << 24);
-        
-        framebuffer.set_current_color(color);
-        framebuffer.point(x, y, position_screen.z);
+    if options.fullscreen {
+        window.set_position(0, 0);
+    } else {
+        window.set_position(500, 500);
     }
+    // El límite de FPS lo aplica el FrameClock con el tiempo medido de cada frame
+    window.set_target_fps(0);
+    window.update();
+    window
 }
 
-// Definir puntos de destino en el sistema solar
-static WARP_POINTS: &[Vec3] = &[
-    Vec3::new(0.0, 0.0, 0.0),   // Sol
-    Vec3::new(-4.0, 0.0, 0.0),  // Asteroide
-    Vec3::new(6.0, 0.0, 0.0),   // Planeta Rocoso
-    Vec3::new(12.0, 0.0, 0.0),  // Tierra
-    Vec3::new(18.0, 0.0, 0.0),  // Planeta Cristal
-    Vec3::new(24.0, 0.0, 0.0),  // Planeta de Fuego
-    Vec3::new(30.0, 0.0, 0.0),  // Planeta de Agua
-    Vec3::new(36.0, 0.0, 0.0),  // Planeta Nube
-];
-
-// Función para realizar el warping
-fn instant_warp(camera: &mut Camera, target_position: Vec3) {
-    camera.eye = target_position + Vec3::new(0.0, 0.0, 10.0); // Ajusta la posición de la cámara
-    camera.center = target_position; // Enfocar en el nuevo destino
+// Guarda el framebuffer como out_dir/frame_NNNN.png
+fn save_frame(framebuffer: &Framebuffer, out_dir: &Path, frame: u32) -> std::io::Result<()> {
+    framebuffer.save_png(&out_dir.join(format!("frame_{:04}.png", frame)))
 }
 
-fn is_in_frustum(body: &CelestialBody, view_matrix: &Mat4, projection_matrix: &Mat4) -> bool {
-    let model_matrix = create_model_matrix(body.position, body.scale, body.rotation);
-    let mvp_matrix = projection_matrix * view_matrix * model_matrix;
-
-    // Comprobar si el cuerpo celeste está dentro del frustum
-    let clip_space_position = mvp_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
-    let w = clip_space_position.w;
+// Guarda <cuerpo>.png para cada cuerpo y sheet.png con todas las miniaturas
+fn save_thumbnails(renderer: &mut Renderer, scene: &Scene, out_dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    let mut sheet = Vec::new();
+    for (index, name) in thumbnails::names(&scene.bodies).into_iter().enumerate() {
+        let mut framebuffer = Framebuffer::new(thumbnails::SIZE, thumbnails::SIZE);
+        thumbnails::render_thumbnail(renderer, &mut framebuffer, scene, index);
+        framebuffer.save_png(&out_dir.join(format!("{}.png", name)))?;
+        sheet.push((name, framebuffer));
+    }
 
-    // Comprobar si está dentro de los límites del frustum
-    clip_space_position.x >= -w && clip_space_position.x <= w &&
-    clip_space_position.y >= -w && clip_space_position.y <= w &&
-    clip_space_position.z >= -w && clip_space_position.z <= w
+    let columns = (sheet.len() as f32).sqrt().ceil() as usize;
+    let sheet = thumbnails::contact_sheet(&sheet, columns);
+    sheet.save_png(&out_dir.join("sheet.png"))?;
+    println!("{} miniaturas guardadas en {}", scene.bodies.len(), out_dir.display());
+    Ok(())
 }
 
 fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(cli::CliError::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
+    // Teclas: las de keybindings.toml (si existe) sobre las por defecto
+    let input_map = InputMap::load(KEYBINDINGS_PATH);
+    if options.dump_keys {
+        print!("{}", input_map.dump());
+        return;
+    }
+
+    let framebuffer_width = options.width;
+    let framebuffer_height = options.height;
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Rust Graphics - Renderer Example",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-        .unwrap();
-
-    window.set_position(500, 500);
-    window.update();
+    // En modo headless y en el benchmark no se crea ninguna ventana: se renderiza directo al framebuffer
+    let offscreen = options.headless.is_some() || options.bench.is_some() || options.thumbnails.is_some();
+    let mut window = if offscreen {
+        None
+    } else {
+        Some(create_window(&options))
+    };
 
     framebuffer.set_background_color(0x000000);
 
-    // model position
-    let translation = Vec3::new(0.0, 0.0, 0.0);
-    let rotation = Vec3::new(0.0, 0.0, 0.0);
-    let scale = 1.0f32;
-
     // camera parameters
     let mut camera = Camera::new(
         Vec3::new(0.0, 0.0, 10.0),
@@ -320,339 +275,891 @@ fn main() {
         Vec3::new(0.0, 1.0, 0.0)
     );
 
-    let obj = Obj::load("assets/models/smooth_sphere.obj").expect("Failed to load obj");
-    let vertex_arrays = obj.get_vertex_array(); 
-    let mut time = 0;
-    let skybox = Skybox::new(1000);
-
-    let noise = create_noise();
-    let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-    let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-    let mut uniforms = Uniforms { 
-        model_matrix: Mat4::identity(), 
-        view_matrix: Mat4::identity(), 
-        projection_matrix, 
-        viewport_matrix, 
-        time: 0, 
-        noise
+    // Todas las semillas salen de una sola raíz (--seed), así una corrida se reproduce entera
+    let seeds = Seeds::from_root(options.seed.unwrap_or(DEFAULT_ROOT));
+    let meteors = Meteors::new(seeds.meteors);
+
+    // Escena: la de --scene si se indicó (R la vuelve a leer), si no el sistema solar por defecto
+    let scene_config = match &options.scene {
+        Some(path) => SceneConfig::load(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }),
+        None => SceneConfig::default(),
     };
-
-    let mut celestial_bodies = vec![
-        CelestialBody {
-            position: Vec3::new(0.0, 0.0, 0.0),
-            scale: 2.0,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Sun,
-            trail: Trail::new(1000),
-        },
-        CelestialBody {
-            position: Vec3::new(-4.0, 0.0, 0.0),
-            scale: 0.3,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Asteroid,
-            trail: Trail::new(7000),
-        },
-        CelestialBody {
-            position: Vec3::new(6.0, 0.0, 0.0),
-            scale: 0.4,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::RockyPlanet,
-            trail: Trail::new(9000),
-        },
-        CelestialBody {
-            position: Vec3::new(12.0, 0.0, 0.0),
-            scale: 0.6,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Earth,
-            trail: Trail::new(12000),
-        },
-        CelestialBody {
-            position: Vec3::new(18.0, 0.0, 0.0),
-            scale: 0.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::CrystalPlanet,
-            trail: Trail::new(14000),
-        },
-        CelestialBody {
-            position: Vec3::new(24.0, 0.0, 0.0),
-            scale: 0.7,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::FirePlanet,
-            trail: Trail::new(17000),
-        },
-        CelestialBody {
-            position: Vec3::new(30.0, 0.0, 0.0),
-            scale: 1.0,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::WaterPlanet,
-            trail: Trail::new(19000),
-        },
-        CelestialBody {
-            position: Vec3::new(36.0, 0.0, 0.0),
-            scale: 0.8,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::CloudPlanet,
-            trail: Trail::new(22000),
-        },
-        CelestialBody {
-            position: Vec3::new(12.0, 0.0, 2.0),
-            scale: 0.2,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Moon,
-            trail: Trail::new(600),
-        },
-    ];
-
-    // Definir los radios de órbita para cada planeta
-    let planet_orbit_radii = vec![
-        0.0, // Radio para el primer planeta (Sol)
-        10.0, // Radio para el segundo planeta
-        15.0, // Radio para el tercer planeta
-        20.0, // Radio para el cuarto planeta (Tierra)
-        25.0, // Radio para el quinto planeta
-        30.0, // Radio para el sexto planeta
-        35.0, // Radio para el séptimo planeta
-        40.0, // Radio para el octavo planeta
-        5.0,  // Radio para el asteroide (más cerca del sol)
-    ];
-
-    // Velocidad de órbita base
-    let base_orbit_speed = 0.02; // Aumentar la velocidad base para el planeta más cercano
-
-    let mut planet_angles: Vec<f32> = vec![0.0; celestial_bodies.len()]; // Ángulos iniciales de los planetas
-
-    // Definir un ángulo para la luna
-    let mut moon_angle: f32 = 0.0; // Ángulo inicial de la luna
-    let moon_orbit_radius = 0.5; // Radio de órbita de la luna alrededor de la Tierra
-
-    // Definir colores para cada cuerpo celeste (sin contar el sol)
-    let colors = vec![
-        0xFF0000, // Rojo para el primer planeta
-        0x00FF00, // Verde para el segundo planeta
-        0x0000FF, // Azul para el tercer planeta
-        0xFFFF00, // Amarillo para el cuarto planeta (Tierra)
-        0xFF00FF, // Magenta para el quinto planeta
-        0x00FFFF, // Cian para el sexto planeta
-        0xFFA500, // Naranja para el séptimo planeta
-        0x800080, // Púrpura para el octavo planeta
-        0xFFFFFF, // Blanco para el asteroide
-    ];
-
-    // Almacenar las posiciones anteriores de cada cuerpo celeste
-    let mut previous_positions: Vec<Vec<Vec3>> = vec![vec![]; celestial_bodies.len()];
-
-    // Cargar el modelo de la nave
-    let spaceship_obj = Obj::load("assets/models/spaceship.obj").expect("Failed to load spaceship obj");
-
-    // Variables para el tiempo delta y entradas
-    let delta_time = 0.016; // Por ejemplo, 60 FPS
-    let inputs = (1.0, 0.0, 0.0, 0.1, 0.0, 0.0); // (forward, right, up, roll, pitch, yaw)
-
-    while window.is_open() {
-        if window.is_key_down(Key::Escape) {
-            break;
+    // Todas las mallas se cargan o generan aquí, antes del bucle; R reutiliza las que ya están
+    let mut meshes = MeshCache::new();
+    let assets = load_assets(&scene_config, &mut meshes);
+    // Sin cubemap, las estrellas procedurales con la semilla del cielo
+    let skybox = assets.cubemap_sky.unwrap_or_else(|| {
+        let sky_config = SkyboxConfig { seed: seeds.sky, ..SkyboxConfig::default() };
+        Skybox::new(sky_config).with_nebula(Some(NebulaConfig { seed: seeds.nebula, ..NebulaConfig::default() }))
+    });
+
+    let mut renderer = Renderer::new(framebuffer_width, framebuffer_height)
+        .with_seeds(&seeds)
+        .with_ship(assets.ship_model);
+    renderer.shading = options.shading;
+    renderer.log_depth = options.log_depth;
+    renderer.threads = options.threads;
+    renderer.particle_softness = options.particle_softness;
+    // Modo determinista: los shaders animan con el número de cuadro, no con el reloj
+    renderer.time_from_frame = options.deterministic;
+    // Con --deterministic F6 también sale de la semilla raíz
+    let mut sky_rng = options.deterministic.then(|| StdRng::seed_from_u64(seeds.sky_regeneration));
+    let mut exposure = Exposure::new();
+    let mut warp_effect = WarpEffect::new();
+    let mut proximity = ProximityTracker::new();
+    let mut notices = Notices::new();
+    for failure in assets.failures {
+        notices.push(failure);
+    }
+    let mut help = HelpOverlay::new();
+    let mut frame_graph = FrameGraph::new();
+    // Panel con los conteos del último cuadro (renderer.stats)
+    let mut show_render_stats = false;
+    let mut minimap = Minimap::new();
+    let mut labels = Labels::new();
+    let mut ruler = Ruler::new();
+    // Cuerpo resaltado con la retícula: el último elegido con clic, Tab, las teclas
+    // numéricas o F
+    let mut selected: Option<usize> = None;
+    let mut reticle = Reticle::new();
+    // Vuelo automático hasta el cuerpo seleccionado (G) y el camino que hizo
+    let mut autopilot = Autopilot::new();
+    // Lista numerada de los cuerpos para elegir el destino del warp
+    let mut body_list = BodyList::new();
+    // Resolución interna adaptativa (--dynamic-res): la escena se dibuja en un framebuffer
+    // más chico que se escala a la ventana; None mientras la escala es 100%
+    let mut resolution = DynamicResolution::new();
+    resolution.enabled = options.dynamic_resolution;
+    resolution.target_fps = options.target_fps;
+    let mut scene_framebuffer: Option<Framebuffer> = None;
+    // Perfil por etapas: F9 lo muestra; sin ventana siempre mide. Se imprime al salir si está activo
+    renderer.profile = FrameProfile::new(offscreen);
+    let mut tweaker = ShaderTweaker::new();
+    let mut comparison = ShaderComparison::new();
+    let mut depth_of_field = DepthOfField::new();
+    let mut bounds = BoundsOverlay::new();
+    // Flechas de velocidad y marcas en las órbitas, solo en la vista de pájaro
+    let mut motion = MotionOverlay::new();
+    let mut sun_shafts = SunShafts::new();
+    depth_of_field.strength = options.dof_strength;
+    depth_of_field.focus_range = options.dof_range;
+
+    renderer.set_shader_params(scene_config.shader_params());
+    let mut scene = build_scene(&scene_config, assets.body_mesh, skybox, meteors);
+    // Degradado vertical del fondo (arriba, abajo) si la escena lo pide; si no, negro sólido
+    framebuffer.set_background_gradient(scene.background_gradient);
+
+    // Miniaturas: un cuadro encuadrado en cada cuerpo y la hoja con todas, y terminar
+    if let Some(out_dir) = &options.thumbnails {
+        let mut thumbnail_renderer = Renderer::new(thumbnails::SIZE, thumbnails::SIZE).with_seeds(&seeds);
+        thumbnail_renderer.shading = options.shading;
+        thumbnail_renderer.log_depth = options.log_depth;
+        thumbnail_renderer.set_shader_params(scene_config.shader_params());
+        scene.update(SIM_STEP);
+        if let Err(err) = save_thumbnails(&mut thumbnail_renderer, &scene, Path::new(out_dir)) {
+            eprintln!("no se pudieron guardar las miniaturas en '{}': {}", out_dir, err);
+            std::process::exit(1);
         }
+        return;
+    }
 
-        time += 1;
-
-        handle_input(&window, &mut camera, &celestial_bodies);
-
-        framebuffer.clear();
-
-        skybox.render(&mut framebuffer, &uniforms, camera.eye);
-
-        // Guardar la posición de la Tierra antes de modificar celestial_bodies
-        let earth_position = celestial_bodies.iter()
-            .find(|b| b.shader_type == PlanetType::Earth)
-            .map(|b| b.position)
-            .unwrap_or(Vec3::new(0.0, 0.0, 0.0)); // Valor por defecto en caso de que no se encuentre
+    // Reloj de frames: mide el tiempo real entre frames y aplica el límite de FPS
+    let mut clock = FrameClock::new(options.fps_cap);
+    let mut timestep = FixedTimestep::new(SIM_STEP);
 
-        // Actualizar la posición de los planetas en órbita
-        for (i, body) in celestial_bodies.iter_mut().enumerate() {
-            if body.shader_type == PlanetType::Sun {
-                continue; // El sol no se mueve
+    if let Some(headless) = &options.headless {
+        if let Err(err) = std::fs::create_dir_all(&headless.out_dir) {
+            eprintln!("no se pudo crear la carpeta de salida '{}': {}", headless.out_dir, err);
+            std::process::exit(1);
+        }
+    }
+    let mut frame: u32 = 0;
+    // Sin ventana se corre una cantidad fija de cuadros y se mide cada uno
+    let frame_limit = options.headless.as_ref().map(|headless| headless.frame_count).or(options.bench);
+    let mut frame_times = Vec::new();
+    // minifb solo dice si el botón está apretado; el clic es el cambio de suelto a apretado
+    let mut mouse_was_down = false;
+    let mut paused = false;
+    // Recorrido inicial de la cámara desde lejos hasta la pose de arranque (solo con ventana;
+    // --no-intro lo salta)
+    let mut intro = (window.is_some() && options.intro).then(|| {
+        let focus = sun_position(&scene.bodies);
+        let extent = scene.bodies.iter().map(|body| (body.position - focus).magnitude() + body.scale).fold(1.0, f32::max);
+        Intro::new(camera.eye, camera.center, focus, extent)
+    });
+
+    loop {
+        let frame_start = Instant::now();
+        if let Some(window) = &window {
+            if !window.is_open() || input_map.controls(window).down(Action::Quit) {
+                break;
             }
+        }
+        frame += 1;
+
+        // Tiempo real del frame: la cámara y el HUD avanzan con él, así un frame lento no
+        // produce cámara lenta. Sin ventana (o con --deterministic) cada cuadro dura un paso
+        // de simulación, para que la salida no dependa de la velocidad de la máquina
+        let measured = window.is_some().then(|| clock.tick()).filter(|_| !options.deterministic);
+        let delta_time = measured.unwrap_or(SIM_STEP);
+
+        // Durante la intro la cámara sigue su recorrido; cualquier tecla o clic la termina en
+        // la pose de arranque, y esa tecla hace lo suyo como siempre
+        if let Some(playing) = &mut intro {
+            let skipped = window.as_ref().is_some_and(|window| {
+                !window.get_keys_pressed(KeyRepeat::No).is_empty() || window.get_mouse_down(MouseButton::Left)
+            });
+            if skipped {
+                playing.skip();
+            } else {
+                playing.update(delta_time);
+            }
+            let (eye, center) = playing.pose();
+            camera.look_at(eye, center);
+            camera.snap_chase();
+            if playing.finished() {
+                intro = None;
+            }
+        }
 
-            // Calcular la posición en órbita
-            let orbit_radius = planet_orbit_radii[i]; // Usar el radio de órbita correspondiente
-            let angle = planet_angles[i]; // Usar el ángulo correspondiente
-
-            // Calcular la velocidad de órbita en función del radio
-            let orbit_speed = base_orbit_speed / orbit_radius; // Planetas más lejanos se mueven más lento
+        if let Some(window) = &window {
+            let controls = input_map.controls(window);
+            // Cualquier control manual de la nave le quita el mando al piloto automático
+            if Action::PILOTING.iter().any(|&action| controls.down(action)) && autopilot.cancel() {
+                notices.push("Piloto automático desactivado".to_string());
+            }
+            handle_input(&controls, &mut camera, &scene.bodies, delta_time);
+        }
 
-            // Actualizar la posición del cuerpo celeste
-            body.position.x = orbit_radius * angle.cos(); // Posición en X
-            body.position.z = orbit_radius * angle.sin(); // Posición en Z
+        // El cuerpo capturado sigue a la nave desde donde quedó en este cuadro
+        scene.hold(camera.eye, delta_time);
+
+        // Órbitas, estelas, meteoros y cometas, en pasos fijos; en pausa todo queda quieto
+        // (la cámara no). Con el reloj real se corren los pasos que entran en el tiempo
+        // acumulado (por la velocidad de la simulación) y los cuerpos se dibujan entre los dos
+        // últimos, para que se muevan suave aunque los cuadros no coincidan con los pasos; sin
+        // reloj real se corre un paso por cuadro, estirado por esa velocidad
+        if !paused {
+            match measured {
+                Some(dt) => {
+                    for _ in 0..timestep.advance(dt) {
+                        scene.update(timestep.step());
+                    }
+                    scene.interpolate(timestep.alpha());
+                }
+                None => scene.update(timestep.frame_step()),
+            }
+        }
 
-            // Incrementar el ángulo para simular la órbita
-            planet_angles[i] += orbit_speed; // Incrementar el ángulo de órbita
+        // El benchmark mueve la cámara por una órbita fija que solo depende del tiempo simulado
+        // (sin amortiguar la persecución, que si no cruzaría el sistema en el primer cuadro)
+        if options.bench.is_some() {
+            let (eye, center) = bench::camera_pose(scene.time);
+            camera.look_at(eye, center);
+            camera.snap_chase();
+        }
 
-            // Si el cuerpo es la luna, ajustar su posición respecto a la Tierra
-            if body.shader_type == PlanetType::Moon {
-                body.position = earth_position + Vec3::new(moon_orbit_radius * moon_angle.cos(), 0.0, moon_orbit_radius * moon_angle.sin());
+        // El piloto automático vuela hacia el cuerpo con la escena ya movida; al llegar
+        // apaga los motores y la cámara sigue al cuerpo desde ahí
+        if !camera.bird_eye_active {
+            match autopilot.update(&scene, &mut camera, delta_time) {
+                Some(AutopilotEvent::Arrived(target)) => {
+                    let body = &scene.bodies[target];
+                    camera.start_follow(target, body.position, body.scale * FOLLOW_DISTANCE_FACTOR + 1.0);
+                    notices.push(format!("Llegada: {}", body.name));
+                }
+                Some(AutopilotEvent::Interrupted) => notices.push("Piloto automático interrumpido".to_string()),
+                None => {}
             }
         }
 
-        // Actualizar el ángulo de la luna
-        moon_angle += 0.05; // Incrementar el ángulo de la luna para simular su órbita
+        // Seguir al cuerpo elegido en su posición de este frame (no al que lleva la nave,
+        // que la seguiría a ella)
+        if camera.follow.is_some_and(|follow| scene.captured == Some(follow.body)) {
+            camera.stop_follow();
+        }
+        if let Some(follow) = camera.follow {
+            camera.update_follow(scene.bodies[follow.body].position, delta_time);
+        }
 
-        // Primero renderizar las estelas
-        for body in &celestial_bodies {
-            for particle in &body.trail.particles {
-                render_trail(&mut framebuffer, &uniforms, particle);
+        // Eventos de proximidad de la nave con los cuerpos (en vista de pájaro la nave
+        // no se mueve, así que no se actualizan)
+        if !camera.bird_eye_active {
+            for event in proximity.update(&scene.bodies, camera.eye) {
+                notices.push(describe_event(&scene.bodies, event));
             }
         }
-
-        // Actualizar las estelas al final del frame
-        for body in &mut celestial_bodies {
-            body.trail.update(0.016);
-            
-            let color = match body.shader_type {
-                PlanetType::Sun => 0xFFFFA500,       // Naranja brillante
-                PlanetType::RockyPlanet => 0xFFD2B48C, // Marrón claro (tono arena)
-                PlanetType::Earth => 0xFF32CD32,     // Verde limón
-                PlanetType::CrystalPlanet => 0xFFFF00FF, // Fucsia
-                PlanetType::FirePlanet => 0xFFFF4500,    // Rojo anaranjado (tono de fuego)
-                PlanetType::WaterPlanet => 0xFF40E0D0,   // Turquesa
-                PlanetType::CloudPlanet => 0xFFFFD700,   // Dorado
-                PlanetType::Moon => 0xFF9370DB,         // Morado
-                PlanetType::Asteroid => 0xFFFFA500,     // Naranja brillante (tono cercano a Sun)
-                PlanetType::Spaceship => 0xFFFFFFFF,    // Blanco
-                PlanetType::Trail => 0xFF888888,        // Gris
-                
-            };
-            
-            let is_moon = matches!(body.shader_type, PlanetType::Moon);
-            body.trail.add_particle(body.position, color, is_moon, &body.shader_type);
-        }
-
-        // Renderizar cada cuerpo celeste
-        for (i, body) in celestial_bodies.iter().enumerate() {
-            if is_in_frustum(body, &uniforms.view_matrix, &uniforms.projection_matrix) {
-                uniforms.model_matrix = create_model_matrix(
-                    body.position,
-                    body.scale,
-                    body.rotation + Vec3::new(0.0, time as f32 * 0.01, 0.0)
-                );
-                uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-                uniforms.time = time;
-
-                render(&mut framebuffer, &uniforms, &vertex_arrays, &body.shader_type);
-
-                // Dibujar la estela
-                let color = colors[i]; // Obtener el color correspondiente
-                for j in 0..previous_positions[i].len() - 1 {
-                    if j + 1 < previous_positions[i].len() {
-                        framebuffer.line(previous_positions[i][j], previous_positions[i][j + 1]);
-                    }
-                }
+        notices.update(delta_time);
+        body_list.update(delta_time);
+        reticle.update(selected, delta_time);
+        help.update(delta_time);
+        warp_effect.update(delta_time);
+
+        // La cámara de persecución se amortigua hacia su pose objetivo
+        camera.update_chase(delta_time);
+
+        // Con la escala por debajo de 100% la escena va a su propio framebuffer, que se
+        // recrea (junto con la proyección del renderer) cuando cambia la escala
+        let scene_size = resolution.internal_size(framebuffer_width, framebuffer_height);
+        if scene_size != (framebuffer_width, framebuffer_height) {
+            if scene_framebuffer.as_ref().map(|fb| (fb.width, fb.height)) != Some(scene_size) {
+                scene_framebuffer = Some(framebuffer.with_same_background(scene_size.0, scene_size.1));
+                renderer.resize(scene_size.0, scene_size.1);
             }
+        } else if scene_framebuffer.take().is_some() {
+            renderer.resize(framebuffer_width, framebuffer_height);
         }
+        let scene_target = scene_framebuffer.as_mut().unwrap_or(&mut framebuffer);
 
-        // Renderizar las órbitas de los planetas
-        for (i, body) in celestial_bodies.iter().enumerate() {
-            if body.shader_type == PlanetType::Sun {
-                continue; // No renderizar la órbita del sol
+        // Comparando shaders, la escena se reemplaza por las dos mitades
+        if comparison.active() {
+            comparison.render(&mut renderer, scene_target, &scene);
+        } else {
+            renderer.render_scene(scene_target, &scene, &camera);
+            // Rayos de sol alrededor de los cuerpos que pasan delante de él
+            if let Some(sun) = scene.bodies.iter().find(|body| body.shader_type == PlanetType::Sun && body.visibility.body) {
+                sun_shafts.apply(scene_target, renderer.frame(), sun);
             }
-            let orbit_radius = planet_orbit_radii[i]; // Usar el radio de órbita correspondiente
-            let color = colors[i]; // Obtener el color correspondiente para la órbita
-            render_orbit(&mut framebuffer, orbit_radius, 100, color); // Asegúrate de que esta línea esté correcta
         }
 
-        // Actualizar la posición de la nave solo si no estamos en vista de pájaro
-        let spaceship_position = if camera.bird_eye_active {
-            Vec3::new(0.0, 5.0, 15.0) // Aumenta la distancia de la nave
-        } else {
-            let camera_direction = (camera.center - camera.eye).normalize();
-            camera.eye + camera_direction * 5.0 + Vec3::new(3.0, 1.0, 0.0) // Mueve la nave más a la derecha y hacia arriba
+        // Destello del warp: las estrellas se estiran alejándose del destino en pantalla
+        if let Some(target) = warp_effect.target() {
+            let focus = project(renderer.frame(), scene.bodies[target].position)
+                .map(|p| (p.x, p.y))
+                .unwrap_or((scene_target.width as f32 * 0.5, scene_target.height as f32 * 0.5));
+            warp_effect.apply(scene_target, focus);
+        }
+
+        // Profundidad de campo alrededor del cuerpo seleccionado, antes del HUD para que el
+        // texto quede nítido; no en vista de pájaro ni al comparar shaders
+        if let Some(index) = selected.filter(|_| !camera.bird_eye_active && !comparison.active()) {
+            depth_of_field.apply(scene_target, renderer.frame(), &scene.bodies[index]);
+        }
+
+        // El HUD se dibuja después del escalado, a la resolución de la ventana
+        if let Some(internal) = &scene_framebuffer {
+            renderer.profile.time("escalar", || upscale(internal, &mut framebuffer, options.upscale));
+        }
+        // Las matrices del cuadro con la proyección de la ventana, para proyectar el HUD
+        let window_frame = FrameUniforms {
+            projection_matrix: transform::create_perspective_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: transform::create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            ..renderer.frame().clone()
         };
 
-        // Ajusta la posición de la cámara en vista de pájaro
-        if camera.bird_eye_active {
-            camera.eye = Vec3::new(0.0, 45.0, 45.0); // Acerca la cámara
-            camera.center = Vec3::new(0.0, 0.0, 0.0); // Mantiene el enfoque en el centro
+        // Exposición automática medida sobre la escena terminada, antes de dibujar el HUD;
+        // se aplica al sombrear los cuerpos del cuadro siguiente
+        exposure.update_auto(&framebuffer, delta_time);
+        renderer.exposure = exposure.value;
+
+        // Las marcas sobre los cuerpos no se dibujan al comparar shaders: las mitades no
+        // se ven desde la cámara de la nave
+        if !comparison.active() {
+            // Nombres de los cuerpos, con el buffer de profundidad de la escena para ocultarlos
+            labels.draw(&mut framebuffer, &window_frame, &scene.bodies, delta_time);
+            // El camino del último vuelo del piloto automático
+            autopilot.draw_path(&mut framebuffer, &window_frame);
+            // La regla entre los dos cuerpos elegidos, medida en este cuadro
+            ruler.draw(&mut framebuffer, &window_frame, &scene);
+            // Retícula alrededor del cuerpo seleccionado, o una flecha hacia él si no está a la vista
+            if let Some(body) = selected.map(|index| &scene.bodies[index]) {
+                if body.visibility.body {
+                    reticle.draw(&mut framebuffer, &window_frame, body);
+                }
+            }
+            // Mira en el centro de la pantalla mientras hay un objetivo elegido o seguido
+            if (selected.is_some() || camera.follow.is_some()) && !camera.bird_eye_active {
+                draw_crosshair(&mut framebuffer);
+            }
+            // Hacia dónde y qué tan rápido va cada cuerpo, en la vista de pájaro
+            if camera.bird_eye_active {
+                motion.draw(&mut framebuffer, &window_frame, &scene, renderer.orbits);
+            }
+            // Esferas envolventes y frustum de depuración (tecla /), encima de todas las marcas
+            bounds.draw(&mut framebuffer, &window_frame, renderer.frame(), &scene.bodies);
+            // Sus datos, medidos en este cuadro
+            if let Some(index) = selected {
+                draw_info_panel(&mut framebuffer, &BodyInfo::new(&scene, index, camera.eye));
+            }
         }
 
-        // Renderizar la nave
-        uniforms.model_matrix = create_model_matrix(
-            spaceship_position,
-            0.003, // Escala de la nave ajustada a un tamaño más pequeño
-            Vec3::new(0.0, 0.0, camera.roll) // Aplicar el roll a la rotación de la nave
+        let exposure_label = format!(
+            "Exposicion x{:.2} ({:+.1} EV){}",
+            exposure.value,
+            exposure.stops(),
+            if exposure.auto { " AUTO" } else { "" }
         );
-        uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        render(&mut framebuffer, &uniforms, &spaceship_obj.get_vertex_array(), &PlanetType::Spaceship);
-
-        // Manejar la entrada para el warping
-        if window.is_key_down(Key::Key1) {
-            instant_warp(&mut camera, WARP_POINTS[0]); // Warp al Sol
+        // Con la luz manual, su dirección al lado para ajustar los shaders
+        let exposure_label = match renderer.override_light {
+            Some(light) => format!("{}  Luz manual ({:.2}, {:.2}, {:.2})", exposure_label, light.x, light.y, light.z),
+            None => exposure_label,
+        };
+        draw_text(&mut framebuffer, 10, framebuffer_height.saturating_sub(10 + GLYPH_HEIGHT), &exposure_label, 0xA0A0A0, 1);
+
+        // Minimapa encima de la exposición
+        minimap.draw(&mut framebuffer, &scene, &camera, 16 + GLYPH_HEIGHT);
+
+        notices.draw(&mut framebuffer, 14 + GLYPH_HEIGHT);
+        autopilot.draw_status(&mut framebuffer, &scene, camera.eye);
+        body_list.draw(&mut framebuffer, &scene.bodies, selected);
+
+        // FPS y tiempo de frame medido (suavizados para que se puedan leer); sin ventana
+        // no tienen sentido y harían que las imágenes guardadas cambiaran entre corridas
+        // (lo mismo en modo determinista)
+        if window.is_some() && !options.deterministic {
+            let mut fps_label = format!("{:.0} FPS  {:.1} ms", clock.display_fps(), clock.display_frame_time() * 1000.0);
+            if resolution.enabled {
+                fps_label.push_str(&format!("  res {:.0}%", resolution.scale() * 100.0));
+            }
+            fps_label.push_str(&format!("  {} partículas", scene.particle_count()));
+            let fps_x = framebuffer_width.saturating_sub(10 + text_width(&fps_label, 1));
+            draw_text(&mut framebuffer, fps_x, 10, &fps_label, 0xA0A0A0, 1);
+            renderer.profile.draw(&mut framebuffer, 14 + LINE_ADVANCE);
+            frame_graph.draw(&mut framebuffer, 10);
         }
-        if window.is_key_down(Key::Key2) {
-            instant_warp(&mut camera, WARP_POINTS[1]); // Warp al Asteroide
+        if show_render_stats {
+            renderer.stats.draw(&mut framebuffer);
         }
-        if window.is_key_down(Key::Key3) {
-            instant_warp(&mut camera, WARP_POINTS[2]); // Warp al Planeta Rocoso
+
+        // Panel de ajuste de shaders para el tipo comparado o el cuerpo más cercano a la cámara
+        let focused_type = comparison.planet().unwrap_or(focused_body(&scene.bodies, camera.eye).shader_type);
+        tweaker.draw(&mut framebuffer, focused_type, &renderer.shader_params.get(focused_type));
+
+        // Ayuda con las teclas actuales (encima de todo lo demás) y el aviso inicial
+        if window.is_some() {
+            help.draw_hint(&mut framebuffer, &input_map);
+            let status = [
+                camera_status(&camera, &scene.bodies),
+                format!("Sombreado: {}", renderer.shading.name()),
+                format!("Estrellas: {}", scene.skybox.mode.name()),
+                format!("Simulación: x{}{}", timestep.time_scale(), if paused { ", en pausa" } else { "" }),
+            ];
+            help.draw(&mut framebuffer, &input_map, &status);
         }
-        if window.is_key_down(Key::Key4) {
-            instant_warp(&mut camera, WARP_POINTS[3]); // Warp a la Tierra
+
+        match &mut window {
+            Some(window) => {
+                let controls = input_map.controls(window);
+                let was_following = camera.follow.map(|follow| follow.body);
+                let warp = handle_keys(&controls, &mut camera, &mut scene, &mut exposure, &mut renderer, sky_rng.as_mut());
+                // Comparar el shader del tipo de cuerpo siguiente (o dejar de comparar)
+                if controls.pressed(Action::CompareShaders) {
+                    comparison.cycle(&scene.bodies, &renderer.shader_params);
+                    notices.push(match comparison.planet() {
+                        Some(planet) => format!("Comparando shaders: {} (izquierda inicial, derecha actual)", planet.name()),
+                        None => "Comparación de shaders desactivada".to_string(),
+                    });
+                }
+                // Panel de ajuste de shaders para el tipo comparado o el cuerpo más cercano a la cámara
+                let tweaked_type = comparison.planet().unwrap_or(focused_body(&scene.bodies, camera.eye).shader_type);
+                if tweaker.handle_input(&controls, tweaked_type, &mut renderer.shader_params) {
+                    // Los impostores lejanos se dibujan con los parámetros nuevos
+                    renderer.update_impostors();
+                }
+                // Empezar a seguir un cuerpo con F también lo selecciona
+                if let Some(follow) = camera.follow.filter(|follow| Some(follow.body) != was_following) {
+                    selected = Some(follow.body);
+                }
+                handle_light_keys(&controls, &mut renderer, delta_time);
+                if let Some(target) = warp {
+                    instant_warp(&mut camera, &scene.bodies, target);
+                    warp_effect.start(target);
+                }
+
+                // Mostrar/ocultar el perfil por etapas y el gráfico de tiempos
+                if controls.pressed(Action::Profiler) {
+                    renderer.profile.toggle();
+                }
+                if controls.pressed(Action::FrameGraph) {
+                    frame_graph.visible = !frame_graph.visible;
+                }
+                if controls.pressed(Action::RenderStats) {
+                    show_render_stats = !show_render_stats;
+                }
+                if controls.pressed(Action::DebugBounds) {
+                    bounds.cycle(renderer.frame());
+                    notices.push(match (bounds.enabled, bounds.frozen()) {
+                        (false, _) => "Esferas envolventes desactivadas",
+                        (true, false) => "Esferas envolventes: verde se dibuja, rojo se recorta",
+                        (true, true) => "Frustum fijo: aléjate para verlo",
+                    }.to_string());
+                }
+                if controls.pressed(Action::Minimap) {
+                    minimap.visible = !minimap.visible;
+                }
+                if controls.pressed(Action::Labels) {
+                    labels.visible = !labels.visible;
+                }
+                if let Some(notice) = handle_visibility_keys(&controls, &mut scene, &mut renderer, &camera) {
+                    notices.push(notice);
+                }
+                if controls.pressed(Action::OrbitTicks) {
+                    motion.ticks = !motion.ticks;
+                    notices.push(match (motion.ticks, camera.bird_eye_active) {
+                        (false, _) => "Marcas en las órbitas desactivadas".to_string(),
+                        (true, true) => format!("Marcas cada {:.0}° en las órbitas", motion.tick_degrees),
+                        (true, false) => "Marcas en las órbitas: se ven en la vista de pájaro (B)".to_string(),
+                    });
+                }
+                if controls.pressed(Action::Ruler) {
+                    ruler.toggle();
+                    notices.push(if ruler.active { "Regla: clic en dos cuerpos" } else { "Regla desactivada" }.to_string());
+                }
+                // Tab (Shift+Tab hacia atrás) recorre los cuerpos y las teclas numéricas eligen
+                // una fila de la lista, que aparece mostrando la selección
+                let row = Action::SELECT_ROWS.iter().position(|&action| controls.pressed(action));
+                if controls.pressed(Action::SelectNext) || row.is_some() {
+                    selected = match row {
+                        Some(row) => body_list.body_at_row(row, scene.bodies.len()).or(selected),
+                        None if controls.shift() => previous_selection(&scene.bodies, selected),
+                        None => next_selection(&scene.bodies, selected),
+                    };
+                    body_list.reveal(selected, scene.bodies.len());
+                    notices.push(match selected {
+                        Some(index) => format!("Seleccionado: {}", scene.bodies[index].name),
+                        None => "Ningún cuerpo seleccionado".to_string(),
+                    });
+                }
+                if controls.pressed(Action::SunShafts) {
+                    sun_shafts.enabled = !sun_shafts.enabled;
+                    notices.push(if sun_shafts.enabled { "Rayos de sol activados" } else { "Rayos de sol desactivados" }.to_string());
+                }
+                if controls.pressed(Action::DepthOfField) {
+                    depth_of_field.enabled = !depth_of_field.enabled;
+                    notices.push(match (depth_of_field.enabled, selected) {
+                        (false, _) => "Profundidad de campo desactivada".to_string(),
+                        (true, Some(_)) => "Profundidad de campo activada".to_string(),
+                        (true, None) => "Profundidad de campo: elige un cuerpo con Tab o clic".to_string(),
+                    });
+                }
+                // Tres capturas del mismo cuadro a -1, 0 y +1 EV; la simulación no avanza
+                // mientras se dibujan y guardan, ni salta después por el tiempo que tardaron.
+                // En la vista de pájaro llevan las flechas de velocidad, salvo con Shift
+                // (captura limpia)
+                if controls.pressed(Action::ExposureBracket) {
+                    let template = scene_framebuffer.as_ref().unwrap_or(&framebuffer);
+                    let with_motion = camera.bird_eye_active && !controls.shift();
+                    let orbits = renderer.orbits;
+                    let shots = render_bracket(&mut renderer, template, &scene, &camera, |shot, frame| {
+                        if let Some(sun) = scene.bodies.iter().find(|body| body.shader_type == PlanetType::Sun && body.visibility.body) {
+                            sun_shafts.apply(shot, frame, sun);
+                        }
+                        if let Some(index) = selected.filter(|_| !camera.bird_eye_active) {
+                            depth_of_field.apply(shot, frame, &scene.bodies[index]);
+                        }
+                        if with_motion {
+                            motion.draw(shot, frame, &scene, orbits);
+                        }
+                    });
+                    notices.push(match save_bracket(&shots, Path::new(SCREENSHOT_DIR)) {
+                        Ok(files) => format!("Capturas guardadas: {}", files[0].display()),
+                        Err(err) => {
+                            eprintln!("no se pudieron guardar las capturas en '{}': {}", SCREENSHOT_DIR, err);
+                            format!("No se pudieron guardar las capturas: {}", err)
+                        }
+                    });
+                    clock.restart();
+                }
+                // Enter: warp al cuerpo seleccionado
+                if let Some(target) = selected.filter(|_| controls.pressed(Action::WarpSelected)) {
+                    instant_warp(&mut camera, &scene.bodies, target);
+                    warp_effect.start(target);
+                }
+                // G: piloto automático hasta el cuerpo seleccionado, o apagarlo si ya vuela
+                if controls.pressed(Action::Autopilot) {
+                    if autopilot.cancel() {
+                        notices.push("Piloto automático desactivado".to_string());
+                    } else if let Some(target) = selected.filter(|&target| scene.captured != Some(target)) {
+                        camera.stop_follow();
+                        let body = &scene.bodies[target];
+                        autopilot.engage(&scene, target, camera.eye, body.scale * FOLLOW_DISTANCE_FACTOR + 1.0);
+                        notices.push(format!("Piloto automático: rumbo a {}", body.name));
+                    } else {
+                        notices.push("Piloto automático: elige un cuerpo con Tab o clic (no el capturado)".to_string());
+                    }
+                }
+                // Capturar el asteroide o la luna más cercanos a la nave, o soltar el que lleva
+                // en una órbita nueva con la velocidad de la nave
+                if controls.pressed(Action::Capture) {
+                    let notice = match scene.release() {
+                        Some(index) => {
+                            let center = scene.parent(index).map_or("el centro", |parent| scene.bodies[parent].name.as_str());
+                            format!("{} soltado: en órbita alrededor de {}", scene.bodies[index].name, center)
+                        }
+                        None => match scene.capture_candidate(camera.eye) {
+                            Some(index) => {
+                                camera.stop_follow();
+                                scene.capture(index, camera.eye);
+                                format!("{} capturado", scene.bodies[index].name)
+                            }
+                            None => format!("Nada que capturar: acércate a menos de {:.1} u de un asteroide o una luna", scene.capture_range),
+                        },
+                    };
+                    notices.push(notice);
+                }
+
+                // Clic izquierdo: en el minimapa hace warp al cuerpo más cercano al clic;
+                // sobre un cuerpo en la escena, lo sigue (o, con la regla, lo toma como extremo)
+                let mouse_down = window.get_mouse_down(MouseButton::Left);
+                let click = (mouse_down && !mouse_was_down).then(|| window.get_mouse_pos(MouseMode::Discard)).flatten();
+                if let Some((x, y)) = click {
+                    if let Some(target) = minimap.body_at(&scene, x, y) {
+                        instant_warp(&mut camera, &scene.bodies, target);
+                        warp_effect.start(target);
+                    } else if let Some(index) = picked_body_index(&scene.bodies, &window_frame, x, y, framebuffer_width, framebuffer_height) {
+                        let body = &scene.bodies[index];
+                        if ruler.active {
+                            ruler.pick(index);
+                            notices.push(match ruler.measure(&scene) {
+                                Some(measurement) => format!("Distancia: {:.2} u", measurement.distance),
+                                None => format!("Regla desde {}; elige otro cuerpo", body.name),
+                            });
+                        } else {
+                            camera.start_follow(index, body.position, body.scale * FOLLOW_DISTANCE_FACTOR + 1.0);
+                            selected = Some(index);
+                            notices.push(format!("Siguiendo: {}", body.name));
+                        }
+                    }
+                }
+                mouse_was_down = mouse_down;
+
+                if controls.pressed(Action::Help) {
+                    help.visible = !help.visible;
+                }
+
+                if controls.pressed(Action::Pause) {
+                    paused = !paused;
+                    notices.push(if paused { "Simulación en pausa" } else { "Simulación reanudada" }.to_string());
+                }
+                if controls.pressed(Action::TimeSlower) || controls.pressed(Action::TimeFaster) {
+                    let factor = if controls.pressed(Action::TimeFaster) { 2.0 } else { 0.5 };
+                    notices.push(format!("Velocidad de la simulación: x{}", timestep.scale_time(factor)));
+                }
+                // Cambiar la ley de las velocidades orbitales sin mover los cuerpos de su lugar
+                if controls.pressed(Action::SpeedPreset) {
+                    let preset = match scene.speed_preset {
+                        OrbitSpeeds::Classic => OrbitSpeeds::Realistic,
+                        OrbitSpeeds::Realistic => OrbitSpeeds::Classic,
+                    };
+                    scene.set_speed_preset(preset);
+                    notices.push(format!("Velocidades orbitales {}", preset.name()));
+                }
+
+                // Releer el archivo de escena y reconstruirla; la cámara se queda donde está
+                if controls.pressed(Action::ReloadScene) {
+                    match reload_scene(options.scene.as_deref(), &mut meshes) {
+                        Ok((config, body_mesh)) => {
+                            renderer.set_shader_params(config.shader_params());
+                            scene.load(&config, body_mesh);
+                            framebuffer.set_background_gradient(scene.background_gradient);
+                            if let Some(internal) = &mut scene_framebuffer {
+                                internal.set_background_gradient(scene.background_gradient);
+                            }
+                            // Los índices de los cuerpos pueden haber cambiado
+                            if camera.follow.as_ref().is_some_and(|follow| follow.body >= scene.bodies.len()) {
+                                camera.stop_follow();
+                            }
+                            selected = selected.filter(|&index| index < scene.bodies.len());
+                            body_list = BodyList::new();
+                            comparison = ShaderComparison::new();
+                            ruler.clear();
+                            autopilot.cancel();
+                            autopilot.clear_path();
+                            proximity = ProximityTracker::new();
+                            warp_effect = WarpEffect::new();
+                            notices.push(format!("Escena recargada: {} cuerpos", scene.bodies.len()));
+                        }
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            notices.push(format!("No se recargó la escena: {}", err));
+                        }
+                    }
+                }
+
+                renderer.profile.time("presentar", || {
+                    window
+                        .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+                        .unwrap();
+                });
+                renderer.profile.end_frame();
+                // Tiempo de trabajo del cuadro, sin la espera del límite de FPS
+                frame_graph.push(frame_start.elapsed());
+                resolution.update(frame_start.elapsed());
+
+                // Esperar lo que falte para cumplir el límite de FPS
+                clock.wait();
+            }
+            None => {
+                if let Some(headless) = &options.headless {
+                    println!("cuadro {}: {}", frame, renderer.stats.summary());
+                    if headless.save_frames.binary_search(&frame).is_ok() {
+                        if let Err(err) = save_frame(&framebuffer, Path::new(&headless.out_dir), frame) {
+                            eprintln!("no se pudo guardar el cuadro {}: {}", frame, err);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                renderer.profile.end_frame();
+                frame_times.push(frame_start.elapsed());
+                if frame_limit.is_none_or(|limit| frame >= limit) {
+                    break;
+                }
+            }
         }
-        if window.is_key_down(Key::Key5) {
-            instant_warp(&mut camera, WARP_POINTS[4]); // Warp al Planeta Cristal
+    }
+
+    if options.bench.is_some() {
+        let mut report = BenchReport::new(&frame_times, framebuffer_width, framebuffer_height, options.threads, &renderer.profile);
+        // Con varios hilos, medir también cuánto ganan frente a uno solo
+        if options.threads > 1 {
+            let speedup = bench::measure_speedup(&mut renderer, &mut framebuffer, &scene, &mut camera, options.threads);
+            report = report.with_speedup(speedup);
         }
-        if window.is_key_down(Key::Key6) {
-            instant_warp(&mut camera, WARP_POINTS[5]); // Warp al Planeta de Fuego
+        println!("{}", report.text());
+        println!("{}", report.json());
+    } else if renderer.profile.enabled() {
+        // Volcar los promedios por etapa al salir (siempre en modo headless)
+        println!("{}", renderer.profile.summary());
+    }
+}
+
+// Lo que se lee de assets/ (y el modelo de body_model de la escena) al arrancar
+struct Assets {
+    body_mesh: BodyMesh,
+    ship_model: Model, // la del OBJ, o una caja con punta si no se pudo cargar
+    cubemap_sky: Option<Skybox>, // el cielo de assets/skybox, si existe
+    failures: Vec<String>,       // un mensaje por archivo que no se pudo usar
+}
+
+// Cargar los modelos y el cubemap. Ninguno es imprescindible: si uno falta o está dañado
+// se avisa en la consola con la ruta y se sigue con un reemplazo (la esfera generada, una
+// nave provisional o el cielo procedural), nunca con un panic
+fn load_assets(scene_config: &SceneConfig, meshes: &mut MeshCache) -> Assets {
+    let mut failures = Vec::new();
+
+    // La malla de los cuerpos: una esfera generada, o el OBJ de body_model si la escena lo
+    // indica. Con icosferas se preparan todos los niveles de detalle de una vez
+    let body_mesh = scene_config.body_mesh(meshes).unwrap_or_else(|err| {
+        failures.push(format!("{}; se usa la esfera generada", err));
+        scene_config.sphere_mesh(meshes)
+    });
+
+    // Normalizado: centrado en su origen y con largo 1, así SPACESHIP_SCALE es su largo en el mundo
+    let ship_model = match Obj::load_normalized(SPACESHIP_MODEL) {
+        Ok(obj) => Model::from(&obj),
+        Err(err) => {
+            failures.push(format!("{}; se usa una nave provisional", err));
+            Model::from(ship_placeholder())
         }
-        if window.is_key_down(Key::Key7) {
-            instant_warp(&mut camera, WARP_POINTS[6]); // Warp al Planeta de Agua
+    };
+
+    // Usar un cubemap si existe assets/skybox
+    let cubemap_sky = if Path::new(SKYBOX_DIR).is_dir() {
+        match Skybox::from_cubemap(SKYBOX_FACES) {
+            Ok(skybox) => Some(skybox),
+            Err(err) => {
+                failures.push(format!("{}; se usan las estrellas procedurales", err));
+                None
+            }
         }
-        if window.is_key_down(Key::Key8) {
-            instant_warp(&mut camera, WARP_POINTS[7]); // Warp al Planeta Nube
+    } else {
+        None
+    };
+
+    for failure in &failures {
+        eprintln!("{}", failure);
+    }
+    Assets { body_mesh, ship_model, cubemap_sky, failures }
+}
+
+// Vuelve a leer el archivo de escena de --scene. Con errores no se toca la escena actual
+fn reload_scene(path: Option<&str>, meshes: &mut MeshCache) -> Result<(SceneConfig, BodyMesh), String> {
+    let path = path.ok_or("no hay archivo de escena, usa --scene <ruta>")?;
+    let config = SceneConfig::load(path)?;
+    let body_mesh = config.body_mesh(meshes)?;
+    Ok((config, body_mesh))
+}
+
+// Teclas de acción que se procesan al final de cada frame (exposición, ajuste de shaders,
+// interruptores de depuración, seguimiento y warp). Devuelve el índice del cuerpo al que
+// se pidió hacer warp, si alguno. El modo headless no las llama
+fn handle_keys(
+    controls: &Controls,
+    camera: &mut Camera,
+    scene: &mut Scene,
+    exposure: &mut Exposure,
+    renderer: &mut Renderer,
+    sky_rng: Option<&mut StdRng>,
+) -> Option<usize> {
+    let celestial_bodies = &scene.bodies;
+    let skybox = &mut scene.skybox;
+    // Exposición: manual con , y . (tercios de paso), automática con F7
+    if controls.pressed_repeat(Action::ExposureDown) {
+        exposure.step(-1.0);
+    }
+    if controls.pressed_repeat(Action::ExposureUp) {
+        exposure.step(1.0);
+    }
+    if controls.pressed(Action::AutoExposure) {
+        exposure.auto = !exposure.auto;
+    }
+
+    // Alternar entre sombreado Gouraud y Phong para compararlos
+    if controls.pressed(Action::Shading) {
+        renderer.shading = renderer.shading.toggled();
+    }
+
+    // Alternar el tramado (dithering) de la salida final
+    if controls.pressed(Action::Dither) {
+        renderer.dither = !renderer.dither;
+    }
+
+    // Depuración: regenerar el cielo con una semilla nueva (se imprime para poder reproducirlo);
+    // en modo determinista la secuencia de semillas sale de la raíz
+    if controls.pressed(Action::RegenerateSky) {
+        let seed = match sky_rng {
+            Some(rng) => rng.gen(),
+            None => rand::random(),
+        };
+        let config = SkyboxConfig { seed, ..skybox.config().clone() };
+        println!("Semilla del cielo: {}", config.seed);
+        skybox.regenerate(config);
+    }
+
+    // Seguir (o dejar de seguir) al cuerpo más cercano
+    if controls.pressed(Action::Follow) {
+        if camera.follow.is_some() {
+            camera.stop_follow();
+        } else {
+            let index = focused_body_index(celestial_bodies, camera.eye);
+            let body = &celestial_bodies[index];
+            camera.start_follow(index, body.position, body.scale * FOLLOW_DISTANCE_FACTOR + 1.0);
         }
+    }
+
+    // Mostrar/ocultar las constelaciones
+    if controls.pressed(Action::Constellations) {
+        skybox.show_constellations = !skybox.show_constellations;
+    }
 
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
-            .unwrap();
+    // Alternar las estrellas entre el infinito (sin paralaje) y posiciones fijas en el mundo
+    if controls.pressed(Action::SkyMode) {
+        skybox.mode = skybox.mode.toggled();
     }
+
+    // Mostrar/ocultar la cuadrícula de referencia del plano orbital
+    if controls.pressed(Action::Grid) {
+        renderer.grid = !renderer.grid;
+    }
+
+    // Warp directo a uno de los primeros cuerpos (teclado numérico): devuelve el cuerpo
+    // destino y main hace el salto (si la escena tiene menos cuerpos que teclas de warp,
+    // las que sobran no hacen nada)
+    Action::WARPS.iter()
+        .position(|&action| controls.down(action))
+        .filter(|&target| target < celestial_bodies.len())
 }
 
-fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &[CelestialBody]) {
-    let movement_speed = 0.5;
-    let rotation_speed = PI / 128.0;
+// Ocultar el cuerpo, la órbita o la estela del cuerpo seguido (o, si no se sigue ninguno,
+// del más cercano), o todas las órbitas o estelas a la vez. Devuelve el aviso para el HUD
+fn handle_visibility_keys(controls: &Controls, scene: &mut Scene, renderer: &mut Renderer, camera: &Camera) -> Option<String> {
+    if controls.pressed(Action::Orbits) {
+        renderer.orbits = !renderer.orbits;
+        return Some(if renderer.orbits { "Órbitas visibles" } else { "Órbitas ocultas" }.to_string());
+    }
+    if controls.pressed(Action::Trails) {
+        renderer.trails = !renderer.trails;
+        return Some(if renderer.trails { "Estelas visibles" } else { "Estelas ocultas" }.to_string());
+    }
+
+    let index = match camera.follow {
+        Some(follow) => follow.body,
+        None => focused_body_index(&scene.bodies, camera.eye),
+    };
+    let body = &mut scene.bodies[index];
+    let shown = |visible: bool| if visible { "visible" } else { "oculta" };
+    if controls.pressed(Action::HideBody) {
+        body.visibility.body = !body.visibility.body;
+        let state = if body.visibility.body { "visible" } else { "oculto" };
+        return Some(format!("{}: {}", body.name, state));
+    }
+    if controls.pressed(Action::HideOrbit) {
+        body.visibility.orbit = !body.visibility.orbit;
+        return Some(format!("Órbita de {}: {}", body.name, shown(body.visibility.orbit)));
+    }
+    if controls.pressed(Action::HideTrail) {
+        body.visibility.trail = !body.visibility.trail;
+        return Some(format!("Estela de {}: {}", body.name, shown(body.visibility.trail)));
+    }
+    None
+}
+
+// Luz manual para revisar el sombreado desde cualquier ángulo: O la separa de la luz
+// normal (o la devuelve a su lugar) y las flechas ← → e Inicio/Fin la hacen orbitar
+// alrededor del cuerpo que se mira
+fn handle_light_keys(controls: &Controls, renderer: &mut Renderer, delta_time: f32) {
+    if controls.pressed(Action::LightOverride) {
+        renderer.override_light = match renderer.override_light {
+            Some(_) => None,
+            None => Some(renderer.frame().light_direction()),
+        };
+    }
+    let turn = LIGHT_TURN_SPEED * delta_time;
+    let axis = |negative: Action, positive: Action| {
+        (controls.down(positive) as i32 - controls.down(negative) as i32) as f32 * turn
+    };
+    let (yaw, pitch) = (axis(Action::LightLeft, Action::LightRight), axis(Action::LightDown, Action::LightUp));
+    if yaw != 0.0 || pitch != 0.0 {
+        let light = renderer.override_light.unwrap_or_else(|| renderer.frame().light_direction());
+        renderer.override_light = Some(orbit_light(&light, yaw, pitch));
+    }
+}
+
+fn handle_input(controls: &Controls, camera: &mut Camera, _celestial_bodies: &[CelestialBody], delta_time: f32) {
+    // Las velocidades están ajustadas para 60 FPS; se escalan con el tiempo real del frame
+    let frame_scale = delta_time * 60.0;
+    let movement_speed = 0.5 * frame_scale;
+    let rotation_speed = PI / 128.0 * frame_scale;
     let bank_angle = PI / 16.0;
 
-    // Manejar la vista aérea
-    if window.is_key_down(Key::B) {
-        if !camera.bird_eye_active {
-            // Guardar el estado actual antes de cambiar a vista aérea
-            camera.previous_state = Some((
-                camera.eye,
-                camera.center,
-                camera.pitch,
-                camera.yaw,
-                camera.roll
-            ));
-            camera.set_bird_eye_view();
-            camera.bird_eye_active = true;
-        }
-    } else if camera.bird_eye_active {
-        // Restaurar la posición anterior cuando se suelta B
-        if let Some((prev_eye, prev_center, prev_pitch, prev_yaw, prev_roll)) = camera.previous_state {
-            camera.eye = prev_eye;
-            camera.center = prev_center;
-            camera.pitch = prev_pitch;
-            camera.yaw = prev_yaw;
-            camera.roll = prev_roll;
-            camera.previous_state = None;
-            camera.bird_eye_active = false;
+    // Alternar entre primera persona (cabina) y cámara de persecución
+    if controls.pressed(Action::CameraMode) {
+        camera.toggle_mode();
+    }
+
+    // Distancia de la cámara de persecución (o al cuerpo seguido)
+    if controls.down(Action::CameraCloser) {
+        if camera.follow.is_some() {
+            camera.adjust_follow_distance(0.98_f32.powf(frame_scale));
+        } else {
+            camera.adjust_chase_distance(-0.2 * frame_scale);
+        }
+    }
+    if controls.down(Action::CameraFarther) {
+        if camera.follow.is_some() {
+            camera.adjust_follow_distance(1.02_f32.powf(frame_scale));
+        } else {
+            camera.adjust_chase_distance(0.2 * frame_scale);
         }
     }
 
+    // Vista aérea mientras se mantiene B; al soltarla se vuelve a la pose anterior
+    if controls.down(Action::BirdEye) {
+        camera.enter_bird_eye_view();
+    } else {
+        camera.exit_bird_eye_view();
+    }
+
     // Solo procesar otros controles si no estamos en vista aérea
     if !camera.bird_eye_active {
         // Rotación de la cámara (mirando arriba/abajo)
-        if window.is_key_down(Key::Up) {
+        if controls.down(Action::LookUp) {
             camera.rotate_pitch(-rotation_speed);
         }
-        if window.is_key_down(Key::Down) {
+        if controls.down(Action::LookDown) {
             camera.rotate_pitch(rotation_speed);
         }
 
@@ -661,17 +1168,17 @@ fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &[Celest
 
         // Movimiento WASD (adelante, izquierda, atrás, derecha)
         let mut movement = Vec3::new(0.0, 0.0, 0.0);
-        if window.is_key_down(Key::W) {
+        if controls.down(Action::MoveForward) {
             movement.z -= movement_speed; // Mover hacia adelante
         }
-        if window.is_key_down(Key::S) {
+        if controls.down(Action::MoveBack) {
             movement.z += movement_speed; // Mover hacia atrás
         }
-        if window.is_key_down(Key::A) {
+        if controls.down(Action::MoveLeft) {
             movement.x -= movement_speed; // Mover a la izquierda
             roll_adjustment += bank_angle; // Inclinación a la izquierda
         }
-        if window.is_key_down(Key::D) {
+        if controls.down(Action::MoveRight) {
             movement.x += movement_speed; // Mover a la derecha
             roll_adjustment -= bank_angle; // Inclinación a la derecha
         }
@@ -690,35 +1197,19 @@ fn handle_input(window: &Window, camera: &mut Camera, celestial_bodies: &[Celest
         }
 
         // Movimiento vertical (Q para subir, E para bajar)
-        if window.is_key_down(Key::Q) {
+        if controls.down(Action::MoveUp) {
             camera.eye.y += movement_speed; // Subir
         }
-        if window.is_key_down(Key::E) {
+        if controls.down(Action::MoveDown) {
             camera.eye.y -= movement_speed; // Bajar
         }
 
-        // Zoom (1 para acercar, 2 para alejar)
-        if window.is_key_down(Key::Key1) {
-            camera.zoom(1.0);
+        // Zoom (por defecto 1 para acercar, 2 para alejar)
+        if controls.down(Action::ZoomIn) {
+            camera.zoom(frame_scale);
         }
-        if window.is_key_down(Key::Key2) {
-            camera.zoom(-1.0);
+        if controls.down(Action::ZoomOut) {
+            camera.zoom(-frame_scale);
         }
     }
 }
-
-// Función para renderizar la órbita
-fn render_orbit(framebuffer: &mut Framebuffer, radius: f32, segments: usize, color: u32) {
-    let mut points = Vec::new();
-    for i in 0..segments {
-        let angle = 2.0 * PI * (i as f32 / segments as f32);
-        let x = radius * angle.cos();
-        let z = radius * angle.sin();
-        points.push(Vec3::new(x, 0.0, z));
-    }
-
-    for i in 0..points.len() {
-        let next_index = (i + 1) % points.len();
-        framebuffer.line(points[i], points[next_index]);
-    }
-}
\ No newline at end of file
@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::gltf::{load_gltf, GltfError};
+use crate::mtl::Material;
+use crate::obj::{Obj, ObjError};
+use crate::vertex::Vertex;
+
+// Tessellation of the default body mesh: the same layout as assets/models/smooth_sphere.obj
+pub const DEFAULT_STACKS: u32 = 16;
+pub const DEFAULT_SLICES: u32 = 32;
+
+// Icosphere subdivision levels kept for level of detail: 0 (the icosahedron) to 4
+pub const LOD_LEVELS: u32 = 5;
+// Longest triangle edge on screen, in pixels, that a level may draw before the next one is used
+const LOD_EDGE_PIXELS: f32 = 8.0;
+// How far (as a share of the projected radius) a body has to go past the size where the
+// level changes before a level already in use is switched, so bodies sitting right at the
+// boundary don't flicker between two levels
+const LOD_HYSTERESIS: f32 = 0.2;
+// Edge length of the unit icosahedron inscribed in the unit sphere, in sphere radii
+const ICOSAHEDRON_EDGE: f32 = 1.0515;
+
+// Indexed triangle mesh as the renderer draws it: every vertex is shaded once per draw
+// and triangles are assembled from the shaded copies by index
+#[derive(Clone)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>, // three per triangle
+}
+
+impl Mesh {
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    // Each triangle corner's vertex, in draw order
+    pub fn corners(&self) -> impl Iterator<Item = &Vertex> {
+        self.indices.iter().map(|&index| &self.vertices[index as usize])
+    }
+
+    // The mesh plus a copy of every triangle facing the other way, with flipped normals.
+    // Nothing is culled, but without the copy the far side of a flat mesh (a ring seen
+    // from below) is shaded as if lit from the side it faces
+    pub fn double_sided(mut self) -> Self {
+        let offset = self.vertices.len() as u32;
+        let back: Vec<Vertex> = self.vertices
+            .iter()
+            .map(|vertex| Vertex::new(vertex.position, -vertex.normal, vertex.tex_coords))
+            .collect();
+        let back_indices: Vec<u32> = self.indices
+            .chunks_exact(3)
+            .flat_map(|triangle| [triangle[0], triangle[2], triangle[1]].map(|index| index + offset))
+            .collect();
+        self.vertices.extend(back);
+        self.indices.extend(back_indices);
+        self
+    }
+}
+
+impl From<&Obj> for Mesh {
+    fn from(obj: &Obj) -> Self {
+        Mesh { vertices: obj.vertices().to_vec(), indices: obj.indices().to_vec() }
+    }
+}
+
+// A mesh split by material, for models whose parts have their own colors (the ship's
+// hull, cockpit and engines). Each part is a mesh of its own, drawn with its material
+#[derive(Clone)]
+pub struct Model {
+    pub parts: Vec<ModelPart>,
+}
+
+#[derive(Clone)]
+pub struct ModelPart {
+    pub mesh: Mesh,
+    pub material: Material,
+}
+
+// One part per material group, each keeping only the vertices its triangles use
+impl From<&Obj> for Model {
+    fn from(obj: &Obj) -> Self {
+        let parts = obj.groups()
+            .iter()
+            .map(|group| {
+                let mut remap: HashMap<u32, u32> = HashMap::new();
+                let mut vertices = Vec::new();
+                let indices = obj.indices()[group.indices.clone()]
+                    .iter()
+                    .map(|&index| {
+                        *remap.entry(index).or_insert_with(|| {
+                            vertices.push(obj.vertices()[index as usize].clone());
+                            (vertices.len() - 1) as u32
+                        })
+                    })
+                    .collect();
+                ModelPart { mesh: Mesh { vertices, indices }, material: group.material.clone() }
+            })
+            .collect();
+        Model { parts }
+    }
+}
+
+// A single part in the fallback material
+impl From<Mesh> for Model {
+    fn from(mesh: Mesh) -> Self {
+        Model { parts: vec![ModelPart { mesh, material: Material::fallback() }] }
+    }
+}
+
+// Every part in one mesh, for drawing a model with a single shader (a body)
+impl From<&Model> for Mesh {
+    fn from(model: &Model) -> Self {
+        let mut mesh = Mesh { vertices: Vec::new(), indices: Vec::new() };
+        for part in &model.parts {
+            let offset = mesh.vertices.len() as u32;
+            mesh.vertices.extend(part.mesh.vertices.iter().cloned());
+            mesh.indices.extend(part.mesh.indices.iter().map(|&index| index + offset));
+        }
+        mesh
+    }
+}
+
+// Unindexed triangles, three vertices each
+impl From<Vec<Vertex>> for Mesh {
+    fn from(vertices: Vec<Vertex>) -> Self {
+        let indices = (0..vertices.len() as u32).collect();
+        Mesh { vertices, indices }
+    }
+}
+
+// Stand-in for the ship when its model can't be loaded: a box hull with a pyramid nose
+// along +Z, the direction the model faces, flat-shaded. Centered and 1 long, as
+// `Obj::load_normalized` leaves a model
+pub fn ship_placeholder() -> Mesh {
+    let (half_width, half_height) = (0.15, 0.1);
+    let rim = |z: f32| [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| Vec3::new(x * half_width, y * half_height, z));
+    let (tail, waist, nose) = (rim(-0.5), rim(0.1), Vec3::new(0.0, 0.0, 0.5));
+
+    let mut triangles = vec![[tail[0], tail[1], tail[2]], [tail[0], tail[2], tail[3]]];
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+        triangles.extend([[tail[i], tail[j], waist[j]], [tail[i], waist[j], waist[i]], [waist[i], waist[j], nose]]);
+    }
+    let vertices = triangles
+        .into_iter()
+        .flat_map(|[a, mut b, mut c]| {
+            // The shape is convex around the origin: wind every face to point away from it
+            let mut normal = (b - a).cross(&(c - a)).normalize();
+            if normal.dot(&(a + b + c)) < 0.0 {
+                std::mem::swap(&mut b, &mut c);
+                normal = -normal;
+            }
+            [a, b, c].map(|position| Vertex::new(position, normal, Vec2::zeros()))
+        })
+        .collect::<Vec<_>>();
+    Mesh::from(vertices)
+}
+
+// Unit sphere with `stacks` bands from pole to pole and `slices` columns around Y, as
+// unindexed triangles. See `uv_sphere_indexed`
+pub fn uv_sphere(stacks: u32, slices: u32) -> Vec<Vertex> {
+    let mesh = uv_sphere_indexed(stacks, slices);
+    mesh.corners().cloned().collect()
+}
+
+// Unit sphere as an indexed mesh, with outward normals and the same UVs as
+// smooth_sphere.obj after loading: v = 0 at the +Y pole and 1 at -Y, u around Y with the
+// wrap column on -X. At least 2 stacks and 3 slices.
+//
+// The wrap column is stored twice, at u = 0 and u = 1, so the last column of triangles
+// doesn't interpolate back across the whole texture. Each pole has one vertex per slice,
+// with u at the slice's middle, and the cap bands are single triangles: no zero-area
+// triangles and no UVs pinched towards one column
+pub fn uv_sphere_indexed(stacks: u32, slices: u32) -> Mesh {
+    let stacks = stacks.max(2);
+    let slices = slices.max(3);
+    let point = |u: f32, v: f32| {
+        let theta = v * PI; // from +Y
+        let phi = (u - 0.5) * 2.0 * PI;
+        Vec3::new(theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin())
+    };
+    let vertex = |u: f32, v: f32| {
+        let position = point(u, v);
+        Vertex::new(position, position, Vec2::new(u, v))
+    };
+
+    // Rings between the poles, slices + 1 vertices each, then the two poles' vertices
+    let columns = slices + 1;
+    let mut vertices = Vec::with_capacity(((stacks - 1) * columns + 2 * slices) as usize);
+    for ring in 1..stacks {
+        for column in 0..columns {
+            vertices.push(vertex(column as f32 / slices as f32, ring as f32 / stacks as f32));
+        }
+    }
+    let ring_vertex = |ring: u32, column: u32| (ring - 1) * columns + column;
+    let top = vertices.len() as u32;
+    for column in 0..slices {
+        let u = (column as f32 + 0.5) / slices as f32;
+        vertices.push(Vertex::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec2::new(u, 0.0)));
+    }
+    let bottom = vertices.len() as u32;
+    for column in 0..slices {
+        let u = (column as f32 + 0.5) / slices as f32;
+        vertices.push(Vertex::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), Vec2::new(u, 1.0)));
+    }
+
+    // Counter-clockwise seen from outside
+    let mut indices = Vec::with_capacity((6 * slices * (stacks - 1)) as usize);
+    for column in 0..slices {
+        indices.extend_from_slice(&[top + column, ring_vertex(1, column), ring_vertex(1, column + 1)]);
+    }
+    for ring in 1..stacks - 1 {
+        for column in 0..slices {
+            let (a, b) = (ring_vertex(ring, column), ring_vertex(ring, column + 1));
+            let (c, d) = (ring_vertex(ring + 1, column), ring_vertex(ring + 1, column + 1));
+            indices.extend_from_slice(&[a, c, d, a, d, b]);
+        }
+    }
+    let last = stacks - 1;
+    for column in 0..slices {
+        indices.extend_from_slice(&[bottom + column, ring_vertex(last, column + 1), ring_vertex(last, column)]);
+    }
+
+    Mesh { vertices, indices }
+}
+
+// Flat annulus in the XZ plane between `inner_radius` and `outer_radius`, facing +Y, for
+// planetary rings. u is the radial fraction (0 on the inner edge, 1 on the outer), so a
+// 1D ring texture or radial noise maps straight onto it, and v goes once around from +X.
+// Like `uv_sphere_indexed`, the wrap column is stored twice, at v = 0 and v = 1. At least
+// 3 segments; see `Mesh::double_sided` for the underside
+pub fn ring(inner_radius: f32, outer_radius: f32, segments: u32) -> Mesh {
+    let segments = segments.max(3);
+    let mut vertices = Vec::with_capacity(2 * (segments + 1) as usize);
+    for segment in 0..=segments {
+        let v = segment as f32 / segments as f32;
+        let angle = v * 2.0 * PI;
+        let direction = Vec3::new(angle.cos(), 0.0, -angle.sin());
+        for (radius, u) in [(inner_radius, 0.0), (outer_radius, 1.0)] {
+            vertices.push(Vertex::new(direction * radius, Vec3::new(0.0, 1.0, 0.0), Vec2::new(u, v)));
+        }
+    }
+
+    // Counter-clockwise seen from +Y
+    let mut indices = Vec::with_capacity(6 * segments as usize);
+    for segment in 0..segments {
+        let (inner, outer) = (2 * segment, 2 * segment + 1);
+        let (next_inner, next_outer) = (inner + 2, outer + 2);
+        indices.extend_from_slice(&[inner, outer, next_outer, inner, next_outer, next_inner]);
+    }
+    Mesh { vertices, indices }
+}
+
+// Sphere point with the same UVs as `uv_sphere_indexed`. The wrap column isn't split, so
+// triangles crossing it interpolate u the long way round; the procedural shaders sample
+// the model position and don't notice
+fn sphere_vertex(position: Vec3) -> Vertex {
+    Vertex::new(position, position, sphere_uv(position))
+}
+
+// Texture coordinates of a point on the unit sphere, as the generated spheres lay them out
+pub(crate) fn sphere_uv(position: Vec3) -> Vec2 {
+    let u = 0.5 + (-position.z).atan2(position.x) / (2.0 * PI);
+    let v = position.y.clamp(-1.0, 1.0).acos() / PI;
+    Vec2::new(u, v)
+}
+
+// Unit sphere built from an icosahedron whose triangles are split in four `subdivisions`
+// times, each new vertex pushed out onto the sphere. Triangles stay close to equal in
+// size everywhere, unlike a UV sphere's slivers near the poles. A midpoint shared by two
+// triangles is one vertex: 10 * 4^n + 2 vertices and 20 * 4^n triangles
+pub fn icosphere(subdivisions: u32) -> Mesh {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    // Counter-clockwise seen from outside
+    let mut indices: Vec<u32> = vec![
+        0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11,
+        1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7, 6, 7, 1, 8,
+        3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9,
+        4, 9, 5, 2, 4, 11, 6, 2, 10, 8, 6, 7, 9, 8, 1,
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(((positions[a as usize] + positions[b as usize]) * 0.5).normalize());
+                (positions.len() - 1) as u32
+            })
+        };
+        let mut subdivided = Vec::with_capacity(indices.len() * 4);
+        for triangle in indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            subdivided.extend_from_slice(&[a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]);
+        }
+        indices = subdivided;
+    }
+
+    Mesh { vertices: positions.into_iter().map(sphere_vertex).collect(), indices }
+}
+
+// Icospheres of every level, coarsest first, and the choice between them by how big a
+// body looks on screen
+pub struct Lod {
+    levels: Vec<Arc<Mesh>>,
+}
+
+impl Default for Lod {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lod {
+    // Builds all LOD_LEVELS icospheres up front
+    pub fn new() -> Self {
+        Lod { levels: (0..LOD_LEVELS).map(|level| Arc::new(icosphere(level))).collect() }
+    }
+
+    // The coarsest level whose edges stay under LOD_EDGE_PIXELS for a body `projected_radius`
+    // pixels across its radius, or the finest one
+    pub fn level_for(&self, projected_radius: f32) -> usize {
+        let last = self.levels.len() - 1;
+        (0..last)
+            .find(|&level| projected_radius * ICOSAHEDRON_EDGE / (1 << level) as f32 <= LOD_EDGE_PIXELS)
+            .unwrap_or(last)
+    }
+
+    // level_for for a body drawn at `previous` on the last frame: that level is kept until
+    // the body is LOD_HYSTERESIS bigger or smaller than where level_for would switch
+    pub fn level_from(&self, projected_radius: f32, previous: usize) -> usize {
+        let coarsest = self.level_for(projected_radius / (1.0 + LOD_HYSTERESIS));
+        let finest = self.level_for(projected_radius * (1.0 + LOD_HYSTERESIS));
+        previous.clamp(coarsest, finest)
+    }
+
+    pub fn select(&self, projected_radius: f32) -> &Mesh {
+        &self.levels[self.level_for(projected_radius)]
+    }
+
+    pub fn finest(&self) -> &Arc<Mesh> {
+        &self.levels[self.levels.len() - 1]
+    }
+}
+
+// What a cached mesh was loaded or generated from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MeshKey {
+    Obj(String),  // path
+    Gltf(String), // path
+    UvSphere { stacks: u32, slices: u32 },
+    Icosphere(u32), // subdivisions
+}
+
+// Meshes keyed by where they come from, each loaded or generated once and then shared.
+// Everything is requested before the render loop starts, so drawing never touches the loader
+#[derive(Default)]
+pub struct MeshCache {
+    meshes: HashMap<MeshKey, Arc<Mesh>>,
+    lod: Option<Arc<Lod>>,
+}
+
+impl MeshCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The model at `path`, read the first time it's asked for. A failed load isn't
+    // cached, so fixing the file and asking again works
+    pub fn obj(&mut self, path: &str) -> Result<Arc<Mesh>, ObjError> {
+        let key = MeshKey::Obj(path.to_string());
+        if let Some(mesh) = self.meshes.get(&key) {
+            return Ok(Arc::clone(mesh));
+        }
+        let mesh = Arc::new(Mesh::from(&Obj::load(path)?));
+        self.meshes.insert(key, Arc::clone(&mesh));
+        Ok(mesh)
+    }
+
+    // Like `obj`, for a glTF model: its first mesh with every primitive merged
+    pub fn gltf(&mut self, path: &str) -> Result<Arc<Mesh>, GltfError> {
+        let key = MeshKey::Gltf(path.to_string());
+        if let Some(mesh) = self.meshes.get(&key) {
+            return Ok(Arc::clone(mesh));
+        }
+        let mesh = Arc::new(Mesh::from(&load_gltf(path)?));
+        self.meshes.insert(key, Arc::clone(&mesh));
+        Ok(mesh)
+    }
+
+    pub fn uv_sphere(&mut self, stacks: u32, slices: u32) -> Arc<Mesh> {
+        self.generated(MeshKey::UvSphere { stacks, slices }, || uv_sphere_indexed(stacks, slices))
+    }
+
+    pub fn icosphere(&mut self, subdivisions: u32) -> Arc<Mesh> {
+        self.generated(MeshKey::Icosphere(subdivisions), || icosphere(subdivisions))
+    }
+
+    // Every icosphere level, sharing the cached icospheres
+    pub fn lod(&mut self) -> Arc<Lod> {
+        if let Some(lod) = &self.lod {
+            return Arc::clone(lod);
+        }
+        let lod = Arc::new(Lod { levels: (0..LOD_LEVELS).map(|level| self.icosphere(level)).collect() });
+        self.lod = Some(Arc::clone(&lod));
+        lod
+    }
+
+    // Distinct meshes loaded or generated so far
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+
+    fn generated(&mut self, key: MeshKey, build: impl FnOnce() -> Mesh) -> Arc<Mesh> {
+        Arc::clone(self.meshes.entry(key).or_insert_with(|| Arc::new(build())))
+    }
+}
+
+// What a body is drawn with: one mesh, or icosphere levels picked by size on screen
+#[derive(Clone)]
+pub struct BodyMesh {
+    pub mesh: Arc<Mesh>, // the mesh, or the finest level
+    pub lod: Option<Arc<Lod>>,
+}
+
+impl BodyMesh {
+    pub fn select(&self, projected_radius: f32) -> &Mesh {
+        match &self.lod {
+            Some(lod) => lod.select(projected_radius),
+            None => &self.mesh,
+        }
+    }
+
+    // Like `select`, remembering the level in `level` between frames so it only changes
+    // past the hysteresis (see Lod::level_from). None starts without a previous level
+    pub fn select_from(&self, projected_radius: f32, level: &mut Option<usize>) -> &Mesh {
+        let Some(lod) = &self.lod else {
+            return &self.mesh;
+        };
+        let next = match *level {
+            Some(previous) => lod.level_from(projected_radius, previous),
+            None => lod.level_for(projected_radius),
+        };
+        *level = Some(next);
+        &lod.levels[next]
+    }
+}
+
+impl From<Mesh> for BodyMesh {
+    fn from(mesh: Mesh) -> Self {
+        BodyMesh { mesh: Arc::new(mesh), lod: None }
+    }
+}
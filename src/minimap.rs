@@ -0,0 +1,155 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::planet::PlanetType;
+use crate::scene::Scene;
+
+// Side of the map in pixels
+const SIZE: usize = 180;
+// Pixels kept free around the outermost orbit
+const PADDING: f32 = 8.0;
+const MARGIN: usize = 10;
+const ORBIT_SEGMENTS: usize = 64;
+const BACKGROUND_ALPHA: f32 = 0.6;
+const BORDER_COLOR: u32 = 0x505050;
+const CAMERA_COLOR: u32 = 0xFFFFFF;
+const CAMERA_RADIUS: f32 = 2.0;
+// Blip radii in pixels, whatever the body's size at the map's scale
+const MIN_BLIP_RADIUS: f32 = 1.5;
+const MAX_BLIP_RADIUS: f32 = 5.0;
+// Half-angle and length (pixels) of the camera's view wedge
+const WEDGE_HALF_ANGLE: f32 = 0.5;
+const WEDGE_LENGTH: f32 = 22.0;
+
+// Top-down schematic of the solar system, drawn into its own small framebuffer and
+// blitted into the bottom-left corner over a translucent background. +X points right and
+// +Z down, and the scale fits the outermost orbit
+pub struct Minimap {
+    pub visible: bool,
+    map: Framebuffer,
+    scale: f32,                      // pixels per world unit, from the last draw
+    origin: Option<(usize, usize)>,  // top-left corner on screen of the last draw
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Minimap {
+            visible: false,
+            map: Framebuffer::new(SIZE, SIZE),
+            scale: 1.0,
+            origin: None,
+        }
+    }
+
+    // Orbits, bodies and the camera, with the map's bottom edge `bottom` pixels above the
+    // framebuffer's
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, bottom: usize) {
+        if !self.visible {
+            self.origin = None;
+            return;
+        }
+
+        // Fit the farthest point of any orbit; a scene with only the sun still gets a scale
+        let extent = scene.orbits.iter()
+            .flat_map(|orbit| (0..ORBIT_SEGMENTS).map(move |i| orbit.position(TAU * i as f32 / ORBIT_SEGMENTS as f32)))
+            .map(|p| p.x.abs().max(p.z.abs()))
+            .fold(1.0, f32::max);
+        self.scale = (SIZE as f32 * 0.5 - PADDING) / extent;
+
+        self.map.clear();
+        for (i, body) in scene.bodies.iter().enumerate() {
+            if body.shader_type == PlanetType::Sun || scene.captured == Some(i) {
+                continue;
+            }
+            let path = scene.orbit_path(i);
+            self.map.set_current_color((Color::from_hex(scene.orbit_colors[i]) * 0.5).to_hex());
+            for segment in 0..ORBIT_SEGMENTS {
+                let angle = |k: usize| TAU * k as f32 / ORBIT_SEGMENTS as f32;
+                let start = self.to_map(path.position(angle(segment)));
+                let end = self.to_map(path.position(angle(segment + 1)));
+                self.map.line(Vec3::new(start.x, start.y, 0.0), Vec3::new(end.x, end.y, 0.0));
+            }
+        }
+
+
+        // The camera: a dot with a wedge along the view direction (none when looking straight
+        // down). Beyond the outermost orbit it sticks to the map's edge
+        let eye = camera.view_eye();
+        let edge = PADDING * 0.5;
+        let position = self.to_map(eye).map(|v| v.clamp(edge, SIZE as f32 - edge));
+        let direction = camera.view_center() - eye;
+        let heading = Vec2::new(direction.x, direction.z);
+        if heading.magnitude() > 1e-4 {
+            let angle = heading.y.atan2(heading.x);
+            self.map.set_current_color(CAMERA_COLOR);
+            for side in [-WEDGE_HALF_ANGLE, WEDGE_HALF_ANGLE] {
+                let tip = position + Vec2::new((angle + side).cos(), (angle + side).sin()) * WEDGE_LENGTH;
+                self.map.line(Vec3::new(position.x, position.y, 0.0), Vec3::new(tip.x, tip.y, 0.0));
+            }
+        }
+
+        let left = MARGIN;
+        let top = framebuffer.height.saturating_sub(bottom + SIZE);
+        self.origin = Some((left, top));
+        self.blit(framebuffer, left, top);
+
+        // The blips go straight onto the screen, over the blitted map, so their edges
+        // blend with what's under them. Each is as big as its body at the map's scale,
+        // within limits that keep the smallest visible and the sun from covering its orbits
+        let corner = Vec2::new(left as f32, top as f32);
+        for body in &scene.bodies {
+            let radius = (body.scale * self.scale).clamp(MIN_BLIP_RADIUS, MAX_BLIP_RADIUS);
+            framebuffer.filled_circle(corner + self.to_map(body.position), radius, body.trail.color() & 0x00FFFFFF);
+        }
+        framebuffer.filled_circle(corner + position, CAMERA_RADIUS, CAMERA_COLOR);
+    }
+
+    // Index of the body nearest to a click at (x, y) on screen, or None when the click
+    // isn't on the map
+    pub fn body_at(&self, scene: &Scene, x: f32, y: f32) -> Option<usize> {
+        let (left, top) = self.origin?;
+        let local = Vec2::new(x - left as f32, y - top as f32);
+        if local.x < 0.0 || local.y < 0.0 || local.x >= SIZE as f32 || local.y >= SIZE as f32 {
+            return None;
+        }
+        scene.bodies.iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                let da = (self.to_map(a.position) - local).magnitude();
+                let db = (self.to_map(b.position) - local).magnitude();
+                da.total_cmp(&db)
+            })
+            .map(|(i, _)| i)
+    }
+
+    // World XZ to map pixels, with the sun's position (the origin) at the center
+    fn to_map(&self, position: Vec3) -> Vec2 {
+        let center = SIZE as f32 * 0.5;
+        Vec2::new(center + position.x * self.scale, center + position.z * self.scale)
+    }
+
+    // Drawn pixels are copied; the rest (nothing written to the depth buffer) only darkens
+    // what's behind, and a one-pixel border frames the map
+    fn blit(&self, framebuffer: &mut Framebuffer, left: usize, top: usize) {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let (screen_x, screen_y) = (left + x, top + y);
+                if x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1 {
+                    framebuffer.set_pixel(screen_x, screen_y, BORDER_COLOR);
+                } else if self.map.depth(x, y).is_some_and(f32::is_finite) {
+                    framebuffer.set_pixel(screen_x, screen_y, self.map.pixel(x, y).unwrap_or(0));
+                } else {
+                    framebuffer.blend_pixel(screen_x, screen_y, 0x000000, BACKGROUND_ALPHA);
+                }
+            }
+        }
+    }
+}
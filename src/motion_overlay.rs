@@ -0,0 +1,97 @@
+use nalgebra_glm::{Vec2, Vec3};
+use crate::framebuffer::{Framebuffer, OVERLAY_DEPTH};
+use crate::scene::Scene;
+use crate::sprite::project;
+use crate::uniforms::FrameUniforms;
+
+// Seconds of travel an arrow covers: its length is the body's speed times this
+pub const ARROW_SECONDS: f32 = 3.0;
+// Degrees of orbit between two tick marks unless `tick_degrees` says otherwise
+pub const DEFAULT_TICK_DEGREES: f32 = 30.0;
+const ARROW_ALPHA: f32 = 0.9;
+const HEAD_ANGLE: f32 = 0.45; // radians between the shaft and each side of the head
+const HEAD_LENGTH: f32 = 7.0; // pixels, less on arrows too short for it
+const TICK_ALPHA: f32 = 0.35;
+const TICK_LENGTH: f32 = 0.06; // of the orbit's radius, on each side of it
+
+// What the bird's eye view adds to show how the bodies move: an arrow from each visible
+// body along its velocity (Scene::velocity), as long as the way it covers in
+// ARROW_SECONDS, in its trail's color; and optionally faint ticks across every drawn
+// orbit each `tick_degrees`, which a fast body passes more often than a slow one
+pub struct MotionOverlay {
+    pub ticks: bool,
+    pub tick_degrees: f32,
+}
+
+impl Default for MotionOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MotionOverlay {
+    pub fn new() -> Self {
+        MotionOverlay { ticks: false, tick_degrees: DEFAULT_TICK_DEGREES }
+    }
+
+    // Over `framebuffer`, seen with `uniforms`. Orbits hidden with `visibility` get no
+    // ticks, or all of them when `orbits` is off, as the renderer draws them
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, scene: &Scene, orbits: bool) {
+        if self.ticks && orbits {
+            for i in 0..scene.bodies.len() {
+                if scene.orbits[i].radius > 0.0 && scene.bodies[i].visibility.orbit && scene.captured != Some(i) {
+                    self.draw_ticks(framebuffer, uniforms, scene, i);
+                }
+            }
+        }
+        for (i, body) in scene.bodies.iter().enumerate() {
+            let velocity = scene.velocity(i);
+            if !body.visibility.body || velocity.magnitude() <= f32::EPSILON {
+                continue;
+            }
+            // From the body's edge on, so the arrow doesn't start hidden in its disc
+            let start = body.position + velocity.normalize() * body.scale;
+            let color = body.trail.color() & 0x00FFFFFF;
+            draw_arrow(framebuffer, uniforms, start, start + velocity * ARROW_SECONDS, color);
+        }
+    }
+
+    fn draw_ticks(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, scene: &Scene, i: usize) {
+        let path = scene.orbit_path(i);
+        let color = scene.orbit_colors[i];
+        let count = (360.0 / self.tick_degrees.max(1.0)).round().max(1.0) as usize;
+        let half = path.orbit.radius * TICK_LENGTH;
+        for tick in 0..count {
+            // Measured from the orbit's start, so they stay put as the body moves
+            let point = path.position(std::f32::consts::TAU * tick as f32 / count as f32);
+            let outward = (point - path.center).normalize();
+            draw_line(framebuffer, uniforms, point - outward * half, point + outward * half, color, TICK_ALPHA);
+        }
+    }
+}
+
+// A shaft from `start` to `tip` and a two-segment head at the tip, turned in screen space
+fn draw_arrow(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, start: Vec3, tip: Vec3, color: u32) {
+    let (Some(a), Some(b)) = (project(uniforms, start), project(uniforms, tip)) else {
+        return;
+    };
+    let shaft = Vec2::new(b.x - a.x, b.y - a.y);
+    let length = shaft.magnitude();
+    if length < 1.0 {
+        return;
+    }
+    let (a, b) = (Vec3::new(a.x, a.y, OVERLAY_DEPTH), Vec3::new(b.x, b.y, OVERLAY_DEPTH));
+    framebuffer.line_aa(a, b, color, ARROW_ALPHA);
+    let back = -shaft / length * HEAD_LENGTH.min(length * 0.5);
+    for angle in [HEAD_ANGLE, -HEAD_ANGLE] {
+        let (sin, cos) = angle.sin_cos();
+        let side = Vec2::new(back.x * cos - back.y * sin, back.x * sin + back.y * cos);
+        framebuffer.line_aa(b, b + Vec3::new(side.x, side.y, 0.0), color, ARROW_ALPHA);
+    }
+}
+
+fn draw_line(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, a: Vec3, b: Vec3, color: u32, alpha: f32) {
+    if let (Some(a), Some(b)) = (project(uniforms, a), project(uniforms, b)) {
+        framebuffer.line_aa(Vec3::new(a.x, a.y, OVERLAY_DEPTH), Vec3::new(b.x, b.y, OVERLAY_DEPTH), color, alpha);
+    }
+}
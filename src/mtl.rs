@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::Path;
+use crate::color::Color;
+use crate::obj::ObjError;
+
+// One `newmtl` entry of an MTL file. Only the diffuse part is read: the renderer has no
+// specular or transparency
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    pub diffuse: Color,              // Kd
+    pub diffuse_map: Option<String>, // map_Kd, resolved against the MTL file's directory
+}
+
+impl Material {
+    // What triangles without a usable material are drawn with: the ship's original grey
+    pub fn fallback() -> Self {
+        Material { name: String::new(), diffuse: Color::new(192, 192, 192), diffuse_map: None }
+    }
+}
+
+// `Kd r g b` with each channel in 0..=1
+fn parse_diffuse(fields: &[&str]) -> Result<Color, String> {
+    if fields.len() < 3 {
+        return Err("Kd necesita 3 números".to_string());
+    }
+    let mut channels = [0u8; 3];
+    for (channel, field) in channels.iter_mut().zip(fields) {
+        let value: f32 = field.parse().map_err(|_| format!("número inválido '{}'", field))?;
+        *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+    Ok(Color::new(channels[0], channels[1], channels[2]))
+}
+
+// The materials of an MTL file, in file order. Texture paths are resolved against the
+// file's directory. Keywords other than newmtl, Kd and map_Kd are ignored
+pub fn load_mtl(filename: &str) -> Result<Vec<Material>, ObjError> {
+    let source = fs::read_to_string(filename).map_err(|err| ObjError::Io(filename.to_string(), err))?;
+    let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+
+    let mut materials: Vec<Material> = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let error = |msg: String| ObjError::Parse(filename.to_string(), number + 1, msg);
+        let line = line.trim();
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match keyword {
+            "newmtl" => materials.push(Material { name: rest.to_string(), ..Material::fallback() }),
+            "Kd" | "map_Kd" => {
+                let material = materials.last_mut().ok_or_else(|| error(format!("{} antes de newmtl", keyword)))?;
+                if keyword == "Kd" {
+                    let fields: Vec<&str> = rest.split_whitespace().collect();
+                    material.diffuse = parse_diffuse(&fields).map_err(error)?;
+                } else {
+                    // The path may have spaces, unless options such as `-s 1 1 1` come before it
+                    let path = if rest.starts_with('-') { rest.split_whitespace().last().unwrap_or("") } else { rest };
+                    material.diffuse_map = Some(directory.join(path).to_string_lossy().into_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
@@ -0,0 +1,56 @@
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+// Seconds a notice stays on screen, the last part of which it spends fading out
+const NOTICE_TIME: f32 = 3.0;
+const FADE_TIME: f32 = 0.6;
+const MAX_NOTICES: usize = 4;
+const NOTICE_COLOR: u32 = 0xE0E0E0;
+
+struct Notice {
+    text: String,
+    age: f32,
+}
+
+// Short-lived HUD messages stacked above the bottom edge, newest at the bottom
+pub struct Notices {
+    entries: Vec<Notice>,
+}
+
+impl Default for Notices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notices {
+    pub fn new() -> Self {
+        Notices { entries: Vec::new() }
+    }
+
+    pub fn push(&mut self, text: String) {
+        if self.entries.len() == MAX_NOTICES {
+            self.entries.remove(0);
+        }
+        self.entries.push(Notice { text, age: 0.0 });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for notice in &mut self.entries {
+            notice.age += dt;
+        }
+        self.entries.retain(|notice| notice.age < NOTICE_TIME);
+    }
+
+    // Centered horizontally, with the newest line `bottom` pixels above the bottom edge
+    pub fn draw(&self, framebuffer: &mut Framebuffer, bottom: usize) {
+        let base = Color::from_hex(NOTICE_COLOR);
+        for (i, notice) in self.entries.iter().rev().enumerate() {
+            let fade = ((NOTICE_TIME - notice.age) / FADE_TIME).clamp(0.0, 1.0);
+            let x = (framebuffer.width / 2).saturating_sub(text_width(&notice.text, 1) / 2);
+            let y = framebuffer.height.saturating_sub(bottom + LINE_ADVANCE * (i + 1));
+            draw_text(framebuffer, x, y, &notice.text, (base * fade).to_hex(), 1);
+        }
+    }
+}
@@ -1,62 +1,353 @@
-use tobj;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
 use nalgebra_glm::{Vec2, Vec3};
+use crate::mtl::{load_mtl, Material};
 use crate::vertex::Vertex;
 
+// A loaded model as an indexed mesh: every distinct (position, UV, normal) combination of
+// the file's face corners becomes one vertex, and triangles refer to them by index
 pub struct Obj {
-    meshes: Vec<Mesh>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>, // three per triangle
+    groups: Vec<MaterialGroup>, // cover `indices` in order
+    normalization: Option<Normalization>,
+    triangulated_faces: usize, // faces with 4 or more corners split into triangles on load
 }
 
-struct Mesh {
-    vertices: Vec<Vec3>,
+// The triangles drawn with one material: every `usemtl` run of that material, gathered in
+// one range of `Obj::indices`. Triangles before any `usemtl`, or whose material isn't in
+// the MTL files, get `Material::fallback`
+#[derive(Debug, Clone)]
+pub struct MaterialGroup {
+    pub material: Material,
+    pub indices: Range<usize>,
+}
+
+// Transform applied by `load_normalized`: normalized = (original - center) * scale
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct Normalization {
+    pub center: Vec3,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    // Store V as 1 - v. Most exporters put v = 0 at the bottom of the image and our
+    // textures start at the top row, but some already write it top-down
+    pub flip_v: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions { flip_v: true }
+    }
+}
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(String, std::io::Error),
+    Parse(String, usize, String), // path, line, message
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(path, err) if err.kind() == std::io::ErrorKind::NotFound => write!(f, "no se encontró {}", path),
+            ObjError::Io(path, err) => write!(f, "no se pudo leer {}: {}", path, err),
+            ObjError::Parse(path, line, msg) => write!(f, "{}: línea {}: {}", path, line, msg),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+// One corner of a face: a zero-based index into each attribute list. UVs and normals are
+// optional per corner (`v`, `v/vt`, `v//vn` and `v/vt/vn` are all valid)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    tex_coords: Option<usize>,
+    normal: Option<usize>,
+}
+
+// Split a face into triangles as a fan from its first corner: (c0, ci, ci+1). Every
+// corner keeps its own attributes and the triangles keep the face's winding. Points and
+// lines (fewer than 3 corners) are dropped. Returns whether the face had to be split
+fn triangulate(face: &[Corner], triangles: &mut Vec<Corner>) -> bool {
+    if face.len() < 3 {
+        return false;
+    }
+    for i in 1..face.len() - 1 {
+        triangles.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+    }
+    face.len() > 3
+}
+
+// Parse the first `N` numbers of a record; missing trailing ones are 0
+fn parse_floats<const N: usize>(fields: &[&str], required: usize) -> Result<[f32; N], String> {
+    if fields.len() < required {
+        return Err(format!("se esperaban al menos {} números", required));
+    }
+    let mut values = [0.0; N];
+    for (value, field) in values.iter_mut().zip(fields) {
+        *value = field.parse().map_err(|_| format!("número inválido '{}'", field))?;
+    }
+    Ok(values)
+}
+
+// A 1-based OBJ index, or a negative one counting back from the last `count` elements
+// read so far (-1 is the latest), as a zero-based index
+fn resolve_index(field: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index: i64 = field.parse().map_err(|_| format!("índice inválido '{}'", field))?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if index == 0 || !(0..count as i64).contains(&resolved) {
+        return Err(format!("índice de {} fuera de rango: {} (hay {})", kind, index, count));
+    }
+    Ok(resolved as usize)
+}
+
+// Attribute lists and triangulated corners while a file is being read
+#[derive(Default)]
+struct Parser {
+    positions: Vec<Vec3>,
+    position_lines: Vec<usize>, // where each position was read, for errors about it
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
-    indices: Vec<u32>,
+    corners: Vec<Corner>, // three per triangle
+    triangulated_faces: usize,
+    material_libraries: Vec<String>, // `mtllib` paths, resolved against the OBJ's directory
+    material_names: Vec<String>,     // every `usemtl` name, in order of first use
+    current_material: Option<usize>, // into material_names
+    triangle_materials: Vec<Option<usize>>, // one per triangle of `corners`
 }
 
-impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
-
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
-            }
-        }).collect();
+impl Parser {
+    // `v`, `v/vt`, `v//vn` or `v/vt/vn`, checked against the elements read so far
+    fn parse_corner(&self, field: &str) -> Result<Corner, String> {
+        let mut parts = field.split('/');
+        let position = resolve_index(parts.next().unwrap_or(""), self.positions.len(), "posición")?;
+        let optional = |part: Option<&str>, count: usize, kind: &str| match part {
+            None | Some("") => Ok(None),
+            Some(part) => resolve_index(part, count, kind).map(Some),
+        };
+        let tex_coords = optional(parts.next(), self.texcoords.len(), "UV")?;
+        let normal = optional(parts.next(), self.normals.len(), "normal")?;
+        if parts.next().is_some() {
+            return Err(format!("vértice de cara inválido '{}'", field));
+        }
+        Ok(Corner { position, tex_coords, normal })
+    }
 
-        Ok(Obj { meshes })
+    // Select `name` for the following faces
+    fn use_material(&mut self, name: &str) {
+        let index = self.material_names.iter().position(|known| known == name).unwrap_or_else(|| {
+            self.material_names.push(name.to_string());
+            self.material_names.len() - 1
+        });
+        self.current_material = Some(index);
     }
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
+    // The named materials from the `mtllib` files. A library that can't be read only
+    // costs its materials, with a warning: the model still loads in the fallback grey
+    fn resolve_materials(&self, filename: &str) -> Vec<Material> {
+        if self.material_names.is_empty() {
+            return Vec::new(); // Nothing refers to the libraries, so don't read them
+        }
+        let mut library = Vec::new();
+        let mut complete = true;
+        for path in &self.material_libraries {
+            match load_mtl(path) {
+                Ok(materials) => library.extend(materials),
+                Err(err) => {
+                    eprintln!("{}: {}; se usa el gris por defecto", filename, err);
+                    complete = false;
+                }
+            }
+        }
+        self.material_names
+            .iter()
+            .map(|name| {
+                library.iter().find(|material| &material.name == name).cloned().unwrap_or_else(|| {
+                    // Without every library a missing name is expected, and already reported
+                    if complete {
+                        eprintln!("{}: material desconocido '{}'; se usa el gris por defecto", filename, name);
+                    }
+                    Material { name: name.clone(), ..Material::fallback() }
+                })
+            })
+            .collect()
+    }
+
+    // Merge corners that share all three indices into one vertex, checked with
+    // Vertex::validated. Corners without a normal get +Y and corners without a UV get
+    // (0, 0). Triangles are gathered by material, keeping the file's order within each one
+    fn finish(self, filename: &str) -> Result<Obj, ObjError> {
+        let materials = self.resolve_materials(filename);
+        let mut order: Vec<usize> = (0..self.triangle_materials.len()).collect();
+        order.sort_by_key(|&triangle| self.triangle_materials[triangle].map_or(0, |material| material + 1));
+
+        let mut groups: Vec<MaterialGroup> = Vec::new();
         let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(self.corners.len());
+        let mut unique: HashMap<Corner, u32> = HashMap::new();
+        for (position, &triangle) in order.iter().enumerate() {
+            let material = self.triangle_materials[triangle];
+            if position == 0 || self.triangle_materials[order[position - 1]] != material {
+                let material = material.map_or_else(Material::fallback, |index| materials[index].clone());
+                groups.push(MaterialGroup { material, indices: indices.len()..indices.len() });
+            }
+            for corner in &self.corners[triangle * 3..triangle * 3 + 3] {
+                let index = match unique.get(corner) {
+                    Some(&index) => index,
+                    None => {
+                        let normal = corner.normal
+                            .map(|index| self.normals[index])
+                            .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                        let tex_coords = corner.tex_coords
+                            .map(|index| self.texcoords[index])
+                            .unwrap_or(Vec2::new(0.0, 0.0));
+                        let vertex = Vertex::validated(self.positions[corner.position], normal, tex_coords)
+                            .map_err(|err| ObjError::Parse(filename.to_string(), self.position_lines[corner.position], err.to_string()))?;
+                        vertices.push(vertex);
+                        let index = (vertices.len() - 1) as u32;
+                        unique.insert(*corner, index);
+                        index
+                    }
+                };
+                indices.push(index);
+            }
+            if let Some(group) = groups.last_mut() {
+                group.indices.end = indices.len();
+            }
+        }
+        Ok(Obj { vertices, indices, groups, normalization: None, triangulated_faces: self.triangulated_faces })
+    }
+}
 
-        for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
-
-                vertices.push(Vertex::new(position, normal, tex_coords));
+impl Obj {
+    pub fn load(filename: &str) -> Result<Self, ObjError> {
+        Self::load_with(filename, &LoadOptions::default())
+    }
+
+    pub fn load_with(filename: &str, options: &LoadOptions) -> Result<Self, ObjError> {
+        let source = fs::read_to_string(filename).map_err(|err| ObjError::Io(filename.to_string(), err))?;
+        Self::parse(&source, filename, options)
+    }
+
+    // Reads positions (`v`), UVs (`vt`), normals (`vn`), faces (`f`) and materials
+    // (`mtllib`, `usemtl`); groups, objects and smoothing groups are ignored. `filename`
+    // names the source in errors, and `mtllib` paths are resolved against its directory
+    pub fn parse(source: &str, filename: &str, options: &LoadOptions) -> Result<Self, ObjError> {
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        let mut parser = Parser::default();
+        let mut face = Vec::new();
+        for (number, line) in source.lines().enumerate() {
+            let error = |msg: String| ObjError::Parse(filename.to_string(), number + 1, msg);
+            let mut fields = line.split_whitespace();
+            let Some(keyword) = fields.next() else {
+                continue;
+            };
+            let fields: Vec<&str> = fields.collect();
+            // Names and paths run to the end of the line and may have spaces
+            let rest = line.trim_start()[keyword.len()..].trim();
+            match keyword {
+                "v" => {
+                    let [x, y, z] = parse_floats(&fields, 3).map_err(error)?;
+                    parser.positions.push(Vec3::new(x, y, z));
+                    parser.position_lines.push(number + 1);
+                }
+                "vn" => {
+                    let [x, y, z] = parse_floats(&fields, 3).map_err(error)?;
+                    parser.normals.push(Vec3::new(x, y, z));
+                }
+                "vt" => {
+                    let [u, v] = parse_floats(&fields, 1).map_err(error)?;
+                    parser.texcoords.push(Vec2::new(u, if options.flip_v { 1.0 - v } else { v }));
+                }
+                "f" => {
+                    face.clear();
+                    for field in &fields {
+                        face.push(parser.parse_corner(field).map_err(error)?);
+                    }
+                    if triangulate(&face, &mut parser.corners) {
+                        parser.triangulated_faces += 1;
+                    }
+                    let triangles = parser.corners.len() / 3 - parser.triangle_materials.len();
+                    parser.triangle_materials.extend(std::iter::repeat_n(parser.current_material, triangles));
+                }
+                "mtllib" => parser.material_libraries.push(directory.join(rest).to_string_lossy().into_owned()),
+                "usemtl" => parser.use_material(rest),
+                _ => {}
             }
         }
 
-        vertices
+        if parser.triangulated_faces > 0 {
+            eprintln!("{}: {} caras de más de 3 vértices divididas en triángulos", filename, parser.triangulated_faces);
+        }
+        parser.finish(filename)
+    }
+
+    // Load and recenter the mesh on its bounding-box center, scaled uniformly so its
+    // largest extent is 1.0. Normals are left as they are: a translation plus a uniform
+    // scale doesn't change their direction
+    pub fn load_normalized(filename: &str) -> Result<Self, ObjError> {
+        let mut obj = Self::load(filename)?;
+
+        let mut min = Vec3::repeat(f32::INFINITY);
+        let mut max = Vec3::repeat(f32::NEG_INFINITY);
+        for vertex in &obj.vertices {
+            min = min.inf(&vertex.position);
+            max = max.sup(&vertex.position);
+        }
+        if min.x > max.x {
+            return Ok(obj); // No vertices, nothing to normalize
+        }
+
+        let center = (min + max) * 0.5;
+        let extent = (max - min).max();
+        let scale = if extent > 0.0 { 1.0 / extent } else { 1.0 };
+        for vertex in &mut obj.vertices {
+            *vertex = Vertex::new((vertex.position - center) * scale, vertex.normal, vertex.tex_coords);
+        }
+
+        obj.normalization = Some(Normalization { center, scale });
+        Ok(obj)
+    }
+
+    // The transform applied by `load_normalized`, or None for raw coordinates
+    #[allow(dead_code)]
+    pub fn normalization(&self) -> Option<Normalization> {
+        self.normalization
+    }
+
+    // Faces with 4 or more corners that `load` split into triangles
+    pub fn triangulated_faces(&self) -> usize {
+        self.triangulated_faces
+    }
+
+    // The triangles by material, in order of the materials' first use. Models without
+    // `usemtl` have a single group with the fallback material
+    pub fn groups(&self) -> &[MaterialGroup] {
+        &self.groups
+    }
+
+    // The mesh's distinct vertices
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    // Three indices into `vertices` per triangle, in the file's face order within each
+    // material group
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    // The triangles expanded to three vertices each, for code that wants unindexed triangles
+    pub fn get_vertex_array(&self) -> Vec<Vertex> {
+        self.indices.iter().map(|&index| self.vertices[index as usize].clone()).collect()
     }
 }
@@ -1,4 +1,6 @@
-#[derive(PartialEq)]
+/// What kind of body something is, which picks its shader. Spaceship and Trail are the
+/// ship's and the trail particles' own shaders, not bodies a scene can place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PlanetType {
     Sun,
     RockyPlanet,
@@ -10,5 +12,54 @@ pub enum PlanetType {
     Moon,
     Asteroid,
     Spaceship,
+    #[allow(dead_code)]
     Trail,
 }
+
+impl PlanetType {
+    /// Celestial body types, i.e. the ones a scene file may place.
+    pub const BODIES: [PlanetType; 9] = [
+        PlanetType::Sun, PlanetType::RockyPlanet, PlanetType::Earth, PlanetType::CrystalPlanet,
+        PlanetType::FirePlanet, PlanetType::WaterPlanet, PlanetType::CloudPlanet,
+        PlanetType::Moon, PlanetType::Asteroid,
+    ];
+
+    /// Display name used by the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlanetType::Sun => "Sol",
+            PlanetType::RockyPlanet => "Planeta Rocoso",
+            PlanetType::Earth => "Tierra",
+            PlanetType::CrystalPlanet => "Planeta Cristal",
+            PlanetType::FirePlanet => "Planeta de Fuego",
+            PlanetType::WaterPlanet => "Planeta de Agua",
+            PlanetType::CloudPlanet => "Planeta Nube",
+            PlanetType::Moon => "Luna",
+            PlanetType::Asteroid => "Asteroide",
+            PlanetType::Spaceship => "Nave",
+            PlanetType::Trail => "Estela",
+        }
+    }
+
+    /// Identifier used in scene files.
+    pub fn id(&self) -> &'static str {
+        match self {
+            PlanetType::Sun => "sun",
+            PlanetType::RockyPlanet => "rocky",
+            PlanetType::Earth => "earth",
+            PlanetType::CrystalPlanet => "crystal",
+            PlanetType::FirePlanet => "fire",
+            PlanetType::WaterPlanet => "water",
+            PlanetType::CloudPlanet => "cloud",
+            PlanetType::Moon => "moon",
+            PlanetType::Asteroid => "asteroid",
+            PlanetType::Spaceship => "spaceship",
+            PlanetType::Trail => "trail",
+        }
+    }
+
+    /// The body type a scene file's `type` names, in any case.
+    pub fn from_id(id: &str) -> Option<PlanetType> {
+        PlanetType::BODIES.iter().copied().find(|planet_type| planet_type.id().eq_ignore_ascii_case(id))
+    }
+}
@@ -0,0 +1,161 @@
+use std::time::{Duration, Instant};
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+// How often the on-screen averages are refreshed
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Scope {
+    name: &'static str,
+    window: Duration, // accumulated since the last report
+    total: Duration,  // accumulated since profiling was enabled
+}
+
+// Per-frame timings of named scopes. Times are summed per frame (a scope entered once
+// per body counts all of them) and shown as a rolling average refreshed every second.
+// While disabled, `time` is just a call through, so it can stay in hot paths
+pub struct FrameProfile {
+    enabled: bool,
+    scopes: Vec<Scope>,
+    counters: Vec<(&'static str, u64)>, // totals since profiling was enabled
+    window_start: Instant,
+    window_frames: u32,
+    total_frames: u32,
+    averages: Vec<(&'static str, f32)>, // milliseconds per frame, from the last report
+}
+
+impl FrameProfile {
+    pub fn new(enabled: bool) -> Self {
+        FrameProfile {
+            enabled,
+            scopes: Vec::new(),
+            counters: Vec::new(),
+            window_start: Instant::now(),
+            window_frames: 0,
+            total_frames: 0,
+            averages: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Turning profiling on starts from a clean slate
+    pub fn toggle(&mut self) {
+        *self = FrameProfile::new(!self.enabled);
+    }
+
+    // Run `f` and charge its duration to scope `name`
+    pub fn time<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    pub fn record(&mut self, name: &'static str, elapsed: Duration) {
+        if !self.enabled {
+            return;
+        }
+        // Only a handful of scopes, so a linear search beats hashing
+        match self.scopes.iter_mut().find(|scope| scope.name == name) {
+            Some(scope) => {
+                scope.window += elapsed;
+                scope.total += elapsed;
+            }
+            None => self.scopes.push(Scope { name, window: elapsed, total: elapsed }),
+        }
+    }
+
+    // Add `amount` to counter `name` (triangles, fragments, ...)
+    pub fn count(&mut self, name: &'static str, amount: usize) {
+        if !self.enabled {
+            return;
+        }
+        match self.counters.iter_mut().find(|(counter, _)| *counter == name) {
+            Some((_, total)) => *total += amount as u64,
+            None => self.counters.push((name, amount as u64)),
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        self.window_frames += 1;
+        self.total_frames += 1;
+
+        if self.window_start.elapsed() >= REPORT_INTERVAL {
+            let frames = self.window_frames as f32;
+            self.averages = self.scopes.iter()
+                .map(|scope| (scope.name, scope.window.as_secs_f32() * 1000.0 / frames))
+                .collect();
+            for scope in &mut self.scopes {
+                scope.window = Duration::ZERO;
+            }
+            self.window_start = Instant::now();
+            self.window_frames = 0;
+        }
+    }
+
+    // Average milliseconds per frame of every scope since profiling was enabled
+    pub fn stage_averages(&self) -> Vec<(&'static str, f32)> {
+        let frames = self.total_frames.max(1) as f32;
+        self.scopes.iter()
+            .map(|scope| (scope.name, scope.total.as_secs_f32() * 1000.0 / frames))
+            .collect()
+    }
+
+    // Average count per frame of every counter since profiling was enabled
+    pub fn counter_averages(&self) -> Vec<(&'static str, f64)> {
+        let frames = self.total_frames.max(1) as f64;
+        self.counters.iter().map(|&(name, total)| (name, total as f64 / frames)).collect()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("Perfil ({} cuadros, ms por cuadro):", self.total_frames)];
+        for (name, ms) in self.stage_averages() {
+            lines.push(format!("  {:<10}{:>8.3}", name, ms));
+        }
+        if !self.counters.is_empty() {
+            lines.push("Promedio por cuadro:".to_string());
+            for (name, count) in self.counter_averages() {
+                lines.push(format!("  {:<10}{:>10.0}", name, count));
+            }
+        }
+        lines.join("\n")
+    }
+
+    // Panel with the rolling averages, anchored to the top-right corner below `top`
+    pub fn draw(&self, framebuffer: &mut Framebuffer, top: usize) {
+        if !self.enabled {
+            return;
+        }
+
+        let title = "Perfil (ms/cuadro)";
+        let lines: Vec<String> = if self.averages.is_empty() {
+            vec!["midiendo...".to_string()]
+        } else {
+            self.averages.iter()
+                .map(|(name, ms)| format!("{:<10}{:>7.2}", name, ms))
+                .collect()
+        };
+
+        let width = lines.iter().map(|l| text_width(l, 1))
+            .chain([text_width(title, 1)])
+            .max()
+            .unwrap_or(0) + 16;
+        let height = LINE_ADVANCE * (lines.len() + 1) + 10;
+        let x = framebuffer.width.saturating_sub(10 + width);
+        framebuffer.blend_rect(x, top, width, height, 0x000000, 0.6);
+
+        draw_text(framebuffer, x + 8, top + 8, title, 0xFFD700, 1);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(framebuffer, x + 8, top + 8 + LINE_ADVANCE * (i + 1), line, 0xA0A0A0, 1);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+const TITLE_COLOR: u32 = 0xFFD700; // the profile panel's
+const TEXT_COLOR: u32 = 0xA0A0A0;
+// Pixels from the left edge of the screen
+const MARGIN: usize = 10;
+
+// What the renderer did on its last frame. Reset at the start of every render_scene and
+// only ever incremented while drawing, so leaving it on costs a few additions per
+// triangle and changes nothing it counts
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStats {
+    pub triangles: usize,      // handed to the rasterizer, over every mesh drawn
+    pub back_facing: usize,    // of those, the ones facing away from the camera: what backface culling would save
+    pub frustum_culled: usize, // in the meshes of bodies is_in_frustum skipped
+    pub fragments: usize,      // produced by the rasterizer
+    pub depth_rejected: usize, // of those, the ones behind what their pixel already held, never shaded
+    pub particles: usize,      // trail particles drawn, after the far trails are thinned out
+}
+
+impl RenderStats {
+    // One label and count per line, as the overlay shows them
+    pub fn lines(&self) -> Vec<String> {
+        [
+            ("triangulos", self.triangles),
+            ("  de espaldas", self.back_facing),
+            ("fuera del frustum", self.frustum_culled),
+            ("fragmentos", self.fragments),
+            ("  ocultos", self.depth_rejected),
+            ("particulas", self.particles),
+        ]
+        .iter()
+        .map(|(name, count)| format!("{:<18}{:>9}", name, count))
+        .collect()
+    }
+
+    // The same counts on a single line, for the console
+    pub fn summary(&self) -> String {
+        format!(
+            "{} triangulos ({} de espaldas, {} fuera del frustum), {} fragmentos ({} ocultos), {} particulas",
+            self.triangles, self.back_facing, self.frustum_culled, self.fragments, self.depth_rejected, self.particles
+        )
+    }
+
+    // Panel at the left edge of the screen, halfway down
+    pub fn draw(&self, framebuffer: &mut Framebuffer) {
+        let title = "Render (ultimo cuadro)";
+        let lines = self.lines();
+        let width = lines.iter().map(|line| text_width(line, 1))
+            .chain([text_width(title, 1)])
+            .max()
+            .unwrap_or(0) + 16;
+        let height = LINE_ADVANCE * (lines.len() + 1) + 10;
+        let y = framebuffer.height.saturating_sub(height) / 2;
+        framebuffer.blend_rect(MARGIN, y, width, height, 0x000000, 0.6);
+
+        draw_text(framebuffer, MARGIN + 8, y + 8, title, TITLE_COLOR, 1);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(framebuffer, MARGIN + 8, y + 8 + LINE_ADVANCE * (i + 1), line, TEXT_COLOR, 1);
+        }
+    }
+}
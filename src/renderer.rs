@@ -0,0 +1,652 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::thread;
+use std::time::{Duration, Instant};
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::camera::{Camera, CameraMode};
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, FramebufferBand, PointTest};
+use crate::grid::render_grid;
+use crate::impostor::{impostor_weight, projected_radius, render_impostor, Impostor, ImpostorSprite};
+use crate::lights::PointLights;
+use crate::mesh::{Mesh, Model};
+use crate::planet::PlanetType;
+use crate::profile::FrameProfile;
+use crate::render_stats::RenderStats;
+use crate::scene::{ambient_floor, default_bump_strength, sun_position, CelestialBody, Orbit, OrbitPath, Scene, TrailParticle};
+use crate::seeds::{body_seed, Seeds};
+use crate::shader_params::{ShaderParamTable, StarParams};
+use crate::shaders::{fragment_shader, vertex_shader, Shading};
+use crate::sprite::{render_billboard, Billboard, DEFAULT_PARTICLE_SOFTNESS};
+use crate::transform::{create_model_matrix, create_perspective_matrix, create_view_matrix, create_viewport_matrix};
+use crate::triangle::{is_back_facing, triangle, triangle_in_rows};
+use crate::uniforms::{DrawUniforms, FrameUniforms};
+
+// Length of the ship in world units (the model is loaded normalized to length 1)
+pub const SPACESHIP_SCALE: f32 = 3.5;
+
+// Orbit polylines: the fewest and most segments `orbit_segments` picks, and how many
+// points of the orbit it checks for the one nearest the camera
+const MIN_ORBIT_SEGMENTS: usize = 24;
+const MAX_ORBIT_SEGMENTS: usize = 1024;
+const ORBIT_PROBES: usize = 64;
+
+// Trails of bodies farther than this from the camera draw only every
+// TRAIL_THINNING_STRIDE-th particle: from there they are thin lines anyway
+pub const TRAIL_THINNING_DISTANCE: f32 = 60.0;
+pub const TRAIL_THINNING_STRIDE: usize = 4;
+
+/// Draws a [`Scene`] as seen from a [`Camera`] into a [`Framebuffer`].
+///
+/// Owns everything that isn't part of the world: the ship's mesh, the shaders' noise and
+/// parameters, output settings and the stage profile.
+pub struct Renderer {
+    pub noise: FastNoiseLite,
+    pub shader_params: ShaderParamTable,
+    pub dither: bool,
+    pub exposure: f32,
+    pub shading: Shading,
+    pub profile: FrameProfile,
+    pub stats: RenderStats, // counts of the last frame drawn
+    pub time_from_frame: bool, // animate with the scene's frame count instead of its clock
+    pub threads: usize, // framebuffer bands drawn in parallel; 1 = everything on this thread
+    pub grid: bool,     // reference grid on the orbital plane
+    pub orbits: bool,   // orbit lines; each body's `visibility` can also hide its own
+    pub trails: bool,   // trail particles, likewise
+    pub particle_softness: f32, // see Billboard::softness
+    pub override_light: Option<Vec3>, // light direction steered by hand instead of the scene's
+    pub log_depth: bool, // logarithmic depth buffer, see FrameUniforms::depth
+    ship_model: Option<Model>,
+    impostors: HashMap<PlanetType, ImpostorSprite>,
+    lod_levels: Vec<Option<usize>>, // each body's icosphere level on the last frame
+    asteroid_seed: u64,
+    frame: FrameUniforms, // uniforms of the last rendered frame
+}
+
+impl Renderer {
+    /// A renderer for a `width` x `height` framebuffer. Each celestial body is drawn with
+    /// its own mesh (`CelestialBody::mesh`), scaled to its size.
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut renderer = Renderer {
+            noise: create_noise(Seeds::default().surface_noise),
+            shader_params: ShaderParamTable::new(),
+            dither: true,
+            exposure: 1.0,
+            shading: Shading::Phong,
+            profile: FrameProfile::new(false),
+            stats: RenderStats::default(),
+            time_from_frame: false,
+            threads: 1,
+            grid: false,
+            orbits: true,
+            trails: true,
+            particle_softness: DEFAULT_PARTICLE_SOFTNESS,
+            override_light: None,
+            log_depth: false,
+            ship_model: None,
+            impostors: HashMap::new(),
+            lod_levels: Vec::new(),
+            asteroid_seed: Seeds::default().asteroids,
+            frame: FrameUniforms::first(width, height),
+        };
+        renderer.update_impostors();
+        renderer
+    }
+
+    // Draw the ship at the camera in chase view (and at a fixed spot in bird's eye view)
+    pub fn with_ship(mut self, model: Model) -> Self {
+        self.ship_model = Some(model);
+        self
+    }
+
+    // Reseed the shaders' noise and random offsets from `seeds`
+    pub fn with_seeds(mut self, seeds: &Seeds) -> Self {
+        self.noise = create_noise(seeds.surface_noise);
+        self.frame.seed = seeds.water;
+        self.asteroid_seed = seeds.asteroids;
+        self.update_impostors();
+        self
+    }
+
+    pub fn frame(&self) -> &FrameUniforms {
+        &self.frame
+    }
+
+    // Render into a framebuffer of a different size from now on (e.g. a scaled internal
+    // resolution); only the projection and viewport depend on it
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.frame.projection_matrix = create_perspective_matrix(width as f32, height as f32);
+        self.frame.viewport_matrix = create_viewport_matrix(width as f32, height as f32);
+    }
+
+    // Replace every body type's shader parameters (e.g. from a reloaded scene)
+    pub fn set_shader_params(&mut self, table: ShaderParamTable) {
+        self.shader_params = table;
+        self.update_impostors();
+    }
+
+    // The distant impostor of `planet_type`: its small image and average color
+    pub fn impostor(&self, planet_type: PlanetType) -> &ImpostorSprite {
+        &self.impostors[&planet_type]
+    }
+
+    // Render every body type's impostor sprite again from the current shader parameters
+    // (done by itself when the renderer is built, reseeded or given a new table; call it
+    // after changing `shader_params` in place). Sampled on a sphere whatever mesh the
+    // bodies are drawn with
+    pub fn update_impostors(&mut self) {
+        let frame = FrameUniforms { shading: self.shading, ..self.frame_uniforms(Mat4::identity(), 0, Vec3::zeros()) };
+        self.impostors = PlanetType::BODIES.iter()
+            .map(|&planet_type| {
+                let uniforms = DrawUniforms {
+                    frame: &frame,
+                    model_matrix: Mat4::identity(),
+                    shader: planet_type,
+                    params: self.shader_params.get(planet_type),
+                    noise: &self.noise,
+                    seed: 0,
+                    base_color: None,
+                    ambient: ambient_floor(planet_type),
+                    bump_strength: default_bump_strength(planet_type),
+                };
+                (planet_type, ImpostorSprite::render(&uniforms))
+            })
+            .collect();
+    }
+
+    fn frame_uniforms(&self, view_matrix: Mat4, time: u32, camera_position: Vec3) -> FrameUniforms {
+        FrameUniforms {
+            view_matrix,
+            projection_matrix: self.frame.projection_matrix,
+            viewport_matrix: self.frame.viewport_matrix,
+            time,
+            camera_position,
+            sun_position: self.frame.sun_position,
+            exposure: self.exposure,
+            dither: self.dither,
+            shading: self.shading,
+            seed: self.frame.seed,
+            star: StarParams::default(),
+            light_intensity: 1.0,
+            override_light: self.override_light,
+            lights: PointLights::default(),
+            log_depth: self.log_depth,
+        }
+    }
+
+    /// Clear the framebuffer and draw the sky, the bodies with their orbits, the ship and
+    /// the trails, in that order. Parts hidden by a body's `visibility` or by `orbits` and
+    /// `trails` are skipped.
+    pub fn render_scene(&mut self, framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera) {
+        let params = std::mem::take(&mut self.shader_params);
+        self.render_scene_with(framebuffer, scene, camera, &params);
+        self.shader_params = params;
+    }
+
+    // render_scene with the shaders' parameters taken from `params` for this call only,
+    // instead of `shader_params` (e.g. two sets of parameters side by side). The impostors
+    // keep the sprites rendered from `shader_params`
+    pub fn render_scene_with(&mut self, framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, params: &ShaderParamTable) {
+        let view_eye = camera.view_eye();
+        let view_matrix = create_view_matrix(view_eye, camera.view_center(), camera.up);
+        // Shaders count time in 60 Hz frames
+        let time = if self.time_from_frame { scene.frame } else { (scene.time * 60.0) as u32 };
+        self.frame = FrameUniforms {
+            star: scene.star,
+            light_intensity: scene.star.brightness(time as f32 / 60.0),
+            sun_position: sun_position(&scene.bodies),
+            lights: scene.lights,
+            ..self.frame_uniforms(view_matrix, time, view_eye)
+        };
+
+        framebuffer.clear();
+        self.stats = RenderStats::default();
+
+        let frame = &self.frame;
+        self.profile.time("cielo", || scene.skybox.render(framebuffer, frame));
+        scene.meteors.render(framebuffer, &self.frame);
+
+        // Far bodies are drawn as a small image of their shader (impostor) that blends into
+        // the mesh as the camera gets closer
+        let (right, up, _) = camera.get_local_axes();
+        self.lod_levels.resize(scene.bodies.len(), None);
+        for (index, body) in scene.bodies.iter().enumerate() {
+            if !body.visibility.body {
+                continue;
+            }
+            let radius_px = projected_radius(&self.frame, framebuffer, body.position, body.scale);
+            if !is_in_frustum(body, &self.frame.view_matrix, &self.frame.projection_matrix) {
+                self.stats.frustum_culled += body.mesh.select(radius_px).triangle_count();
+                continue;
+            }
+            let weight = impostor_weight(radius_px);
+
+            if weight < 1.0 {
+                let model_matrix = create_model_matrix(
+                    body.position,
+                    body.scale,
+                    body.rotation + Vec3::new(0.0, time as f32 * 0.01, 0.0)
+                );
+                let uniforms = DrawUniforms {
+                    frame: &self.frame,
+                    model_matrix,
+                    shader: body.shader_type,
+                    params: params.get(body.shader_type),
+                    noise: &self.noise,
+                    seed: body_seed(self.asteroid_seed, index),
+                    base_color: None,
+                    ambient: body.ambient,
+                    bump_strength: body.bump_strength,
+                };
+                let mesh = body.mesh.select_from(radius_px, &mut self.lod_levels[index]);
+                render(framebuffer, &uniforms, mesh, &mut self.profile, &mut self.stats, self.threads);
+            }
+            if weight > 0.0 {
+                let impostor = Impostor {
+                    center: body.position,
+                    projected_radius: radius_px,
+                    brightness: self.frame.light_intensity,
+                    alpha: weight,
+                };
+                render_impostor(framebuffer, &self.frame, &impostor, &self.impostors[&body.shader_type]);
+            }
+        }
+
+        // The grid and the orbits go after the bodies so the depth test hides them behind
+        // the planets
+        let frame = &self.frame;
+        if self.grid {
+            self.profile.time("cuadricula", || render_grid(framebuffer, frame));
+        }
+        let orbits = self.orbits;
+        self.profile.time("orbitas", || {
+            for (i, body) in scene.bodies.iter().enumerate() {
+                if body.shader_type == PlanetType::Sun || scene.captured == Some(i) {
+                    continue; // The sun has no orbit, and the ship holds the captured body off its own
+                }
+                if !orbits || !body.visibility.orbit {
+                    continue;
+                }
+                let path = scene.orbit_path(i);
+                let segments = orbit_segments(frame, framebuffer.height, &path);
+                render_orbit_path(framebuffer, frame, &path.orbit, path.center, path.angle, segments, scene.orbit_colors[i]);
+            }
+        });
+
+        // In first person the camera is the cockpit and the ship isn't drawn. In bird's eye
+        // view the ship stays at a fixed spot; in third person it sits at the cockpit (camera.eye)
+        if let Some(model) = &self.ship_model {
+            if camera.bird_eye_active || camera.mode == CameraMode::ThirdPerson {
+                let (position, rotation) = if camera.bird_eye_active {
+                    (Vec3::new(0.0, 5.0, 15.0), Vec3::new(0.0, 0.0, camera.roll))
+                } else {
+                    (camera.eye, spaceship_rotation(camera.look_direction(), camera.roll))
+                };
+                let model_matrix = create_model_matrix(position, SPACESHIP_SCALE, rotation);
+                // One draw per material, each with its own base color
+                for part in &model.parts {
+                    let uniforms = DrawUniforms {
+                        frame: &self.frame,
+                        model_matrix,
+                        shader: PlanetType::Spaceship,
+                        params: params.get(PlanetType::Spaceship),
+                        noise: &self.noise,
+                        seed: 0,
+                        base_color: Some(part.material.diffuse),
+                        ambient: 0.0,
+                        bump_strength: 0.0,
+                    };
+                    render(framebuffer, &uniforms, &part.mesh, &mut self.profile, &mut self.stats, self.threads);
+                }
+            }
+        }
+
+        // Transparent pass: comets and trails go after all opaque geometry (trails sorted
+        // back to front so they blend correctly)
+        let frame = &self.frame;
+        scene.meteors.render_comets(framebuffer, frame);
+        let (trails, softness) = (self.trails, self.particle_softness);
+        let drawn = self.profile.time("estelas", || {
+            // Parent-relative trails are placed around their parent's current position
+            let mut particles: Vec<(f32, Vec3, &TrailParticle)> = scene.bodies.iter()
+                .enumerate()
+                .filter(|(_, body)| trails && body.visibility.trail)
+                .flat_map(|(i, body)| {
+                    let origin = scene.trail_origin(i);
+                    body.trail.thinned(trail_stride(body, view_eye)).map(move |particle| (origin, particle))
+                })
+                .map(|(origin, particle)| ((origin + particle.position - view_eye).magnitude_squared(), origin, particle))
+                .collect();
+            particles.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let drawn = particles.len();
+            for (_, origin, particle) in particles {
+                render_trail(framebuffer, frame, particle, origin, right, up, softness);
+            }
+            drawn
+        });
+        self.profile.count("particulas", drawn);
+        self.stats.particles = drawn;
+    }
+}
+
+fn create_noise(seed: i32) -> FastNoiseLite {
+    create_cloud_noise(seed)
+}
+
+fn create_cloud_noise(seed: i32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise
+}
+
+// Rotation (x, y, z) that lines the model's +Z axis up with the flight direction
+fn spaceship_rotation(direction: Vec3, roll: f32) -> Vec3 {
+    let pitch = -direction.y.clamp(-1.0, 1.0).asin();
+    let yaw = direction.x.atan2(direction.z);
+    Vec3::new(pitch, yaw, roll)
+}
+
+fn render(framebuffer: &mut Framebuffer, uniforms: &DrawUniforms, mesh: &Mesh, profile: &mut FrameProfile, stats: &mut RenderStats, threads: usize) {
+    if threads > 1 {
+        render_bands(framebuffer, uniforms, mesh, profile, stats, threads);
+        return;
+    }
+
+    // Vertex Shader Stage: each distinct vertex once, however many triangles share it
+    let transformed_vertices = profile.time("vertices", || {
+        let mut transformed_vertices = Vec::with_capacity(mesh.vertices.len());
+        for vertex in &mesh.vertices {
+            let transformed = vertex_shader(vertex, uniforms);
+            transformed_vertices.push(transformed);
+        }
+        transformed_vertices
+    });
+    profile.count("triangulos", mesh.triangle_count());
+    stats.triangles += mesh.triangle_count();
+
+    // Primitive Assembly + Rasterization Stage
+    let light_direction = uniforms.frame.light_direction();
+    let fragments = profile.time("raster", || {
+        let mut fragments = Vec::new();
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|index| &transformed_vertices[index as usize]);
+            stats.back_facing += usize::from(is_back_facing(a, b, c));
+            fragments.extend(triangle(a, b, c, uniforms.frame.shading, &light_direction));
+        }
+        fragments
+    });
+    profile.count("fragmentos", fragments.len());
+    stats.fragments += fragments.len();
+
+    // Fragment Processing Stage
+    profile.time("fragmentos", || {
+        for fragment in fragments {
+            // Off-screen and hidden fragments are dropped before the shader runs
+            let test = framebuffer.try_point(fragment.position.x, fragment.position.y, fragment.depth, |x, y| {
+                shade_fragment(&fragment, uniforms, x, y)
+            });
+            stats.depth_rejected += usize::from(test == PointTest::Hidden);
+        }
+    });
+}
+
+// Final color of a fragment landing on pixel (x, y): shader, exposure and dithering
+fn shade_fragment(fragment: &Fragment, uniforms: &DrawUniforms, x: usize, y: usize) -> u32 {
+    let shaded_color = fragment_shader(fragment, uniforms).exposed(uniforms.frame.exposure);
+    if uniforms.frame.dither {
+        shaded_color.to_hex_dithered(x, y)
+    } else {
+        shaded_color.to_hex()
+    }
+}
+
+// What one band's pass through the pipeline took, merged into the profile afterwards
+struct BandStats {
+    vertices: Duration,
+    raster: Duration,
+    fragments: Duration,
+    triangles: usize,
+    back_facing: usize,
+    fragment_count: usize,
+    depth_rejected: usize,
+}
+
+// `render` split across `threads` horizontal bands of the framebuffer, one thread each.
+// Every thread runs the whole pipeline over the whole mesh but only rasterizes its own
+// rows, so each pixel sees the same fragments in the same order as with one thread and
+// the image is identical. Stage times are the slowest band's, since the bands overlap
+fn render_bands(framebuffer: &mut Framebuffer, uniforms: &DrawUniforms, mesh: &Mesh, profile: &mut FrameProfile, render_stats: &mut RenderStats, threads: usize) {
+    let bands = framebuffer.bands(threads);
+    let last_band = bands.len().saturating_sub(1);
+    let stats: Vec<BandStats> = thread::scope(|scope| {
+        let workers: Vec<_> = bands
+            .into_iter()
+            .enumerate()
+            .map(|(i, band)| {
+                // The first band also rasterizes every row above the screen and the last one
+                // every row below; try_point drops them, but the fragment count matches `render`
+                let first_row = if i == 0 { i32::MIN } else { band.first_row as i32 };
+                let last_row = if i == last_band { i32::MAX } else { (band.first_row + band.rows) as i32 - 1 };
+                scope.spawn(move || render_band(band, first_row, last_row, uniforms, mesh))
+            })
+            .collect();
+        workers.into_iter().map(|worker| worker.join().expect("render band thread panicked")).collect()
+    });
+
+    let slowest = |stage: fn(&BandStats) -> Duration| stats.iter().map(stage).max().unwrap_or_default();
+    profile.record("vertices", slowest(|band| band.vertices));
+    profile.record("raster", slowest(|band| band.raster));
+    profile.record("fragmentos", slowest(|band| band.fragments));
+    profile.count("triangulos", stats.first().map_or(0, |band| band.triangles));
+    profile.count("fragmentos", stats.iter().map(|band| band.fragment_count).sum());
+    // Every band sees every triangle; each fragment lands in one band
+    render_stats.triangles += stats.first().map_or(0, |band| band.triangles);
+    render_stats.back_facing += stats.first().map_or(0, |band| band.back_facing);
+    render_stats.fragments += stats.iter().map(|band| band.fragment_count).sum::<usize>();
+    render_stats.depth_rejected += stats.iter().map(|band| band.depth_rejected).sum::<usize>();
+}
+
+fn render_band(mut band: FramebufferBand, first_row: i32, last_row: i32, uniforms: &DrawUniforms, mesh: &Mesh) -> BandStats {
+    let start = Instant::now();
+    let transformed_vertices: Vec<_> = mesh.vertices.iter().map(|vertex| vertex_shader(vertex, uniforms)).collect();
+    let vertices = start.elapsed();
+
+    let start = Instant::now();
+    let light_direction = uniforms.frame.light_direction();
+    let mut fragments = Vec::new();
+    let mut back_facing = 0;
+    for tri in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|index| &transformed_vertices[index as usize]);
+        back_facing += usize::from(is_back_facing(a, b, c));
+        fragments.extend(triangle_in_rows(a, b, c, uniforms.frame.shading, &light_direction, first_row, last_row));
+    }
+    let raster = start.elapsed();
+    let fragment_count = fragments.len();
+
+    let start = Instant::now();
+    let mut depth_rejected = 0;
+    for fragment in fragments {
+        let test = band.try_point(fragment.position.x, fragment.position.y, fragment.depth, |x, y| {
+            shade_fragment(&fragment, uniforms, x, y)
+        });
+        depth_rejected += usize::from(test == PointTest::Hidden);
+    }
+
+    BandStats {
+        vertices,
+        raster,
+        fragments: start.elapsed(),
+        triangles: mesh.triangle_count(),
+        back_facing,
+        fragment_count,
+        depth_rejected,
+    }
+}
+
+fn render_trail(
+    framebuffer: &mut Framebuffer,
+    uniforms: &FrameUniforms,
+    particle: &TrailParticle,
+    origin: Vec3, // what the particle's position is relative to (Scene::trail_origin)
+    right: Vec3,
+    up: Vec3,
+    softness: f32,
+) {
+    // Particles fade out during their last second of life
+    let billboard = Billboard {
+        center: origin + particle.position,
+        half_size: particle.size * 0.5,
+        color: particle.color & 0x00FFFFFF,
+        alpha: particle.lifetime.clamp(0.0, 1.0) * 0.35,
+        softness,
+    };
+
+    render_billboard(framebuffer, uniforms, &billboard, right, up);
+}
+
+// Which of `body`'s trail particles get drawn from `eye`: all of them up close, every
+// TRAIL_THINNING_STRIDE-th one for a body past TRAIL_THINNING_DISTANCE
+pub fn trail_stride(body: &CelestialBody, eye: Vec3) -> usize {
+    if (body.position - eye).magnitude() > TRAIL_THINNING_DISTANCE {
+        TRAIL_THINNING_STRIDE
+    } else {
+        1
+    }
+}
+
+// Whether a body gets drawn from this view: only its center is tested, so a body whose
+// center has left the screen is culled even while part of its disc is still in view
+pub fn is_in_frustum(body: &CelestialBody, view_matrix: &Mat4, projection_matrix: &Mat4) -> bool {
+    let model_matrix = create_model_matrix(body.position, body.scale, body.rotation);
+    let mvp_matrix = projection_matrix * view_matrix * model_matrix;
+
+    let clip_space_position = mvp_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let w = clip_space_position.w;
+
+    // Inside the frustum if the body's center is within the clip volume
+    clip_space_position.x >= -w && clip_space_position.x <= w &&
+    clip_space_position.y >= -w && clip_space_position.y <= w &&
+    clip_space_position.z >= -w && clip_space_position.z <= w
+}
+
+// How many segments an orbit's polyline needs for its chords to stay within half a pixel
+// of the true curve where it passes closest to the camera, so an orbit seen up close
+// stays smooth and a far one stays cheap
+pub fn orbit_segments(uniforms: &FrameUniforms, height: usize, path: &OrbitPath) -> usize {
+    let nearest = (0..ORBIT_PROBES)
+        .map(|i| (path.position(2.0 * PI * i as f32 / ORBIT_PROBES as f32) - uniforms.camera_position).magnitude())
+        .fold(f32::INFINITY, f32::min)
+        .max(1e-3);
+    // A chord over 1/n of a circle of radius r strays r * (1 - cos(pi / n)) ~ r * pi^2 / (2 n^2)
+    // from it, which at distance d is that times focal / d pixels
+    let focal = uniforms.projection_matrix[(1, 1)] * height as f32 * 0.5;
+    let segments = PI * (path.orbit.radius * focal / nearest).sqrt();
+    if segments.is_finite() {
+        (segments.ceil() as usize).clamp(MIN_ORBIT_SEGMENTS, MAX_ORBIT_SEGMENTS)
+    } else {
+        MIN_ORBIT_SEGMENTS
+    }
+}
+
+// Trace `orbit`, laid out around `parent_position`, as a closed depth-tested polyline.
+// The first vertex is at `start_angle` (the body's current angle), so the line goes
+// exactly through the body instead of cutting a chord near it
+pub fn render_orbit_path(
+    framebuffer: &mut Framebuffer,
+    uniforms: &FrameUniforms,
+    orbit: &Orbit,
+    parent_position: Vec3,
+    start_angle: f32,
+    segments: usize,
+    color: u32,
+) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+
+    let mut points = Vec::with_capacity(segments);
+    for i in 0..segments {
+        let angle = start_angle + 2.0 * PI * (i as f32 / segments as f32);
+        let p = parent_position + orbit.position(angle);
+        points.push(view_projection * Vec4::new(p.x, p.y, p.z, 1.0));
+    }
+
+    // The ring isn't visible if every point is outside the same frustum plane
+    let outside = |test: fn(&Vec4) -> bool| points.iter().all(test);
+    if outside(|p| p.x < -p.w) || outside(|p| p.x > p.w)
+        || outside(|p| p.y < -p.w) || outside(|p| p.y > p.w)
+        || outside(|p| p.z < -p.w) {
+        return;
+    }
+
+    framebuffer.set_current_color(color);
+    for i in 0..points.len() {
+        let next_index = (i + 1) % points.len();
+        if let Some((start, end)) = clip_segment_near(points[i], points[next_index]) {
+            framebuffer.line(clip_to_screen(uniforms, start), clip_to_screen(uniforms, end));
+        }
+    }
+}
+
+// Clip a clip-space segment against the near plane (z >= -w)
+pub fn clip_segment_near(a: Vec4, b: Vec4) -> Option<(Vec4, Vec4)> {
+    let da = a.z + a.w;
+    let db = b.z + b.w;
+    if da < 0.0 && db < 0.0 {
+        return None;
+    }
+    if da >= 0.0 && db >= 0.0 {
+        return Some((a, b));
+    }
+
+    let t = da / (da - db);
+    let intersection = a + (b - a) * t;
+    if da < 0.0 {
+        Some((intersection, b))
+    } else {
+        Some((a, intersection))
+    }
+}
+
+// Clip space to screen coordinates (x, y, depth)
+pub fn clip_to_screen(uniforms: &FrameUniforms, clip: Vec4) -> Vec3 {
+    let w = clip.w.max(f32::EPSILON);
+    let screen = uniforms.viewport_matrix * Vec4::new(clip.x / w, clip.y / w, clip.z / w, 1.0);
+    Vec3::new(screen.x, screen.y, uniforms.depth(clip.w, screen.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ambient::Meteors;
+    use crate::mesh::BodyMesh;
+    use crate::obj::Obj;
+    use crate::scene::{build_scene, SceneConfig};
+    use crate::skybox::{Skybox, SkyboxConfig};
+
+    #[test]
+    fn renders_one_planet_offscreen() {
+        let mesh = Mesh::from(&Obj::load("assets/models/smooth_sphere.obj").expect("sphere model"));
+        let default = SceneConfig::default();
+        let config = SceneConfig { bodies: vec![default.bodies[0].clone()], ..default };
+        assert_eq!(config.bodies[0].planet_type, PlanetType::Sun);
+
+        // An empty sky, so only the planet can light up pixels
+        let skybox = Skybox::new(SkyboxConfig { star_count: 0, band_strength: 0.0, ..Default::default() });
+        let mut scene = build_scene(&config, BodyMesh::from(mesh), skybox, Meteors::new(1));
+        scene.update(1.0 / 60.0);
+
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        camera.mode = CameraMode::FirstPerson;
+
+        let (width, height) = (64, 48);
+        let mut framebuffer = Framebuffer::new(width, height);
+        let mut renderer = Renderer::new(width, height);
+        renderer.set_shader_params(config.shader_params());
+        renderer.render_scene(&mut framebuffer, &scene, &camera);
+
+        let center = height / 2 * width + width / 2;
+        assert_ne!(framebuffer.buffer[center], 0);
+        assert!(framebuffer.zbuffer[center] <= 1.0);
+        assert_eq!(framebuffer.buffer[0], 0);
+    }
+}
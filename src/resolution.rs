@@ -0,0 +1,225 @@
+use std::time::Duration;
+use crate::framebuffer::Framebuffer;
+
+pub const DEFAULT_TARGET_FPS: u32 = 30;
+pub const MIN_SCALE: f32 = 0.5;
+pub const MAX_SCALE: f32 = 1.0;
+// The scale moves in steps of this size, so small timing noise doesn't resize every second
+const SCALE_STEP: f32 = 0.05;
+// Seconds of frame times averaged before each adjustment
+const ADJUST_INTERVAL: f32 = 1.0;
+// Frame time relative to the target inside which the scale is left alone
+const SLOW_MARGIN: f32 = 1.05;
+const FAST_MARGIN: f32 = 0.8;
+// Largest change of the scale in a single adjustment, down and up
+const MAX_SHRINK: f32 = 0.8;
+const MAX_GROW: f32 = 1.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Upscale {
+    Nearest,
+    Bilinear,
+}
+
+// Scale of the internal render resolution relative to the window, driven by the measured
+// frame time: once per second the average is compared against the target and the scale
+// shrinks or grows towards it. Render cost follows the pixel count, the square of the scale
+pub struct DynamicResolution {
+    pub enabled: bool,
+    pub target_fps: u32,
+    scale: f32,
+    elapsed: f32,    // seconds of frames since the last adjustment
+    frames: u32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicResolution {
+    pub fn new() -> Self {
+        DynamicResolution {
+            enabled: false,
+            target_fps: DEFAULT_TARGET_FPS,
+            scale: MAX_SCALE,
+            elapsed: 0.0,
+            frames: 0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.enabled { self.scale } else { MAX_SCALE }
+    }
+
+    // Record one frame's work time; adjusts the scale once ADJUST_INTERVAL has passed
+    pub fn update(&mut self, frame_time: Duration) {
+        if !self.enabled || self.target_fps == 0 {
+            return;
+        }
+        self.elapsed += frame_time.as_secs_f32();
+        self.frames += 1;
+        if self.elapsed < ADJUST_INTERVAL {
+            return;
+        }
+
+        let average = self.elapsed / self.frames as f32;
+        let target = 1.0 / self.target_fps as f32;
+        let load = average / target;
+        if !(FAST_MARGIN..=SLOW_MARGIN).contains(&load) {
+            let factor = (1.0 / load).sqrt().clamp(MAX_SHRINK, MAX_GROW);
+            let stepped = (self.scale * factor / SCALE_STEP).round() * SCALE_STEP;
+            self.scale = stepped.clamp(MIN_SCALE, MAX_SCALE);
+        }
+        self.elapsed = 0.0;
+        self.frames = 0;
+    }
+
+    // Internal framebuffer size for a window of width x height, never below one pixel
+    pub fn internal_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let scale = self.scale();
+        (
+            ((width as f32 * scale).round() as usize).max(1),
+            ((height as f32 * scale).round() as usize).max(1),
+        )
+    }
+}
+
+// Stretch `source` over all of `target`. Colors use the chosen filter; depth is always
+// copied from the nearest source pixel so overlays that read it still see the scene
+pub fn upscale(source: &Framebuffer, target: &mut Framebuffer, filter: Upscale) {
+    if source.width == 0 || source.height == 0 {
+        return;
+    }
+    let scale_x = source.width as f32 / target.width as f32;
+    let scale_y = source.height as f32 / target.height as f32;
+    for y in 0..target.height {
+        // Pixel centers of the target mapped into the source
+        let source_y = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (source.height - 1) as f32);
+        let nearest_y = ((y as f32 + 0.5) * scale_y) as usize;
+        for x in 0..target.width {
+            let source_x = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (source.width - 1) as f32);
+            let nearest_x = ((x as f32 + 0.5) * scale_x) as usize;
+            let nearest = nearest_y.min(source.height - 1) * source.width + nearest_x.min(source.width - 1);
+            let index = y * target.width + x;
+            target.zbuffer[index] = source.zbuffer[nearest];
+            target.buffer[index] = match filter {
+                Upscale::Nearest => source.buffer[nearest],
+                Upscale::Bilinear => bilinear(source, source_x, source_y),
+            };
+        }
+    }
+}
+
+fn bilinear(source: &Framebuffer, x: f32, y: f32) -> u32 {
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(source.width - 1), (y0 + 1).min(source.height - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let at = |x: usize, y: usize| source.buffer[y * source.width + x];
+    let (a, b, c, d) = (at(x0, y0), at(x1, y0), at(x0, y1), at(x1, y1));
+    let channel = |shift: u32| -> u32 {
+        let value = |color: u32| ((color >> shift) & 0xFF) as f32;
+        let top = value(a) + (value(b) - value(a)) * tx;
+        let bottom = value(c) + (value(d) - value(c)) * tx;
+        ((top + (bottom - top) * ty).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frames that each took `seconds`, up to and including the next adjustment
+    fn run_interval(resolution: &mut DynamicResolution, seconds: f32) {
+        resolution.update(Duration::from_secs_f32(seconds));
+        while resolution.frames > 0 {
+            resolution.update(Duration::from_secs_f32(seconds));
+        }
+    }
+
+    fn enabled(target_fps: u32) -> DynamicResolution {
+        DynamicResolution { enabled: true, target_fps, ..DynamicResolution::new() }
+    }
+
+    #[test]
+    fn slow_frames_step_the_scale_down_and_fast_ones_back_up() {
+        let mut resolution = enabled(30);
+        // Twice the target frame time would want 1/sqrt(2), but a single step shrinks by 0.8 at most
+        run_interval(&mut resolution, 2.0 / 30.0);
+        assert!((resolution.scale() - 0.8).abs() < 1e-5, "{}", resolution.scale());
+        // Half the target would want sqrt(2), capped at 1.1 and rounded to the step: 0.88 -> 0.9
+        run_interval(&mut resolution, 0.5 / 30.0);
+        assert!((resolution.scale() - 0.9).abs() < 1e-5, "{}", resolution.scale());
+    }
+
+    #[test]
+    fn frame_times_near_the_target_leave_the_scale_alone() {
+        let mut resolution = enabled(30);
+        run_interval(&mut resolution, 2.0 / 30.0);
+        let scale = resolution.scale();
+        for load in [FAST_MARGIN + 0.01, 1.0, SLOW_MARGIN - 0.01] {
+            run_interval(&mut resolution, load / 30.0);
+            assert_eq!(resolution.scale(), scale, "load {}", load);
+        }
+        // Nor does anything short of a full interval
+        resolution.update(Duration::from_secs_f32(0.5));
+        resolution.update(Duration::from_secs_f32(0.4));
+        assert_eq!(resolution.scale(), scale);
+    }
+
+    #[test]
+    fn the_scale_stays_between_min_and_max() {
+        let mut resolution = enabled(30);
+        for _ in 0..20 {
+            run_interval(&mut resolution, 10.0 / 30.0);
+            assert!(resolution.scale() >= MIN_SCALE);
+        }
+        assert_eq!(resolution.scale(), MIN_SCALE);
+        for _ in 0..20 {
+            run_interval(&mut resolution, 0.1 / 30.0);
+            assert!(resolution.scale() <= MAX_SCALE);
+        }
+        assert_eq!(resolution.scale(), MAX_SCALE);
+    }
+
+    #[test]
+    fn disabled_renders_at_full_size() {
+        let mut resolution = DynamicResolution::new();
+        run_interval(&mut resolution, 10.0 / 30.0);
+        assert_eq!(resolution.scale(), MAX_SCALE);
+        assert_eq!(resolution.internal_size(640, 360), (640, 360));
+
+        let mut resolution = enabled(30);
+        for _ in 0..20 {
+            run_interval(&mut resolution, 10.0 / 30.0);
+        }
+        assert_eq!(resolution.internal_size(640, 360), (320, 180));
+        assert_eq!(resolution.internal_size(1, 1), (1, 1));
+    }
+
+    #[test]
+    fn upscaling_fills_every_output_pixel() {
+        const UNTOUCHED: u32 = 0x0000FF;
+        for (source_size, target_size) in [((7, 5), (20, 13)), ((13, 9), (32, 24)), ((1, 1), (5, 3)), ((4, 4), (4, 4))] {
+            let mut source = Framebuffer::new(source_size.0, source_size.1);
+            for (index, (color, depth)) in source.buffer.iter_mut().zip(&mut source.zbuffer).enumerate() {
+                // No blue, so no blend of them comes out as UNTOUCHED
+                *color = ((index as u32 + 1) * 0x0B0700) & 0xFFFF00;
+                *depth = index as f32;
+            }
+            for filter in [Upscale::Nearest, Upscale::Bilinear] {
+                let mut target = Framebuffer::new(target_size.0, target_size.1);
+                target.buffer.fill(UNTOUCHED);
+                target.zbuffer.fill(f32::NAN);
+                upscale(&source, &mut target, filter);
+                assert!(target.buffer.iter().all(|&color| color != UNTOUCHED), "{:?} {:?}", filter, target_size);
+                assert!(target.zbuffer.iter().all(|depth| source.zbuffer.contains(depth)), "{:?} {:?}", filter, target_size);
+                if filter == Upscale::Nearest {
+                    assert!(target.buffer.iter().all(|color| source.buffer.contains(color)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use crate::framebuffer::{Framebuffer, OVERLAY_DEPTH};
+use crate::impostor::projected_radius;
+use crate::renderer::clip_to_screen;
+use crate::scene::CelestialBody;
+use crate::uniforms::FrameUniforms;
+
+const RETICLE_COLOR: u32 = 0x70FFA0;
+// Pixels between the body's disc and the bracket, and the smallest bracket, for far bodies
+const GAP: f32 = 6.0;
+const MIN_RADIUS: f32 = 10.0;
+// Each corner's arms, as a fraction of the bracket's half side
+const CORNER_FRACTION: f32 = 0.35;
+// Locking onto a new target: the bracket starts this many times its size and shrinks
+// onto the body over LOCK_TIME seconds, fading in as it goes
+const LOCK_SCALE: f32 = 2.5;
+const LOCK_TIME: f32 = 0.4;
+const LOCK_START_ALPHA: f32 = 0.3;
+// Crosshair in the middle of the screen: arm length and the gap they leave around the
+// center, in pixels
+const CROSSHAIR_ARM: f32 = 6.0;
+const CROSSHAIR_GAP: f32 = 3.0;
+const CROSSHAIR_ALPHA: f32 = 0.6;
+// Off-screen arrow: distance of its tip from the screen edge, its arms' length and
+// half-angle, in pixels and radians, and the dot at its tip
+const ARROW_MARGIN: f32 = 14.0;
+const ARROW_LENGTH: f32 = 10.0;
+const ARROW_SPREAD: f32 = 0.6;
+const PIP_RADIUS: f32 = 1.5;
+
+// Highlight of the selected body: a square bracket just outside its disc on screen, as
+// large as the disc is, which locks on by shrinking onto a newly picked body. Drawn over
+// everything, so it also shows where a body hidden behind another one is. When the body
+// is off-screen or behind the camera, an arrow at the screen edge points the way to turn
+// instead
+pub struct Reticle {
+    target: Option<usize>,
+    lock_age: f32, // seconds since `target` was picked
+}
+
+impl Default for Reticle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reticle {
+    pub fn new() -> Self {
+        Reticle { target: None, lock_age: LOCK_TIME }
+    }
+
+    // Once a frame with the selection: a different body than last frame locks on again
+    pub fn update(&mut self, selected: Option<usize>, dt: f32) {
+        if selected != self.target {
+            self.target = selected;
+            self.lock_age = 0.0;
+        } else {
+            self.lock_age = (self.lock_age + dt).min(LOCK_TIME);
+        }
+    }
+
+    // How far the lock-on animation is, from 0 as the target is picked to 1 once locked;
+    // eased out, so the bracket snaps in fast and settles
+    pub fn lock(&self) -> f32 {
+        let t = (self.lock_age / LOCK_TIME).clamp(0.0, 1.0);
+        1.0 - (1.0 - t).powi(3)
+    }
+
+    // Half the side of the bracket around a body whose disc on screen is `disc` pixels in
+    // radius (impostor::projected_radius)
+    pub fn bracket_half_size(&self, disc: f32) -> f32 {
+        (disc + GAP).max(MIN_RADIUS) * (LOCK_SCALE + (1.0 - LOCK_SCALE) * self.lock())
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, body: &CelestialBody) {
+        if let Some(direction) = offscreen_direction(uniforms, framebuffer.width, framebuffer.height, body.position) {
+            draw_arrow(framebuffer, direction);
+            return;
+        }
+        let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(body.position.x, body.position.y, body.position.z, 1.0);
+        let screen = clip_to_screen(uniforms, clip);
+        let center = Vec2::new(screen.x, screen.y);
+        let disc = projected_radius(uniforms, framebuffer, body.position, body.scale);
+        if !disc.is_finite() {
+            return; // the camera is inside the body
+        }
+
+        let half = self.bracket_half_size(disc);
+        let arm = half * CORNER_FRACTION;
+        let alpha = LOCK_START_ALPHA + (1.0 - LOCK_START_ALPHA) * self.lock();
+        let point = |p: Vec2| Vec3::new(p.x, p.y, OVERLAY_DEPTH);
+        for (x, y) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+            let corner = center + Vec2::new(x, y) * half;
+            framebuffer.line_aa(point(corner), point(corner - Vec2::new(x * arm, 0.0)), RETICLE_COLOR, alpha);
+            framebuffer.line_aa(point(corner), point(corner - Vec2::new(0.0, y * arm)), RETICLE_COLOR, alpha);
+        }
+    }
+}
+
+// Crosshair in the middle of the screen, to aim at a target with while one is picked or
+// followed: four short arms around a gap and a dot at the center
+pub fn draw_crosshair(framebuffer: &mut Framebuffer) {
+    let center = Vec2::new(framebuffer.width as f32, framebuffer.height as f32) * 0.5;
+    let point = |p: Vec2| Vec3::new(p.x, p.y, OVERLAY_DEPTH);
+    for direction in [Vec2::new(1.0, 0.0), Vec2::new(-1.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(0.0, -1.0)] {
+        let inner = center + direction * CROSSHAIR_GAP;
+        framebuffer.line_aa(point(inner), point(inner + direction * CROSSHAIR_ARM), RETICLE_COLOR, CROSSHAIR_ALPHA);
+    }
+    framebuffer.filled_circle(center, 0.5, RETICLE_COLOR);
+}
+
+// Unit screen-space direction (y down) from the middle of a width x height screen towards
+// `position`, or None while its center is on screen. For points behind the camera it's
+// the shortest way to turn towards them; straight behind, it points down
+pub fn offscreen_direction(uniforms: &FrameUniforms, width: usize, height: usize, position: Vec3) -> Option<Vec2> {
+    let view = uniforms.view_matrix * Vec4::new(position.x, position.y, position.z, 1.0);
+    let clip = uniforms.projection_matrix * view;
+    if clip.w > 0.0 {
+        let screen = clip_to_screen(uniforms, clip);
+        if (0.0..width as f32).contains(&screen.x) && (0.0..height as f32).contains(&screen.y) {
+            return None;
+        }
+    }
+
+    // The view-space offset from the view axis, stretched like the projection and the
+    // viewport stretch it. In front of the camera that's exactly the direction to the
+    // projected point; behind it, dividing by w would mirror it, so it isn't divided
+    let half = Vec2::new(width as f32, height as f32) * 0.5;
+    let projection = &uniforms.projection_matrix;
+    let direction = Vec2::new(projection[(0, 0)] * view.x * half.x, -projection[(1, 1)] * view.y * half.y);
+    if direction.magnitude() < f32::EPSILON {
+        return Some(Vec2::new(0.0, 1.0));
+    }
+    Some(direction.normalize())
+}
+
+// Arrowhead at the screen edge, ARROW_MARGIN inside it, on the ray from the middle of the
+// screen along `direction`
+fn draw_arrow(framebuffer: &mut Framebuffer, direction: Vec2) {
+    let half = Vec2::new(framebuffer.width as f32, framebuffer.height as f32) * 0.5;
+    let inset = Vec2::new((half.x - ARROW_MARGIN).max(0.0), (half.y - ARROW_MARGIN).max(0.0));
+    let reach = [(inset.x, direction.x), (inset.y, direction.y)]
+        .into_iter()
+        .filter(|(_, component)| component.abs() > f32::EPSILON)
+        .map(|(extent, component)| extent / component.abs())
+        .fold(f32::INFINITY, f32::min);
+    let tip = half + direction * reach;
+
+    let point = |p: Vec2| Vec3::new(p.x, p.y, OVERLAY_DEPTH);
+    let (sin, cos) = ARROW_SPREAD.sin_cos();
+    let back = -direction * ARROW_LENGTH;
+    for side in [1.0, -1.0] {
+        let arm = Vec2::new(back.x * cos - side * back.y * sin, side * back.x * sin + back.y * cos);
+        framebuffer.line_aa(point(tip), point(tip + arm), RETICLE_COLOR, 1.0);
+    }
+    framebuffer.filled_circle(tip, PIP_RADIUS, RETICLE_COLOR);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    // From the first frame's view: eye at the origin, looking down -z with +y up
+    fn direction(position: Vec3) -> Option<Vec2> {
+        offscreen_direction(&FrameUniforms::first(200, 100), 200, 100, position)
+    }
+
+    #[test]
+    fn no_arrow_while_the_body_is_on_screen() {
+        assert_eq!(direction(Vec3::new(0.0, 0.0, -10.0)), None);
+        assert_eq!(direction(Vec3::new(3.0, -2.0, -10.0)), None);
+    }
+
+    #[test]
+    fn arrows_point_towards_bodies_off_the_sides() {
+        let right = direction(Vec3::new(50.0, 0.0, -10.0)).expect("off screen");
+        assert!((right - Vec2::new(1.0, 0.0)).magnitude() < EPSILON, "{:?}", right);
+        let up = direction(Vec3::new(0.0, 50.0, -10.0)).expect("off screen");
+        assert!((up - Vec2::new(0.0, -1.0)).magnitude() < EPSILON, "{:?}", up);
+    }
+
+    #[test]
+    fn bodies_behind_are_the_short_way_round() {
+        // Behind and a little to the left: turn left, not right through the mirrored image
+        let left = direction(Vec3::new(-1.0, 0.0, 10.0)).expect("behind");
+        assert!((left - Vec2::new(-1.0, 0.0)).magnitude() < EPSILON, "{:?}", left);
+        let below = direction(Vec3::new(0.0, -1.0, 10.0)).expect("behind");
+        assert!((below - Vec2::new(0.0, 1.0)).magnitude() < EPSILON, "{:?}", below);
+        assert_eq!(direction(Vec3::new(0.0, 0.0, 10.0)), Some(Vec2::new(0.0, 1.0)));
+    }
+}
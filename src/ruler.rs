@@ -0,0 +1,157 @@
+use nalgebra_glm::{Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::{clip_segment_near, clip_to_screen};
+use crate::scene::Scene;
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT, LINE_ADVANCE};
+use crate::uniforms::FrameUniforms;
+
+const RULER_COLOR: u32 = 0xFFD060;
+// Pixels between the line's midpoint and the bottom of its label
+const LABEL_GAP: usize = 6;
+
+// Distance between two bodies, in the same world units as the scene file's orbit_radius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub distance: f32,          // straight line between the centers, as they are now
+    pub orbit_difference: f32,  // between the two orbit radii (semi-major axes), never negative
+}
+
+// Measuring tape between two bodies picked with the mouse: a line joining them with the
+// distance written at its middle, following them as they move
+pub struct Ruler {
+    pub active: bool, // while on, clicks on bodies pick the ends instead of following them
+    ends: Vec<usize>, // indices of the picked bodies, at most two
+}
+
+impl Default for Ruler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ruler {
+    pub fn new() -> Self {
+        Ruler { active: false, ends: Vec::new() }
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.ends.clear();
+    }
+
+    // Add `body` as an end. A third pick starts a new measurement; picking the same body
+    // twice in a row keeps a single end
+    pub fn pick(&mut self, body: usize) {
+        if self.ends.len() == 2 {
+            self.ends.clear();
+        }
+        if self.ends.last() != Some(&body) {
+            self.ends.push(body);
+        }
+    }
+
+    pub fn ends(&self) -> &[usize] {
+        &self.ends
+    }
+
+    // Forget the ends (e.g. the scene was reloaded and the indices mean other bodies)
+    pub fn clear(&mut self) {
+        self.ends.clear();
+    }
+
+    // The current measurement, once both ends are picked
+    pub fn measure(&self, scene: &Scene) -> Option<Measurement> {
+        let &[a, b] = self.ends.as_slice() else {
+            return None;
+        };
+        let (body_a, body_b) = (scene.bodies.get(a)?, scene.bodies.get(b)?);
+        Some(Measurement {
+            distance: (body_a.position - body_b.position).magnitude(),
+            orbit_difference: (scene.orbits[a].radius - scene.orbits[b].radius).abs(),
+        })
+    }
+
+    // Draw the line between the bodies' centers and, if its midpoint is in front of the
+    // camera, the label with both numbers there. Depth-tested, so the bodies hide the
+    // part of the line inside them
+    pub fn draw(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, scene: &Scene) {
+        if !self.active {
+            return;
+        }
+        let Some(measurement) = self.measure(scene) else {
+            return;
+        };
+        let (a, b) = (&scene.bodies[self.ends[0]], &scene.bodies[self.ends[1]]);
+
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let clip = |p: Vec3| view_projection * Vec4::new(p.x, p.y, p.z, 1.0);
+        if let Some((start, end)) = clip_segment_near(clip(a.position), clip(b.position)) {
+            framebuffer.line_aa(clip_to_screen(uniforms, start), clip_to_screen(uniforms, end), RULER_COLOR, 1.0);
+        }
+
+        let middle = clip((a.position + b.position) * 0.5);
+        if middle.w <= 0.0 {
+            return; // behind the camera
+        }
+        let center = clip_to_screen(uniforms, middle);
+        if center.x < 0.0 || center.y < 0.0 || center.x >= framebuffer.width as f32 || center.y >= framebuffer.height as f32 {
+            return;
+        }
+        let lines = [
+            format!("{} - {}", a.name, b.name),
+            format!("{:.2} u  (orbitas: {:.2} u)", measurement.distance, measurement.orbit_difference),
+        ];
+        let bottom = (center.y as usize).saturating_sub(LABEL_GAP);
+        for (i, line) in lines.iter().enumerate() {
+            let width = text_width(line, 1);
+            let left = (center.x as usize).saturating_sub(width / 2).min(framebuffer.width.saturating_sub(width));
+            let top = bottom.saturating_sub(GLYPH_HEIGHT + (lines.len() - 1 - i) * LINE_ADVANCE);
+            draw_text(framebuffer, left, top, line, RULER_COLOR, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ambient::Meteors;
+    use crate::mesh::{icosphere, BodyMesh};
+    use crate::scene::{build_scene, SceneConfig};
+    use crate::skybox::{Skybox, SkyboxConfig};
+
+    fn scene() -> Scene {
+        let skybox = Skybox::new(SkyboxConfig { star_count: 0, ..SkyboxConfig::default() });
+        build_scene(&SceneConfig::default(), BodyMesh::from(icosphere(0)), skybox, Meteors::new(1))
+    }
+
+    #[test]
+    fn a_third_pick_starts_over() {
+        let mut ruler = Ruler::new();
+        ruler.pick(1);
+        ruler.pick(1);
+        assert_eq!(ruler.ends(), [1]);
+        ruler.pick(3);
+        assert_eq!(ruler.ends(), [1, 3]);
+        ruler.pick(4);
+        assert_eq!(ruler.ends(), [4]);
+    }
+
+    #[test]
+    fn distances_are_in_orbit_radius_units() {
+        // Every body starts at its orbit's angle 0, (radius, 0, 0) for the default circles
+        let mut scene = scene();
+        let mut ruler = Ruler::new();
+        ruler.pick(1);
+        assert_eq!(ruler.measure(&scene), None);
+        ruler.pick(3);
+        let measurement = ruler.measure(&scene).expect("two ends");
+        assert!((measurement.distance - 10.0).abs() < 1e-4, "{:?}", measurement);
+        assert_eq!(measurement.orbit_difference, 10.0);
+
+        // The distance follows the bodies; the orbits don't change
+        scene.update(1.0);
+        let moved = ruler.measure(&scene).expect("two ends");
+        assert!((moved.distance - measurement.distance).abs() > 1e-3);
+        assert_eq!(moved.orbit_difference, 10.0);
+    }
+}
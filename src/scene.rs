@@ -0,0 +1,1247 @@
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::Vec3;
+use crate::toml_lite::{key_value, list_items, strip_comment, unquote};
+use crate::planet::PlanetType;
+use crate::shader_params::{ShaderParamTable, ShaderParams, StarParams};
+use crate::ambient::{Meteors, DEFAULT_COMET_RATE};
+use crate::color::Color;
+use crate::skybox::Skybox;
+use crate::mesh::{BodyMesh, MeshCache, DEFAULT_SLICES, DEFAULT_STACKS, LOD_LEVELS};
+use crate::gltf::is_gltf;
+use crate::lights::{PointLight, PointLights, MAX_LIGHTS};
+
+// One body of a scene file
+#[derive(Debug, Clone)]
+pub struct BodyConfig {
+    pub planet_type: PlanetType,
+    pub name: String, // shown in labels and notices; defaults to the type's name
+    pub scale: f32,
+    pub orbit_radius: f32, // semi-major axis around the sun; ignored for the sun and the moon (see moon_orbit_radius)
+    pub eccentricity: f32, // 0 = circle, up to (not including) 1
+    pub inclination: f32,    // degrees, tilt of the orbital plane from the XZ plane
+    pub ascending_node: f32, // degrees about Y from +X to where the orbit rises through the XZ plane
+    pub orbit_color: u32,
+    pub trail_length: Option<usize>, // particles kept; None = enough for the scene's trail_arc (see SceneConfig::trail_length)
+    pub trail_color: u32,              // ARGB of new particles; defaults to the type's `trail_color`
+    pub trail_tail_color: Option<u32>, // ARGB particles fade to as they age; None keeps trail_color
+    pub trail_space: TrailSpace,       // defaults to the type's `default_trail_space`
+    pub orbit_speed_multiplier: f32, // on top of the scene's speed law; 1 = unchanged
+    pub ambient: f32, // least light the unlit side gets, 0..=1; defaults to the type's `ambient_floor`
+    pub bump_strength: f32, // relief the Phong lighting reads from the surface noise; defaults to the type's `default_bump_strength`
+    pub params: ShaderParams,
+}
+
+// How a body's angular speed around the sun falls off with its orbit's semi-major axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitSpeeds {
+    Classic,   // base_orbit_speed / radius, the project's original look
+    Realistic, // Kepler's third law: angular speed proportional to radius^(-3/2)
+}
+
+// The generated sphere the bodies are drawn with when the scene doesn't name a model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SphereMesh {
+    Icosphere, // subdivided icosahedron, with a level of detail picked per body by its size on screen
+    Uv,        // sphere_stacks x sphere_slices, the same for every body
+}
+
+impl SphereMesh {
+    pub fn id(self) -> &'static str {
+        match self {
+            SphereMesh::Icosphere => "icosphere",
+            SphereMesh::Uv => "uv",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        [SphereMesh::Icosphere, SphereMesh::Uv].into_iter().find(|sphere| sphere.id() == id)
+    }
+}
+
+// Where a body's trail particles are kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailSpace {
+    World,  // left where the body was, whatever its parent does afterwards
+    Parent, // relative to the body its orbit is centered on, so the trail travels with it
+}
+
+impl TrailSpace {
+    pub fn id(self) -> &'static str {
+        match self {
+            TrailSpace::World => "world",
+            TrailSpace::Parent => "parent",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        [TrailSpace::World, TrailSpace::Parent].into_iter().find(|space| space.id() == id)
+    }
+}
+
+// Default trail space of a body type: the moon's trail follows the earth, so it draws the
+// circle the moon actually makes around it instead of loops left behind along the earth's
+// orbit. Everything else leaves its trail in world space
+pub fn default_trail_space(planet_type: PlanetType) -> TrailSpace {
+    match planet_type {
+        PlanetType::Moon => TrailSpace::Parent,
+        _ => TrailSpace::World,
+    }
+}
+
+// Most trail particles a scene keeps at once, over all its trails, unless its file sets
+// `particle_budget`. The default system's trails fit; long arcs or many wide orbits don't
+pub const DEFAULT_PARTICLE_BUDGET: usize = 20_000;
+
+// Fraction of its orbit a body's trail covers unless the scene file sets `trail_arc`
+pub const DEFAULT_TRAIL_ARC: f32 = 0.3;
+
+// Seconds between two particles of a trail: one per Scene::update, at the fixed step the
+// simulation runs at
+pub const TRAIL_SPAWN_INTERVAL: f32 = 1.0 / 60.0;
+
+// How far from the surface of an asteroid or a moon the ship can capture it, unless the
+// scene file sets `capture_range`
+pub const DEFAULT_CAPTURE_RANGE: f32 = 2.0;
+// A released body goes around the planet it's nearest to, for the planet's size, if it's
+// within this many of the planet's radii of it; around the origin otherwise
+const RELEASE_REACH: f32 = 12.0;
+// Released slower than this across the line to its new center, a body keeps the pace it
+// had on its old orbit instead of hanging in place
+const MIN_RELEASE_SPEED: f32 = 0.05;
+
+// Newton steps solving Kepler's equation; enough for f32 at any eccentricity a scene allows
+const KEPLER_ITERATIONS: usize = 8;
+
+// Radius where both laws give the same speed, so the realistic preset keeps the middle of
+// the default system (the earth) where it was and spreads the rest out around it
+const KEPLER_REFERENCE_RADIUS: f32 = 20.0;
+
+impl OrbitSpeeds {
+    pub fn id(self) -> &'static str {
+        match self {
+            OrbitSpeeds::Classic => "classic",
+            OrbitSpeeds::Realistic => "realistic",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        [OrbitSpeeds::Classic, OrbitSpeeds::Realistic].into_iter().find(|speeds| speeds.id() == id)
+    }
+
+    // How the HUD names it
+    pub fn name(self) -> &'static str {
+        match self {
+            OrbitSpeeds::Classic => "clásicas",
+            OrbitSpeeds::Realistic => "realistas (Kepler)",
+        }
+    }
+
+    // Angular speed at `radius` relative to base_orbit_speed
+    fn falloff(self, radius: f32) -> f32 {
+        match self {
+            OrbitSpeeds::Classic => 1.0 / radius,
+            OrbitSpeeds::Realistic => KEPLER_REFERENCE_RADIUS.sqrt() / radius.powf(1.5),
+        }
+    }
+}
+
+/// Everything needed to build the solar system: what `--scene` files describe.
+///
+/// [`SceneConfig::default`] is the built-in system; [`SceneConfig::load`] and
+/// [`SceneConfig::parse`] read a scene file (see `scene.example.toml`). Hand it to
+/// [`build_scene`] with the mesh from [`SceneConfig::body_mesh`] to get a [`Scene`].
+#[derive(Debug, Clone)]
+pub struct SceneConfig {
+    pub bodies: Vec<BodyConfig>,
+    pub base_orbit_speed: f32,  // radians per second at radius 1; farther bodies are slower
+    pub orbit_speeds: OrbitSpeeds,
+    pub moon_orbit_radius: f32, // around the earth
+    pub moon_orbit_speed: f32,  // radians per second
+    pub star: StarParams,       // the sun's pulsation and variability
+    pub comet_rate: f32,        // comets crossing the view per minute, on average; 0 = none
+    pub trail_arc: f32,         // fraction of its orbit each body's trail covers, unless the body sets its own `trail`
+    pub particle_budget: usize, // most trail particles kept at once over all trails (see Scene::set_particle_budget)
+    pub capture_range: f32,     // from an asteroid's or a moon's surface, how close the ship captures it
+    pub body_model: Option<String>, // OBJ or glTF model drawn for every body instead of the generated sphere
+    pub sphere: SphereMesh,
+    pub sphere_stacks: u32,     // tessellation of the UV sphere
+    pub sphere_slices: u32,
+    pub lights: Vec<PointLight>, // extra colored point lights, at most MAX_LIGHTS
+    pub background_gradient: Option<(u32, u32)>, // top and bottom colors the frame clears to; None = solid black
+}
+
+impl Default for SceneConfig {
+    // The built-in solar system
+    fn default() -> Self {
+        let body = |planet_type: PlanetType, scale, orbit_radius, orbit_color| BodyConfig {
+            planet_type,
+            name: planet_type.name().to_string(),
+            scale,
+            orbit_radius,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            orbit_color,
+            trail_length: None,
+            trail_color: trail_color(planet_type),
+            trail_tail_color: None,
+            trail_space: default_trail_space(planet_type),
+            orbit_speed_multiplier: 1.0,
+            ambient: ambient_floor(planet_type),
+            bump_strength: default_bump_strength(planet_type),
+            params: ShaderParams::defaults_for(planet_type),
+        };
+        SceneConfig {
+            bodies: vec![
+                body(PlanetType::Sun, 2.0, 0.0, 0xFF0000),
+                body(PlanetType::Asteroid, 0.3, 10.0, 0x00FF00),
+                body(PlanetType::RockyPlanet, 0.4, 15.0, 0x0000FF),
+                body(PlanetType::Earth, 0.6, 20.0, 0xFFFF00),
+                body(PlanetType::CrystalPlanet, 0.5, 25.0, 0xFF00FF),
+                body(PlanetType::FirePlanet, 0.7, 30.0, 0x00FFFF),
+                body(PlanetType::WaterPlanet, 1.0, 35.0, 0xFFA500),
+                body(PlanetType::CloudPlanet, 0.8, 40.0, 0x800080),
+                body(PlanetType::Moon, 0.2, 5.0, 0xFFFFFF),
+            ],
+            base_orbit_speed: 1.2,
+            orbit_speeds: OrbitSpeeds::Classic,
+            moon_orbit_radius: 0.5,
+            moon_orbit_speed: 3.0,
+            star: StarParams::default(),
+            comet_rate: DEFAULT_COMET_RATE,
+            trail_arc: DEFAULT_TRAIL_ARC,
+            particle_budget: DEFAULT_PARTICLE_BUDGET,
+            capture_range: DEFAULT_CAPTURE_RANGE,
+            body_model: None,
+            sphere: SphereMesh::Icosphere,
+            sphere_stacks: DEFAULT_STACKS,
+            sphere_slices: DEFAULT_SLICES,
+            lights: Vec::new(),
+            background_gradient: None,
+        }
+    }
+}
+
+impl SceneConfig {
+    /// Read and parse the scene file at `path`. The error names the file and, for
+    /// mistakes inside it, the line.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("no se pudo leer '{}': {}", path, err))?;
+        SceneConfig::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    /// Parse a scene file: a TOML subset with top-level `key = value` settings followed by
+    /// one `[[body]]` table per body, in scene order, and up to [`MAX_LIGHTS`] `[[light]]`
+    /// tables. Unlike keybindings.toml, any mistake is an error, since a half-read scene
+    /// would be more confusing than none.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let defaults = SceneConfig::default();
+        let mut config = SceneConfig { bodies: Vec::new(), ..defaults };
+        let mut current: Option<Table> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |msg: String| format!("línea {}: {}", number, msg);
+
+            if line.starts_with('[') {
+                if let Some(table) = current.take() {
+                    table.finish(&mut config)?;
+                }
+                current = Some(match line {
+                    "[[body]]" => Table::Body(PendingBody { line: number, planet_type: None, fields: Vec::new() }),
+                    "[[light]]" => {
+                        if config.lights.len() == MAX_LIGHTS {
+                            return Err(error(format!("hay más de {} [[light]]", MAX_LIGHTS)));
+                        }
+                        Table::Light(PendingLight { line: number, position: None, light: PointLight::default() })
+                    }
+                    _ => return Err(error(format!("sección desconocida '{}', se esperaba [[body]] o [[light]]", line))),
+                });
+                continue;
+            }
+
+            let (key, value) = key_value(line).ok_or_else(|| error(format!("se esperaba 'clave = valor', no '{}'", line)))?;
+
+            match &mut current {
+                None => match key {
+                    "base_orbit_speed" => config.base_orbit_speed = parse_number(key, value, 0.0).map_err(error)?,
+                    "orbit_speeds" => {
+                        let id = unquote(value);
+                        config.orbit_speeds = OrbitSpeeds::from_id(id)
+                            .ok_or_else(|| error(format!("orbit_speeds debe ser \"classic\" o \"realistic\", no '{}'", id)))?;
+                    }
+                    "moon_orbit_radius" => config.moon_orbit_radius = parse_number(key, value, 0.0).map_err(error)?,
+                    "comet_rate" => config.comet_rate = parse_number(key, value, 0.0).map_err(error)?,
+                    "trail_arc" => config.trail_arc = parse_number(key, value, 0.0).map_err(error)?,
+                    "capture_range" => config.capture_range = parse_number(key, value, 0.0).map_err(error)?,
+                    "particle_budget" => config.particle_budget = parse_count(key, value, 0).map_err(error)? as usize,
+                    "background_gradient" => config.background_gradient = Some(parse_color_pair(key, value).map_err(error)?),
+                    "body_model" => config.body_model = Some(unquote(value).to_string()),
+                    "sphere" => {
+                        let id = unquote(value);
+                        config.sphere = SphereMesh::from_id(id)
+                            .ok_or_else(|| error(format!("sphere debe ser \"icosphere\" o \"uv\", no '{}'", id)))?;
+                    }
+                    "sphere_stacks" => config.sphere_stacks = parse_count(key, value, 2).map_err(error)?,
+                    "sphere_slices" => config.sphere_slices = parse_count(key, value, 3).map_err(error)?,
+                    "moon_orbit_speed" => config.moon_orbit_speed = parse_number(key, value, f32::MIN).map_err(error)?,
+                    "star_pulse_frequency" => config.star.pulse_frequency = parse_number(key, value, f32::MIN).map_err(error)?,
+                    "star_pulse_amplitude" => config.star.pulse_amplitude = parse_number(key, value, f32::MIN).map_err(error)?,
+                    "star_variability_period" => config.star.variability_period = parse_number(key, value, 0.0).map_err(error)?,
+                    "star_variability_amplitude" => {
+                        config.star.variability_amplitude = parse_number(key, value, 0.0)
+                            .and_then(|a| if a <= 1.0 { Ok(a) } else { Err(format!("star_variability_amplitude debe estar entre 0 y 1, no '{}'", value)) })
+                            .map_err(error)?;
+                    }
+                    _ => return Err(error(format!("opción de escena desconocida '{}'", key))),
+                },
+                Some(Table::Light(light)) => light.set(key, value).map_err(error)?,
+                Some(Table::Body(body)) => {
+                    if key == "type" {
+                        let id = unquote(value);
+                        body.planet_type = Some(PlanetType::from_id(id).ok_or_else(|| error(format!(
+                            "tipo de cuerpo desconocido '{}' (válidos: {})",
+                            id,
+                            PlanetType::BODIES.iter().map(|t| t.id()).collect::<Vec<_>>().join(", ")
+                        )))?);
+                    } else {
+                        body.fields.push((key.to_string(), value.to_string(), number));
+                    }
+                }
+            }
+        }
+        if let Some(table) = current.take() {
+            table.finish(&mut config)?;
+        }
+
+        if config.bodies.is_empty() {
+            return Err("la escena no tiene ningún [[body]]".to_string());
+        }
+        Ok(config)
+    }
+
+    // Radians per second `body` turns around what it orbits (0 for the sun itself)
+    pub fn angular_speed(&self, body: &BodyConfig) -> f32 {
+        if body.planet_type == PlanetType::Moon {
+            return self.moon_orbit_speed;
+        }
+        // The sun doesn't move
+        if body.planet_type == PlanetType::Sun || body.orbit_radius <= 0.0 {
+            return 0.0;
+        }
+        self.base_orbit_speed * self.orbit_speeds.falloff(body.orbit_radius) * body.orbit_speed_multiplier
+    }
+
+    // How many particles `body`'s trail keeps: its own `trail` if it sets one, otherwise
+    // as many as it leaves while going trail_arc of the way around its orbit, one per
+    // TRAIL_SPAWN_INTERVAL. 0 for a body that doesn't orbit, which would only pile them up
+    pub fn trail_length(&self, body: &BodyConfig) -> usize {
+        body.trail_length.unwrap_or_else(|| {
+            let speed = self.angular_speed(body).abs();
+            if speed <= 0.0 {
+                return 0;
+            }
+            let period = TAU / speed;
+            (self.trail_arc * period / TRAIL_SPAWN_INTERVAL).round() as usize
+        })
+    }
+
+    // The orbit `body` follows around its parent (see Scene::parents): a circle of
+    // moon_orbit_radius for the moon, its own orbit for everything else
+    pub fn body_orbit(&self, body: &BodyConfig) -> Orbit {
+        if body.planet_type == PlanetType::Moon {
+            Orbit { radius: self.moon_orbit_radius, eccentricity: 0.0, inclination: 0.0, ascending_node: 0.0 }
+        } else {
+            body.orbit()
+        }
+    }
+
+    // Shader parameters of the scene's bodies; types not in the scene keep their defaults
+    pub fn shader_params(&self) -> ShaderParamTable {
+        let mut table = ShaderParamTable::new();
+        for body in &self.bodies {
+            *table.get_mut(body.planet_type) = body.params;
+        }
+        table
+    }
+
+    // What every body is drawn with: `body_model` if the scene names one (an OBJ, or glTF
+    // by its .glb/.gltf extension), otherwise the generated sphere
+    pub fn body_mesh(&self, meshes: &mut MeshCache) -> Result<BodyMesh, String> {
+        match &self.body_model {
+            Some(path) => {
+                let mesh = if is_gltf(path) {
+                    meshes.gltf(path).map_err(|err| err.to_string())?
+                } else {
+                    meshes.obj(path).map_err(|err| err.to_string())?
+                };
+                Ok(BodyMesh { mesh, lod: None })
+            }
+            None => Ok(self.sphere_mesh(meshes)),
+        }
+    }
+
+    // The generated sphere, whether or not `body_model` replaces it. Also the stand-in
+    // when the model can't be loaded
+    pub fn sphere_mesh(&self, meshes: &mut MeshCache) -> BodyMesh {
+        match self.sphere {
+            SphereMesh::Icosphere => BodyMesh { mesh: meshes.icosphere(LOD_LEVELS - 1), lod: Some(meshes.lod()) },
+            SphereMesh::Uv => BodyMesh { mesh: meshes.uv_sphere(self.sphere_stacks, self.sphere_slices), lod: None },
+        }
+    }
+}
+
+impl BodyConfig {
+    // The orbit around the sun the scene file gives. The moon's is SceneConfig::body_orbit
+    pub fn orbit(&self) -> Orbit {
+        Orbit {
+            radius: self.orbit_radius,
+            eccentricity: self.eccentricity,
+            inclination: self.inclination.to_radians(),
+            ascending_node: self.ascending_node.to_radians(),
+        }
+    }
+}
+
+// The table the lines after a `[[...]]` header belong to
+enum Table {
+    Body(PendingBody),
+    Light(PendingLight),
+}
+
+impl Table {
+    fn finish(self, config: &mut SceneConfig) -> Result<(), String> {
+        match self {
+            Table::Body(body) => config.bodies.push(body.finish()?),
+            Table::Light(light) => config.lights.push(light.finish()?),
+        }
+        Ok(())
+    }
+}
+
+// A [[light]] table while it's being read
+struct PendingLight {
+    line: usize,
+    position: Option<Vec3>, // required: a light at the sun's center would only light the sun
+    light: PointLight,
+}
+
+impl PendingLight {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "position" => self.position = Some(parse_vec3(key, value)?),
+            "color" => {
+                let hex = parse_color(key, value)?;
+                self.light.color = Vec3::new((hex >> 16) as f32, ((hex >> 8) & 0xFF) as f32, (hex & 0xFF) as f32) / 255.0;
+            }
+            "intensity" => self.light.intensity = parse_number(key, value, 0.0)?,
+            "range" => self.light.range = parse_number(key, value, f32::MIN_POSITIVE)?,
+            _ => return Err(format!("campo de luz desconocido '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<PointLight, String> {
+        let position = self.position.ok_or_else(|| format!("línea {}: a la [[light]] le falta 'position'", self.line))?;
+        Ok(PointLight { position, ..self.light })
+    }
+}
+
+// A [[body]] table while it's being read
+struct PendingBody {
+    line: usize,
+    planet_type: Option<PlanetType>,
+    fields: Vec<(String, String, usize)>, // key, value, line; applied once the type is known
+}
+
+impl PendingBody {
+    fn finish(self) -> Result<BodyConfig, String> {
+        let planet_type = self.planet_type.ok_or_else(|| format!("línea {}: al [[body]] le falta 'type'", self.line))?;
+        let mut body = BodyConfig {
+            planet_type,
+            name: planet_type.name().to_string(),
+            scale: 1.0,
+            orbit_radius: 0.0,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            ascending_node: 0.0,
+            orbit_color: 0xFFFFFF,
+            trail_length: None,
+            trail_color: trail_color(planet_type),
+            trail_tail_color: None,
+            trail_space: default_trail_space(planet_type),
+            orbit_speed_multiplier: 1.0,
+            ambient: ambient_floor(planet_type),
+            bump_strength: default_bump_strength(planet_type),
+            params: ShaderParams::defaults_for(planet_type),
+        };
+
+        for (key, value, number) in self.fields {
+            let error = |msg: String| format!("línea {}: {}", number, msg);
+            let (key, value) = (key.as_str(), value.as_str());
+            match key {
+                "name" => {
+                    let name = unquote(value);
+                    if name.is_empty() {
+                        return Err(error("name no puede estar vacío".to_string()));
+                    }
+                    body.name = name.to_string();
+                }
+                "scale" => body.scale = parse_number(key, value, f32::MIN_POSITIVE).map_err(error)?,
+                "orbit_radius" => body.orbit_radius = parse_number(key, value, 0.0).map_err(error)?,
+                "eccentricity" => {
+                    body.eccentricity = parse_number(key, value, 0.0)
+                        .and_then(|e| if e < 1.0 { Ok(e) } else { Err(format!("eccentricity debe ser menor que 1, no '{}'", value)) })
+                        .map_err(error)?;
+                }
+                "inclination" => body.inclination = parse_number(key, value, f32::MIN).map_err(error)?,
+                "ascending_node" => body.ascending_node = parse_number(key, value, f32::MIN).map_err(error)?,
+                "orbit_color" => body.orbit_color = parse_color(key, value).map_err(error)?,
+                "trail_color" => body.trail_color = 0xFF000000 | parse_color(key, value).map_err(error)?,
+                "trail_tail_color" => body.trail_tail_color = Some(0xFF000000 | parse_color(key, value).map_err(error)?),
+                "trail_space" => {
+                    let id = unquote(value);
+                    body.trail_space = TrailSpace::from_id(id)
+                        .ok_or_else(|| error(format!("trail_space debe ser \"world\" o \"parent\", no '{}'", id)))?;
+                }
+                "orbit_speed_multiplier" => body.orbit_speed_multiplier = parse_number(key, value, 0.0).map_err(error)?,
+                "ambient" => {
+                    body.ambient = parse_number(key, value, 0.0)
+                        .and_then(|a| if a <= 1.0 { Ok(a) } else { Err(format!("ambient debe estar entre 0 y 1, no '{}'", value)) })
+                        .map_err(error)?;
+                }
+                "bump_strength" => body.bump_strength = parse_number(key, value, 0.0).map_err(error)?,
+                "trail" => {
+                    body.trail_length = Some(value
+                        .parse::<usize>()
+                        .map_err(|_| error(format!("trail debe ser un entero no negativo, no '{}'", value)))?);
+                }
+                "zoom" => body.params.zoom = parse_number(key, value, f32::MIN).map_err(error)?,
+                "threshold" => body.params.threshold = parse_number(key, value, f32::MIN).map_err(error)?,
+                "cloud_threshold" => body.params.cloud_threshold = parse_number(key, value, f32::MIN).map_err(error)?,
+                "blend" => body.params.blend = parse_number(key, value, f32::MIN).map_err(error)?,
+                "brightness" => body.params.brightness = parse_number(key, value, f32::MIN).map_err(error)?,
+                _ => return Err(error(format!("campo de cuerpo desconocido '{}'", key))),
+            }
+        }
+
+        // Orbit speed falls off with the radius, so only the sun may sit at the center. The
+        // moon's orbit is the scene's moon_orbit_radius around the earth instead
+        if planet_type != PlanetType::Sun && planet_type != PlanetType::Moon && body.orbit_radius <= 0.0 {
+            return Err(format!("línea {}: {} necesita un orbit_radius mayor que 0", self.line, planet_type.id()));
+        }
+        Ok(body)
+    }
+}
+
+fn parse_count(key: &str, value: &str, min: u32) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|v| *v >= min)
+        .ok_or_else(|| format!("{} debe ser un entero de al menos {}, no '{}'", key, min, value))
+}
+
+fn parse_number(key: &str, value: &str, min: f32) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= min)
+        .ok_or_else(|| {
+            if min > 0.0 {
+                format!("{} debe ser un número mayor que 0, no '{}'", key, value)
+            } else if min == 0.0 {
+                format!("{} debe ser un número no negativo, no '{}'", key, value)
+            } else {
+                format!("{} debe ser un número, no '{}'", key, value)
+            }
+        })
+}
+
+// [x, y, z]
+fn parse_vec3(key: &str, value: &str) -> Result<Vec3, String> {
+    let error = || format!("{} debe ser [x, y, z], no '{}'", key, value);
+    let components: Vec<f32> = list_items(value)
+        .ok_or_else(error)?
+        .into_iter()
+        .map(|c| c.parse::<f32>().ok().filter(|c| c.is_finite()))
+        .collect::<Option<_>>()
+        .ok_or_else(error)?;
+    match components[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(error()),
+    }
+}
+
+// "#RRGGBB" or 0xRRGGBB
+fn parse_color(key: &str, value: &str) -> Result<u32, String> {
+    let unquoted = unquote(value);
+    let hex = unquoted
+        .strip_prefix('#')
+        .or_else(|| unquoted.strip_prefix("0x"))
+        .filter(|hex| hex.len() == 6);
+    hex.and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| format!("{} debe ser \"#RRGGBB\" o 0xRRGGBB, no '{}'", key, value))
+}
+
+// ["#RRGGBB", "#RRGGBB"], each color as in parse_color
+fn parse_color_pair(key: &str, value: &str) -> Result<(u32, u32), String> {
+    let error = || format!("{} debe ser [\"#RRGGBB\", \"#RRGGBB\"], no '{}'", key, value);
+    match list_items(value).ok_or_else(error)?[..] {
+        [top, bottom] => Ok((parse_color(key, top).map_err(|_| error())?, parse_color(key, bottom).map_err(|_| error())?)),
+        _ => Err(error()),
+    }
+}
+
+// Path of a body around the sun: an ellipse with the sun at one focus, in a plane tilted
+// by `inclination` about the line of nodes, which is turned `ascending_node` about Y from +X.
+// Both the simulation and the orbit lines go through `position`, so a body is always on its line.
+// The simulation keeps each body's mean anomaly, which grows at a constant rate, and turns it
+// into the eccentric anomaly `position` takes with `eccentric_anomaly`, so bodies speed up
+// towards the sun and slow down away from it as Kepler's second law has them
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub radius: f32, // semi-major axis
+    pub eccentricity: f32,
+    pub inclination: f32,    // radians
+    pub ascending_node: f32, // radians
+}
+
+impl Orbit {
+    // Point of the orbit at `angle` (the eccentric anomaly: 0 is the point nearest the sun).
+    // A circle in the XZ plane is (radius * cos, 0, radius * sin)
+    pub fn position(&self, angle: f32) -> Vec3 {
+        // In the orbital plane, with the sun at the focus on the +X side of the center
+        let minor = self.radius * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+        let x = self.radius * (angle.cos() - self.eccentricity);
+        let z = minor * angle.sin();
+
+        // Tilt the plane about the line of nodes (X), then turn that line about Y
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_node, cos_node) = self.ascending_node.sin_cos();
+        let (y, z) = (z * sin_i, z * cos_i);
+        Vec3::new(x * cos_node + z * sin_node, y, z * cos_node - x * sin_node)
+    }
+
+    // The eccentric anomaly E at mean anomaly `mean` (M), solving Kepler's equation
+    // M = E - e sin E with Newton's method. Whole turns carry over, so E grows with M
+    pub fn eccentric_anomaly(&self, mean: f32) -> f32 {
+        let e = self.eccentricity;
+        if e <= 0.0 {
+            return mean;
+        }
+        // In -PI..PI, where starting from M (from PI for very elongated orbits) converges
+        let turns = mean - ((mean + PI).rem_euclid(TAU) - PI);
+        let mean = mean - turns;
+        let mut anomaly = if e < 0.8 { mean } else { PI.copysign(mean) };
+        for _ in 0..KEPLER_ITERATIONS {
+            anomaly -= (anomaly - e * anomaly.sin() - mean) / (1.0 - e * anomaly.cos());
+        }
+        anomaly + turns
+    }
+}
+
+// An orbit as it's drawn: `orbit` laid out around `center`, with its body at eccentric
+// anomaly `angle`
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitPath {
+    pub orbit: Orbit,
+    pub center: Vec3,
+    pub angle: f32,
+}
+
+impl OrbitPath {
+    pub fn position(&self, angle: f32) -> Vec3 {
+        self.center + self.orbit.position(angle)
+    }
+}
+
+pub struct CelestialBody {
+    pub name: String,
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation: Vec3,
+    pub shader_type: PlanetType,
+    pub ambient: f32, // BodyConfig::ambient
+    pub bump_strength: f32, // BodyConfig::bump_strength
+    pub mesh: BodyMesh,
+    pub trail: Trail,
+    pub visibility: Visibility,
+}
+
+// Which of a body's parts get drawn. Only drawing looks at these: a hidden body keeps
+// orbiting and leaving particles, so it shows up where it should when it's shown again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visibility {
+    pub body: bool,
+    pub orbit: bool,
+    pub trail: bool,
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Visibility { body: true, orbit: true, trail: true }
+    }
+}
+
+impl CelestialBody {
+    // Radius of the sphere that encloses the body's mesh, the one picking and the debug
+    // bounds overlay use. Body meshes are unit spheres before scaling, and asteroid lumps
+    // only push the surface in
+    pub fn bounding_radius(&self) -> f32 {
+        self.scale
+    }
+}
+
+// Particles left behind a body, oldest first. Each particle's color goes from the head
+// color to the tail color with its age: the larger of how much of its lifetime has passed
+// and its place along the trail, so the gradient spans the trail while it's still growing
+// and long-lived trails (which end by pushing out old particles) still get one
+pub struct Trail {
+    pub particles: Vec<TrailParticle>,
+    pub space: TrailSpace,
+    max_particles: usize, // the body's SceneConfig::trail_length
+    retention: usize,     // how many it actually keeps: max_particles, or less to stay within the scene's budget
+    emitted: u64,         // particles left since the start, the newest one included
+    lifetime: f32, // seconds
+    size: f32,
+    head_color: u32,
+    tail_color: u32,
+}
+
+pub struct TrailParticle {
+    pub position: Vec3, // in the trail's `space`: see Scene::trail_origin
+    pub color: u32,
+    pub lifetime: f32,
+    pub size: f32,
+}
+
+impl Trail {
+    // `length` is the body's SceneConfig::trail_length
+    fn new(body: &BodyConfig, length: usize) -> Self {
+        // The moon's short trail fades quickly; the planets' last until pushed out
+        let is_moon = body.planet_type == PlanetType::Moon;
+        Self {
+            particles: Vec::with_capacity(length),
+            space: body.trail_space,
+            max_particles: length,
+            retention: length,
+            emitted: 0,
+            lifetime: if is_moon { 2.0 } else { 200000.0 },
+            size: if is_moon { 0.2 } else { 0.5 },
+            head_color: body.trail_color,
+            tail_color: body.trail_tail_color.unwrap_or(body.trail_color),
+        }
+    }
+
+    // Color of the newest particles (ARGB); also the body's dot on the minimap
+    pub fn color(&self) -> u32 {
+        self.head_color
+    }
+
+    // How many particles the trail keeps at most
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    // Keep at most `retention` particles (never more than the body's trail_length),
+    // dropping the oldest ones past it right away
+    fn set_retention(&mut self, retention: usize) {
+        self.retention = retention.min(self.max_particles);
+        let excess = self.particles.len().saturating_sub(self.retention);
+        self.particles.drain(..excess);
+    }
+
+    // Every `stride`th particle, oldest first. The same particles are picked frame after
+    // frame (counting from the first one the trail ever left), so a thinned out trail
+    // doesn't flicker as it moves on
+    pub fn thinned(&self, stride: usize) -> impl Iterator<Item = &TrailParticle> {
+        let stride = stride.max(1) as u64;
+        let first = self.emitted - self.particles.len() as u64;
+        self.particles.iter()
+            .enumerate()
+            .filter(move |(i, _)| (first + *i as u64).is_multiple_of(stride))
+            .map(|(_, particle)| particle)
+    }
+
+    // Drop every particle, for a trail whose space moved out from under it
+    fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.particles.retain_mut(|particle| {
+            particle.lifetime -= dt;
+            particle.size *= 0.999;
+            particle.lifetime > 0.0
+        });
+
+        if self.tail_color != self.head_color {
+            let (head, tail) = (Color::from_hex(self.head_color), Color::from_hex(self.tail_color));
+            let oldest = self.particles.len().saturating_sub(1).max(1) as f32;
+            for (i, particle) in self.particles.iter_mut().enumerate() {
+                let age = 1.0 - particle.lifetime / self.lifetime;
+                let rank = 1.0 - i as f32 / oldest;
+                let t = age.max(rank).clamp(0.0, 1.0);
+                particle.color = 0xFF000000 | head.lerp(&tail, t).to_hex();
+            }
+        }
+    }
+
+    fn add_particle(&mut self, position: Vec3) {
+        if self.retention == 0 {
+            return;
+        }
+        if self.particles.len() >= self.retention {
+            self.particles.remove(0);
+        }
+        self.emitted += 1;
+
+        self.particles.push(TrailParticle {
+            position,
+            color: self.head_color,
+            lifetime: self.lifetime,
+            size: self.size,
+        });
+    }
+}
+
+// Default trail color of a body type (ARGB); scene files can override it per body
+pub fn trail_color(planet_type: PlanetType) -> u32 {
+    match planet_type {
+        PlanetType::Sun => 0xFFFFA500,       // Naranja brillante
+        PlanetType::RockyPlanet => 0xFFD2B48C, // Marrón claro (tono arena)
+        PlanetType::Earth => 0xFF32CD32,     // Verde limón
+        PlanetType::CrystalPlanet => 0xFFFF00FF, // Fucsia
+        PlanetType::FirePlanet => 0xFFFF4500,    // Rojo anaranjado (tono de fuego)
+        PlanetType::WaterPlanet => 0xFF40E0D0,   // Turquesa
+        PlanetType::CloudPlanet => 0xFFFFD700,   // Dorado
+        PlanetType::Moon => 0xFF9370DB,         // Morado
+        PlanetType::Asteroid => 0xFFFFA500,     // Naranja brillante (tono cercano a Sun)
+        PlanetType::Spaceship => 0xFFFFFFFF,    // Blanco
+        PlanetType::Trail => 0xFF888888,        // Gris
+    }
+}
+
+// Default least light of a body type's unlit side, as a fraction of full light, so the
+// night side keeps a trace of its surface instead of going black; scene files can override
+// it per body. Airless bodies get the darkest nights, and the sun lights itself
+pub fn ambient_floor(planet_type: PlanetType) -> f32 {
+    match planet_type {
+        PlanetType::Moon => 0.02,
+        PlanetType::Asteroid | PlanetType::RockyPlanet => 0.03,
+        PlanetType::Earth | PlanetType::WaterPlanet | PlanetType::CloudPlanet | PlanetType::CrystalPlanet => 0.06,
+        PlanetType::FirePlanet => 0.1, // the lava glows on the night side too
+        PlanetType::Sun | PlanetType::Spaceship | PlanetType::Trail => 0.0,
+    }
+}
+
+// Default relief of a body type's surface: how strongly Phong lighting tilts the normal
+// along the slopes of the noise its shader paints with. Only the rocky planet, the moon and
+// the asteroid have one; on the other types the setting does nothing
+pub fn default_bump_strength(planet_type: PlanetType) -> f32 {
+    match planet_type {
+        PlanetType::RockyPlanet | PlanetType::Asteroid => 1.0,
+        PlanetType::Moon => 0.6,
+        _ => 0.0,
+    }
+}
+
+/// Everything that gets drawn in world space: the bodies (with their trails) and their
+/// orbit state, plus the sky around them.
+///
+/// Built by [`build_scene`] and advanced with [`Scene::update`]. The bodies come from a
+/// [`SceneConfig`] and can be swapped for a reloaded one with [`Scene::load`]; the sky stays.
+pub struct Scene {
+    pub bodies: Vec<CelestialBody>,
+    pub orbits: Vec<Orbit>, // each body's orbit around its parent, from SceneConfig::body_orbit
+    pub parents: Vec<Option<usize>>, // the body each one's orbit is centered on; None for the origin
+    pub orbit_colors: Vec<u32>,
+    pub angles: Vec<f32>, // current mean anomaly of each body; a body is at orbits[i].position(orbits[i].eccentric_anomaly(angles[i])) from its parent
+    pub orbit_speeds: Vec<f32>, // radians per second of each body, from SceneConfig::angular_speed
+    pub speed_preset: OrbitSpeeds, // the law orbit_speeds follow, SceneConfig::orbit_speeds unless switched
+    pub star: StarParams,
+    pub lights: PointLights,
+    pub skybox: Skybox,
+    pub meteors: Meteors,
+    pub time: f32, // simulated seconds
+    pub frame: u32, // updates since the start
+    pub captured: Option<usize>, // the body the ship holds, off its orbit (see `capture`)
+    pub capture_range: f32,      // SceneConfig::capture_range
+    pub background_gradient: Option<(u32, u32)>, // SceneConfig::background_gradient, for Framebuffer::set_background_gradient
+    capture: Capture,
+    steps: Steps,
+}
+
+// Where the captured body is kept: `offset` from the ship, which is at `ship` and moving
+// at `ship_velocity`, as of the last `hold`
+#[derive(Default)]
+struct Capture {
+    ship: Vec3,
+    ship_velocity: Vec3,
+    offset: Vec3,
+    pace: f32, // the body's speed on the orbit it was taken from, in world units per second
+}
+
+// Body positions and clock before and after the last `update`, for drawing in between
+#[derive(Default)]
+struct Steps {
+    previous: Vec<Vec3>,
+    latest: Vec<Vec3>,
+    previous_time: f32,
+    latest_time: f32,
+}
+
+// Where the light comes from: the first sun in the scene, or the origin when there is none
+pub fn sun_position(bodies: &[CelestialBody]) -> Vec3 {
+    bodies.iter()
+        .find(|body| body.shader_type == PlanetType::Sun)
+        .map(|body| body.position)
+        .unwrap_or(Vec3::zeros())
+}
+
+/// The scene `config` describes, with its bodies at their starting places, under `skybox`.
+///
+/// `body_mesh` comes from [`SceneConfig::body_mesh`] (or [`SceneConfig::sphere_mesh`] if
+/// that failed); `meteors` decides where the meteors and comets fly, so a fixed seed makes
+/// runs repeat.
+pub fn build_scene(config: &SceneConfig, body_mesh: BodyMesh, skybox: Skybox, meteors: Meteors) -> Scene {
+    let mut scene = Scene {
+        bodies: Vec::new(),
+        orbits: Vec::new(),
+        parents: Vec::new(),
+        orbit_colors: Vec::new(),
+        angles: Vec::new(),
+        orbit_speeds: Vec::new(),
+        speed_preset: OrbitSpeeds::Classic,
+        star: StarParams::default(),
+        lights: PointLights::default(),
+        skybox,
+        meteors,
+        time: 0.0,
+        frame: 0,
+        captured: None,
+        capture_range: DEFAULT_CAPTURE_RANGE,
+        background_gradient: None,
+        capture: Capture::default(),
+        steps: Steps::default(),
+    };
+    scene.load(config, body_mesh);
+    scene
+}
+
+impl Scene {
+    /// Replace the bodies and their orbits with the ones in `config`, starting them over.
+    /// Every body shares `body_mesh`.
+    pub fn load(&mut self, config: &SceneConfig, body_mesh: BodyMesh) {
+        self.bodies = config.bodies.iter()
+            .map(|body| CelestialBody {
+                name: body.name.clone(),
+                position: Vec3::zeros(), // placed below, once the orbits are known
+                scale: body.scale,
+                rotation: Vec3::new(0.0, 0.0, 0.0),
+                shader_type: body.planet_type,
+                ambient: body.ambient,
+                bump_strength: body.bump_strength,
+                mesh: body_mesh.clone(),
+                trail: Trail::new(body, config.trail_length(body)),
+                visibility: Visibility::default(),
+            })
+            .collect();
+        self.orbits = config.bodies.iter().map(|body| config.body_orbit(body)).collect();
+        // Moons go around the first earth (the origin without one); everything else around the origin
+        let earth = config.bodies.iter().position(|body| body.planet_type == PlanetType::Earth);
+        self.parents = config.bodies.iter()
+            .map(|body| if body.planet_type == PlanetType::Moon { earth } else { None })
+            .collect();
+        self.orbit_colors = config.bodies.iter().map(|body| body.orbit_color).collect();
+        self.angles = vec![0.0; self.bodies.len()];
+        self.orbit_speeds = config.bodies.iter().map(|body| config.angular_speed(body)).collect();
+        self.speed_preset = config.orbit_speeds;
+        self.star = config.star;
+        self.lights = PointLights::new(&config.lights);
+        self.meteors.comet_rate = config.comet_rate;
+        self.captured = None;
+        self.capture_range = config.capture_range;
+        self.background_gradient = config.background_gradient;
+        self.set_particle_budget(config.particle_budget);
+        self.place_bodies();
+        // No steps yet: the previous and the latest are both the starting positions
+        self.record_step();
+        self.record_step();
+    }
+
+    // Keep the trails within `budget` particles in all: when their trail_lengths add up to
+    // more, each one keeps the same fraction of its own (rounded down), dropping its
+    // oldest particles past that
+    pub fn set_particle_budget(&mut self, budget: usize) {
+        let total: usize = self.bodies.iter().map(|body| body.trail.max_particles).sum();
+        for body in &mut self.bodies {
+            let length = body.trail.max_particles;
+            let retention = if total <= budget { length } else { (length as u64 * budget as u64 / total as u64) as usize };
+            body.trail.set_retention(retention);
+        }
+    }
+
+    // Trail particles alive right now, over all bodies
+    pub fn particle_count(&self) -> usize {
+        self.bodies.iter().map(|body| body.trail.particles.len()).sum()
+    }
+
+    // Remember where the bodies are after a step; the step before becomes the previous one
+    fn record_step(&mut self) {
+        let latest = self.bodies.iter().map(|body| body.position).collect();
+        self.steps.previous = std::mem::replace(&mut self.steps.latest, latest);
+        self.steps.previous_time = std::mem::replace(&mut self.steps.latest_time, self.time);
+    }
+
+    // Put the bodies, and the clock, `alpha` (0 to 1) of the way from the previous step to
+    // the latest one, to draw between simulation steps. Only the drawn state moves: the
+    // next `update` carries on from the latest step
+    pub fn interpolate(&mut self, alpha: f32) {
+        let steps = &self.steps;
+        for ((body, previous), latest) in self.bodies.iter_mut().zip(&steps.previous).zip(&steps.latest) {
+            body.position = previous.lerp(latest, alpha);
+        }
+        self.time = steps.previous_time + (steps.latest_time - steps.previous_time) * alpha;
+    }
+
+    // The path body `i` is following now, for drawing it: its orbit laid out around its
+    // parent's current position (the moon's circle around the earth), or around the origin
+    pub fn orbit_path(&self, i: usize) -> OrbitPath {
+        let orbit = self.orbits[i];
+        OrbitPath { orbit, center: self.orbit_center(i), angle: orbit.eccentric_anomaly(self.angles[i]) }
+    }
+
+    // What body `i`'s trail particles are relative to right now: its orbit's center with a
+    // parent-relative trail, the world's origin otherwise
+    pub fn trail_origin(&self, i: usize) -> Vec3 {
+        match self.bodies[i].trail.space {
+            TrailSpace::Parent => self.orbit_center(i),
+            TrailSpace::World => Vec3::zeros(),
+        }
+    }
+
+    // Where body `i`'s orbit is centered right now
+    fn orbit_center(&self, i: usize) -> Vec3 {
+        self.parents[i].map_or(Vec3::zeros(), |parent| self.bodies[parent].position)
+    }
+
+    // The body `i` goes around: its parent (the earth for the moon), or the first sun for
+    // everything else on an orbit around the origin. None for the suns and bodies that sit
+    // still at the origin
+    pub fn parent(&self, i: usize) -> Option<usize> {
+        if self.parents[i].is_some() {
+            return self.parents[i];
+        }
+        match self.bodies[i].shader_type {
+            PlanetType::Sun => None,
+            _ if self.orbits[i].radius > 0.0 => self.bodies.iter().position(|body| body.shader_type == PlanetType::Sun),
+            _ => None,
+        }
+    }
+
+    // How many bodies go around body `i`
+    pub fn child_count(&self, i: usize) -> usize {
+        (0..self.bodies.len()).filter(|&j| self.parent(j) == Some(i)).count()
+    }
+
+    // Speed of body `i` in world units per second over the last step (see `velocity`)
+    pub fn orbital_speed(&self, i: usize) -> f32 {
+        self.velocity(i).magnitude()
+    }
+
+    // Velocity of body `i` in world units per second over the last step: how far and where
+    // it moved between the two positions `interpolate` draws between. Zero before the
+    // first step
+    pub fn velocity(&self, i: usize) -> Vec3 {
+        let elapsed = self.steps.latest_time - self.steps.previous_time;
+        if elapsed <= 0.0 {
+            return Vec3::zeros();
+        }
+        (self.steps.latest[i] - self.steps.previous[i]) / elapsed
+    }
+
+    // Seconds body `i` takes to go once around its orbit (the moon, around the earth).
+    // None for bodies that don't orbit
+    pub fn orbital_period(&self, i: usize) -> Option<f32> {
+        let speed = self.orbit_speeds[i].abs();
+        (speed > 0.0).then(|| TAU / speed)
+    }
+
+    // Put every body at its orbit's current angle: first the ones around the origin, then
+    // the ones around another body, from where that body is now. The captured body goes
+    // where the ship holds it instead
+    fn place_bodies(&mut self) {
+        for pass_children in [false, true] {
+            for i in 0..self.bodies.len() {
+                if self.parents[i].is_some() != pass_children {
+                    continue;
+                }
+                self.bodies[i].position = if self.captured == Some(i) {
+                    self.capture.ship + self.capture.offset
+                } else {
+                    self.orbit_center(i) + self.orbits[i].position(self.orbits[i].eccentric_anomaly(self.angles[i]))
+                };
+            }
+        }
+    }
+
+    // Whether the ship can capture body `i`: asteroids and moons, small enough to tow
+    pub fn capturable(&self, i: usize) -> bool {
+        matches!(self.bodies[i].shader_type, PlanetType::Asteroid | PlanetType::Moon)
+    }
+
+    // Switch the law the orbit speeds follow, keeping every body where it is on its orbit:
+    // the speed of each body going around the origin is rescaled by how the two laws differ
+    // at its radius, so a body released onto an orbit of its own follows the new law too.
+    // Moons keep moon_orbit_speed, and a held body stays held (it's let go at the new pace)
+    pub fn set_speed_preset(&mut self, preset: OrbitSpeeds) {
+        let old = self.speed_preset;
+        self.speed_preset = preset;
+        for i in 0..self.bodies.len() {
+            let radius = self.orbits[i].radius;
+            if self.parents[i].is_some() || self.bodies[i].shader_type == PlanetType::Moon || radius <= 0.0 {
+                continue;
+            }
+            let ratio = preset.falloff(radius) / old.falloff(radius);
+            self.orbit_speeds[i] *= ratio;
+            if self.captured == Some(i) {
+                self.capture.pace *= ratio;
+            }
+        }
+    }
+
+    // The body the ship at `ship` would capture: the nearest shown asteroid or moon within
+    // capture_range of its surface
+    pub fn capture_candidate(&self, ship: Vec3) -> Option<usize> {
+        let gap = |i: usize| (self.bodies[i].position - ship).magnitude() - self.bodies[i].scale;
+        (0..self.bodies.len())
+            .filter(|&i| self.capturable(i) && self.bodies[i].visibility.body && gap(i) <= self.capture_range)
+            .min_by(|&a, &b| gap(a).total_cmp(&gap(b)))
+    }
+
+    // Take body `i` off its orbit: from the next update on it keeps the offset it has now
+    // from the ship at `ship`, wherever `hold` says the ship went. Releases whatever was
+    // held before
+    pub fn capture(&mut self, i: usize, ship: Vec3) {
+        self.release();
+        self.capture = Capture {
+            ship,
+            ship_velocity: Vec3::zeros(),
+            offset: self.bodies[i].position - ship,
+            pace: self.orbit_speeds[i].abs() * self.orbits[i].radius,
+        };
+        self.set_parent(i, None);
+        self.orbit_speeds[i] = 0.0;
+        self.captured = Some(i);
+    }
+
+    // Where the ship is now, `dt` seconds after the last call: the captured body follows it
+    // on the next updates, and takes its velocity along when released
+    pub fn hold(&mut self, ship: Vec3, dt: f32) {
+        if self.captured.is_none() {
+            return;
+        }
+        if dt > 0.0 {
+            self.capture.ship_velocity = (ship - self.capture.ship) / dt;
+        }
+        self.capture.ship = ship;
+    }
+
+    // Let the captured body go, on a new circular orbit through where it is now, with the
+    // ship's velocity: around the planet it's within reach of (see RELEASE_REACH), or the
+    // origin. The orbit's plane and direction are the ones that velocity turns it in, and
+    // its speed the part of the velocity across the line to the center; let go with
+    // hardly any, it goes on at the pace it had before it was captured. Returns the body
+    pub fn release(&mut self) -> Option<usize> {
+        let i = self.captured.take()?;
+        // From the latest step, which the next update carries on from, so it doesn't jump
+        let position = self.steps.latest[i];
+        let parent = (0..self.bodies.len())
+            .filter(|&j| j != i && !self.capturable(j) && self.parents[j].is_none())
+            .filter(|&j| self.bodies[j].shader_type != PlanetType::Sun)
+            .map(|j| (j, (position - self.steps.latest[j]).magnitude() / self.bodies[j].scale))
+            .filter(|&(_, reach)| reach < RELEASE_REACH)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(j, _)| j);
+        let (center, center_velocity) = match parent {
+            Some(j) => (self.steps.latest[j], self.velocity(j)),
+            None => (Vec3::zeros(), Vec3::zeros()),
+        };
+        let offset = position - center;
+        let radius = offset.magnitude();
+        let outward = offset.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+        let momentum = offset.cross(&(self.capture.ship_velocity - center_velocity));
+
+        // Unit normal the body turns around (right-handed), and its angular speed
+        let (normal, speed) = if momentum.magnitude() / radius.max(f32::EPSILON) >= MIN_RELEASE_SPEED {
+            (momentum.normalize(), momentum.magnitude() / (radius * radius))
+        } else {
+            // The way the scene's own orbits go (-Y), in the plane through the center
+            // that's closest to level
+            let down = Vec3::new(0.0, -1.0, 0.0);
+            let normal = (down - outward * outward.dot(&down)).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+            (normal, self.capture.pace / radius.max(f32::EPSILON))
+        };
+
+        // An Orbit going the usual way (increasing angle) turns around
+        // (sin i sin node, -cos i, sin i cos node); solve that for `normal`
+        let inclination = (-normal.y).clamp(-1.0, 1.0).acos();
+        let ascending_node = if inclination.sin().abs() > f32::EPSILON { normal.x.atan2(normal.z) } else { 0.0 };
+        let orbit = Orbit { radius, eccentricity: 0.0, inclination, ascending_node };
+        // Where the orbit's +X and +Z axes ended up, to read the body's angle on it
+        let (sin_i, cos_i) = inclination.sin_cos();
+        let (sin_node, cos_node) = ascending_node.sin_cos();
+        let x_axis = Vec3::new(cos_node, 0.0, -sin_node);
+        let z_axis = Vec3::new(cos_i * sin_node, sin_i, cos_i * cos_node);
+
+        self.orbits[i] = orbit;
+        self.angles[i] = offset.dot(&z_axis).atan2(offset.dot(&x_axis));
+        self.orbit_speeds[i] = speed;
+        self.set_parent(i, parent);
+        Some(i)
+    }
+
+    // Center body `i`'s orbit on `parent`. A trail kept relative to the old one would be
+    // drawn around the new one, so it starts over
+    fn set_parent(&mut self, i: usize, parent: Option<usize>) {
+        if self.parents[i] != parent {
+            if self.bodies[i].trail.space == TrailSpace::Parent {
+                self.bodies[i].trail.clear();
+            }
+            self.parents[i] = parent;
+        }
+    }
+
+    /// Advance the simulation by `dt` seconds: orbits, trails, meteors and comets.
+    pub fn update(&mut self, dt: f32) {
+        // Start from the latest step, not from wherever `interpolate` left the bodies
+        for (body, latest) in self.bodies.iter_mut().zip(&self.steps.latest) {
+            body.position = *latest;
+        }
+        self.time = self.steps.latest_time;
+        self.time += dt;
+        self.frame += 1;
+
+        // Farther bodies orbit more slowly
+        for (angle, speed) in self.angles.iter_mut().zip(&self.orbit_speeds) {
+            *angle += speed * dt;
+        }
+        self.place_bodies();
+
+        self.meteors.update(dt);
+
+        for i in 0..self.bodies.len() {
+            let origin = self.trail_origin(i);
+            let body = &mut self.bodies[i];
+            body.trail.update(dt);
+            body.trail.add_particle(body.position - origin);
+        }
+        self.record_step();
+    }
+}
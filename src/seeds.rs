@@ -0,0 +1,60 @@
+// Root seed when --seed isn't given: the procedural sky's historical default
+pub const DEFAULT_ROOT: u64 = 7;
+
+// Base seed of each derived stream, i.e. its value under the default root
+const NEBULA_BASE: u64 = 2024;
+const SURFACE_NOISE_BASE: u64 = 1337;
+const WATER_BASE: u64 = 0;
+const SKY_REGENERATION_BASE: u64 = 0x5EED_5107;
+const ASTEROIDS_BASE: u64 = 0xA57E_401D;
+
+// Seeds of every random source, all derived from one root seed (--seed). The sky and the
+// meteors use the root itself, so a seed printed by F6 reproduces that sky. The other
+// streams are shifted away from their base seeds by the root's distance from
+// DEFAULT_ROOT, so the default root keeps the look the project has always had
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seeds {
+    pub root: u64,
+    pub sky: u64,
+    pub meteors: u64,
+    pub nebula: i32,
+    pub surface_noise: i32, // FastNoiseLite behind the planet shaders
+    pub water: u64,         // mixed into the water shader's per-fragment offsets
+    pub sky_regeneration: u64, // seeds the RNG that picks F6's new skies
+    pub asteroids: u64,        // per-asteroid shape and surface, see `body_seed`
+}
+
+impl Seeds {
+    pub fn from_root(root: u64) -> Self {
+        Seeds {
+            root,
+            sky: root,
+            meteors: root,
+            nebula: derive(NEBULA_BASE, root) as i32,
+            surface_noise: derive(SURFACE_NOISE_BASE, root) as i32,
+            water: derive(WATER_BASE, root),
+            sky_regeneration: derive(SKY_REGENERATION_BASE, root),
+            asteroids: derive(ASTEROIDS_BASE, root),
+        }
+    }
+}
+
+impl Default for Seeds {
+    fn default() -> Self {
+        Seeds::from_root(DEFAULT_ROOT)
+    }
+}
+
+// Seed of the body at `index` in the scene, from a stream such as `Seeds::asteroids`.
+// A splitmix64 step, so neighbouring bodies get unrelated seeds
+pub fn body_seed(stream: u64, index: usize) -> u64 {
+    let mut z = stream.wrapping_add((index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Distinct roots land far apart (golden-ratio multiplier), and DEFAULT_ROOT gives `base`
+fn derive(base: u64, root: u64) -> u64 {
+    base.wrapping_add(root.wrapping_sub(DEFAULT_ROOT).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use crate::planet::PlanetType;
+
+// Tunable parameters read by the planet shaders instead of hard-coded literals.
+// Each shader uses the subset that applies to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShaderParams {
+    pub zoom: f32,            // noise scale
+    pub threshold: f32,       // main noise threshold (land, craters, lava, rock layers)
+    pub cloud_threshold: f32, // noise value above which clouds appear
+    pub blend: f32,           // color lerp factor between layers
+    pub brightness: f32,      // final intensity multiplier
+}
+
+impl ShaderParams {
+    // Defaults reproduce the values the shaders were originally written with
+    pub fn defaults_for(planet_type: PlanetType) -> Self {
+        let base = ShaderParams { zoom: 30.0, threshold: 0.5, cloud_threshold: 0.5, blend: 0.5, brightness: 1.0 };
+        match planet_type {
+            PlanetType::Sun => ShaderParams { zoom: 1000.0, ..base },
+            PlanetType::RockyPlanet => ShaderParams { brightness: 1.95, ..base },
+            PlanetType::Earth => base,
+            PlanetType::CrystalPlanet => ShaderParams { brightness: 2.8, ..base },
+            PlanetType::FirePlanet => ShaderParams { zoom: 80.0, ..base },
+            PlanetType::WaterPlanet => ShaderParams { zoom: 40.0, blend: 1.5, brightness: 0.9, ..base },
+            PlanetType::CloudPlanet => ShaderParams { zoom: 50.0, cloud_threshold: 0.4, ..base },
+            PlanetType::Moon => ShaderParams { zoom: 100.0, blend: 0.1, ..base },
+            PlanetType::Asteroid => ShaderParams { zoom: 20.0, ..base },
+            PlanetType::Spaceship | PlanetType::Trail => base,
+        }
+    }
+
+    pub const FIELD_COUNT: usize = 5;
+
+    // (name, step) for each field, in display order
+    pub fn field_info(index: usize) -> (&'static str, f32) {
+        match index {
+            0 => ("zoom ruido", 5.0),
+            1 => ("umbral", 0.02),
+            2 => ("umbral nubes", 0.02),
+            3 => ("mezcla", 0.05),
+            _ => ("brillo", 0.05),
+        }
+    }
+
+    pub fn field(&self, index: usize) -> f32 {
+        match index {
+            0 => self.zoom,
+            1 => self.threshold,
+            2 => self.cloud_threshold,
+            3 => self.blend,
+            _ => self.brightness,
+        }
+    }
+
+    pub fn field_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.zoom,
+            1 => &mut self.threshold,
+            2 => &mut self.cloud_threshold,
+            3 => &mut self.blend,
+            _ => &mut self.brightness,
+        }
+    }
+}
+
+// The sun's animation: the pulsation of its surface spots and, optionally, a slow
+// variable-star oscillation of its brightness that also dims and brightens everything it lights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StarParams {
+    pub pulse_frequency: f32,       // of the spot pulsation, per 100 frames
+    pub pulse_amplitude: f32,       // how far the spots' noise slice moves
+    pub variability_period: f32,    // seconds per brightness cycle; 0 = steady star
+    pub variability_amplitude: f32, // fraction the brightness swings above and below 1, in [0, 1]
+}
+
+impl Default for StarParams {
+    // The sun_shader's original pulsation, with a steady brightness
+    fn default() -> Self {
+        StarParams { pulse_frequency: 0.2, pulse_amplitude: 0.5, variability_period: 0.0, variability_amplitude: 0.0 }
+    }
+}
+
+impl StarParams {
+    // Light multiplier at `seconds` of simulated time: a sine around 1 so it brightens
+    // and dims smoothly
+    pub fn brightness(&self, seconds: f32) -> f32 {
+        if self.variability_period <= 0.0 || self.variability_amplitude == 0.0 {
+            return 1.0;
+        }
+        1.0 + self.variability_amplitude * (std::f32::consts::TAU * seconds / self.variability_period).sin()
+    }
+}
+
+// Per-PlanetType parameter table stored on Uniforms
+#[derive(Clone)]
+pub struct ShaderParamTable {
+    params: HashMap<PlanetType, ShaderParams>,
+}
+
+impl Default for ShaderParamTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShaderParamTable {
+    pub fn new() -> Self {
+        ShaderParamTable { params: HashMap::new() }
+    }
+
+    pub fn get(&self, planet_type: PlanetType) -> ShaderParams {
+        self.params
+            .get(&planet_type)
+            .copied()
+            .unwrap_or_else(|| ShaderParams::defaults_for(planet_type))
+    }
+
+    pub fn get_mut(&mut self, planet_type: PlanetType) -> &mut ShaderParams {
+        self.params
+            .entry(planet_type)
+            .or_insert_with(|| ShaderParams::defaults_for(planet_type))
+    }
+}
@@ -1,22 +1,127 @@
 use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
 use crate::vertex::Vertex;
-use crate::Uniforms;
+use crate::uniforms::DrawUniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
+use crate::lights::compress_light;
 use rand::Rng;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
-use crate::PlanetType;
+use crate::planet::PlanetType;
+use crate::shader_params::ShaderParams;
+
+/// Modelo de iluminación: Gouraud calcula la luz en cada vértice e interpola la intensidad;
+/// Phong interpola la normal y calcula la luz en cada fragmento (terminador suave).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shading {
+  Gouraud,
+  Phong,
+}
+
+impl Shading {
+  /// Nombre que muestra el HUD.
+  pub fn name(self) -> &'static str {
+    match self {
+      Shading::Gouraud => "Gouraud",
+      Shading::Phong => "Phong",
+    }
+  }
+
+  /// El otro modelo.
+  pub fn toggled(self) -> Shading {
+    match self {
+      Shading::Gouraud => Shading::Phong,
+      Shading::Phong => Shading::Gouraud,
+    }
+  }
+}
+
+/// Dirección (unitaria, hacia la luz) de la luz principal cuando no hay una manual.
+pub const LIGHT_DIRECTION: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+// Elevación máxima de la luz manual: sin llegar al polo, donde el giro no está definido
+const MAX_LIGHT_ELEVATION: f32 = 1.55;
+
+/// Término difuso de la luz principal para una normal unitaria.
+pub fn diffuse(normal: &Vec3, light_direction: &Vec3) -> f32 {
+  normal.dot(light_direction).max(0.0)
+}
+
+/// Girar una dirección de luz alrededor del eje Y (`yaw`) y subirla o bajarla (`pitch`),
+/// en radianes, como si orbitara alrededor del cuerpo que se mira.
+pub fn orbit_light(direction: &Vec3, yaw: f32, pitch: f32) -> Vec3 {
+  let azimuth = direction.x.atan2(direction.z) + yaw;
+  let elevation = (direction.y.clamp(-1.0, 1.0).asin() + pitch).clamp(-MAX_LIGHT_ELEVATION, MAX_LIGHT_ELEVATION);
+  Vec3::new(elevation.cos() * azimuth.sin(), elevation.sin(), elevation.cos() * azimuth.cos())
+}
+
+// Profundidad máxima de los bultos de un asteroide, como fracción del radio. Solo hunden
+// la superficie, así la esfera de escala `scale` sigue envolviendo al cuerpo (recorte por
+// frustum, selección con el mouse, etiquetas)
+const ASTEROID_LUMP_DEPTH: f32 = 0.3;
+// Bultos por radio; el ruido multiplica además por su frecuencia por defecto (0.01)
+const ASTEROID_LUMP_SCALE: f32 = 130.0;
+// Paso en la esfera unitaria para estimar la normal de la superficie deformada
+const ASTEROID_NORMAL_STEP: f32 = 0.01;
+
+// Desplazamiento en el espacio del ruido propio de cada cuerpo: dos asteroides con
+// semillas distintas leen zonas del ruido muy separadas
+fn seed_offset(seed: u64) -> Vec3 {
+  let component = |shift: u32| ((seed >> shift) & 0xFFFF) as f32 / 65535.0 * 1000.0;
+  Vec3::new(component(0), component(16), component(32))
+}
+
+// Radio del asteroide en la dirección unitaria `direction`, entre 1 - ASTEROID_LUMP_DEPTH y 1
+fn asteroid_radius(direction: &Vec3, uniforms: &DrawUniforms) -> f32 {
+  let p = (direction + seed_offset(uniforms.seed)) * ASTEROID_LUMP_SCALE;
+  let coarse = uniforms.noise.get_noise_3d(p.x, p.y, p.z);
+  let fine = uniforms.noise.get_noise_3d(p.x * 2.5, p.y * 2.5, p.z * 2.5);
+  let lumps = ((coarse + 0.35 * fine) / 1.35).clamp(-1.0, 1.0);
+  1.0 - ASTEROID_LUMP_DEPTH * (0.5 + 0.5 * lumps)
+}
+
+// Posición y normal del vértice de la esfera sobre la superficie irregular del asteroide.
+// La normal sale del plano que forman el punto y dos vecinos a lo largo de la superficie
+fn displace_asteroid(position: &Vec3, uniforms: &DrawUniforms) -> (Vec3, Vec3) {
+  let direction = position.normalize();
+  let surface = |d: Vec3| {
+    let d = d.normalize();
+    d * asteroid_radius(&d, uniforms)
+  };
+  let center = surface(direction);
+
+  let helper = if direction.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+  let tangent = direction.cross(&helper).normalize();
+  let bitangent = direction.cross(&tangent);
+  let along_tangent = surface(direction + tangent * ASTEROID_NORMAL_STEP) - center;
+  let along_bitangent = surface(direction + bitangent * ASTEROID_NORMAL_STEP) - center;
+  let mut normal = along_tangent.cross(&along_bitangent).normalize();
+  if normal.dot(&direction) < 0.0 {
+    normal = -normal;
+  }
+
+  (center * position.magnitude(), normal)
+}
+
+/// Lleva un vértice del modelo a la pantalla con las matrices de `uniforms`, con la normal en
+/// el mundo y la profundidad que interpola el rasterizador. Los asteroides se deforman según
+/// su semilla.
+pub fn vertex_shader(vertex: &Vertex, uniforms: &DrawUniforms) -> Vertex {
+  // Los asteroides deforman la esfera según su semilla, siempre igual para el mismo cuerpo
+  let (object_position, object_normal) = if uniforms.shader == PlanetType::Asteroid {
+    displace_asteroid(&vertex.position, uniforms)
+  } else {
+    (vertex.position, vertex.normal)
+  };
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   // Transform position
   let position = Vec4::new(
-    vertex.position.x,
-    vertex.position.y,
-    vertex.position.z,
+    object_position.x,
+    object_position.y,
+    object_position.z,
     1.0
   );
-  let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
+  let world_position = uniforms.model_matrix * position;
+  let transformed = uniforms.frame.projection_matrix * uniforms.frame.view_matrix * world_position;
 
   // Perform perspective division
   let w = transformed.w;
@@ -28,57 +133,156 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   );
 
   // apply viewport matrix
-  let screen_position = uniforms.viewport_matrix * ndc_position;
+  let screen_position = uniforms.frame.viewport_matrix * ndc_position;
+  // El rasterizador interpola esta profundidad linealmente en pantalla, sea NDC o logarítmica
+  let depth = uniforms.frame.depth(w, screen_position.z);
 
   // Transform normal
   let model_mat3 = mat4_to_mat3(&uniforms.model_matrix); 
   let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
 
-  let transformed_normal = normal_matrix * vertex.normal;
+  let transformed_normal = normal_matrix * object_normal;
 
   // Create a new Vertex with transformed attributes
   Vertex {
-    position: vertex.position,
-    normal: vertex.normal,
+    position: object_position,
+    normal: object_normal,
     tex_coords: vertex.tex_coords,
     color: vertex.color,
-    transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+    transformed_position: Vec3::new(screen_position.x, screen_position.y, depth),
     transformed_normal,
+    world_position: Vec3::new(world_position.x, world_position.y, world_position.z),
   }
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, planet_type: &PlanetType) -> Color {
-    match planet_type {
-        PlanetType::Sun => sun_shader(fragment, uniforms),
-        PlanetType::RockyPlanet => rocky_planet_shader(fragment, uniforms),
+/// Color de un fragmento antes de la exposición: el shader del tipo de cuerpo de `uniforms`,
+/// con la luz del modelo de sombreado del cuadro, el brillo del sol y las luces puntuales.
+pub fn fragment_shader(fragment: &Fragment, uniforms: &DrawUniforms) -> Color {
+    // Phong: la intensidad se calcula aquí con la normal interpolada y renormalizada del
+    // fragmento; en Gouraud ya viene interpolada desde los vértices
+    // La luz se escala con el brillo actual del sol (estrella variable), y nunca baja del
+    // piso de luz ambiental del cuerpo: el lado de noche conserva algo de su superficie
+    // Con Phong los cuerpos con relieve inclinan además la normal según su ruido (bump_normal)
+    let normal = match uniforms.frame.shading {
+        Shading::Phong => bump_normal(fragment, uniforms),
+        Shading::Gouraud => fragment.normal,
+    };
+    let intensity = match uniforms.frame.shading {
+        Shading::Phong => diffuse(&normal, &uniforms.frame.light_direction()),
+        Shading::Gouraud => fragment.intensity,
+    };
+    let intensity = (intensity * uniforms.frame.light_intensity).max(uniforms.ambient);
+
+    // Sin luces puntuales (o en lo que no se ilumina) la luz es un solo escalar, como siempre
+    let lights = &uniforms.frame.lights;
+    if lights.is_empty() || !receives_point_lights(uniforms.shader) {
+        return surface_color(&Fragment { intensity, ..*fragment }, uniforms);
+    }
+
+    // Con luces puntuales: la luz del sol más la de cada luz, por canal, comprimida para que
+    // varias luces fuertes no quemen la superficie; se aplica sobre el color a plena luz
+    let light = Vec3::repeat(intensity) + lights.illuminate(&fragment.world_position, &normal);
+    let albedo = surface_color(&Fragment { intensity: 1.0, ..*fragment }, uniforms);
+    albedo.scale_rgb(compress_light(light.x), compress_light(light.y), compress_light(light.z))
+}
+
+// Paso de las diferencias finitas del relieve, en unidades del ruido (coordenadas por zoom)
+const BUMP_STEP: f32 = 1.0;
+// Altura del relieve con bump_strength 1, en las mismas unidades: una formación del ruido
+// (frecuencia 0.01) mide unas 100, así la pendiente no depende del zoom del cuerpo
+const BUMP_RELIEF: f32 = 10.0;
+
+// Altura del relieve en el punto `p` (espacio del modelo) de los cuerpos que lo tienen: el
+// mismo ruido que reparte sus formaciones, así las sombras siguen a los colores. None en el resto
+fn bump_height(p: &Vec3, uniforms: &DrawUniforms) -> Option<f32> {
+    let zoom = uniforms.params.zoom;
+    let noise = |x: f32, y: f32| uniforms.noise.get_noise_2d(x * zoom, y * zoom);
+    match uniforms.shader {
+        PlanetType::RockyPlanet => Some(noise(p.x, p.y)),
+        PlanetType::Moon => Some(-noise(p.x, p.y)), // los cráteres (ruido alto) son hoyos
+        PlanetType::Asteroid => {
+            let offset = seed_offset(uniforms.seed);
+            Some(noise(p.x + offset.x, p.y + offset.y))
+        }
+        _ => None,
+    }
+}
+
+// Normal del fragmento inclinada por la pendiente del relieve. La pendiente se mide en dos
+// tangentes del espacio del modelo (una base fija para cada punto del cuerpo, como en
+// displace_asteroid), que luego giran con la matriz del modelo: el relieve gira con el
+// cuerpo en vez de deslizarse sobre él
+fn bump_normal(fragment: &Fragment, uniforms: &DrawUniforms) -> Vec3 {
+    let zoom = uniforms.params.zoom.abs();
+    if uniforms.bump_strength <= 0.0 || zoom < f32::EPSILON {
+        return fragment.normal;
+    }
+    let position = fragment.vertex_position;
+    let Some(height) = bump_height(&position, uniforms) else {
+        return fragment.normal;
+    };
+    let Some(direction) = position.try_normalize(f32::EPSILON) else {
+        return fragment.normal;
+    };
+
+    let helper = if direction.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = direction.cross(&helper).normalize();
+    let bitangent = direction.cross(&tangent);
+    let step = BUMP_STEP / zoom;
+    let slope = |axis: &Vec3| {
+        let next = bump_height(&(position + axis * step), uniforms).unwrap_or(height);
+        (next - height) * BUMP_RELIEF / BUMP_STEP
+    };
+
+    let model = mat4_to_mat3(&uniforms.model_matrix);
+    let world_tangent = (model * tangent).try_normalize(f32::EPSILON).unwrap_or(tangent);
+    let world_bitangent = (model * bitangent).try_normalize(f32::EPSILON).unwrap_or(bitangent);
+    let gradient = world_tangent * slope(&tangent) + world_bitangent * slope(&bitangent);
+    (fragment.normal - gradient * uniforms.bump_strength)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(fragment.normal)
+}
+
+// El sol brilla por sí mismo, y la estela y la nave no usan la luz
+fn receives_point_lights(shader: PlanetType) -> bool {
+    !matches!(shader, PlanetType::Sun | PlanetType::Trail | PlanetType::Spaceship)
+}
+
+// Color de la superficie con la intensidad de luz que trae el fragmento
+fn surface_color(fragment: &Fragment, uniforms: &DrawUniforms) -> Color {
+    // Parámetros ajustables del shader para este tipo de cuerpo
+    let params = &uniforms.params;
+
+    match uniforms.shader {
+        PlanetType::Sun => sun_shader(fragment, uniforms, params),
+        PlanetType::RockyPlanet => rocky_planet_shader(fragment, uniforms, params),
         PlanetType::Earth => {
-            let earth_color = earth_shader(fragment, uniforms);
-            let cloud_color = cloud_shader(fragment, uniforms);
+            let earth_color = earth_shader(fragment, uniforms, params);
+            let cloud_color = cloud_shader(fragment, uniforms, params);
             blend_layers(earth_color, cloud_color)
         },
-        PlanetType::CrystalPlanet => crystal_planet_shader(fragment, uniforms),
-        PlanetType::FirePlanet => fire_planet_shader(fragment, uniforms),
-        PlanetType::WaterPlanet => water_planet_shader(fragment, uniforms),
-        PlanetType::CloudPlanet => cloud_planet_shader(fragment, uniforms),
-        PlanetType::Moon => moon_shader(fragment, uniforms),
-        PlanetType::Asteroid => asteroid_shader(fragment, uniforms),
+        PlanetType::CrystalPlanet => crystal_planet_shader(fragment, uniforms, params),
+        PlanetType::FirePlanet => fire_planet_shader(fragment, uniforms, params),
+        PlanetType::WaterPlanet => water_planet_shader(fragment, uniforms, params),
+        PlanetType::CloudPlanet => cloud_planet_shader(fragment, uniforms, params),
+        PlanetType::Moon => moon_shader(fragment, uniforms, params),
+        PlanetType::Asteroid => asteroid_shader(fragment, uniforms, params),
         PlanetType::Trail => {
             let base_color = Color::new(100, 100, 255); // Color base para la estela (puedes personalizar)
             let trail_effect = calculate_trail_effect(fragment, uniforms); // Efecto dinámico
             blend_layers(base_color, trail_effect)
         },
         PlanetType::Spaceship => {
-            // Color o shader específico para la nave
-            Color::new(192, 192, 192) // Color gris para la nave
+            // El color base del material de cada parte (del MTL), o gris si el modelo no trae
+            uniforms.base_color.unwrap_or(Color::new(192, 192, 192))
         }
-        _ => Color::new(0, 0, 0),
     }
 }
 
 // Implementación de la función de cálculo para la estela
-fn calculate_trail_effect(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn calculate_trail_effect(fragment: &Fragment, uniforms: &DrawUniforms) -> Color {
     // Un ejemplo de cálculo para un efecto dinámico de estela
-    let intensity = (fragment.position.y * (uniforms.time as f32).sin()).abs(); // Variación con el tiempo
+    let intensity = (fragment.position.y * (uniforms.frame.time as f32).sin()).abs(); // Variación con el tiempo
     Color::new(
         (intensity * 255.0) as u8,
         (intensity * 100.0) as u8,
@@ -92,18 +296,18 @@ fn blend_layers(base_color: Color, overlay_color: Color) -> Color {
     base_color.lerp(&overlay_color, 0.5) // Mezcla 50% de cada color
 }
 
-fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn cloud_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
   let zoom = 100.0;  // to move our values 
   let ox = 100.0; // offset x in the noise map
   let oy = 100.0;
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
-  let t = uniforms.time as f32 * 0.5;
+  let t = uniforms.frame.time as f32 * 0.5;
 
   let noise_value = uniforms.noise.get_noise_2d(x * zoom + ox + t, y * zoom + oy);
 
   // Define cloud threshold and colors
-  let cloud_threshold = 0.5; // Adjust this value to change cloud density
+  let cloud_threshold = params.cloud_threshold; // Adjust this value to change cloud density
   let cloud_color = Color::new(255, 255, 255); // White for clouds
   let sky_color = Color::new(30, 97, 145); // Sky blue
 
@@ -117,8 +321,8 @@ fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   noise_color * fragment.intensity
 }
 
-fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let zoom = 30.0;
+fn rocky_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+  let zoom = params.zoom;
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
@@ -135,24 +339,23 @@ fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let brown_rock_color = Color::new(140, 120, 60);    // Marrón terracota
   let rust_rock_color = Color::new(120, 80, 40);      // Rojo oxidado
   let rocky_surface_color = Color::new(90, 70, 50);   // Superficie rocoso oscura
-  let crater_color = Color::new(50, 50, 50);         // Color oscuro para los cráteres
 
   // Mezcla de colores según el ruido de las formaciones rocosas
-  let base_color = if noise_value > 0.5 {
+  let base_color = if noise_value > params.threshold {
       lighter_rock_color
   } else {
       base_rock_color
   };
 
   // Crear una mezcla de colores más variados para las capas
-  let detailed_color = if medium_noise_value > 0.5 {
+  let detailed_color = if medium_noise_value > params.threshold {
       brown_rock_color
   } else {
       dark_rock_color
   };
 
   // Mezclar con el color de oxidación para crear texturas rocosas
-  let oxide_layer_color = if very_small_noise_value > 0.5 {
+  let oxide_layer_color = if very_small_noise_value > params.threshold {
       rust_rock_color
   } else {
       rocky_surface_color
@@ -162,10 +365,9 @@ fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let texture_color = base_color.lerp(&detailed_color, small_noise_value.abs());
 
   // Añadir detalles de óxido o desgaste en la superficie
-  let final_color = texture_color.lerp(&oxide_layer_color, small_noise_value.abs());
+  let _final_color = texture_color.lerp(&oxide_layer_color, small_noise_value.abs());
 
   // Crear un efecto de capas para simular formaciones rocosas más grandes
-  let layer_effect = (noise_value * 0.5 + small_noise_value * 0.5).clamp(0.0, 1.0);
   let layered_color = detailed_color;
 
   // Crear un efecto de textura punteada o rugosa con ruido de alta frecuencia
@@ -187,10 +389,10 @@ fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
 
   // Ajustar la intensidad final de la textura
-  illuminated_color * fragment.intensity * 1.95 // Reducir un poco la intensidad general para un acabado más equilibrado
+  illuminated_color * fragment.intensity * params.brightness // Reducir un poco la intensidad general para un acabado más equilibrado
 }
 
-fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn sun_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
   // Base colors for the lava effect
   let bright_color = Color::new(255, 240, 0); // yellow
   let dark_color = Color::new(211, 84, 0);   //Burnt orange
@@ -202,16 +404,16 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     fragment.depth
   );
 
-  // Base frequency and amplitude for the pulsating effect
-  let base_frequency = 0.2;
-  let pulsate_amplitude = 0.5;
-  let t = uniforms.time as f32 * 0.01;
+  // Base frequency and amplitude for the pulsating effect (from the scene's star settings)
+  let base_frequency = uniforms.frame.star.pulse_frequency;
+  let pulsate_amplitude = uniforms.frame.star.pulse_amplitude;
+  let t = uniforms.frame.time as f32 * 0.01;
 
   // Pulsate on the z-axis to change spot size
   let pulsate = (t * base_frequency).sin() * pulsate_amplitude;
 
   // Apply noise to coordinates with subtle pulsating on z-axis
-  let zoom = 1000.0; // Constant zoom factor
+  let zoom = params.zoom; // Constant zoom factor
   let noise_value1 = uniforms.noise.get_noise_3d(
     position.x * zoom,
     position.y * zoom,
@@ -227,11 +429,11 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   // Use lerp for color blending based on noise value
   let color = dark_color.lerp(&bright_color, noise_value);
 
-  color * fragment.intensity
+  color * fragment.intensity * params.brightness
 }
 
-fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 100.0;
+fn moon_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom;
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
 
@@ -241,21 +443,21 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let crater_color = Color::new(150, 150, 150); // Color más oscuro para los cráteres
 
     // Mezclar colores según el ruido
-    let final_color = if noise_value > 0.5 {
+    let final_color = if noise_value > params.threshold {
         crater_color
     } else {
         moon_color
     };
 
     // Simular rotación de la luna
-    let rotation_effect = (uniforms.time as f32 * 0.1).sin() * 0.1;
+    let rotation_effect = (uniforms.frame.time as f32 * 0.1).sin() * params.blend;
     let rotated_color = final_color.lerp(&Color::new(255, 255, 255), rotation_effect);
 
-    rotated_color * fragment.intensity
+    rotated_color * fragment.intensity * params.brightness
 }
 
-fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 30.0; // Zoom para la textura de la Tierra
+fn earth_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom; // Zoom para la textura de la Tierra
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
 
@@ -269,34 +471,34 @@ fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let island_color = Color::new(0, 255, 0); // Color verde brillante para la isla
 
     // Mezclar colores según el ruido para simular tierra y agua
-    let base_color = if noise_value > 0.5 {
+    let base_color = if noise_value > params.threshold {
         land_color
     } else {
         water_color
     };
 
     // Determinar si hay una isla o continente adicional
-    let island_effect = if land_noise > 0.5 {
+    let island_effect = if land_noise > params.threshold {
         island_color // Si el ruido es alto, usar el color de la isla
     } else {
         Color::new(0, 0, 0) // Sin isla
     };
 
     // Aplicar el shader de nubes
-    let cloud_color = cloud_shader(fragment, uniforms);
+    let cloud_color = cloud_shader(fragment, uniforms, params);
 
     // Mezclar el color base con el color de las nubes y la isla
-    let final_color = base_color.lerp(&cloud_color, 0.5).lerp(&island_effect, 0.5); // Mezcla 50% de nubes y 50% de isla
+    let final_color = base_color.lerp(&cloud_color, params.blend).lerp(&island_effect, params.blend); // Mezcla 50% de nubes y 50% de isla
 
-    final_color * fragment.intensity
+    final_color * fragment.intensity * params.brightness
 }
 
 
-fn cloud_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 50.0; // Controla la escala del ruido
+fn cloud_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom; // Controla la escala del ruido
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
-    let t = uniforms.time as f32 * 0.5; // Tiempo para animar las nubes
+    let t = uniforms.frame.time as f32 * 0.5; // Tiempo para animar las nubes
 
     // Generar múltiples capas de ruido para simular nubes
     let noise_value1 = uniforms.noise.get_noise_2d(x * zoom + t, y * zoom + t);
@@ -309,9 +511,9 @@ fn cloud_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let cloud_shadow_color = Color::new(200, 200, 200); // Sombra de nubes
 
     // Definir umbrales para determinar la densidad de las nubes
-    let cloud_threshold1 = 0.4; // Umbral para la primera capa de nubes
-    let cloud_threshold2 = 0.6; // Umbral para la segunda capa de nubes
-    let cloud_threshold3 = 0.8; // Umbral para la tercera capa de nubes
+    let cloud_threshold1 = params.cloud_threshold; // Umbral para la primera capa de nubes
+    let cloud_threshold2 = (params.cloud_threshold + 0.2).min(0.99); // Umbral para la segunda capa de nubes
+    let cloud_threshold3 = (params.cloud_threshold + 0.4).min(0.99); // Umbral para la tercera capa de nubes
 
     // Determinar el color de las nubes basado en el ruido
     let mut noise_color = sky_color; // Comenzar con el color del cielo
@@ -327,89 +529,90 @@ fn cloud_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     }
 
     // Ajustar la intensidad del color final
-    noise_color * fragment.intensity
+    noise_color * fragment.intensity * params.brightness
 }
 
-fn crystal_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 30.0;
+fn crystal_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom;
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
 
     // Generar ruido para simular cristales brillantes
-    let noise_value = uniforms.noise.get_noise_2d(x * zoom + uniforms.time as f32 * 0.2, y * zoom);
+    let noise_value = uniforms.noise.get_noise_2d(x * zoom + uniforms.frame.time as f32 * 0.2, y * zoom);
     
     // Colores base para los cristales
     let crystal_color1 = Color::new(0, 255, 255); // Cian
     let crystal_color2 = Color::new(255, 0, 255); // Magenta
 
-    // Interpolación suave para simular el brillo de los cristales
-    let color = crystal_color1.lerp(&crystal_color2, noise_value);
+    // Interpolación suave para simular el brillo de los cristales; en HSV el paso de cian a
+    // magenta pasa por el azul en lugar de un gris apagado
+    let color = crystal_color1.lerp_hsv(&crystal_color2, noise_value);
 
     // Aumentar el brillo
     let bright_color = color * 1.5; // Aumentar el brillo
 
     // Ajustar la intensidad del color final
-    bright_color * fragment.intensity * 2.8 // Reducir la intensidad para un efecto más sutil
+    bright_color * fragment.intensity * params.brightness // Reducir la intensidad para un efecto más sutil
 }
 
-fn fire_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 80.0;
+fn fire_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom;
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
 
     // Generar ruido para simular fuego con movimiento
-    let noise_value = uniforms.noise.get_noise_2d(x * zoom + uniforms.time as f32 * 0.5, y * zoom);
+    let noise_value = uniforms.noise.get_noise_2d(x * zoom + uniforms.frame.time as f32 * 0.5, y * zoom);
     
     // Colores base para el fuego
     let fire_color1 = Color::new(255, 140, 0); // Naranja
     let fire_color2 = Color::new(255, 0, 0);   // Rojo
 
-    // Interpolación suave para simular el movimiento del fuego
-    let color = fire_color1.lerp(&fire_color2, noise_value);
+    // Interpolación suave para simular el movimiento del fuego (en HSV, sin perder saturación)
+    let color = fire_color1.lerp_hsv(&fire_color2, noise_value);
 
     // Aplicar el shader de franjas como capa extra
     let stripe_color = striped_planet_shader(fragment, uniforms);
     
     // Ajustar la opacidad del shader de franjas
-    let opacity = 0.5; // Ajustar la opacidad según sea necesario
+    let opacity = params.blend; // Ajustar la opacidad según sea necesario
     let final_color = color.lerp(&stripe_color, opacity);
 
     // Ajustar la intensidad del color final
-    final_color * fragment.intensity
+    final_color * fragment.intensity * params.brightness
 }
 
-fn water_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let zoom = 40.0;
+fn water_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+  let zoom = params.zoom;
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
   // Crear un seed basado en la posición
   let seed = (x * 1000.0 + y * 1000.0) as u64; 
-  let mut rng = StdRng::seed_from_u64(seed);
+  let mut rng = StdRng::seed_from_u64(seed ^ uniforms.frame.seed);
   let random_offset = rng.gen_range(0.0..=1.0); // Generar un desplazamiento aleatorio
 
   // Generar ruido para simular agua con movimiento
-  let noise_value = uniforms.noise.get_noise_2d(x * zoom + random_offset, y * zoom + (uniforms.time as f32 * 0.1).sin());
+  let noise_value = uniforms.noise.get_noise_2d(x * zoom + random_offset, y * zoom + (uniforms.frame.time as f32 * 0.1).sin());
 
   // Generar ondas con mayor amplitud utilizando una función seno controlada
-  let wave_effect = ((x + uniforms.time as f32 * 0.1).sin() * 0.5 + (y + uniforms.time as f32 * 0.1).cos() * 0.5).sin() * 1.0; // Aumentar la amplitud de la ola
+  let wave_effect = ((x + uniforms.frame.time as f32 * 0.1).sin() * 0.5 + (y + uniforms.frame.time as f32 * 0.1).cos() * 0.5).sin() * 1.0; // Aumentar la amplitud de la ola
 
   // Colores base para el agua, con un celeste más saturado y profundo
   let water_color1 = Color::new(0, 0, 255);     // Azul profundo
   let water_color2 = Color::new(0, 150, 255);   // Celeste intenso
 
   // Interpolación suave para simular el movimiento del agua
-  let wave_intensity = wave_effect * noise_value.abs() * 1.5; // Aumentar la intensidad de la ola
+  let wave_intensity = wave_effect * noise_value.abs() * params.blend; // Aumentar la intensidad de la ola
 
   // Lerp entre los dos colores usando la intensidad de la ola
   let color = water_color1.lerp(&water_color2, wave_intensity);
 
   // Ajustar la intensidad del color final
-  color * fragment.intensity * 0.9 // Aumentar ligeramente la intensidad para resaltar más el celeste
+  color * fragment.intensity * params.brightness // Aumentar ligeramente la intensidad para resaltar más el celeste
 }
 
 
-fn striped_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn striped_planet_shader(fragment: &Fragment, uniforms: &DrawUniforms) -> Color {
     let zoom = 10.0; // Controla la frecuencia de las franjas
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
@@ -419,7 +622,7 @@ fn striped_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let stripe_color3 = Color::new(255, 50, 0);  // Naranja rojizo
 
     // Crear un patrón de franjas utilizando una función seno
-    let stripe_pattern = (y * zoom + uniforms.time as f32 * 0.1).sin() + (x * zoom * 0.5).sin(); // Movimiento más lento
+    let stripe_pattern = (y * zoom + uniforms.frame.time as f32 * 0.1).sin() + (x * zoom * 0.5).sin(); // Movimiento más lento
 
     // Determinar el color basado en el patrón de franjas
     let color = if stripe_pattern > 0.0 {
@@ -433,10 +636,13 @@ fn striped_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     color * opacity * fragment.intensity
 }
 
-pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let zoom = 20.0; // Controla la escala del ruido
-    let x = fragment.vertex_position.x;
-    let y = fragment.vertex_position.y;
+/// Roca agrietada con vetas de lava, distinta para cada asteroide según su semilla.
+pub fn asteroid_shader(fragment: &Fragment, uniforms: &DrawUniforms, params: &ShaderParams) -> Color {
+    let zoom = params.zoom; // Controla la escala del ruido
+    // Cada asteroide lee su propia zona del ruido: grietas y lava en otro lugar
+    let offset = seed_offset(uniforms.seed);
+    let x = fragment.vertex_position.x + offset.x;
+    let y = fragment.vertex_position.y + offset.y;
 
     // Generar múltiples capas de ruido para textura detallada
     let base_noise = uniforms.noise.get_noise_2d(x * zoom, y * zoom); // Ruido base
@@ -453,18 +659,18 @@ pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let lava_color2 = Color::new(255, 50, 0);  // Color de lava (más oscuro)
 
     // Mezcla de colores según el ruido
-    let color_variation = if base_noise > 0.5 {
+    let color_variation = if base_noise > params.threshold {
         lighter_color.lerp(&base_color, small_noise.abs())
     } else {
         dark_color.lerp(&rust_color, medium_noise.abs())
     };
 
     // Efecto de lava dinámico
-    let time = uniforms.time as f32 * 0.5; // Controlar la velocidad de pulsación
+    let time = uniforms.frame.time as f32 * 0.5; // Controlar la velocidad de pulsación
     let blend_factor = time.sin() * 0.5 + 0.5; // Oscilar entre 0 y 1
 
     // Determinar si hay lava en la superficie
-    let lava_effect = if lava_noise > 0.5 {
+    let lava_effect = if lava_noise > params.threshold {
         lava_color1.lerp(&lava_color2, blend_factor) // Mezclar colores de lava
     } else {
         Color::new(0, 0, 0) // Sin lava
@@ -474,5 +680,5 @@ pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let final_color = color_variation.lerp(&lava_effect, lava_noise.abs());
 
     // Ajustar la intensidad del color final
-    final_color * fragment.intensity
+    final_color * fragment.intensity * params.brightness
 }
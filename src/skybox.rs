@@ -1,49 +1,520 @@
-use nalgebra_glm::{Vec3, Vec4};
+use nalgebra_glm::{Mat4, Vec3, Vec4};
 use rand::prelude::*;
 use std::f32::consts::PI;
-use crate::{Framebuffer, Uniforms};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::{clip_segment_near, clip_to_screen};
+use crate::uniforms::FrameUniforms;
+use crate::color::Color;
+use crate::constellations::{self, Constellation};
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::texture::{Texture, TextureError};
+use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
 
+/// One star of the procedural field: where it is, how bright and what color, and how it twinkles.
 pub struct Star {
     position: Vec3,
     brightness: f32,
+    color: Color,
+    twinkle_phase: f32,
+    twinkle_speed: f32,
 }
 
+/// Everything drawn behind the bodies: a cubemap if one was loaded, otherwise the procedural
+/// stars over the milky way band and the nebula, plus the constellation lines.
 pub struct Skybox {
     stars: Vec<Star>,
+    cubemap: Option<Cubemap>,
+    nebula: Option<Nebula>,
+    milky_way: Option<Nebula>,
+    constellations: Vec<Constellation>,
+    pub show_constellations: bool,
+    pub mode: SkyMode,
+    config: SkyboxConfig,
+}
+
+/// How the star field moves with the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkyMode {
+    // Stars sit on a sphere centered on the eye: they rotate with the view but ignore
+    // the camera's translation, as if infinitely far away
+    Infinite,
+    // Stars stay at fixed world positions on a sphere around the origin, so moving the
+    // camera shifts them (parallax). The Milky Way, nebula, cubemap and constellations
+    // stay at infinity in both modes
+    WorldFixed,
+}
+
+impl SkyMode {
+    /// The other mode.
+    pub fn toggled(self) -> Self {
+        match self {
+            SkyMode::Infinite => SkyMode::WorldFixed,
+            SkyMode::WorldFixed => SkyMode::Infinite,
+        }
+    }
+
+    /// Name shown on the HUD.
+    pub fn name(self) -> &'static str {
+        match self {
+            SkyMode::Infinite => "infinito",
+            SkyMode::WorldFixed => "fijo al mundo",
+        }
+    }
+}
+
+/// Parameters of the procedural sky; generation is deterministic for a given config.
+#[derive(Debug, Clone)]
+pub struct SkyboxConfig {
+    pub star_count: usize,
+    pub seed: u64,
+    pub min_brightness: f32, // brightness of the faintest stars (0..1)
+    pub band_strength: f32,  // 0 = uniform sky, 1 = full Milky Way band and star bias
+    pub galactic_pole: Vec3, // normal of the Milky Way's plane
+}
+
+impl Default for SkyboxConfig {
+    fn default() -> Self {
+        SkyboxConfig {
+            star_count: 1000,
+            seed: 7,
+            min_brightness: 0.25,
+            band_strength: 1.0,
+            galactic_pole: default_galactic_pole(),
+        }
+    }
+}
+
+/// Settings for the procedural nebula layer drawn behind the stars.
+#[derive(Debug, Clone)]
+pub struct NebulaConfig {
+    pub seed: i32,
+    pub palette: Vec<Color>, // two or three gradient stops
+    pub intensity: f32,
+}
+
+impl Default for NebulaConfig {
+    fn default() -> Self {
+        NebulaConfig {
+            seed: 2024,
+            palette: vec![
+                Color::new(45, 15, 80),   // deep purple
+                Color::new(25, 40, 110),  // dusk blue
+                Color::new(20, 110, 120), // teal
+            ],
+            intensity: 0.35,
+        }
+    }
+}
+
+/// Nebula or Milky Way layer baked once into a lat-long grid, sampled per pixel with bilinear filtering.
+pub struct Nebula {
+    width: usize,
+    height: usize,
+    data: Vec<Color>,
+}
+
+const NEBULA_WIDTH: usize = 512;
+const NEBULA_HEIGHT: usize = 256;
+
+fn gradient(palette: &[Color], t: f32) -> Color {
+    match palette.len() {
+        0 => Color::black(),
+        1 => palette[0],
+        n => {
+            let scaled = t.clamp(0.0, 1.0) * (n - 1) as f32;
+            let index = (scaled.floor() as usize).min(n - 2);
+            palette[index].lerp(&palette[index + 1], scaled - index as f32)
+        }
+    }
+}
+
+// Band brightness for a direction whose sine of galactic latitude is `latitude`:
+// a narrow bright plane plus a wider faint halo
+fn milky_way_profile(latitude: f32) -> f32 {
+    (-(latitude / 0.16).powi(2)).exp() * 0.8 + (-(latitude / 0.4).powi(2)).exp() * 0.2
+}
+
+const MILKY_WAY_INTENSITY: f32 = 0.45;
+
+/// Galactic north pole (RA 12h51m, Dec +27.1°) in the same sky frame as the constellations.
+pub fn default_galactic_pole() -> Vec3 {
+    constellations::equatorial(12.86, 27.13)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Unit direction for a lat-long texel center: u wraps around the Y axis, v goes from +Y to -Y
+fn lat_long_direction(u: f32, v: f32) -> Vec3 {
+    let theta = u * 2.0 * PI;
+    let phi = v * PI;
+    Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin())
+}
+
+impl Nebula {
+    /// Generate the layer `config` describes; the same config always bakes the same layer.
+    pub fn bake(config: &NebulaConfig) -> Self {
+        // FBm for the cloud shapes and a second, lower-frequency field for the color
+        let mut shape = FastNoiseLite::with_seed(config.seed);
+        shape.set_noise_type(Some(NoiseType::OpenSimplex2));
+        shape.set_fractal_type(Some(FractalType::FBm));
+        shape.set_fractal_octaves(Some(5));
+        shape.set_frequency(Some(1.2));
+
+        let mut hue = FastNoiseLite::with_seed(config.seed.wrapping_add(1));
+        hue.set_noise_type(Some(NoiseType::OpenSimplex2));
+        hue.set_frequency(Some(0.7));
+
+        Self::bake_with(|d| {
+            let density = shape.get_noise_3d(d.x, d.y, d.z) * 0.5 + 0.5;
+            let coverage = smoothstep(0.4, 0.85, density);
+            let t = hue.get_noise_3d(d.x, d.y, d.z) * 0.5 + 0.5;
+            gradient(&config.palette, t) * (coverage * config.intensity)
+        })
+    }
+
+    /// Soft glowing band along the great circle perpendicular to `galactic_pole`,
+    /// with dark dust lanes carved out of its core.
+    pub fn bake_milky_way(galactic_pole: Vec3, seed: i32, intensity: f32) -> Self {
+        let pole = galactic_pole.normalize();
+
+        let mut clouds = FastNoiseLite::with_seed(seed);
+        clouds.set_noise_type(Some(NoiseType::OpenSimplex2));
+        clouds.set_fractal_type(Some(FractalType::FBm));
+        clouds.set_fractal_octaves(Some(5));
+        clouds.set_frequency(Some(2.0));
+
+        let mut dust = FastNoiseLite::with_seed(seed.wrapping_add(1));
+        dust.set_noise_type(Some(NoiseType::OpenSimplex2));
+        dust.set_fractal_type(Some(FractalType::Ridged));
+        dust.set_fractal_octaves(Some(4));
+        dust.set_frequency(Some(3.5));
+
+        let palette = [
+            Color::new(110, 95, 80),   // faint warm halo
+            Color::new(230, 215, 190), // warm white
+            Color::new(255, 240, 215), // bright core
+        ];
+
+        Self::bake_with(|d| {
+            let latitude = d.dot(&pole);
+            let band = milky_way_profile(latitude);
+            if band < 0.002 {
+                return Color::black();
+            }
+
+            let structure = clouds.get_noise_3d(d.x, d.y, d.z) * 0.5 + 0.5;
+            let lanes = smoothstep(0.35, 0.75, dust.get_noise_3d(d.x, d.y, d.z) * 0.5 + 0.5);
+            // Dust lanes only darken the thin central plane
+            let extinction = 1.0 - 0.85 * lanes * (-(latitude / 0.07).powi(2)).exp();
+
+            let brightness = band * (0.45 + 0.55 * structure) * extinction;
+            gradient(&palette, brightness) * (brightness * intensity)
+        })
+    }
+
+    // Evaluate `shade` for every texel center. It's given the direction on the sphere, so any
+    // noise sampled from it is continuous across the longitude wrap and the layer has no seam
+    fn bake_with(mut shade: impl FnMut(Vec3) -> Color) -> Self {
+        let mut data = Vec::with_capacity(NEBULA_WIDTH * NEBULA_HEIGHT);
+        for j in 0..NEBULA_HEIGHT {
+            for i in 0..NEBULA_WIDTH {
+                data.push(shade(lat_long_direction(
+                    (i as f32 + 0.5) / NEBULA_WIDTH as f32,
+                    (j as f32 + 0.5) / NEBULA_HEIGHT as f32,
+                )));
+            }
+        }
+
+        Nebula { width: NEBULA_WIDTH, height: NEBULA_HEIGHT, data }
+    }
+
+    fn texel(&self, x: i32, y: i32) -> Color {
+        let x = x.rem_euclid(self.width as i32) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// Bilinear sample of the layer towards `direction`.
+    pub fn sample(&self, direction: Vec3) -> Color {
+        let d = direction.normalize();
+        let theta = d.z.atan2(d.x).rem_euclid(2.0 * PI);
+        let phi = d.y.clamp(-1.0, 1.0).acos();
+
+        let x = theta / (2.0 * PI) * self.width as f32 - 0.5;
+        let y = phi / PI * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+
+        let top = self.texel(x0, y0).lerp(&self.texel(x0 + 1, y0), fx);
+        let bottom = self.texel(x0, y0 + 1).lerp(&self.texel(x0 + 1, y0 + 1), fx);
+        top.lerp(&bottom, fy)
+    }
+}
+
+/// Six cube faces in the usual +X, -X, +Y, -Y, +Z, -Z order.
+pub struct Cubemap {
+    faces: [Texture; 6],
+}
+
+// Depth written by the cubemap: farther than anything else, but still depth-tested
+const CUBEMAP_DEPTH: f32 = f32::MAX;
+
+impl Cubemap {
+    /// Load the six faces, in the +X, -X, +Y, -Y, +Z, -Z order, each with its mip pyramid.
+    pub fn load(paths: [&str; 6]) -> Result<Self, TextureError> {
+        let [px, nx, py, ny, pz, nz] = paths;
+        Ok(Cubemap {
+            faces: [
+                Texture::load(px)?,
+                Texture::load(nx)?,
+                Texture::load(py)?,
+                Texture::load(ny)?,
+                Texture::load(pz)?,
+                Texture::load(nz)?,
+            ],
+        })
+    }
+
+    // Face index and (u, v) in [0, 1] for a direction (v = 0 is the top row of the image)
+    fn face_coords(direction: Vec3) -> (usize, f32, f32) {
+        let (ax, ay, az) = (direction.x.abs(), direction.y.abs(), direction.z.abs());
+        let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+            if direction.x > 0.0 { (0, -direction.z, -direction.y, ax) } else { (1, direction.z, -direction.y, ax) }
+        } else if ay >= az {
+            if direction.y > 0.0 { (2, direction.x, direction.z, ay) } else { (3, direction.x, -direction.z, ay) }
+        } else if direction.z > 0.0 {
+            (4, direction.x, -direction.y, az)
+        } else {
+            (5, -direction.x, -direction.y, az)
+        };
+        (face, (sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5)
+    }
+
+    // Inverse of face_coords; (u, v) may lie outside [0, 1] to reach into neighbouring faces
+    fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+        let sc = u * 2.0 - 1.0;
+        let tc = v * 2.0 - 1.0;
+        match face {
+            0 => Vec3::new(1.0, -tc, -sc),
+            1 => Vec3::new(-1.0, -tc, sc),
+            2 => Vec3::new(sc, 1.0, tc),
+            3 => Vec3::new(sc, -1.0, -tc),
+            4 => Vec3::new(sc, -tc, 1.0),
+            _ => Vec3::new(-sc, -tc, -1.0),
+        }
+    }
+
+    // Texel (x, y) of a face's mip level `level`; coordinates past the edge are fetched
+    // from the adjacent face so bilinear filtering blends across face boundaries without seams
+    fn texel(&self, face: usize, level: usize, x: i32, y: i32) -> Color {
+        let (width, height) = self.faces[face].level_size(level);
+        if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+            return self.faces[face].level_texel(level, x, y);
+        }
+
+        let u = (x as f32 + 0.5) / width as f32;
+        let v = (y as f32 + 0.5) / height as f32;
+        let (other, ou, ov) = Self::face_coords(Self::face_direction(face, u, v));
+        // Faces of different sizes have a different number of levels
+        let other_level = level.min(self.faces[other].mip_levels() - 1);
+        let (other_width, other_height) = self.faces[other].level_size(other_level);
+        self.faces[other].level_texel(other_level, (ou * other_width as f32) as i32, (ov * other_height as f32) as i32)
+    }
+
+    fn bilinear(&self, face: usize, level: usize, u: f32, v: f32) -> Color {
+        let (width, height) = self.faces[face].level_size(level);
+        let x = u * width as f32 - 0.5;
+        let y = v * height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+
+        let top = self.texel(face, level, x0, y0).lerp(&self.texel(face, level, x0 + 1, y0), fx);
+        let bottom = self.texel(face, level, x0, y0 + 1).lerp(&self.texel(face, level, x0 + 1, y0 + 1), fx);
+        top.lerp(&bottom, fy)
+    }
+
+    /// Trilinear sample towards `direction`, reading the mip levels of a pixel
+    /// `radians_per_pixel` wide (see Texture::sample).
+    pub fn sample(&self, direction: Vec3, radians_per_pixel: f32) -> Color {
+        let (face, u, v) = Self::face_coords(direction);
+        let level = self.faces[face].mip_level(self.footprint(face, radians_per_pixel));
+        let lower = level.floor();
+        let fine = self.bilinear(face, lower as usize, u, v);
+        if level > lower {
+            fine.lerp(&self.bilinear(face, lower as usize + 1, u, v), level - lower)
+        } else {
+            fine
+        }
+    }
+
+    // Full-size texels of `face` a pixel covers, as measured at the middle of the face,
+    // where its width spans 2 units of tangent (a right angle): texels per radian there
+    // times the pixel's angle. Towards the corners a face packs more texels per radian, so
+    // this slightly underestimates, erring on the sharp side
+    fn footprint(&self, face: usize, radians_per_pixel: f32) -> f32 {
+        let texture = &self.faces[face];
+        texture.width.max(texture.height) as f32 / 2.0 * radians_per_pixel
+    }
+}
+
+// Fraction of the star density kept far from the galactic plane (1.0 = uniform sky)
+const STAR_FIELD_DENSITY: f32 = 0.35;
+
+// Amount of brightness modulation from twinkling (kept small so it doesn't look noisy)
+const TWINKLE_AMPLITUDE: f32 = 0.08;
+
+// Tint for a normalized color temperature: 0.0 is cool blue-white, 1.0 is deep red
+fn temperature_tint(t: f32) -> Color {
+    let blue_white = Color::new(170, 195, 255);
+    let white = Color::new(255, 250, 245);
+    let yellow = Color::new(255, 225, 160);
+    let red = Color::new(255, 150, 110);
+
+    if t < 0.35 {
+        blue_white.lerp(&white, t / 0.35)
+    } else if t < 0.75 {
+        white.lerp(&yellow, (t - 0.35) / 0.4)
+    } else {
+        yellow.lerp(&red, (t - 0.75) / 0.25)
+    }
 }
 
 impl Skybox {
-    pub fn new(star_count: usize) -> Self {
-        let mut rng = rand::thread_rng();
-        let mut stars = Vec::with_capacity(star_count);
+    /// The procedural sky `config` describes, without a nebula (see `with_nebula`).
+    pub fn new(config: SkyboxConfig) -> Self {
+        let mut skybox = Skybox {
+            stars: Vec::new(),
+            cubemap: None,
+            nebula: None,
+            milky_way: None,
+            constellations: constellations::builtin(),
+            show_constellations: false,
+            mode: SkyMode::Infinite,
+            config: config.clone(),
+        };
+        skybox.regenerate(config);
+        skybox
+    }
+
+    /// The config the stars were last generated from.
+    pub fn config(&self) -> &SkyboxConfig {
+        &self.config
+    }
+
+    /// Rebuild the star field and Milky Way from `config`. The same config always
+    /// produces the same sky; the nebula, cubemap and constellations are kept.
+    pub fn regenerate(&mut self, config: SkyboxConfig) {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut stars = Vec::with_capacity(config.star_count);
+        let pole = config.galactic_pole.normalize();
+        let band_strength = config.band_strength.clamp(0.0, 1.0);
+        let min_brightness = config.min_brightness.clamp(0.0, 1.0);
 
-        for _ in 0..star_count {
-            // Generate random spherical coordinates
-            let theta = rng.gen::<f32>() * 2.0 * PI;  // Azimuth angle
-            let phi = rng.gen::<f32>() * PI;          // Polar angle
-            let radius = 100.0;  // Fixed radius for all stars
+        while stars.len() < config.star_count {
+            // Generate random spherical coordinates, uniformly distributed over the sphere
+            let theta = rng.gen::<f32>() * 2.0 * PI;             // Azimuth angle
+            let phi = (1.0 - 2.0 * rng.gen::<f32>()).acos();     // Polar angle
+            let radius = SKY_RADIUS;  // Fixed radius for all stars
 
             // Convert spherical to Cartesian coordinates
             let x = radius * phi.sin() * theta.cos();
             let y = radius * phi.sin() * theta.sin();
             let z = radius * phi.cos();
 
-            // Random brightness between 0.5 and 1.0
-            let brightness = rng.gen::<f32>() * 0.5 + 0.5;
+            // Rejection sampling: keep every star near the galactic plane, fewer far from it
+            let band = milky_way_profile(Vec3::new(x, y, z).dot(&pole) / radius) * band_strength;
+            if rng.gen::<f32>() > 1.0 - band_strength * (1.0 - STAR_FIELD_DENSITY) * (1.0 - band) {
+                continue;
+            }
+
+            // Power-law magnitude: most stars are faint, a few are very bright.
+            // Stars in the band are slightly brighter on average
+            let brightness = min_brightness + (1.0 - min_brightness) * rng.gen::<f32>().powf(4.0 - 1.5 * band);
+
+            // Hotter (bluer) and cooler (redder) stars are rarer than white/yellow ones
+            let temperature = (rng.gen::<f32>() + rng.gen::<f32>()) * 0.5;
 
             stars.push(Star {
                 position: Vec3::new(x, y, z),
                 brightness,
+                color: temperature_tint(temperature),
+                twinkle_phase: rng.gen::<f32>() * 2.0 * PI,
+                twinkle_speed: 0.02 + rng.gen::<f32>() * 0.06,
             });
         }
 
-        Skybox { stars }
+        self.stars = stars;
+        self.milky_way = (band_strength > 0.0).then(|| {
+            Nebula::bake_milky_way(pole, config.seed as i32, MILKY_WAY_INTENSITY * band_strength)
+        });
+        self.config = config;
     }
 
-    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
+    /// Replace the nebula layer (None disables it).
+    pub fn with_nebula(mut self, config: Option<NebulaConfig>) -> Self {
+        self.nebula = config.map(|c| Nebula::bake(&c));
+        self
+    }
+
+    /// Load a cubemap skybox from six images (+X, -X, +Y, -Y, +Z, -Z). The caller picks
+    /// the fallback if a face can't be loaded.
+    pub fn from_cubemap(paths: [&str; 6]) -> Result<Self, TextureError> {
+        Cubemap::load(paths).map(|cubemap| Skybox {
+            stars: Vec::new(),
+            cubemap: Some(cubemap),
+            nebula: None,
+            milky_way: None,
+            constellations: constellations::builtin(),
+            show_constellations: false,
+            mode: SkyMode::Infinite,
+            config: SkyboxConfig::default(),
+        })
+    }
+
+    /// In Infinite mode stars are placed relative to the frame's camera position (the eye the
+    /// view matrix was built from), so they only rotate with the view and never translate.
+    /// In WorldFixed mode they keep their world positions and show parallax.
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        if let Some(cubemap) = &self.cubemap {
+            // A pixel's angle at the middle of the view: the projection's [1][1] is
+            // 1 / tan(fov / 2), and the screen's height spans twice that tangent
+            let radians_per_pixel = 2.0 / (uniforms.projection_matrix[(1, 1)] * framebuffer.height.max(1) as f32);
+            for_each_view_ray(framebuffer, uniforms, |framebuffer, x, y, direction| {
+                framebuffer.set_current_color(cubemap.sample(direction, radians_per_pixel).to_hex());
+                framebuffer.point(x, y, CUBEMAP_DEPTH);
+            });
+        } else {
+            self.render_procedural(framebuffer, uniforms);
+        }
+
+        if self.show_constellations {
+            self.render_constellations(framebuffer, uniforms);
+        }
+    }
+
+    fn render_procedural(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        // The Milky Way and nebula are written without depth so the stars and everything else draw over them.
+        // They're added on top of the cleared background (solid or gradient) rather than replacing it
+        if self.nebula.is_some() || self.milky_way.is_some() {
+            for_each_view_ray(framebuffer, uniforms, |framebuffer, x, y, direction| {
+                let mut color = Color::from_hex(framebuffer.buffer[y * framebuffer.width + x]);
+                for layer in self.milky_way.iter().chain(&self.nebula) {
+                    color += layer.sample(direction);
+                }
+                framebuffer.set_pixel(x, y, color.to_hex_dithered(x, y));
+            });
+        }
+
         for star in &self.stars {
-            // Calculate star position relative to camera
-            let position = star.position + camera_position;
+            let position = match self.mode {
+                SkyMode::Infinite => star.position + uniforms.camera_position,
+                SkyMode::WorldFixed => star.position,
+            };
             
             // Project the star position to screen space
             let pos_vec4 = Vec4::new(position.x, position.y, position.z, 1.0);
@@ -58,18 +529,225 @@ impl Skybox {
             
             // Check if star is in front of camera and within screen bounds
             if screen_pos.z < 0.0 { continue; }
+            if screen_pos.x < 0.0 || screen_pos.y < 0.0 { continue; }
             
             let x = screen_pos.x as usize;
             let y = screen_pos.y as usize;
             
             if x < framebuffer.width && y < framebuffer.height {
-                // Calculate star color based on brightness
-                let intensity = (star.brightness * 255.0) as u8;
-                let color = (intensity as u32) << 16 | (intensity as u32) << 8 | intensity as u32;
-                
-                framebuffer.set_current_color(color);
+                // Subtle twinkle around the star's base brightness
+                let twinkle = (uniforms.time as f32 * star.twinkle_speed + star.twinkle_phase).sin();
+                let brightness = (star.brightness * (1.0 + TWINKLE_AMPLITUDE * twinkle)).clamp(0.0, 1.0);
+                let color = star.color * brightness;
+
+                framebuffer.set_current_color(color.to_hex());
                 framebuffer.point(x, y, 100.0);
+
+                // Bright stars become small crosses, the brightest ones small discs
+                if star.brightness > 0.6 {
+                    let arm = (color * 0.5).to_hex();
+                    framebuffer.set_current_color(arm);
+                    framebuffer.point(x + 1, y, 100.0);
+                    framebuffer.point(x, y + 1, 100.0);
+                    if x > 0 { framebuffer.point(x - 1, y, 100.0); }
+                    if y > 0 { framebuffer.point(x, y - 1, 100.0); }
+                }
+                if star.brightness > 0.85 {
+                    let corner = (color * 0.25).to_hex();
+                    framebuffer.set_current_color(corner);
+                    framebuffer.point(x + 1, y + 1, 100.0);
+                    if x > 0 { framebuffer.point(x - 1, y + 1, 100.0); }
+                    if y > 0 { framebuffer.point(x + 1, y - 1, 100.0); }
+                    if x > 0 && y > 0 { framebuffer.point(x - 1, y - 1, 100.0); }
+                }
             }
         }
     }
+
+    // Constellation figures as dim lines on the sky sphere. Segments are split along the
+    // great circle so long ones follow the sphere instead of cutting across it
+    fn render_constellations(&self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms) {
+        let sky = SkyProjection::new(uniforms);
+
+        // The third row of the view matrix is the camera's backward axis
+        let view = &uniforms.view_matrix;
+        let forward = -Vec3::new(view[(2, 0)], view[(2, 1)], view[(2, 2)]);
+
+        for constellation in &self.constellations {
+            for &(i, j) in &constellation.lines {
+                let (a, b) = (constellation.stars[i].direction, constellation.stars[j].direction);
+                let mut previous = a;
+                for step in 1..=CONSTELLATION_SUBDIVISIONS {
+                    let t = step as f32 / CONSTELLATION_SUBDIVISIONS as f32;
+                    let current = a.lerp(&b, t).normalize();
+                    sky.line(framebuffer, previous, current, CONSTELLATION_COLOR, CONSTELLATION_ALPHA);
+                    previous = current;
+                }
+            }
+
+            // Names only for figures the camera is roughly looking at
+            let facing = forward.dot(&constellation.centroid());
+            if facing < LABEL_COS_ANGLE {
+                continue;
+            }
+            draw_sky_label(framebuffer, uniforms, sky.to_clip(constellation.centroid()), constellation.name, CONSTELLATION_LABEL_COLOR);
+            if facing >= STAR_LABEL_COS_ANGLE {
+                for star in &constellation.stars {
+                    draw_sky_label(framebuffer, uniforms, sky.to_clip(star.direction), star.name, STAR_LABEL_COLOR);
+                }
+            }
+        }
+    }
+}
+
+const SKY_RADIUS: f32 = 100.0;
+// Depth of lines drawn on the sky: behind all geometry, over the nebula/cubemap, under the stars
+const SKY_OVERLAY_DEPTH: f32 = 1000.0;
+const CONSTELLATION_SUBDIVISIONS: usize = 8;
+const CONSTELLATION_COLOR: u32 = 0x6080C0;
+const CONSTELLATION_ALPHA: f32 = 0.45;
+const CONSTELLATION_LABEL_COLOR: u32 = 0x7890C0;
+const STAR_LABEL_COLOR: u32 = 0x506080;
+// Constellation names appear within ~35 degrees of the view direction, star names within ~15
+const LABEL_COS_ANGLE: f32 = 0.82;
+const STAR_LABEL_COS_ANGLE: f32 = 0.97;
+
+/// Projects directions on the sky sphere the way the stars are placed: relative to the
+/// camera, so they rotate with the view but never translate.
+pub struct SkyProjection<'a> {
+    uniforms: &'a FrameUniforms,
+    view_projection: Mat4,
+}
+
+impl<'a> SkyProjection<'a> {
+    /// The projection for the frame `uniforms` describes.
+    pub fn new(uniforms: &'a FrameUniforms) -> Self {
+        SkyProjection {
+            uniforms,
+            view_projection: uniforms.projection_matrix * uniforms.view_matrix,
+        }
+    }
+
+    /// Clip-space position of the sky point towards `direction`.
+    pub fn to_clip(&self, direction: Vec3) -> Vec4 {
+        let p = self.uniforms.camera_position + direction * SKY_RADIUS;
+        self.view_projection * Vec4::new(p.x, p.y, p.z, 1.0)
+    }
+
+    /// Anti-aliased segment between two nearby sky directions, clipped against the near
+    /// plane so parts behind the camera never wrap onto the screen.
+    pub fn line(&self, framebuffer: &mut Framebuffer, a: Vec3, b: Vec3, color: u32, alpha: f32) {
+        let Some((start, end)) = clip_segment_near(self.to_clip(a), self.to_clip(b)) else {
+            return;
+        };
+        let to_screen = |clip: Vec4| {
+            let screen = clip_to_screen(self.uniforms, clip);
+            Vec3::new(screen.x, screen.y, SKY_OVERLAY_DEPTH)
+        };
+        framebuffer.line_aa(to_screen(start), to_screen(end), color, alpha);
+    }
+}
+
+// Text centered on a clip-space point, skipped if the point is behind the camera or off-screen
+fn draw_sky_label(framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, clip: Vec4, text: &str, color: u32) {
+    if clip.w <= 0.0 || clip.z < -clip.w {
+        return;
+    }
+    let screen = clip_to_screen(uniforms, clip);
+    let x = screen.x - text_width(text, 1) as f32 * 0.5;
+    let y = screen.y - GLYPH_HEIGHT as f32 * 0.5;
+    if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32 {
+        return;
+    }
+    draw_text(framebuffer, x as usize, y as usize, text, color, 1);
+}
+
+// For each pixel, reconstruct the world-space view ray and pass it to `shade`.
+// Far-plane points are an affine function of NDC, so the ray is exact per pixel
+fn for_each_view_ray(
+    framebuffer: &mut Framebuffer,
+    uniforms: &FrameUniforms,
+    mut shade: impl FnMut(&mut Framebuffer, usize, usize, Vec3),
+) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let (Some(inverse), Some(inverse_view)) = (view_projection.try_inverse(), uniforms.view_matrix.try_inverse()) else {
+        return;
+    };
+
+    let eye = inverse_view * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let far_point = |nx: f32, ny: f32| {
+        let p = inverse * Vec4::new(nx, ny, 1.0, 1.0);
+        Vec3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+    };
+    let origin = far_point(0.0, 0.0);
+    let step_x = far_point(1.0, 0.0) - origin;
+    let step_y = far_point(0.0, 1.0) - origin;
+    let base = origin - Vec3::new(eye.x, eye.y, eye.z);
+
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    for y in 0..framebuffer.height {
+        // The viewport flips Y: screen row 0 is NDC +1
+        let ny = 1.0 - (y as f32 + 0.5) / height * 2.0;
+        for x in 0..framebuffer.width {
+            let nx = (x as f32 + 0.5) / width * 2.0 - 1.0;
+            shade(framebuffer, x, y, base + step_x * nx + step_y * ny);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lights::PointLights;
+    use crate::shader_params::StarParams;
+    use crate::shaders::Shading;
+    use crate::transform::{create_perspective_matrix, create_view_matrix, create_viewport_matrix};
+
+    fn frame_uniforms(eye: Vec3, width: usize, height: usize) -> FrameUniforms {
+        let forward = Vec3::new(0.3, -0.1, 1.0);
+        FrameUniforms {
+            view_matrix: create_view_matrix(eye, eye + forward, Vec3::new(0.0, 1.0, 0.0)),
+            projection_matrix: create_perspective_matrix(width as f32, height as f32),
+            viewport_matrix: create_viewport_matrix(width as f32, height as f32),
+            time: 0,
+            camera_position: eye,
+            sun_position: Vec3::zeros(),
+            exposure: 1.0,
+            dither: false,
+            shading: Shading::Phong,
+            seed: 0,
+            star: StarParams::default(),
+            light_intensity: 1.0,
+            override_light: None,
+            lights: PointLights::default(),
+            log_depth: false,
+        }
+    }
+
+    // Stars only: no Milky Way or nebula, which never move with the camera anyway
+    fn render_stars(mode: SkyMode, eye: Vec3) -> Vec<u32> {
+        let mut skybox = Skybox::new(SkyboxConfig { star_count: 2000, band_strength: 0.0, ..Default::default() });
+        skybox.mode = mode;
+        let (width, height) = (160, 120);
+        let mut framebuffer = Framebuffer::new(width, height);
+        skybox.render(&mut framebuffer, &frame_uniforms(eye, width, height));
+        framebuffer.buffer
+    }
+
+    #[test]
+    fn infinite_stars_ignore_camera_translation() {
+        let home = render_stars(SkyMode::Infinite, Vec3::zeros());
+        assert!(home.iter().any(|&pixel| pixel != 0));
+        for eye in [Vec3::new(40.0, 0.0, 0.0), Vec3::new(-12.5, 30.0, 75.0), Vec3::new(0.0, 0.0, -90.0)] {
+            assert!(home == render_stars(SkyMode::Infinite, eye), "stars moved with the eye at {:?}", eye);
+        }
+    }
+
+    #[test]
+    fn world_fixed_stars_show_parallax() {
+        let home = render_stars(SkyMode::WorldFixed, Vec3::zeros());
+        assert!(home == render_stars(SkyMode::Infinite, Vec3::zeros()));
+        assert!(home != render_stars(SkyMode::WorldFixed, Vec3::new(40.0, 0.0, 0.0)));
+    }
 }
@@ -0,0 +1,117 @@
+use nalgebra_glm::{Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::uniforms::FrameUniforms;
+
+// World units in front of opaque geometry over which trail particles fade out by default
+pub const DEFAULT_PARTICLE_SOFTNESS: f32 = 0.3;
+
+// A camera-facing quad with a radial soft falloff, used for trail particles
+pub struct Billboard {
+    pub center: Vec3,
+    pub half_size: f32,
+    pub color: u32,
+    pub alpha: f32,
+    pub softness: f32, // view distance in front of opaque geometry over which it fades out; 0 = hard edge
+}
+
+// Project a world-space point to screen space (x, y in pixels, z as stored in the depth buffer)
+pub fn project(uniforms: &FrameUniforms, point: Vec3) -> Option<Vec3> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = clip / clip.w;
+    let screen = uniforms.viewport_matrix * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+    Some(Vec3::new(screen.x, screen.y, uniforms.depth(clip.w, screen.z)))
+}
+
+pub fn render_billboard(
+    framebuffer: &mut Framebuffer,
+    uniforms: &FrameUniforms,
+    billboard: &Billboard,
+    right: Vec3,
+    up: Vec3,
+) {
+    let r = right * billboard.half_size;
+    let u = up * billboard.half_size;
+    let corners = [
+        billboard.center - r - u,
+        billboard.center + r - u,
+        billboard.center + r + u,
+        billboard.center - r + u,
+    ];
+
+    // The quad lies in a plane parallel to the image plane, so it projects to an
+    // axis-aligned rectangle with constant depth and perspective size attenuation
+    let mut projected = [Vec3::zeros(); 4];
+    for (i, corner) in corners.iter().enumerate() {
+        match project(uniforms, *corner) {
+            Some(p) => projected[i] = p,
+            None => return,
+        }
+    }
+
+    let min_x = projected.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+    let max_x = projected.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+    let min_y = projected.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+    let max_y = projected.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+    let depth = projected.iter().map(|p| p.z).sum::<f32>() / 4.0;
+    let distance = uniforms.view_distance(depth);
+
+    if !(min_x.is_finite() && max_x.is_finite() && min_y.is_finite() && max_y.is_finite() && depth.is_finite()) {
+        return;
+    }
+    if max_x < 0.0 || max_y < 0.0 || min_x >= framebuffer.width as f32 || min_y >= framebuffer.height as f32 {
+        return;
+    }
+
+    let width = max_x - min_x;
+    let height = max_y - min_y;
+
+    // Sub-pixel sprites: a single point weighted by the covered area
+    if width < 1.0 || height < 1.0 {
+        let x = (min_x + max_x) * 0.5;
+        let y = (min_y + max_y) * 0.5;
+        if x >= 0.0 && y >= 0.0 {
+            let (x, y) = (x as usize, y as usize);
+            let coverage = (width * height).clamp(0.0, 1.0) * 0.5;
+            let fade = soft_fade(framebuffer, uniforms, x, y, distance, billboard.softness);
+            framebuffer.blend_point(x, y, depth, billboard.color, billboard.alpha * coverage * fade);
+        }
+        return;
+    }
+
+    let start_x = min_x.floor().max(0.0) as usize;
+    let start_y = min_y.floor().max(0.0) as usize;
+    let end_x = (max_x.ceil() as usize).min(framebuffer.width);
+    let end_y = (max_y.ceil() as usize).min(framebuffer.height);
+
+    for y in start_y..end_y {
+        let v = ((y as f32 + 0.5 - min_y) / height) * 2.0 - 1.0;
+        for x in start_x..end_x {
+            let u = ((x as f32 + 0.5 - min_x) / width) * 2.0 - 1.0;
+            let r2 = u * u + v * v;
+            if r2 >= 1.0 {
+                continue;
+            }
+
+            // Radial soft falloff: full alpha at the center, zero at the quad edge
+            let falloff = (1.0 - r2) * (1.0 - r2);
+            let fade = soft_fade(framebuffer, uniforms, x, y, distance, billboard.softness);
+            framebuffer.blend_point(x, y, depth, billboard.color, billboard.alpha * falloff * fade);
+        }
+    }
+}
+
+// Soft particles: how much of a sprite at view distance `distance` shows at pixel (x, y),
+// fading linearly from whole `softness` in front of the opaque surface already there to
+// nothing where it meets it. The depth buffer is only read, so this needs every opaque
+// surface drawn first
+fn soft_fade(framebuffer: &Framebuffer, uniforms: &FrameUniforms, x: usize, y: usize, distance: f32, softness: f32) -> f32 {
+    if softness <= 0.0 {
+        return 1.0;
+    }
+    let surface = framebuffer.depth(x, y).map_or(f32::INFINITY, |depth| uniforms.view_distance(depth));
+    ((surface - distance) / softness).clamp(0.0, 1.0)
+}
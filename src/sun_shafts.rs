@@ -0,0 +1,176 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use crate::dof::{to_hex, to_vec, upsample, DOWNSAMPLE};
+use crate::framebuffer::Framebuffer;
+use crate::impostor::projected_radius;
+use crate::renderer::clip_to_screen;
+use crate::scene::CelestialBody;
+use crate::uniforms::FrameUniforms;
+
+pub const DEFAULT_STRENGTH: f32 = 0.5;
+// Samples along each ray, the share of the way to the sun they cover, and how much each
+// one counts less than the one before it
+const SAMPLES: usize = 32;
+const DENSITY: f32 = 0.8;
+const DECAY: f32 = 0.94;
+// Points of the sun's disc whose depth is checked for occlusion: its center and a ring at
+// this fraction of its radius
+const PROBE_RING: f32 = 0.6;
+const RING_PROBES: usize = 8;
+
+// Screen-space light shafts around whatever passes in front of the sun. A half-resolution
+// mask keeps only the sun's own pixels (the ones inside its disc whose depth is the sun's
+// surface); each pixel then gathers that mask along its ray toward the sun's center with
+// decaying weights, so the light streaks out past the edges of an occluder. The result is
+// added to the frame, faded out as the sun leaves the screen or gets covered
+pub struct SunShafts {
+    pub enabled: bool,
+    pub strength: f32, // brightness of the shafts next to the sun, relative to the sun's
+    mask: Vec<Vec3>,
+    shafts: Vec<Vec3>,
+}
+
+impl Default for SunShafts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SunShafts {
+    pub fn new() -> Self {
+        SunShafts { enabled: true, strength: DEFAULT_STRENGTH, mask: Vec::new(), shafts: Vec::new() }
+    }
+
+    // Add the shafts of `sun` to `framebuffer`, drawn with `uniforms`. Depth is read but not
+    // changed. Does nothing while disabled or when the sun can't be seen
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, uniforms: &FrameUniforms, sun: &CelestialBody) {
+        if !self.enabled || framebuffer.width == 0 || framebuffer.height == 0 {
+            return;
+        }
+        let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(sun.position.x, sun.position.y, sun.position.z, 1.0);
+        if clip.w <= 0.0 {
+            return;
+        }
+        let screen = clip_to_screen(uniforms, clip);
+        let radius = projected_radius(uniforms, framebuffer, sun.position, sun.scale);
+        if !radius.is_finite() {
+            return; // the camera is inside the sun
+        }
+        let disc = Disc {
+            center: Vec2::new(screen.x, screen.y),
+            radius,
+            near: clip.w - sun.scale * 1.05,
+            far: clip.w + sun.scale,
+        };
+        let fade = disc.on_screen(framebuffer) * disc.unoccluded(framebuffer, uniforms);
+        if fade <= 0.0 {
+            return;
+        }
+
+        let (width, height) = (framebuffer.width.div_ceil(DOWNSAMPLE), framebuffer.height.div_ceil(DOWNSAMPLE));
+        self.build_mask(framebuffer, uniforms, width, height, &disc);
+        self.gather(width, height, disc.center / DOWNSAMPLE as f32);
+
+        let strength = self.strength * fade;
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let index = y * framebuffer.width + x;
+                let light = upsample(&self.shafts, width, height, x, y) * strength;
+                framebuffer.buffer[index] = to_hex(to_vec(framebuffer.buffer[index]) + light);
+            }
+        }
+    }
+
+    // The sun's pixels averaged into DOWNSAMPLE x DOWNSAMPLE blocks, everything else black
+    fn build_mask(&mut self, framebuffer: &Framebuffer, uniforms: &FrameUniforms, width: usize, height: usize, disc: &Disc) {
+        let reach = disc.radius * 1.05 + 1.0;
+        self.mask.clear();
+        self.mask.resize(width * height, Vec3::zeros());
+        for sy in 0..height {
+            for sx in 0..width {
+                let mut sum = Vec3::zeros();
+                for y in (sy * DOWNSAMPLE..(sy + 1) * DOWNSAMPLE).take_while(|&y| y < framebuffer.height) {
+                    for x in (sx * DOWNSAMPLE..(sx + 1) * DOWNSAMPLE).take_while(|&x| x < framebuffer.width) {
+                        let index = y * framebuffer.width + x;
+                        let pixel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                        let distance = uniforms.view_distance(framebuffer.zbuffer[index]);
+                        if (pixel - disc.center).magnitude() <= reach && (disc.near..=disc.far).contains(&distance) {
+                            sum += to_vec(framebuffer.buffer[index]);
+                        }
+                    }
+                }
+                self.mask[sy * width + sx] = sum / (DOWNSAMPLE * DOWNSAMPLE) as f32;
+            }
+        }
+    }
+
+    // March from every mask pixel toward `light` (in mask pixels), adding up the mask with
+    // decaying weights. Normalized so a ray that only crosses the sun gets its color back
+    fn gather(&mut self, width: usize, height: usize, light: Vec2) {
+        let total: f32 = (0..SAMPLES).map(|i| DECAY.powi(i as i32)).sum();
+        self.shafts.clear();
+        self.shafts.resize(width * height, Vec3::zeros());
+        for y in 0..height {
+            for x in 0..width {
+                let start = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let step = (light - start) * (DENSITY / SAMPLES as f32);
+                let mut sum = Vec3::zeros();
+                let mut weight = 1.0;
+                for i in 1..=SAMPLES {
+                    let sample = start + step * i as f32;
+                    if sample.x >= 0.0 && sample.y >= 0.0 && (sample.x as usize) < width && (sample.y as usize) < height {
+                        sum += self.mask[sample.y as usize * width + sample.x as usize] * weight;
+                    }
+                    weight *= DECAY;
+                }
+                self.shafts[y * width + x] = sum / total;
+            }
+        }
+    }
+}
+
+// The sun as seen this frame: its disc on screen and the depths along the view axis its
+// visible surface can be at
+struct Disc {
+    center: Vec2,
+    radius: f32,
+    near: f32,
+    far: f32,
+}
+
+impl Disc {
+    // 1 while the center is on screen, down to 0 once it's a radius past the edge (at which
+    // point the whole disc has left the screen)
+    fn on_screen(&self, framebuffer: &Framebuffer) -> f32 {
+        let outside = (-self.center.x)
+            .max(self.center.x - framebuffer.width as f32)
+            .max(-self.center.y)
+            .max(self.center.y - framebuffer.height as f32)
+            .max(0.0);
+        (1.0 - outside / self.radius.max(1.0)).clamp(0.0, 1.0)
+    }
+
+    // Share of the probes across the disc that nothing in front of the sun's surface
+    // covers, read from the depth buffer. Probes off the screen don't count
+    fn unoccluded(&self, framebuffer: &Framebuffer, uniforms: &FrameUniforms) -> f32 {
+        let probes = std::iter::once(self.center).chain((0..RING_PROBES).map(|i| {
+            let (sin, cos) = (std::f32::consts::TAU * i as f32 / RING_PROBES as f32).sin_cos();
+            self.center + Vec2::new(cos, sin) * self.radius * PROBE_RING
+        }));
+        let (mut visible, mut count) = (0, 0);
+        for probe in probes {
+            if probe.x < 0.0 || probe.y < 0.0 || probe.x >= framebuffer.width as f32 || probe.y >= framebuffer.height as f32 {
+                continue;
+            }
+            count += 1;
+            let depth = framebuffer.zbuffer[probe.y as usize * framebuffer.width + probe.x as usize];
+            if uniforms.view_distance(depth) >= self.near {
+                visible += 1;
+            }
+        }
+        if count == 0 {
+            0.0
+        } else {
+            visible as f32 / count as f32
+        }
+    }
+}
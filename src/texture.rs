@@ -0,0 +1,170 @@
+use std::f32::consts::PI;
+use std::fmt;
+use image::ImageError;
+use nalgebra_glm::Vec2;
+use crate::color::Color;
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(String, std::io::Error),
+    Image(String, ImageError),
+    Format(String, String),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Io(path, err) if err.kind() == std::io::ErrorKind::NotFound => write!(f, "could not find {}", path),
+            TextureError::Io(path, err) => write!(f, "could not read {}: {}", path, err),
+            TextureError::Image(path, err) => write!(f, "{}: {}", path, err),
+            TextureError::Format(path, msg) => write!(f, "{}: {}", path, msg),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+// One level of the mip pyramid
+struct MipLevel {
+    width: usize,
+    height: usize,
+    data: Vec<Color>,
+}
+
+impl MipLevel {
+    // Black for an empty image, which has no texel to clamp to
+    fn texel(&self, x: i32, y: i32) -> Color {
+        if self.data.is_empty() {
+            return Color::black();
+        }
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    // Half the size (rounded down, at least 1), each texel the average of the 2x2 block
+    // above it; odd edges reuse their last row or column
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let top = self.texel(2 * x, 2 * y).lerp(&self.texel(2 * x + 1, 2 * y), 0.5);
+                let bottom = self.texel(2 * x, 2 * y + 1).lerp(&self.texel(2 * x + 1, 2 * y + 1), 0.5);
+                data.push(top.lerp(&bottom, 0.5));
+            }
+        }
+        MipLevel { width, height, data }
+    }
+
+    // Bilinear sample at (u, v) in 0..1; u wraps around (equirectangular maps), v clamps
+    fn sample(&self, u: f32, v: f32) -> Color {
+        if self.data.is_empty() {
+            return Color::black();
+        }
+        let x = u.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let wrap = |x: i32| x.rem_euclid(self.width as i32);
+
+        let top = self.texel(wrap(x0), y0).lerp(&self.texel(wrap(x0 + 1), y0), fx);
+        let bottom = self.texel(wrap(x0), y0 + 1).lerp(&self.texel(wrap(x0 + 1), y0 + 1), fx);
+        top.lerp(&bottom, fy)
+    }
+}
+
+/// An image plus its mip pyramid (level 0 is the image itself, each next level half the
+/// size down to 1x1), so far-away surfaces read pre-averaged texels instead of aliasing.
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// A texture from `width` x `height` colors, row by row from the top.
+    pub fn from_pixels(width: usize, height: usize, data: Vec<Color>) -> Self {
+        let mut levels = vec![MipLevel { width, height, data }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+        Texture { width, height, levels }
+    }
+
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Width and height of mip level `level` (0 is the full-size image)
+    pub fn level_size(&self, level: usize) -> (usize, usize) {
+        let level = &self.levels[level];
+        (level.width, level.height)
+    }
+
+    // Texel fetch from mip level `level`, with coordinates clamped to its edge
+    pub fn level_texel(&self, level: usize, x: i32, y: i32) -> Color {
+        self.levels[level].texel(x, y)
+    }
+
+    // The mip level, fractional, that a footprint of this many full-size texels per pixel
+    // reads from: its log2, from 0 up to the last (1x1) level
+    pub fn mip_level(&self, footprint: f32) -> f32 {
+        footprint.max(1.0).log2().min((self.levels.len() - 1) as f32)
+    }
+
+    /// Load an image file in any format the `image` crate reads (the skybox faces are PNGs;
+    /// PPMs work too).
+    pub fn load(path: &str) -> Result<Self, TextureError> {
+        let image = image::open(path)
+            .map_err(|err| match err {
+                ImageError::IoError(err) => TextureError::Io(path.to_string(), err),
+                err => TextureError::Image(path.to_string(), err),
+            })?
+            .to_rgba8();
+        if image.width() == 0 || image.height() == 0 {
+            return Err(TextureError::Format(path.to_string(), "zero-sized image".to_string()));
+        }
+        let data = image.pixels().map(|p| Color::new(p[0], p[1], p[2])).collect();
+        Ok(Texture::from_pixels(image.width() as usize, image.height() as usize, data))
+    }
+
+    /// Texel fetch from the full-size image with coordinates clamped to the edge.
+    pub fn texel(&self, x: i32, y: i32) -> Color {
+        self.levels[0].texel(x, y)
+    }
+
+    /// Trilinear sample at (u, v) in 0..1: `footprint` is how many full-size texels one
+    /// screen pixel covers (see [`Texture::footprint`] and [`Texture::sphere_footprint`]).
+    /// Its log2 picks the mip level, and the two nearest levels are blended so the switch
+    /// never pops.
+    pub fn sample(&self, u: f32, v: f32, footprint: f32) -> Color {
+        let level = self.mip_level(footprint);
+        let lower = level.floor();
+        let fine = self.levels[lower as usize].sample(u, v);
+        if level > lower {
+            fine.lerp(&self.levels[lower as usize + 1].sample(u, v), level - lower)
+        } else {
+            fine
+        }
+    }
+
+    // Footprint from the screen-space derivatives of the UVs (change of u, v per pixel
+    // along x and along y): the longer of the two steps, in full-size texels
+    pub fn footprint(&self, duv_dx: Vec2, duv_dy: Vec2) -> f32 {
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        duv_dx.component_mul(&size).magnitude().max(duv_dy.component_mul(&size).magnitude())
+    }
+
+    // Footprint for an equirectangular map wrapped around a sphere drawn `projected_radius`
+    // pixels wide, measured at the center of its disc: one pixel there spans
+    // 1 / projected_radius radians of longitude and latitude
+    pub fn sphere_footprint(&self, projected_radius: f32) -> f32 {
+        let radians_per_pixel = 1.0 / projected_radius.max(f32::EPSILON);
+        let texels_per_radian = (self.width as f32 / (2.0 * PI)).max(self.height as f32 / PI);
+        texels_per_radian * radians_per_pixel
+    }
+}
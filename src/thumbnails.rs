@@ -0,0 +1,68 @@
+use nalgebra_glm::Vec3;
+use crate::camera::{Camera, CameraMode};
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Renderer;
+use crate::scene::{CelestialBody, Scene};
+use crate::text::{draw_text, GLYPH_HEIGHT};
+use crate::transform::framing_distance;
+
+// Side of each square thumbnail in pixels
+pub const SIZE: usize = 256;
+// Fraction of the thumbnail's height the body's silhouette spans
+pub const FILL: f32 = 0.6;
+const SHEET_GAP: usize = 4;
+const SHEET_BACKGROUND: u32 = 0x202020;
+const LABEL_COLOR: u32 = 0xE0E0E0;
+
+// A camera looking at `body` from the lit (+Z) side, a little to the right and above so the
+// sphere reads as round, at the distance where it spans `fill` of the frame. The distance
+// scales with the body's radius, so every body is framed the same whatever its size
+pub fn framing_camera(body: &CelestialBody, fill: f32) -> Camera {
+    let direction = Vec3::new(0.35, 0.3, 1.0).normalize();
+    let eye = body.position + direction * framing_distance(body.scale, fill);
+    let mut camera = Camera::new(eye, body.position, Vec3::new(0.0, 1.0, 0.0));
+    // The cockpit view, so the ship isn't drawn in front of the body
+    camera.mode = CameraMode::FirstPerson;
+    camera
+}
+
+// Render `scene` framed on the body at `index`; `renderer` must be sized for `framebuffer`
+pub fn render_thumbnail(renderer: &mut Renderer, framebuffer: &mut Framebuffer, scene: &Scene, index: usize) {
+    let camera = framing_camera(&scene.bodies[index], FILL);
+    renderer.render_scene(framebuffer, scene, &camera);
+}
+
+// File name (without extension) of each body's thumbnail: its type id, numbered from the
+// second body of the same type on ("moon", "moon_2", ...)
+pub fn names(bodies: &[CelestialBody]) -> Vec<String> {
+    bodies.iter()
+        .enumerate()
+        .map(|(i, body)| {
+            let id = body.shader_type.id();
+            let earlier = bodies[..i].iter().filter(|other| other.shader_type == body.shader_type).count();
+            if earlier == 0 { id.to_string() } else { format!("{}_{}", id, earlier + 1) }
+        })
+        .collect()
+}
+
+// The thumbnails in a grid of `columns`, each labeled in its bottom-left corner
+pub fn contact_sheet(thumbnails: &[(String, Framebuffer)], columns: usize) -> Framebuffer {
+    let columns = columns.clamp(1, thumbnails.len().max(1));
+    let rows = thumbnails.len().div_ceil(columns);
+    let cell = SIZE + SHEET_GAP;
+    let mut sheet = Framebuffer::new(columns * cell + SHEET_GAP, rows * cell + SHEET_GAP);
+    sheet.set_background_color(SHEET_BACKGROUND);
+    sheet.clear();
+
+    for (i, (label, thumbnail)) in thumbnails.iter().enumerate() {
+        let left = SHEET_GAP + (i % columns) * cell;
+        let top = SHEET_GAP + (i / columns) * cell;
+        for y in 0..thumbnail.height.min(SIZE) {
+            for x in 0..thumbnail.width.min(SIZE) {
+                sheet.set_pixel(left + x, top + y, thumbnail.pixel(x, y).unwrap_or(0));
+            }
+        }
+        draw_text(&mut sheet, left + 6, top + SIZE - 6 - GLYPH_HEIGHT, label, LABEL_COLOR, 1);
+    }
+    sheet
+}
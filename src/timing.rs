@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+// Longest step fed to the simulation; after a stall (window drag, breakpoint) the
+// world skips ahead by at most this much instead of lurching
+const MAX_FRAME_TIME: f32 = 0.25;
+// Most simulation steps run in one frame. When the machine can't keep up, the rest of the
+// backlog is dropped instead of running ever more steps per frame (the spiral of death)
+pub const MAX_STEPS_PER_FRAME: u32 = 8;
+// Simulation speeds the time scale goes between, in halves and doubles. Past x4 a 60 FPS
+// frame needs more than MAX_STEPS_PER_FRAME steps of 1/60 s
+pub const TIME_SCALE_MIN: f32 = 0.125;
+pub const TIME_SCALE_MAX: f32 = 4.0;
+// The last stretch of each frame is busy-waited, since sleep can overshoot by a millisecond or more
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+// Smoothing factor for the displayed frame time (exponential moving average)
+const DISPLAY_SMOOTHING: f32 = 0.1;
+
+// Measures the real time between frames and paces the loop to an optional target rate
+pub struct FrameClock {
+    target: Option<Duration>,
+    frame_start: Instant,
+    smoothed_frame_time: f32,
+}
+
+impl FrameClock {
+    // `fps_cap` of 0 means uncapped
+    pub fn new(fps_cap: u32) -> Self {
+        FrameClock {
+            target: (fps_cap > 0).then(|| Duration::from_secs_f64(1.0 / fps_cap as f64)),
+            frame_start: Instant::now(),
+            smoothed_frame_time: 0.0,
+        }
+    }
+
+    // Start a new frame and return the seconds elapsed since the previous one
+    pub fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let measured = now.duration_since(self.frame_start).as_secs_f32();
+        self.frame_start = now;
+
+        self.smoothed_frame_time = if self.smoothed_frame_time == 0.0 {
+            measured
+        } else {
+            self.smoothed_frame_time + (measured - self.smoothed_frame_time) * DISPLAY_SMOOTHING
+        };
+        measured.min(MAX_FRAME_TIME)
+    }
+
+    // Count the frame as starting now, dropping the time since `tick`: after the loop
+    // stalls on purpose (a capture), the world doesn't skip ahead by the stall
+    pub fn restart(&mut self) {
+        self.frame_start = Instant::now();
+    }
+
+    // Smoothed frame time and rate for on-screen display
+    pub fn display_frame_time(&self) -> f32 {
+        self.smoothed_frame_time
+    }
+
+    pub fn display_fps(&self) -> f32 {
+        if self.smoothed_frame_time > 0.0 { 1.0 / self.smoothed_frame_time } else { 0.0 }
+    }
+
+    // Wait out the rest of the frame budget: sleep for most of it, then spin to the deadline
+    pub fn wait(&self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let deadline = self.frame_start + target;
+
+        let now = Instant::now();
+        if deadline > now + SPIN_MARGIN {
+            std::thread::sleep(deadline - now - SPIN_MARGIN);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+// Turns the real time between frames into whole simulation steps of `step` seconds,
+// carrying what's left over to the next frame. The simulation then only ever sees the
+// same step, whatever the frame rate, and draws `alpha` of the way into the next one.
+// The time scale speeds the simulation up or slows it down against the real time
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32, // simulated seconds still to run, less than one step after `advance`
+    time_scale: f32,  // simulated seconds per real second
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        FixedTimestep { step, accumulator: 0.0, time_scale: 1.0 }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    // Multiply the time scale by `factor`, within TIME_SCALE_MIN and TIME_SCALE_MAX
+    pub fn scale_time(&mut self, factor: f32) -> f32 {
+        self.time_scale = (self.time_scale * factor).clamp(TIME_SCALE_MIN, TIME_SCALE_MAX);
+        self.time_scale
+    }
+
+    // The step to run once per frame when there's no real time to go by (a fixed frame
+    // rate: headless, bench and deterministic runs): one step, at the time scale
+    pub fn frame_step(&self) -> f32 {
+        self.step * self.time_scale
+    }
+
+    // Add `dt` seconds of real time and return how many steps to run for them
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt * self.time_scale;
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        if steps > MAX_STEPS_PER_FRAME {
+            steps = MAX_STEPS_PER_FRAME;
+        }
+        steps
+    }
+
+    // How far the time not yet simulated goes into the next step, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+}
@@ -0,0 +1,55 @@
+// The TOML subset keybindings.toml and scene files are written in: one `key = value` per
+// line, `# comments`, quoted strings and one-line `[a, b]` lists. What the keys and values
+// mean, and how strict to be about them, is up to each file's parser
+
+// Drop a trailing `# comment`, ignoring '#' inside quotes
+pub fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+pub fn unquote(text: &str) -> &str {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text)
+}
+
+// `key = value` split at the first '=', both sides trimmed; None without one
+pub fn key_value(line: &str) -> Option<(&str, &str)> {
+    line.split_once('=').map(|(key, value)| (key.trim(), value.trim()))
+}
+
+// The trimmed items of a `[a, b, ...]` value, still quoted, skipping empty ones (so `[]`
+// has none and a trailing comma is allowed); None if the value isn't a list
+pub fn list_items(value: &str) -> Option<Vec<&str>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    Some(inner.split(',').map(str::trim).filter(|item| !item.is_empty()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_end_outside_quotes() {
+        assert_eq!(strip_comment("color = \"#FF0000\" # red"), "color = \"#FF0000\" ");
+        assert_eq!(strip_comment("# only a comment"), "");
+        assert_eq!(key_value(strip_comment("forward = \"W\"  # W")), Some(("forward", "\"W\"")));
+        assert_eq!(key_value("[[body]]"), None);
+    }
+
+    #[test]
+    fn lists_split_into_items() {
+        assert_eq!(list_items("[\"W\", \"Up\"]"), Some(vec!["\"W\"", "\"Up\""]));
+        assert_eq!(list_items("[1, 2, 3,]"), Some(vec!["1", "2", "3"]));
+        assert_eq!(list_items("[ ]"), Some(vec![]));
+        assert_eq!(list_items("\"W\""), None);
+        assert_eq!(unquote("\"W\""), "W");
+        assert_eq!(unquote("0x00FF00"), "0x00FF00");
+    }
+}
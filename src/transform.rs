@@ -0,0 +1,386 @@
+use std::f32::consts::PI;
+use nalgebra_glm::{look_at, perspective, Mat4, Vec2, Vec3, Vec4};
+use crate::uniforms::FrameUniforms;
+
+// Note on `Mat4::new`: its arguments are written row by row, as the matrix reads on
+// paper, even though nalgebra stores it column-major. So the translation goes in the
+// last argument of each of the first three rows, and `m[(row, column)]` indexes it the
+// same way
+
+pub fn translation(offset: Vec3) -> Mat4 {
+    Mat4::new(
+        1.0, 0.0, 0.0, offset.x,
+        0.0, 1.0, 0.0, offset.y,
+        0.0, 0.0, 1.0, offset.z,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+// Rotation by `rotation.x` about X, then `rotation.y` about Y, then `rotation.z` about Z
+// (radians, right-handed: +90 degrees about Y takes +X to -Z)
+pub fn rotation_euler(rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    rotation_matrix_z * rotation_matrix_y * rotation_matrix_x
+}
+
+// Uniform scale
+pub fn scale(factor: f32) -> Mat4 {
+    Mat4::new(
+        factor, 0.0,    0.0,    0.0,
+        0.0,    factor, 0.0,    0.0,
+        0.0,    0.0,    factor, 0.0,
+        0.0,    0.0,    0.0,    1.0,
+    )
+}
+
+// Product of `matrices` in the order given, so the last one is applied to a point first:
+// compose(&[t, r, s]) is T * R * S
+pub fn compose(matrices: &[Mat4]) -> Mat4 {
+    matrices.iter().fold(Mat4::identity(), |product, matrix| product * matrix)
+}
+
+// Scale, then rotate, then translate
+pub fn create_model_matrix(translation_offset: Vec3, scale_factor: f32, rotation: Vec3) -> Mat4 {
+    compose(&[translation(translation_offset), rotation_euler(rotation), scale(scale_factor)])
+}
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+// Vertical field of view of every projection
+pub const FIELD_OF_VIEW: f32 = 75.0 * PI / 180.0;
+
+// Clipping planes of every projection, in world units from the eye
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 1000.0;
+
+pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
+    let fov = FIELD_OF_VIEW;
+    let aspect_ratio = window_width / window_height;
+
+    // nalgebra-glm takes the aspect ratio first, the reverse of GLM's perspective()
+    perspective(aspect_ratio, fov, NEAR_PLANE, FAR_PLANE)
+}
+
+// Depth buffer value for a point at clip-space `w` (its distance along the view axis),
+// spread logarithmically over the same -1..=1 as NDC z: -1 at the eye, 1 at FAR_PLANE.
+// NDC z spends almost all its precision right in front of the near plane; this keeps
+// the same relative precision at every distance
+pub fn logarithmic_depth(w: f32) -> f32 {
+    2.0 * (1.0 + w.max(0.0)).log2() / (1.0 + FAR_PLANE).log2() - 1.0
+}
+
+// The clip-space w a logarithmic_depth came from
+pub fn logarithmic_depth_distance(depth: f32) -> f32 {
+    ((depth + 1.0) * 0.5 * (1.0 + FAR_PLANE).log2()).exp2() - 1.0
+}
+
+// Distance from a sphere of `radius` at which its silhouette spans `fill` of the frame's
+// height. Exact for a sphere on the view axis: its edge is at asin(radius / distance)
+pub fn framing_distance(radius: f32, fill: f32) -> f32 {
+    let half_angle = (fill * (FIELD_OF_VIEW * 0.5).tan()).atan();
+    radius / half_angle.sin()
+}
+
+// NDC to screen pixels. Y is flipped: NDC +1 (up) is row 0 at the top of the screen
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+// Screen pixels back to NDC; the exact inverse of create_viewport_matrix
+pub fn create_inverse_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        2.0 / width, 0.0, 0.0, -1.0,
+        0.0, -2.0 / height, 0.0, 1.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+// Inverse of the viewport matrix for a point on screen (the same as multiplying by
+// create_inverse_viewport_matrix). The viewport flips Y (screen rows grow downwards, NDC Y
+// grows upwards), so the top-left corner is NDC (-1, +1)
+pub fn screen_to_ndc(x: f32, y: f32, width: f32, height: f32) -> Vec2 {
+    Vec2::new(x / width * 2.0 - 1.0, 1.0 - y / height * 2.0)
+}
+
+// World position of a point in NDC (z = -1 on the near plane, +1 on the far plane)
+pub fn ndc_to_world(ndc: Vec3, inv_view_proj: &Mat4) -> Vec3 {
+    let world = inv_view_proj * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+    world.xyz() / world.w
+}
+
+// Ray (origin on the near plane, unit direction) through a point on screen. The inverse
+// projection undoes the aspect ratio, so the ray is right for any window shape
+pub fn pick_ray(uniforms: &FrameUniforms, x: f32, y: f32, width: f32, height: f32) -> Option<(Vec3, Vec3)> {
+    let inv_view_proj = (uniforms.projection_matrix * uniforms.view_matrix).try_inverse()?;
+    let ndc = screen_to_ndc(x, y, width, height);
+    let near = ndc_to_world(Vec3::new(ndc.x, ndc.y, -1.0), &inv_view_proj);
+    let far = ndc_to_world(Vec3::new(ndc.x, ndc.y, 1.0), &inv_view_proj);
+    Some((near, (far - near).try_normalize(f32::EPSILON)?))
+}
+
+// Distance along a ray (unit direction) to the first hit on a sphere, if it hits in front
+pub fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let offset = origin - center;
+    let b = offset.dot(&direction);
+    let c = offset.magnitude_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    // From inside the sphere the near root is behind the origin; use the exit point
+    [-b - root, -b + root].into_iter().find(|&t| t >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::Shading;
+    use crate::shader_params::StarParams;
+    use crate::lights::PointLights;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn frame_uniforms(width: f32, height: f32, eye: Vec3, center: Vec3) -> FrameUniforms {
+        FrameUniforms {
+            view_matrix: create_view_matrix(eye, center, Vec3::new(0.0, 1.0, 0.0)),
+            projection_matrix: create_perspective_matrix(width, height),
+            viewport_matrix: create_viewport_matrix(width, height),
+            time: 0,
+            camera_position: eye,
+            sun_position: Vec3::zeros(),
+            exposure: 1.0,
+            dither: false,
+            shading: Shading::Phong,
+            seed: 0,
+            star: StarParams::default(),
+            light_intensity: 1.0,
+            override_light: None,
+            lights: PointLights::default(),
+            log_depth: false,
+        }
+    }
+
+    #[test]
+    fn screen_center_is_ndc_origin() {
+        let ndc = screen_to_ndc(400.0, 300.0, 800.0, 600.0);
+        assert!(ndc.magnitude() < EPSILON, "{:?}", ndc);
+    }
+
+    #[test]
+    fn top_left_is_ndc_minus_one_plus_one() {
+        let ndc = screen_to_ndc(0.0, 0.0, 800.0, 600.0);
+        assert!((ndc - Vec2::new(-1.0, 1.0)).magnitude() < EPSILON, "{:?}", ndc);
+        let ndc = screen_to_ndc(800.0, 600.0, 800.0, 600.0);
+        assert!((ndc - Vec2::new(1.0, -1.0)).magnitude() < EPSILON, "{:?}", ndc);
+    }
+
+    #[test]
+    fn screen_to_ndc_inverts_the_viewport_matrix() {
+        let viewport = create_viewport_matrix(640.0, 360.0);
+        for &(x, y) in &[(0.0, 0.0), (100.0, 50.0), (639.0, 12.0), (320.0, 359.0)] {
+            let ndc = screen_to_ndc(x, y, 640.0, 360.0);
+            let screen = viewport * Vec4::new(ndc.x, ndc.y, 0.0, 1.0);
+            assert!((screen.x - x).abs() < 1e-3 && (screen.y - y).abs() < 1e-3, "({}, {}) -> {:?}", x, y, screen);
+        }
+    }
+
+    #[test]
+    fn center_click_casts_a_forward_ray() {
+        let eye = Vec3::new(3.0, 2.0, 10.0);
+        let center = Vec3::new(0.0, 0.0, 0.0);
+        let uniforms = frame_uniforms(800.0, 600.0, eye, center);
+        let (origin, direction) = pick_ray(&uniforms, 400.0, 300.0, 800.0, 600.0).unwrap();
+        let forward = (center - eye).normalize();
+        assert!((direction - forward).magnitude() < EPSILON, "{:?}", direction);
+        assert!((origin - eye).magnitude() < 0.2, "{:?}", origin);
+    }
+
+    #[test]
+    fn top_click_picks_the_body_above_not_below() {
+        // A wide window, so a wrong aspect ratio would also miss
+        let (width, height) = (1200.0, 400.0);
+        let uniforms = frame_uniforms(width, height, Vec3::new(0.0, 0.0, 10.0), Vec3::zeros());
+        let above = Vec3::new(2.0, 2.0, 0.0);
+        let below = Vec3::new(2.0, -2.0, 0.0);
+
+        let screen = crate::sprite::project(&uniforms, above).unwrap();
+        assert!(screen.y < height / 2.0, "the upper body must be on the upper half of the screen");
+        let (origin, direction) = pick_ray(&uniforms, screen.x, screen.y, width, height).unwrap();
+        assert!(ray_sphere(origin, direction, above, 0.5).is_some());
+        assert!(ray_sphere(origin, direction, below, 0.5).is_none());
+    }
+
+    #[test]
+    fn ray_sphere_hits_the_near_side() {
+        let hit = ray_sphere(Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -1.0), Vec3::zeros(), 2.0);
+        assert!((hit.unwrap() - 8.0).abs() < EPSILON);
+        assert!(ray_sphere(Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, 1.0), Vec3::zeros(), 2.0).is_none());
+    }
+
+    fn transform_point(matrix: &Mat4, point: Vec3) -> Vec3 {
+        let p = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+        p.xyz() / p.w
+    }
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).magnitude() < EPSILON, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn quarter_turn_about_y_takes_x_to_minus_z() {
+        let rotation = rotation_euler(Vec3::new(0.0, PI / 2.0, 0.0));
+        assert_close(transform_point(&rotation, Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn euler_angles_apply_x_then_y_then_z() {
+        // +Y about X goes to +Z, then about Y to +X, then about Z to +Y
+        let rotation = rotation_euler(Vec3::new(PI / 2.0, PI / 2.0, PI / 2.0));
+        assert_close(transform_point(&rotation, Vec3::new(0.0, 1.0, 0.0)), Vec3::new(0.0, 1.0, 0.0));
+        // +X stays put about X, goes to -Z about Y, and stays put about Z
+        assert_close(transform_point(&rotation, Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn translation_lands_in_the_last_column() {
+        let matrix = translation(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(matrix[(0, 3)], 1.0);
+        assert_eq!(matrix[(1, 3)], 2.0);
+        assert_eq!(matrix[(2, 3)], 3.0);
+        assert_eq!(matrix.column(3), Vec4::new(1.0, 2.0, 3.0, 1.0));
+        assert_eq!(matrix.row(3), Vec4::new(0.0, 0.0, 0.0, 1.0).transpose());
+    }
+
+    #[test]
+    fn model_matrix_is_translate_rotate_scale() {
+        let position = Vec3::new(4.0, -1.0, 2.5);
+        let rotation = Vec3::new(0.3, -1.2, 0.7);
+        let model = create_model_matrix(position, 2.0, rotation);
+
+        // The same transform built with nalgebra-glm's own helpers
+        let expected = nalgebra_glm::translation(&position)
+            * nalgebra_glm::rotation(rotation.z, &Vec3::z())
+            * nalgebra_glm::rotation(rotation.y, &Vec3::y())
+            * nalgebra_glm::rotation(rotation.x, &Vec3::x())
+            * nalgebra_glm::scaling(&Vec3::new(2.0, 2.0, 2.0));
+        assert!((model - expected).abs().max() < EPSILON, "{} vs {}", model, expected);
+        assert_eq!(model, compose(&[translation(position), rotation_euler(rotation), scale(2.0)]));
+
+        // The origin of the model ends at `position`; a unit offset is scaled before it moves
+        assert_close(transform_point(&model, Vec3::zeros()), position);
+        let unrotated = create_model_matrix(position, 2.0, Vec3::zeros());
+        assert_close(transform_point(&unrotated, Vec3::new(1.0, 0.0, 0.0)), position + Vec3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn compose_applies_the_last_matrix_first() {
+        let shift = translation(Vec3::new(1.0, 0.0, 0.0));
+        let double = scale(2.0);
+        assert_close(transform_point(&compose(&[shift, double]), Vec3::new(1.0, 0.0, 0.0)), Vec3::new(3.0, 0.0, 0.0));
+        assert_close(transform_point(&compose(&[double, shift]), Vec3::new(1.0, 0.0, 0.0)), Vec3::new(4.0, 0.0, 0.0));
+        assert_eq!(compose(&[]), Mat4::identity());
+    }
+
+    #[test]
+    fn viewport_maps_ndc_corners_to_screen_corners() {
+        let viewport = create_viewport_matrix(800.0, 600.0);
+        assert_close(transform_point(&viewport, Vec3::new(-1.0, -1.0, 0.0)), Vec3::new(0.0, 600.0, 0.0));
+        assert_close(transform_point(&viewport, Vec3::new(1.0, 1.0, 0.0)), Vec3::new(800.0, 0.0, 0.0));
+        assert_close(transform_point(&viewport, Vec3::new(0.0, 0.0, 0.5)), Vec3::new(400.0, 300.0, 0.5));
+    }
+
+    #[test]
+    fn inverse_viewport_undoes_the_viewport() {
+        let (width, height) = (1024.0, 576.0);
+        let product = create_inverse_viewport_matrix(width, height) * create_viewport_matrix(width, height);
+        assert!((product - Mat4::identity()).abs().max() < EPSILON, "{}", product);
+
+        let inverse = create_inverse_viewport_matrix(width, height);
+        let ndc = transform_point(&inverse, Vec3::new(100.0, 50.0, 0.0));
+        let expected = screen_to_ndc(100.0, 50.0, width, height);
+        assert_close(ndc, Vec3::new(expected.x, expected.y, 0.0));
+    }
+
+    #[test]
+    fn perspective_has_a_75_degree_vertical_fov() {
+        let projection = create_perspective_matrix(1280.0, 720.0);
+        let half_fov = (1.0 / projection[(1, 1)]).atan();
+        assert!((half_fov.to_degrees() * 2.0 - 75.0).abs() < 1e-3, "{}", half_fov.to_degrees() * 2.0);
+        assert!((projection[(1, 1)] / projection[(0, 0)] - 1280.0 / 720.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn perspective_keeps_the_aspect_ratio() {
+        // Equal offsets right and up land equally far from the center, even in a wide window
+        let (width, height) = (1200.0, 400.0);
+        let uniforms = frame_uniforms(width, height, Vec3::new(0.0, 0.0, 10.0), Vec3::zeros());
+        let screen = crate::sprite::project(&uniforms, Vec3::new(1.0, 1.0, 0.0)).unwrap();
+        let (dx, dy) = (screen.x - width / 2.0, height / 2.0 - screen.y);
+        assert!(dx > 0.0 && (dx - dy).abs() < 1e-2, "dx {} dy {}", dx, dy);
+    }
+
+    #[test]
+    fn logarithmic_depth_spans_ndc_and_separates_far_surfaces() {
+        assert_eq!(logarithmic_depth(0.0), -1.0);
+        assert!((logarithmic_depth(FAR_PLANE) - 1.0).abs() < EPSILON);
+
+        // Two surfaces 5 cm apart, 500 units away: NDC z can barely tell them apart,
+        // the logarithmic depth keeps them well separated and in order
+        let mut uniforms = frame_uniforms(800.0, 600.0, Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0));
+        let gap = |uniforms: &FrameUniforms| {
+            let [near, far] = [500.0, 500.05].map(|distance| crate::sprite::project(uniforms, Vec3::new(0.0, 0.0, -distance)).unwrap().z);
+            far - near
+        };
+        let ndc_gap = gap(&uniforms);
+        uniforms.log_depth = true;
+        let log_gap = gap(&uniforms);
+        assert!(log_gap > 1e-5 && log_gap > ndc_gap * 100.0, "log {} ndc {}", log_gap, ndc_gap);
+    }
+
+    #[test]
+    fn view_distance_undoes_either_depth() {
+        let mut uniforms = frame_uniforms(800.0, 600.0, Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0));
+        for log_depth in [false, true] {
+            uniforms.log_depth = log_depth;
+            for distance in [0.5, 3.0, 40.0, 700.0] {
+                let depth = crate::sprite::project(&uniforms, Vec3::new(0.0, 0.0, -distance)).unwrap().z;
+                let back = uniforms.view_distance(depth);
+                assert!((back - distance).abs() < distance * 1e-3, "{} -> {} (log {})", distance, back, log_depth);
+            }
+            assert_eq!(uniforms.view_distance(f32::INFINITY), f32::INFINITY);
+        }
+    }
+}
@@ -1,55 +1,123 @@
-use nalgebra_glm::{Vec3, dot, Vec2};
+use nalgebra_glm::{Vec3, Vec2};
 use crate::fragment::Fragment;
 use crate::vertex::{Vertex};
 use crate::color::Color;
+use crate::shaders::{diffuse, Shading};
+
+// Vertices are snapped to 1/256 of a pixel and coverage is decided with edge functions
+// computed exactly in integers at the pixel centers, so two triangles sharing an edge get
+// exactly opposite values along it
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_STEPS: i64 = 1 << SUBPIXEL_BITS;
+// Snapped coordinates are clamped to a million pixels either way, which keeps the products
+// in the edge functions within an i64
+const MAX_SUBPIXEL: i64 = 1 << 28;
+
+type Point = (i64, i64);
+
+// `light_direction` is only used by Gouraud shading, which lights the vertices here
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, shading: Shading, light_direction: &Vec3) -> Vec<Fragment> {
+  triangle_in_rows(v1, v2, v3, shading, light_direction, i32::MIN, i32::MAX)
+}
+
+// Whether the triangle's screen-space corners (transformed_position) wind the way a mesh's
+// triangles do when seen from behind: clockwise on screen for the counter-clockwise
+// outward winding of the meshes, since screen y grows downward. Nothing is culled for it;
+// the renderer counts them (RenderStats::back_facing). Degenerate triangles aren't
+pub fn is_back_facing(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> bool {
+  let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+  if !is_finite(&a) || !is_finite(&b) || !is_finite(&c) {
+    return false;
+  }
+  edge_function(snap(&a), snap(&b), snap(&c)) < 0
+}
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
+// Only the fragments on rows first_row..=last_row, for rasterizing one framebuffer band
+pub fn triangle_in_rows(
+  v1: &Vertex,
+  v2: &Vertex,
+  v3: &Vertex,
+  shading: Shading,
+  light_direction: &Vec3,
+  first_row: i32,
+  last_row: i32,
+) -> Vec<Fragment> {
   let mut fragments = Vec::new();
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
-  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  // NaN/inf vertices (e.g. from a w = 0 perspective divide) would poison the
+  // bounding box and every barycentric weight
+  if !is_finite(&a) || !is_finite(&b) || !is_finite(&c) {
+    return fragments;
+  }
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+  // Zero-area (degenerate) triangles cover no pixels and would divide by zero below
+  let (sa, sb, sc) = (snap(&a), snap(&b), snap(&c));
+  let triangle_area = edge_function(sa, sb, sc);
+  if triangle_area == 0 {
+    return fragments;
+  }
+  // Either winding is drawn: flip the edge functions of clockwise triangles so they are
+  // positive inside. Each edge is listed with the weight it gives (the first one is the
+  // edge opposite a) and whether pixel centers exactly on it are this triangle's
+  let sign = triangle_area.signum();
+  let area = (triangle_area * sign) as f32;
+  let edges = [(sb, sc), (sc, sa), (sa, sb)];
+  let owned = edges.map(|(from, to)| is_top_left(from, to, sign));
+
+  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  let (min_y, max_y) = (min_y.max(first_row), max_y.min(last_row));
 
-  let triangle_area = edge_function(&a, &b, &c);
+  // Gouraud: light each vertex once; the intensity is then interpolated like any attribute
+  let vertex_intensity = |v: &Vertex| diffuse(&v.transformed_normal.try_normalize(f32::EPSILON).unwrap_or_else(Vec3::zeros), light_direction);
+  let intensities = match shading {
+    Shading::Gouraud => (vertex_intensity(v1), vertex_intensity(v2), vertex_intensity(v3)),
+    Shading::Phong => (0.0, 0.0, 0.0),
+  };
 
   // Iterate over each pixel in the bounding box
   for y in min_y..=max_y {
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      let center = (x as i64 * SUBPIXEL_STEPS + SUBPIXEL_STEPS / 2, y as i64 * SUBPIXEL_STEPS + SUBPIXEL_STEPS / 2);
+      let weights = edges.map(|(from, to)| edge_function(from, to, center) * sign);
 
-      // Calculate barycentric coordinates
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
-
-      // Check if the point is inside the triangle
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+      // Top-left fill rule: a pixel center on an edge belongs to the triangle only if that
+      // is a top or left edge, so one of two triangles sharing the edge draws it
+      if (0..3).all(|i| weights[i] > 0 || (weights[i] == 0 && owned[i])) {
+        let (w1, w2, w3) = (weights[0] as f32 / area, weights[1] as f32 / area, weights[2] as f32 / area);
 
         // Interpolate normal
         let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
+        // A zero-length normal can't be normalized; treat it as unlit instead of NaN
+        let normal = normal.try_normalize(f32::EPSILON).unwrap_or_else(Vec3::zeros);
 
-        // Calculate lighting intensity
-        let intensity = dot(&normal, &light_dir).max(0.0);
+        // Gouraud intensity; with Phong it is computed per fragment in the fragment shader
+        let intensity = intensities.0 * w1 + intensities.1 * w2 + intensities.2 * w3;
 
         // Create a gray color (unchanged)
         let color = Color::new(100, 100, 100); // Medium gray
 
         // Interpolate depth
         let depth = a.z * w1 + b.z * w2 + c.z * w3;
+        if !depth.is_finite() || !intensity.is_finite() {
+          continue;
+        }
 
-        // Positions of the original vertex
+        // Positions of the original vertex, in model and world space, and the UVs
         let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+        let world_position = v1.world_position * w1 + v2.world_position * w2 + v3.world_position * w3;
+        let tex_coords = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
 
-        fragments.push(Fragment::new(
-            Vec2::new(x as f32, y as f32),
-            color,
+        fragments.push(Fragment {
+            position: Vec2::new(x as f32, y as f32),
             depth,
+            vertex_position,
+            world_position,
             normal,
+            tex_coords,
             intensity,
-            vertex_position,
-        ));
+            color,
+        });
       }
     }
   }
@@ -57,6 +125,10 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
   fragments
 }
 
+fn is_finite(v: &Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
@@ -66,16 +138,23 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
-
-    (w1, w2, w3)
+// (x, y) in 1/SUBPIXEL_STEPS of a pixel
+fn snap(v: &Vec3) -> Point {
+    let snap = |coordinate: f32| ((coordinate * SUBPIXEL_STEPS as f32).round() as i64).clamp(-MAX_SUBPIXEL, MAX_SUBPIXEL);
+    (snap(v.x), snap(v.y))
 }
 
-fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
-    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
+// Twice the signed area of a, b, c: positive when c is on one side of the line a -> b,
+// negative on the other and exactly 0 on it
+fn edge_function(a: Point, b: Point, c: Point) -> i64 {
+    (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
 }
 
-
+// Whether the edge from -> to, with the inside of its triangle where `sign` times its edge
+// function is positive, is a left edge (the inside is to its right, +x) or a top edge (flat,
+// with the inside below it, +y, since screen y grows downward)
+fn is_top_left(from: Point, to: Point, sign: i64) -> bool {
+    // Gradient of sign * edge_function(from, to, p) over p, pointing into the triangle
+    let (inward_x, inward_y) = (sign * (to.1 - from.1), -sign * (to.0 - from.0));
+    inward_x > 0 || (inward_x == 0 && inward_y > 0)
+}
@@ -0,0 +1,101 @@
+use crate::framebuffer::Framebuffer;
+use crate::input::{Action, Controls};
+use crate::planet::PlanetType;
+use crate::shader_params::{ShaderParamTable, ShaderParams};
+use crate::text::{draw_text, text_width, LINE_ADVANCE};
+
+// Keyboard-driven panel for tuning the focused planet's shader parameters at runtime.
+// Default keys (rebindable, see input.rs):
+//   P      show/hide (prints the final values on close so they can be copied)
+//   [ / ]  select parameter
+//   - / +  decrease / increase the selected parameter
+pub struct ShaderTweaker {
+    pub visible: bool,
+    selected: usize,
+}
+
+impl Default for ShaderTweaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShaderTweaker {
+    pub fn new() -> Self {
+        ShaderTweaker { visible: false, selected: 0 }
+    }
+
+    // Toggle the panel and step the selected parameter of `planet_type`. True when a value
+    // in `table` changed
+    pub fn handle_input(&mut self, controls: &Controls, planet_type: PlanetType, table: &mut ShaderParamTable) -> bool {
+        if controls.pressed(Action::TweakerToggle) {
+            self.visible = !self.visible;
+            if !self.visible {
+                println!("{}: {}", planet_type.name(), format_params(&table.get(planet_type)));
+            }
+        }
+        if !self.visible {
+            return false;
+        }
+
+        if controls.pressed(Action::TweakerPrev) {
+            self.selected = (self.selected + ShaderParams::FIELD_COUNT - 1) % ShaderParams::FIELD_COUNT;
+        }
+        if controls.pressed(Action::TweakerNext) {
+            self.selected = (self.selected + 1) % ShaderParams::FIELD_COUNT;
+        }
+
+        let (_, step) = ShaderParams::field_info(self.selected);
+        let mut delta = 0.0;
+        if controls.pressed_repeat(Action::TweakerDecrease) {
+            delta -= step;
+        }
+        if controls.pressed_repeat(Action::TweakerIncrease) {
+            delta += step;
+        }
+        if delta == 0.0 {
+            return false;
+        }
+        let value = table.get_mut(planet_type).field_mut(self.selected);
+        *value = (*value + delta).max(0.0);
+        true
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, planet_type: PlanetType, params: &ShaderParams) {
+        if !self.visible {
+            return;
+        }
+
+        let title = format!("Shader: {}", planet_type.name());
+        let hint = "[ ] elegir  - + ajustar";
+        let lines: Vec<String> = (0..ShaderParams::FIELD_COUNT)
+            .map(|i| {
+                let marker = if i == self.selected { ">" } else { " " };
+                format!("{} {:<13}{:>8.3}", marker, ShaderParams::field_info(i).0, params.field(i))
+            })
+            .collect();
+
+        let x = 10;
+        let y = 10;
+        let width = lines.iter().map(|l| text_width(l, 1))
+            .chain([text_width(&title, 1), text_width(hint, 1)])
+            .max()
+            .unwrap_or(0) + 16;
+        let height = LINE_ADVANCE * (lines.len() + 2) + 10;
+        framebuffer.blend_rect(x, y, width, height, 0x000000, 0.6);
+
+        draw_text(framebuffer, x + 8, y + 8, &title, 0xFFD700, 1);
+        for (i, line) in lines.iter().enumerate() {
+            let color = if i == self.selected { 0xFFFFFF } else { 0xA0A0A0 };
+            draw_text(framebuffer, x + 8, y + 8 + LINE_ADVANCE * (i + 1), line, color, 1);
+        }
+        draw_text(framebuffer, x + 8, y + 8 + LINE_ADVANCE * (lines.len() + 1), hint, 0x808080, 1);
+    }
+}
+
+fn format_params(params: &ShaderParams) -> String {
+    (0..ShaderParams::FIELD_COUNT)
+        .map(|i| format!("{} = {:.3}", ShaderParams::field_info(i).0, params.field(i)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,98 @@
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::{Mat4, Vec3};
+use crate::color::Color;
+use crate::lights::PointLights;
+use crate::planet::PlanetType;
+use crate::shader_params::{ShaderParams, StarParams};
+use crate::shaders::{Shading, LIGHT_DIRECTION};
+use crate::transform::{create_perspective_matrix, create_viewport_matrix, logarithmic_depth, logarithmic_depth_distance};
+
+// Data shared by every draw of a frame: camera transforms, time and output settings.
+// Built once per frame by the Renderer and never changed while drawing
+#[derive(Clone)]
+pub struct FrameUniforms {
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: u32, // simulated time in 60 Hz frames, what the shaders animate with
+    pub camera_position: Vec3, // world space, the eye the view matrix looks from
+    pub sun_position: Vec3,    // world space, where light comes from (origin without a sun)
+    pub exposure: f32,
+    pub dither: bool,
+    pub shading: Shading,
+    pub seed: u64, // mixed into shaders that draw their own random numbers (water)
+    pub star: StarParams,
+    pub light_intensity: f32, // the sun's current brightness, scales every lit fragment
+    pub override_light: Option<Vec3>, // unit direction towards a light steered by hand, for previewing shading
+    pub lights: PointLights, // the scene's extra colored lights, added on top of the sun's
+    pub log_depth: bool, // store logarithmic_depth of clip w instead of NDC z in the depth buffer
+}
+
+impl FrameUniforms {
+    // Uniforms of a `width` x `height` frame before the first one is rendered: the view is
+    // the identity, eye at the origin looking down -z with +y up
+    pub fn first(width: usize, height: usize) -> Self {
+        FrameUniforms {
+            view_matrix: Mat4::identity(),
+            projection_matrix: create_perspective_matrix(width as f32, height as f32),
+            viewport_matrix: create_viewport_matrix(width as f32, height as f32),
+            time: 0,
+            camera_position: Vec3::zeros(),
+            sun_position: Vec3::zeros(),
+            exposure: 1.0,
+            dither: true,
+            shading: Shading::Phong,
+            seed: 0,
+            star: StarParams::default(),
+            light_intensity: 1.0,
+            override_light: None,
+            lights: PointLights::default(),
+            log_depth: false,
+        }
+    }
+
+    // Unit direction towards the light the bodies are shaded with: the manual override
+    // if one is set, otherwise the scene's light
+    pub fn light_direction(&self) -> Vec3 {
+        self.override_light.unwrap_or(LIGHT_DIRECTION)
+    }
+
+    // What the depth buffer stores for a point with clip-space `w` whose screen-space
+    // (NDC) z is `ndc_z`: that z itself, or its logarithmic depth with `log_depth`.
+    // Every depth written or compared in a frame goes through here, so both never mix
+    pub fn depth(&self, w: f32, ndc_z: f32) -> f32 {
+        if self.log_depth {
+            logarithmic_depth(w)
+        } else {
+            ndc_z
+        }
+    }
+
+    // Back from a depth buffer value to the clip-space w (distance along the view axis)
+    // it was stored for; INFINITY where nothing was drawn
+    pub fn view_distance(&self, depth: f32) -> f32 {
+        if !depth.is_finite() {
+            return f32::INFINITY;
+        }
+        if self.log_depth {
+            return logarithmic_depth_distance(depth);
+        }
+        // NDC z = (A * z + B) / w for view-space z = -w, with A and B from the projection
+        let (a, b) = (self.projection_matrix[(2, 2)], self.projection_matrix[(2, 3)]);
+        b / (depth + a)
+    }
+}
+
+// Data for one draw call. Shaders reach the frame's data through `frame`, so a draw only
+// needs its own model matrix and shader
+pub struct DrawUniforms<'a> {
+    pub frame: &'a FrameUniforms,
+    pub model_matrix: Mat4,
+    pub shader: PlanetType,
+    pub params: ShaderParams, // `shader`'s tunable parameters
+    pub noise: &'a FastNoiseLite,
+    pub seed: u64, // this body's own variation (asteroid shape and surface); 0 when unused
+    pub base_color: Option<Color>, // the material's diffuse color, for models drawn part by part
+    pub ambient: f32, // least intensity of the main light, so the unlit side isn't black
+    pub bump_strength: f32, // how far the noise's relief tilts the normal; 0 keeps the surface smooth
+}
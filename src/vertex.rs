@@ -1,17 +1,23 @@
+use std::fmt;
 use nalgebra_glm::{Vec2, Vec3};
 use crate::color::Color;
 
+/// A mesh vertex. The first four fields come from the model; `vertex_shader` fills in the
+/// rest, which the rasterizer interpolates into each [`Fragment`](crate::fragment::Fragment).
 #[derive(Clone, Debug)]
 pub struct Vertex {
-  pub position: Vec3,
-  pub normal: Vec3,
+  pub position: Vec3, // model space
+  pub normal: Vec3,   // model space
   pub tex_coords: Vec2,
   pub color: Color,
-  pub transformed_position: Vec3,
-  pub transformed_normal: Vec3,
+  pub transformed_position: Vec3, // screen space: pixel x, y and viewport depth
+  pub transformed_normal: Vec3,   // world space (normal matrix of the model matrix), not normalized
+  pub world_position: Vec3,       // world space, after the model matrix
 }
 
 impl Vertex {
+  /// A model vertex, black, with the transformed fields set to the model ones until the
+  /// vertex shader runs.
   pub fn new(position: Vec3, normal: Vec3, tex_coords: Vec2) -> Self {
     Vertex {
       position,
@@ -20,9 +26,32 @@ impl Vertex {
       color: Color::black(),
       transformed_position: position,
       transformed_normal: normal,
+      world_position: position,
     }
   }
 
+  /// A model vertex checked before it reaches the rasterizer: the normal is made unit
+  /// length (+Y, what a corner without one gets, if it has no direction), UV components
+  /// that aren't finite become 0 and v is clamped to 0..=1 as texture sampling does. u is
+  /// left as it is: sampling wraps it, and wrapping it here would tear the triangles that
+  /// cross the seam. A position that isn't finite can't be fixed and is an error.
+  pub fn validated(position: Vec3, normal: Vec3, tex_coords: Vec2) -> Result<Self, VertexError> {
+    if !position.iter().all(|c| c.is_finite()) {
+      return Err(VertexError::Position(position));
+    }
+    let length = normal.magnitude();
+    let normal = if length.is_finite() && length > f32::EPSILON {
+      normal / length
+    } else {
+      Vec3::new(0.0, 1.0, 0.0)
+    };
+    let finite = |c: f32| if c.is_finite() { c } else { 0.0 };
+    let tex_coords = Vec2::new(finite(tex_coords.x), finite(tex_coords.y).clamp(0.0, 1.0));
+    Ok(Vertex::new(position, normal, tex_coords))
+  }
+
+  /// A vertex of a flat-colored shape, with no normal or UVs.
+  #[allow(dead_code)]
   pub fn new_with_color(position: Vec3, color: Color) -> Self {
     Vertex {
       position,
@@ -31,9 +60,12 @@ impl Vertex {
       color,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+      world_position: position,
     }
   }
 
+  /// Set the screen-space position and world-space normal, as the vertex shader does.
+  #[allow(dead_code)]
   pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
     self.transformed_position = position;
     self.transformed_normal = normal;
@@ -49,6 +81,23 @@ impl Default for Vertex {
       color: Color::black(),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+    }
+  }
+}
+
+/// Why [`Vertex::validated`] turned a vertex down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VertexError {
+  Position(Vec3), // with a NaN or infinite component
+}
+
+impl fmt::Display for VertexError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VertexError::Position(p) => write!(f, "posición de vértice inválida ({}, {}, {})", p.x, p.y, p.z),
     }
   }
 }
+
+impl std::error::Error for VertexError {}
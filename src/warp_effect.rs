@@ -0,0 +1,122 @@
+use crate::framebuffer::Framebuffer;
+
+// Seconds the flash and streaks take to ramp up and fade out after a warp starts
+const WARP_EFFECT_TIME: f32 = 0.6;
+// At full intensity a sky pixel at distance r from the target point picks up light
+// from as far in as r * (1 - MAX_STREAK), so stars smear outward by that fraction
+const MAX_STREAK: f32 = 0.35;
+// Samples are taken about a pixel apart along the streak, up to this many
+const MAX_STREAK_SAMPLES: usize = 48;
+// Peak opacity of the white flash
+const FLASH_ALPHA: f32 = 0.25;
+
+// Timeline of the warp flash: stars stretch away from the destination while the
+// screen briefly flashes, driven by `t` going 0 -> 1 over WARP_EFFECT_TIME
+pub struct WarpEffect {
+    elapsed: Option<f32>,
+    target: usize,
+}
+
+impl Default for WarpEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WarpEffect {
+    pub fn new() -> Self {
+        WarpEffect { elapsed: None, target: 0 }
+    }
+
+    // Start the effect towards body `target`; holding the warp key keeps the running
+    // effect instead of restarting it every frame
+    pub fn start(&mut self, target: usize) {
+        if self.elapsed.is_none() || self.target != target {
+            self.elapsed = Some(0.0);
+            self.target = target;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if let Some(elapsed) = self.elapsed {
+            let elapsed = elapsed + dt;
+            self.elapsed = (elapsed < WARP_EFFECT_TIME).then_some(elapsed);
+        }
+    }
+
+    // Body the warp is heading to, while the effect is running
+    pub fn target(&self) -> Option<usize> {
+        self.elapsed.map(|_| self.target)
+    }
+
+    // 0 -> 1 -> 0 over the effect's duration
+    pub fn intensity(&self) -> f32 {
+        match self.elapsed {
+            Some(elapsed) => (elapsed / WARP_EFFECT_TIME * std::f32::consts::PI).sin().max(0.0),
+            None => 0.0,
+        }
+    }
+
+    // Post-process the finished frame: radially stretch the sky away from `focus`
+    // (screen pixels) and blend the flash on top. Geometry pixels (depth <= 1) are
+    // neither smeared nor used as streak sources, so planets stay sharp
+    pub fn apply(&self, framebuffer: &mut Framebuffer, focus: (f32, f32)) {
+        let intensity = self.intensity();
+        if intensity <= 0.0 {
+            return;
+        }
+
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let source = framebuffer.buffer.clone();
+        let streak = MAX_STREAK * intensity;
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                if framebuffer.zbuffer[index] <= 1.0 {
+                    continue;
+                }
+
+                let dx = x as f32 - focus.0;
+                let dy = y as f32 - focus.1;
+                let mut rgb = unpack(source[index]);
+                let samples = ((dx.hypot(dy) * streak).ceil() as usize).clamp(1, MAX_STREAK_SAMPLES);
+                for i in 1..=samples {
+                    let f = i as f32 / samples as f32;
+                    let sx = focus.0 + dx * (1.0 - streak * f);
+                    let sy = focus.1 + dy * (1.0 - streak * f);
+                    if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                        continue;
+                    }
+                    let sample_index = sy as usize * width + sx as usize;
+                    if framebuffer.zbuffer[sample_index] <= 1.0 {
+                        continue;
+                    }
+                    // Light further along the streak fades, giving each star a tapered tail
+                    let weight = 1.0 - f;
+                    let sample = unpack(source[sample_index]);
+                    for (channel, sample) in rgb.iter_mut().zip(sample) {
+                        *channel = channel.max(sample * weight);
+                    }
+                }
+                framebuffer.buffer[index] = pack(rgb);
+            }
+        }
+
+        let flash = FLASH_ALPHA * intensity * intensity;
+        for y in 0..height {
+            for x in 0..width {
+                framebuffer.blend_pixel(x, y, 0xFFFFFF, flash);
+            }
+        }
+    }
+}
+
+fn unpack(color: u32) -> [f32; 3] {
+    [((color >> 16) & 0xFF) as f32, ((color >> 8) & 0xFF) as f32, (color & 0xFF) as f32]
+}
+
+fn pack(rgb: [f32; 3]) -> u32 {
+    let channel = |c: f32| c.round().clamp(0.0, 255.0) as u32;
+    (channel(rgb[0]) << 16) | (channel(rgb[1]) << 8) | channel(rgb[2])
+}
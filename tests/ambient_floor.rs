@@ -0,0 +1,61 @@
+use nalgebra_glm::Vec3;
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+use space_travel::scene::{ambient_floor, Scene, SceneConfig};
+use space_travel::shaders::LIGHT_DIRECTION;
+
+mod common;
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+fn scene(source: &str) -> Scene {
+    common::dark_scene_from_config(&SceneConfig::parse(source).expect("scene"))
+}
+
+// Brightest channel at the middle of a frame looking straight at the night side of a
+// rocky planet with the given ambient floor, sitting at the origin with the sun hidden
+fn night_side(ambient: f32) -> u32 {
+    let mut scene = scene("[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"rocky\"\norbit_radius = 10\n");
+    scene.bodies[0].visibility.body = false;
+    scene.bodies[1].position = Vec3::zeros();
+    scene.bodies[1].ambient = ambient;
+
+    let mut camera = Camera::new(-LIGHT_DIRECTION * 5.0, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.dither = false;
+    renderer.render_scene(&mut framebuffer, &scene, &camera);
+    let color = framebuffer.pixel(WIDTH / 2, HEIGHT / 2).unwrap();
+    [color >> 16, color >> 8, color].into_iter().map(|channel| channel & 0xFF).max().unwrap()
+}
+
+#[test]
+fn the_unlit_side_keeps_the_floor_of_light() {
+    assert_eq!(night_side(0.0), 0, "without a floor the night side is black");
+    let dim = night_side(0.05);
+    let bright = night_side(0.2);
+    assert!(dim > 0 && bright > dim, "{} then {}", dim, bright);
+}
+
+#[test]
+fn each_body_type_has_its_own_floor_and_scene_files_override_it() {
+    assert!(ambient_floor(PlanetType::Moon) < ambient_floor(PlanetType::Earth));
+    assert_eq!(ambient_floor(PlanetType::Sun), 0.0);
+
+    let source = "[[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 20\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 30\nambient = 0.15\n";
+    let scene = scene(source);
+    assert_eq!(scene.bodies[1].ambient, ambient_floor(PlanetType::Earth));
+    assert_eq!(scene.bodies[2].ambient, 0.15, "per body, not per type");
+
+    for bad in ["-0.1", "1.5", "dark"] {
+        let source = format!("[[body]]\ntype = \"sun\"\nambient = {}\n", bad);
+        let error = SceneConfig::parse(&source).expect_err(bad);
+        assert!(error.contains("ambient"), "{}", error);
+    }
+}
@@ -0,0 +1,72 @@
+use space_travel::mesh::ship_placeholder;
+use space_travel::obj::{LoadOptions, Obj, ObjError};
+
+const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\n";
+
+fn parse(source: &str) -> Result<Obj, ObjError> {
+    Obj::parse(source, "corrupt.obj", &LoadOptions::default())
+}
+
+// The line the parser stopped at, failing the test for any other kind of error
+fn error_line(source: &str) -> usize {
+    match parse(source) {
+        Err(ObjError::Parse(path, line, _)) => {
+            assert_eq!(path, "corrupt.obj");
+            line
+        }
+        Err(err) => panic!("expected a parse error, got {}", err),
+        Ok(_) => panic!("corrupt OBJ parsed:\n{}", source),
+    }
+}
+
+#[test]
+fn a_valid_string_parses() {
+    let obj = parse(&format!("{}f 1/1/1 2/1/1 3/1/1\n", TRIANGLE)).expect("valid OBJ");
+    assert_eq!(obj.indices().len(), 3);
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    assert_eq!(error_line("v 0 0 0\nv 1 zero 0\n"), 2);
+    assert_eq!(error_line("v 0 0\n"), 1);
+    assert_eq!(error_line("vn 0 0 x\n"), 1);
+    // Faces pointing past what was read so far, or written wrong
+    assert_eq!(error_line(&format!("{}f 1 2 4\n", TRIANGLE)), 6);
+    assert_eq!(error_line(&format!("{}f 1/2 2 3\n", TRIANGLE)), 6);
+    assert_eq!(error_line(&format!("{}f 1//2 2 3\n", TRIANGLE)), 6);
+    assert_eq!(error_line(&format!("{}\n# comment\nf 1 2 a\n", TRIANGLE)), 8);
+    assert_eq!(error_line(&format!("{}f 1/1/1/1 2 3\n", TRIANGLE)), 6);
+
+    let message = parse("v 0 0 0\nv 1 zero 0\n").err().expect("parse error").to_string();
+    assert!(message.starts_with("corrupt.obj: línea 2:") && message.contains("'zero'"), "{}", message);
+}
+
+#[test]
+fn missing_files_are_not_parse_errors() {
+    match Obj::load("tests/fixtures/missing.obj") {
+        Err(err @ ObjError::Io(..)) => assert_eq!(err.to_string(), "no se encontró tests/fixtures/missing.obj"),
+        Err(err) => panic!("expected an I/O error, got {}", err),
+        Ok(_) => panic!("a missing file loaded"),
+    }
+}
+
+#[test]
+fn the_ship_placeholder_is_centered_and_faces_outward() {
+    let mesh = ship_placeholder();
+    assert!(mesh.triangle_count() > 0);
+
+    let (mut min, mut max) = (mesh.vertices[0].position, mesh.vertices[0].position);
+    for vertex in &mesh.vertices {
+        min = min.inf(&vertex.position);
+        max = max.sup(&vertex.position);
+    }
+    assert!(((max - min).max() - 1.0).abs() < 1e-6, "not 1 long: {} to {}", min, max);
+    assert!((max + min).magnitude() < 1e-6, "not centered: {} to {}", min, max);
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| &mesh.vertices[index as usize]);
+        let normal = (b.position - a.position).cross(&(c.position - a.position));
+        assert!(normal.dot(&(a.position + b.position + c.position)) > 0.0, "inward face");
+        assert!((normal.normalize() - a.normal).magnitude() < 1e-5, "normal doesn't match winding");
+    }
+}
@@ -0,0 +1,83 @@
+use space_travel::color::Color;
+use space_travel::exposure::Exposure;
+use space_travel::framebuffer::Framebuffer;
+
+const WIDTH: usize = 37; // odd sizes, so the meter's levels don't divide evenly
+const HEIGHT: usize = 23;
+const STEP: f32 = 1.0 / 60.0;
+
+// Geometry of a single grey level over the whole frame (depth 0.5), seen at `exposure`
+// the way the renderer would have drawn it
+fn frame(grey: u8, exposure: f32) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    let color = Color::new(grey, grey, grey).exposed(exposure).to_hex();
+    framebuffer.buffer.fill(color);
+    framebuffer.zbuffer.fill(0.5);
+    framebuffer
+}
+
+// Run the adaptation for `seconds` on a scene of `grey`, re-rendered at each new exposure
+fn adapt(exposure: &mut Exposure, grey: u8, seconds: f32) {
+    for _ in 0..(seconds / STEP).round() as usize {
+        exposure.update_auto(&frame(grey, exposure.value), STEP);
+    }
+}
+
+// The same on a frame that stays as it is
+fn adapt_on(exposure: &mut Exposure, framebuffer: &Framebuffer, seconds: f32) {
+    for _ in 0..(seconds / STEP).round() as usize {
+        exposure.update_auto(framebuffer, STEP);
+    }
+}
+
+#[test]
+fn a_bright_scene_darkens_within_about_a_second_and_a_dim_one_brightens_back() {
+    let mut exposure = Exposure::new();
+    assert!(exposure.auto, "on from the start");
+
+    adapt(&mut exposure, 230, 0.1);
+    let early = exposure.value;
+    assert!(early < 1.0 && early > 0.7, "adapts gradually, not at once: {}", early);
+    adapt(&mut exposure, 230, 0.9);
+    let settled = exposure.value;
+    adapt(&mut exposure, 230, 5.0);
+    assert!((settled.log2() - exposure.value.log2()).abs() < 0.1, "{} after a second, {} at rest", settled, exposure.value);
+    assert!(exposure.value < 0.6);
+
+    adapt(&mut exposure, 30, 2.0);
+    assert!(exposure.value > 1.5, "{}", exposure.value);
+}
+
+#[test]
+fn the_multiplier_stays_within_its_range() {
+    let mut exposure = Exposure::new();
+    adapt(&mut exposure, 255, 10.0);
+    let lowest = exposure.value;
+    adapt(&mut exposure, 1, 10.0);
+    let highest = exposure.value;
+    assert!(lowest > 0.2 && highest < 5.0, "{} .. {}", lowest, highest);
+}
+
+#[test]
+fn only_geometry_is_metered_and_manual_exposure_is_left_alone() {
+    // A bright sky behind nothing changes nothing
+    let mut sky = Framebuffer::new(WIDTH, HEIGHT);
+    sky.buffer.fill(0xFFFFFF);
+    let mut exposure = Exposure::new();
+    exposure.update_auto(&sky, STEP);
+    assert_eq!(exposure.value, 1.0);
+
+    // A small bright body on the sky is metered on its own
+    for y in 10..13 {
+        for x in 20..23 {
+            sky.zbuffer[y * WIDTH + x] = 0.5;
+        }
+    }
+    adapt_on(&mut exposure, &sky, 2.0);
+    assert!(exposure.value < 0.6, "{}", exposure.value);
+
+    let mut manual = Exposure::new();
+    manual.auto = false;
+    adapt(&mut manual, 255, 1.0);
+    assert_eq!(manual.value, 1.0);
+}
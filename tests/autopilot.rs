@@ -0,0 +1,134 @@
+use minifb::Key;
+use nalgebra_glm::Vec3;
+use space_travel::autopilot::{Autopilot, AutopilotEvent, ARRIVAL_DISTANCE, MAX_SPEED, MAX_THRUST};
+use space_travel::camera::Camera;
+use space_travel::input::{Action, InputMap};
+use space_travel::planet::PlanetType;
+use space_travel::scene::{Scene, SceneConfig};
+
+mod common;
+
+const DT: f32 = 1.0 / 60.0;
+const STANDOFF: f32 = 3.0;
+
+fn scene() -> Scene {
+    common::scene_from_config(&SceneConfig::default())
+}
+
+fn camera(eye: Vec3) -> Camera {
+    Camera::new(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0))
+}
+
+// A planet that moves, as far out as the default scene goes
+fn outer_planet(scene: &Scene) -> usize {
+    (0..scene.bodies.len())
+        .filter(|&index| scene.velocity(index).magnitude() > 0.0)
+        .max_by(|&a, &b| scene.bodies[a].position.magnitude().total_cmp(&scene.bodies[b].position.magnitude()))
+        .expect("orbiting bodies")
+}
+
+// Fly from the same start to `target`, checking the engines' limits on the way, until it
+// arrives (true) or two minutes pass
+fn fly(scene: &mut Scene, target: usize, camera: &mut Camera, autopilot: &mut Autopilot) -> bool {
+    autopilot.engage(scene, target, camera.eye, STANDOFF);
+    assert_eq!(autopilot.target(), Some(target));
+    let mut previous_velocity = Vec3::zeros();
+    for _ in 0..(120.0 / DT) as usize {
+        scene.update(DT);
+        if let Some(event) = autopilot.update(scene, camera, DT) {
+            assert_eq!(event, AutopilotEvent::Arrived(target));
+            return true;
+        }
+        assert!((autopilot.velocity - previous_velocity).magnitude() <= MAX_THRUST * DT * 1.001);
+        assert!(autopilot.velocity.magnitude() <= MAX_SPEED + scene.velocity(target).magnitude() + 0.1);
+        previous_velocity = autopilot.velocity;
+    }
+    false
+}
+
+#[test]
+fn flies_to_a_moving_body_and_stops_beside_it() {
+    let mut scene = scene();
+    scene.update(DT);
+    let moon = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Moon).expect("the moon");
+    // The moon, turning around the earth in a few seconds, is the hardest to catch
+    for target in [outer_planet(&scene), moon] {
+        let start = Vec3::new(-40.0, 15.0, 40.0);
+        let mut camera = camera(start);
+        let mut autopilot = Autopilot::new();
+        let arrived = fly(&mut scene, target, &mut camera, &mut autopilot);
+        assert!(arrived, "{} still {:?} away", scene.bodies[target].name, autopilot.distance(&scene, camera.eye));
+        assert_eq!(autopilot.target(), None, "engines cut on arrival");
+        let from_body = (camera.eye - scene.bodies[target].position).magnitude();
+        assert!((from_body - STANDOFF).abs() < ARRIVAL_DISTANCE * 2.0, "{}", from_body);
+        // Facing the body it came to
+        let to_body = (scene.bodies[target].position - camera.eye).normalize();
+        assert!(camera.look_direction().dot(&to_body) > 0.9);
+        // The way it flew stays to look at
+        let path = autopilot.path();
+        assert!(path.len() > 10);
+        assert_eq!(path[0], start);
+        assert_eq!(*path.last().unwrap(), camera.eye);
+    }
+}
+
+#[test]
+fn reports_distance_and_eta_on_the_way() {
+    let mut scene = scene();
+    scene.update(DT);
+    let target = outer_planet(&scene);
+    let mut camera = camera(Vec3::new(-40.0, 15.0, 40.0));
+    let mut autopilot = Autopilot::new();
+    assert_eq!(autopilot.distance(&scene, camera.eye), None);
+    autopilot.engage(&scene, target, camera.eye, STANDOFF);
+    let start = autopilot.distance(&scene, camera.eye).unwrap();
+
+    for _ in 0..120 {
+        scene.update(DT);
+        autopilot.update(&scene, &mut camera, DT);
+    }
+    let distance = autopilot.distance(&scene, camera.eye).unwrap();
+    assert!(distance < start);
+    let eta = autopilot.eta(&scene, camera.eye).expect("closing in");
+    assert!(eta > 0.0 && eta < distance, "{} s for {} u", eta, distance);
+}
+
+#[test]
+fn anything_else_moving_the_camera_ends_the_flight() {
+    let mut scene = scene();
+    scene.update(DT);
+    let target = outer_planet(&scene);
+    let mut autopilot = Autopilot::new();
+
+    // A warp or the keys moving the ship
+    let mut camera = camera(Vec3::new(-40.0, 15.0, 40.0));
+    autopilot.engage(&scene, target, camera.eye, STANDOFF);
+    assert_eq!(autopilot.update(&scene, &mut camera, DT), None);
+    camera.move_center(Vec3::new(0.5, 0.0, 0.0));
+    assert_eq!(autopilot.update(&scene, &mut camera, DT), Some(AutopilotEvent::Interrupted));
+    assert_eq!(autopilot.update(&scene, &mut camera, DT), None);
+
+    // Following a body
+    autopilot.engage(&scene, target, camera.eye, STANDOFF);
+    camera.start_follow(0, scene.bodies[0].position, 5.0);
+    assert_eq!(autopilot.update(&scene, &mut camera, DT), Some(AutopilotEvent::Interrupted));
+
+    // Cancelling keeps the ship where it is
+    camera.stop_follow();
+    autopilot.engage(&scene, target, camera.eye, STANDOFF);
+    let eye = camera.eye;
+    assert!(autopilot.cancel());
+    assert!(!autopilot.cancel());
+    assert_eq!(autopilot.update(&scene, &mut camera, DT), None);
+    assert_eq!(camera.eye, eye);
+}
+
+#[test]
+fn g_engages_it_and_shading_moved_to_f11() {
+    let map = InputMap::new();
+    assert_eq!(map.keys(Action::Autopilot), [Key::G]);
+    assert_eq!(map.keys(Action::Shading), [Key::F11]);
+    for action in Action::PILOTING {
+        assert!(!map.keys(action).contains(&Key::G), "{:?}", action);
+    }
+}
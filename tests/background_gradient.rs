@@ -0,0 +1,37 @@
+use space_travel::framebuffer::Framebuffer;
+use space_travel::scene::SceneConfig;
+
+mod common;
+
+const BODY: &str = "[[body]]\ntype = \"sun\"\n";
+
+#[test]
+fn the_scene_file_sets_the_background_gradient() {
+    assert_eq!(SceneConfig::parse(BODY).expect("scene").background_gradient, None);
+    let config = SceneConfig::parse(&format!("background_gradient = [\"#0b1026\", 0x000000]\n{}", BODY)).expect("scene");
+    assert_eq!(config.background_gradient, Some((0x0B1026, 0x000000)));
+
+    for bad in ["\"#0B1026\"", "[\"#0B1026\"]", "[\"#0B1026\", \"#000000\", \"#FFFFFF\"]", "[\"#0B1026\", \"black\"]"] {
+        let error = SceneConfig::parse(&format!("background_gradient = {}\n{}", bad, BODY)).expect_err(bad);
+        assert!(error.contains("background_gradient"), "{}", error);
+    }
+}
+
+#[test]
+fn frames_clear_to_the_scene_gradient() {
+    let mut scene = common::scene_from(&format!("background_gradient = [\"#FF0000\", \"#0000FF\"]\n{}", BODY));
+    assert_eq!(scene.background_gradient, Some((0xFF0000, 0x0000FF)));
+    let mut framebuffer = Framebuffer::new(4, 5);
+    framebuffer.set_background_gradient(scene.background_gradient);
+    framebuffer.clear();
+    assert_eq!(framebuffer.pixel(0, 0), Some(0xFF0000));
+    assert_eq!(framebuffer.pixel(3, 4), Some(0x0000FF));
+    let middle = framebuffer.pixel(2, 2).unwrap();
+    assert!(middle >> 16 & 0xFF > 0 && middle & 0xFF > 0, "{:06X}", middle);
+
+    // Reloading a scene without one goes back to the solid clear
+    let config = SceneConfig::parse(BODY).expect("scene");
+    let body_mesh = scene.bodies[0].mesh.clone();
+    scene.load(&config, body_mesh);
+    assert_eq!(scene.background_gradient, None);
+}
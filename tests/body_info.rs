@@ -0,0 +1,66 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::Vec3;
+use space_travel::info_panel::{format_distance, format_duration, BodyInfo};
+use space_travel::scene::Scene;
+
+mod common;
+
+const STEP: f32 = 1.0 / 60.0;
+
+// A sun, a circular earth with the moon and a rocky planet, in that order
+fn scene() -> Scene {
+    let source = "[[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 20\n\
+        [[body]]\ntype = \"moon\"\norbit_radius = 5\n\
+        [[body]]\ntype = \"rocky\"\norbit_radius = 30\n";
+    common::scene_from(source)
+}
+
+#[test]
+fn bodies_know_what_they_orbit() {
+    let scene = scene();
+    assert_eq!((0..4).map(|i| scene.parent(i)).collect::<Vec<_>>(), [None, Some(0), Some(1), Some(0)]);
+    assert_eq!((0..4).map(|i| scene.child_count(i)).collect::<Vec<_>>(), [2, 1, 0, 0]);
+}
+
+#[test]
+fn speed_and_period_follow_the_orbit_model() {
+    let mut scene = scene();
+    assert_eq!(scene.orbital_speed(1), 0.0, "no step yet");
+    scene.update(STEP);
+
+    // On a circle the speed is the radius times the angular speed
+    let expected = 20.0 * scene.orbit_speeds[1];
+    assert!((scene.orbital_speed(1) - expected).abs() < expected * 1e-3, "{} vs {}", scene.orbital_speed(1), expected);
+    assert_eq!(scene.orbital_speed(0), 0.0);
+
+    assert_eq!(scene.orbital_period(0), None);
+    assert_eq!(scene.orbital_period(1), Some(TAU / scene.orbit_speeds[1]));
+    assert_eq!(scene.orbital_period(2), Some(TAU / scene.orbit_speeds[2]));
+}
+
+#[test]
+fn panel_values_are_measured_on_the_current_frame() {
+    let mut scene = scene();
+    scene.update(STEP);
+    let camera = scene.bodies[1].position + Vec3::new(0.0, 3.0, 4.0);
+    let info = BodyInfo::new(&scene, 1, camera);
+    assert_eq!(info.name, scene.bodies[1].name);
+    assert!((info.sun_distance - 20.0).abs() < 1e-3);
+    assert!((info.camera_distance - 5.0).abs() < 1e-4);
+    assert_eq!(info.children, 1);
+
+    // A step later the earth has moved on; the camera stayed
+    scene.update(STEP);
+    let later = BodyInfo::new(&scene, 1, camera);
+    assert_ne!(later.camera_distance, info.camera_distance);
+}
+
+#[test]
+fn numbers_keep_about_three_digits() {
+    assert_eq!(format_distance(1.23456), "1.23 u");
+    assert_eq!(format_distance(42.345), "42.3 u");
+    assert_eq!(format_distance(1234.5), "1234 u");
+    assert_eq!(format_duration(75.5), "75.5 s");
+    assert_eq!(format_duration(600.0), "10.0 min");
+}
@@ -0,0 +1,92 @@
+use minifb::Key;
+use space_travel::body_list::{next_selection, previous_selection, BodyList, VISIBLE_ROWS};
+use space_travel::input::{Action, InputMap};
+use space_travel::scene::Scene;
+
+mod common;
+
+// The sun and `planets` rocky planets
+fn scene(planets: usize) -> Scene {
+    let mut source = "[[body]]\ntype = \"sun\"\n".to_string();
+    for i in 0..planets {
+        source.push_str(&format!("[[body]]\ntype = \"rocky\"\nname = \"P{}\"\norbit_radius = {}\n", i, 10 + i * 5));
+    }
+    common::scene_from(&source)
+}
+
+#[test]
+fn the_list_scrolls_to_keep_the_selection_in_view() {
+    let len = 14;
+    let mut list = BodyList::new();
+    assert!(!list.visible());
+    list.reveal(Some(2), len);
+    assert!(list.visible());
+    assert_eq!(list.rows(len), 0..VISIBLE_ROWS);
+    assert_eq!(list.body_at_row(2, len), Some(2));
+
+    // Past the last row it scrolls by as little as it takes
+    list.reveal(Some(VISIBLE_ROWS), len);
+    assert_eq!(list.rows(len), 1..VISIBLE_ROWS + 1);
+    assert_eq!(list.body_at_row(0, len), Some(1), "number keys follow the rows shown");
+    list.reveal(Some(len - 1), len);
+    assert_eq!(list.rows(len), len - VISIBLE_ROWS..len);
+    // A selection already in view doesn't move it
+    list.reveal(Some(len - 3), len);
+    assert_eq!(list.rows(len), len - VISIBLE_ROWS..len);
+    list.reveal(Some(0), len);
+    assert_eq!(list.rows(len), 0..VISIBLE_ROWS);
+
+    // A short scene never scrolls, and rows past its end hold nothing
+    let mut short = BodyList::new();
+    short.reveal(Some(3), 4);
+    assert_eq!(short.rows(4), 0..4);
+    assert_eq!(short.body_at_row(4, 4), None);
+    assert_eq!(short.body_at_row(VISIBLE_ROWS, 20), None);
+
+    // It fades out when left alone
+    list.update(10.0);
+    assert!(!list.visible());
+}
+
+#[test]
+fn tab_and_shift_tab_step_through_the_shown_bodies() {
+    let mut scene = scene(11);
+    scene.bodies[3].visibility.body = false;
+    let len = scene.bodies.len();
+
+    let mut forward = vec![];
+    let mut current = None;
+    while let Some(index) = next_selection(&scene.bodies, current) {
+        forward.push(index);
+        current = Some(index);
+    }
+    assert_eq!(forward, (0..len).filter(|&index| index != 3).collect::<Vec<_>>());
+
+    let mut backward = vec![];
+    let mut current = None;
+    while let Some(index) = previous_selection(&scene.bodies, current) {
+        backward.push(index);
+        current = Some(index);
+    }
+    backward.reverse();
+    assert_eq!(backward, forward, "Shift+Tab walks back the way Tab came");
+    assert_eq!(previous_selection(&scene.bodies, Some(0)), None);
+}
+
+#[test]
+fn number_keys_select_and_no_longer_share_keys_with_the_zoom() {
+    let map = InputMap::new();
+    let digits = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+    for (action, key) in Action::SELECT_ROWS.iter().zip(digits) {
+        assert_eq!(map.keys(*action), [key]);
+    }
+    for other in [Action::ZoomIn, Action::ZoomOut] {
+        for action in Action::SELECT_ROWS.iter().chain(&Action::WARPS) {
+            assert!(map.keys(other).iter().all(|key| !map.keys(*action).contains(key)), "{:?} and {:?}", other, action);
+        }
+    }
+    // The direct warps are still there for whoever rebinds them back
+    let (map, warnings) = InputMap::parse("warp_1 = \"Key1\"\nselect_1 = []\n");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(map.keys(Action::Warp1), [Key::Key1]);
+}
@@ -0,0 +1,101 @@
+use nalgebra_glm::{look_at, Vec3};
+use space_travel::bounds::BoundsOverlay;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::impostor::projected_radius;
+use space_travel::scene::{Scene, SceneConfig};
+use space_travel::uniforms::FrameUniforms;
+
+mod common;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 40;
+
+// Only the first body of the default scene left visible, at `position`
+fn scene(position: Vec3, scale: f32) -> Scene {
+    let mut scene = common::scene_from_config(&SceneConfig::default());
+    for body in &mut scene.bodies[1..] {
+        body.visibility.body = false;
+    }
+    scene.bodies[0].position = position;
+    scene.bodies[0].scale = scale;
+    scene
+}
+
+// Coordinates of the pixels the overlay drew, whatever their color
+fn drawn(overlay: &BoundsOverlay, uniforms: &FrameUniforms, culling: &FrameUniforms, scene: &Scene) -> (Framebuffer, Vec<(usize, usize)>) {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.clear();
+    overlay.draw(&mut framebuffer, uniforms, culling, &scene.bodies);
+    let pixels = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| framebuffer.pixel(x, y) != Some(0))
+        .collect();
+    (framebuffer, pixels)
+}
+
+fn red_and_green(color: u32) -> (u32, u32) {
+    ((color >> 16) & 0xFF, (color >> 8) & 0xFF)
+}
+
+#[test]
+fn a_kept_body_is_outlined_in_green_at_its_projected_radius() {
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    let scene = scene(Vec3::new(0.0, 0.0, -10.0), 1.0);
+    let mut overlay = BoundsOverlay::new();
+    assert!(drawn(&overlay, &uniforms, &uniforms, &scene).1.is_empty(), "nothing while off");
+
+    overlay.cycle(&uniforms);
+    let (framebuffer, pixels) = drawn(&overlay, &uniforms, &uniforms, &scene);
+    assert!(!pixels.is_empty());
+    for &(x, y) in &pixels {
+        let (red, green) = red_and_green(framebuffer.pixel(x, y).unwrap());
+        assert!(green > red, "({}, {}) should be green", x, y);
+    }
+
+    // The same radius the body's disc is drawn with
+    let radius = projected_radius(&uniforms, &framebuffer, scene.bodies[0].position, scene.bodies[0].bounding_radius());
+    let right = pixels.iter().map(|&(x, _)| x).max().unwrap() as f32 + 0.5;
+    assert!((right - (WIDTH as f32 / 2.0 + radius)).abs() <= 1.5, "right edge {} for radius {}", right, radius);
+}
+
+#[test]
+fn a_body_culled_by_its_center_is_outlined_in_red() {
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    // Center just past the right edge of the screen, with most of the sphere still in view
+    let edge = 10.0 / uniforms.projection_matrix[(0, 0)];
+    let scene = scene(Vec3::new(edge * 1.1, 0.0, -10.0), edge * 0.5);
+    let mut overlay = BoundsOverlay::new();
+    overlay.cycle(&uniforms);
+
+    let (framebuffer, pixels) = drawn(&overlay, &uniforms, &uniforms, &scene);
+    assert!(!pixels.is_empty(), "part of the outline is on screen");
+    for &(x, y) in &pixels {
+        let (red, green) = red_and_green(framebuffer.pixel(x, y).unwrap());
+        assert!(red > green, "({}, {}) should be red", x, y);
+    }
+}
+
+#[test]
+fn the_frozen_frustum_stays_behind_when_the_view_moves() {
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    let scene = scene(Vec3::new(0.0, 0.0, -10.0), 1.0);
+    let mut overlay = BoundsOverlay::new();
+    overlay.cycle(&uniforms);
+    overlay.cycle(&uniforms);
+    assert!(overlay.enabled && overlay.frozen());
+
+    // Seen from the side, the frustum's edges show up next to the body
+    let side = FrameUniforms {
+        view_matrix: look_at(&Vec3::new(30.0, 0.0, -10.0), &Vec3::new(0.0, 0.0, -10.0), &Vec3::new(0.0, 1.0, 0.0)),
+        camera_position: Vec3::new(30.0, 0.0, -10.0),
+        ..uniforms.clone()
+    };
+    let mut live = BoundsOverlay::new();
+    live.cycle(&uniforms);
+    let (_, outline) = drawn(&live, &side, &uniforms, &scene);
+    let (_, with_frustum) = drawn(&overlay, &side, &uniforms, &scene);
+    assert!(with_frustum.len() > outline.len());
+
+    overlay.cycle(&uniforms);
+    assert!(!overlay.enabled && !overlay.frozen());
+}
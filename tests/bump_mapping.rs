@@ -0,0 +1,116 @@
+use nalgebra_glm::{mat4_to_mat3, Vec2, Vec3};
+use space_travel::color::Color;
+use space_travel::fragment::Fragment;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+use space_travel::scene::{default_bump_strength, SceneConfig};
+use space_travel::shaders::{fragment_shader, Shading};
+use space_travel::transform::create_model_matrix;
+use space_travel::uniforms::{DrawUniforms, FrameUniforms};
+
+// A Phong frame lit from `light`, with the renderer's noise
+fn frame(light: Vec3) -> (Renderer, FrameUniforms) {
+    let renderer = Renderer::new(16, 16);
+    let frame = FrameUniforms { shading: Shading::Phong, override_light: Some(light), ..renderer.frame().clone() };
+    (renderer, frame)
+}
+
+// The fragment of a unit sphere drawn with `uniforms` at model-space `direction`
+fn fragment(direction: Vec3, uniforms: &DrawUniforms) -> Fragment {
+    let normal = (mat4_to_mat3(&uniforms.model_matrix) * direction).normalize();
+    Fragment {
+        position: Vec2::zeros(),
+        depth: 0.5,
+        vertex_position: direction,
+        world_position: normal,
+        normal,
+        tex_coords: Vec2::zeros(),
+        intensity: 0.0,
+        color: Color::black(),
+    }
+}
+
+// Points spread over the side of the unit sphere facing +z
+fn patch() -> Vec<Vec3> {
+    (0..24)
+        .flat_map(|i| (0..24).map(move |j| Vec3::new(i as f32 / 12.0 - 1.0, j as f32 / 12.0 - 1.0, 0.5).normalize()))
+        .collect()
+}
+
+#[test]
+fn the_noise_tilts_the_lighting_of_rocky_bodies_only() {
+    let light = Vec3::new(1.0, 0.0, 1.0).normalize();
+    let (renderer, frame) = frame(light);
+    let shade = |shader: PlanetType, bump_strength: f32| -> Vec<f32> {
+        let uniforms = DrawUniforms {
+            frame: &frame,
+            model_matrix: create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()),
+            shader,
+            params: renderer.shader_params.get(shader),
+            noise: &renderer.noise,
+            seed: 7,
+            base_color: None,
+            ambient: 0.0,
+            bump_strength,
+        };
+        patch().into_iter().map(|direction| fragment_shader(&fragment(direction, &uniforms), &uniforms).luminance()).collect()
+    };
+
+    for shader in [PlanetType::RockyPlanet, PlanetType::Moon, PlanetType::Asteroid] {
+        let (flat, bumped) = (shade(shader, 0.0), shade(shader, default_bump_strength(shader)));
+        let changed = flat.iter().zip(&bumped).filter(|(a, b)| (*a - *b).abs() > 0.005).count();
+        assert!(changed > flat.len() / 2, "{:?}: only {} of {} fragments changed", shader, changed, flat.len());
+        // Some slopes face the light and some face away
+        assert!(flat.iter().zip(&bumped).any(|(a, b)| b > a) && flat.iter().zip(&bumped).any(|(a, b)| b < a), "{:?}", shader);
+    }
+    assert_eq!(shade(PlanetType::Earth, 0.0), shade(PlanetType::Earth, 1.0), "the earth has no relief");
+}
+
+#[test]
+fn the_relief_turns_with_the_body_instead_of_swimming() {
+    // The same points of the moon lit from the same side of the moon, before and after it
+    // turns: every one is shaded the same
+    let rotation = Vec3::new(0.3, 1.1, -0.4);
+    let model_matrix = create_model_matrix(Vec3::zeros(), 1.0, rotation);
+    let model_light = Vec3::new(1.0, 0.5, 1.0).normalize();
+    let turned_light = (mat4_to_mat3(&model_matrix) * model_light).normalize();
+
+    let shade = |light: Vec3, model_matrix| -> Vec<Color> {
+        let (renderer, frame) = frame(light);
+        let uniforms = DrawUniforms {
+            frame: &frame,
+            model_matrix,
+            shader: PlanetType::Moon,
+            params: renderer.shader_params.get(PlanetType::Moon),
+            noise: &renderer.noise,
+            seed: 0,
+            base_color: None,
+            ambient: 0.0,
+            bump_strength: 1.0,
+        };
+        patch().into_iter().map(|direction| fragment_shader(&fragment(direction, &uniforms), &uniforms)).collect()
+    };
+    let still = shade(model_light, create_model_matrix(Vec3::zeros(), 1.0, Vec3::zeros()));
+    let turned = shade(turned_light, model_matrix);
+    for (a, b) in still.iter().zip(&turned) {
+        let difference = (a.r() - b.r()).abs() + (a.g() - b.g()).abs() + (a.b() - b.b()).abs();
+        assert!(difference <= 3.0, "{:?} then {:?}", a, b);
+    }
+}
+
+#[test]
+fn scene_files_set_the_relief_per_body() {
+    assert!(default_bump_strength(PlanetType::Moon) > 0.0);
+    assert_eq!(default_bump_strength(PlanetType::Earth), 0.0);
+
+    let source = "[[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"rocky\"\norbit_radius = 20\n\
+        [[body]]\ntype = \"rocky\"\norbit_radius = 30\nbump_strength = 0\n";
+    let config = SceneConfig::parse(source).expect("scene");
+    assert_eq!(config.bodies[1].bump_strength, default_bump_strength(PlanetType::RockyPlanet));
+    assert_eq!(config.bodies[2].bump_strength, 0.0, "per body, not per type");
+
+    let error = SceneConfig::parse("[[body]]\ntype = \"moon\"\nbump_strength = -1\n").expect_err("negative relief");
+    assert!(error.contains("bump_strength"), "{}", error);
+}
+
@@ -0,0 +1,121 @@
+use nalgebra_glm::Vec3;
+use space_travel::planet::PlanetType;
+use space_travel::scene::{Scene, SceneConfig, DEFAULT_CAPTURE_RANGE};
+
+mod common;
+
+const DT: f32 = 1.0 / 60.0;
+
+// The sun, an asteroid at radius 10 and an earth at radius 30 (so no moon)
+fn scene() -> Scene {
+    common::scene_from("[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"asteroid\"\norbit_radius = 10\n[[body]]\ntype = \"earth\"\norbit_radius = 30\n")
+}
+
+// Step the scene with the ship moving at `velocity` from `ship`, as main does each frame
+fn tow(scene: &mut Scene, ship: &mut Vec3, velocity: Vec3, steps: usize) {
+    for _ in 0..steps {
+        *ship += velocity * DT;
+        scene.hold(*ship, DT);
+        scene.update(DT);
+    }
+}
+
+#[test]
+fn only_small_bodies_in_range_can_be_captured() {
+    let scene = scene();
+    let asteroid = &scene.bodies[1];
+    let near = asteroid.position + Vec3::new(0.0, asteroid.scale + 1.0, 0.0);
+    assert_eq!(scene.capture_candidate(near), Some(1));
+    let far = asteroid.position + Vec3::new(0.0, asteroid.scale + DEFAULT_CAPTURE_RANGE + 0.5, 0.0);
+    assert_eq!(scene.capture_candidate(far), None);
+    // Planets are too big to tow
+    let earth = &scene.bodies[2];
+    assert_eq!(scene.capture_candidate(earth.position + Vec3::new(0.0, earth.scale + 0.5, 0.0)), None);
+
+    let scene = common::scene_from("capture_range = 10\n[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"asteroid\"\norbit_radius = 10\n");
+    assert_eq!(scene.capture_range, 10.0);
+    assert_eq!(scene.capture_candidate(far), Some(1));
+}
+
+#[test]
+fn a_captured_body_keeps_its_offset_from_the_ship() {
+    let mut scene = scene();
+    let mut ship = scene.bodies[1].position + Vec3::new(0.0, 1.0, 0.0);
+    let offset = scene.bodies[1].position - ship;
+    scene.capture(1, ship);
+    assert_eq!(scene.captured, Some(1));
+    assert_eq!(scene.orbital_period(1), None, "off its orbit");
+
+    tow(&mut scene, &mut ship, Vec3::new(3.0, 0.0, -2.0), 90);
+    assert!((scene.bodies[1].position - (ship + offset)).magnitude() < 1e-4);
+    assert!((scene.velocity(1) - Vec3::new(3.0, 0.0, -2.0)).magnitude() < 1e-2);
+    // The others go on as before
+    assert!((scene.bodies[2].position.magnitude() - 30.0).abs() < 1e-3);
+
+    // Reloading lets go of it
+    scene.load(&SceneConfig::default(), scene.bodies[0].mesh.clone());
+    assert_eq!(scene.captured, None);
+}
+
+#[test]
+fn released_near_a_planet_it_orbits_the_planet() {
+    let mut scene = scene();
+    let mut ship = scene.bodies[1].position + Vec3::new(0.0, 1.0, 0.0);
+    scene.capture(1, ship);
+    // Tow it next to the earth, then fly past it sideways
+    let earth = scene.bodies[2].position;
+    let start = ship;
+    tow(&mut scene, &mut ship, (earth + Vec3::new(3.0, -1.0, 0.0) - start) / 2.0, 120);
+    let sideways = Vec3::new(0.0, 0.0, 2.0);
+    let earth_velocity = scene.velocity(2);
+    tow(&mut scene, &mut ship, earth_velocity + sideways, 1);
+    let released_at = scene.bodies[1].position;
+    let offset = released_at - scene.bodies[2].position;
+    let across = offset.cross(&sideways).magnitude() / offset.magnitude();
+
+    assert_eq!(scene.release(), Some(1));
+    assert_eq!(scene.captured, None);
+    assert_eq!(scene.parent(1), Some(2), "around the earth");
+    // It starts from where it was let go, not back on its old ring
+    let path = scene.orbit_path(1);
+    assert!((path.position(path.angle) - released_at).magnitude() < 1e-3);
+    let radius = (released_at - scene.bodies[2].position).magnitude();
+    assert!((path.orbit.radius - radius).abs() < 1e-3);
+    assert!(scene.orbital_period(1).is_some());
+
+    // And stays around the earth as both move on
+    let mut moved = 0.0;
+    let mut last = scene.bodies[1].position - scene.bodies[2].position;
+    for step in 0..120 {
+        scene.update(DT);
+        let around = scene.bodies[1].position - scene.bodies[2].position;
+        assert!((around.magnitude() - radius).abs() < 1e-3);
+        if step == 0 {
+            assert!((around - last).dot(&sideways) > 0.0, "the way it was pushed");
+        }
+        moved += (around - last).magnitude();
+        last = around;
+    }
+    // At the speed it was let go with, across the line to the earth
+    assert!(across > 1.0);
+    assert!((moved / 2.0 - across).abs() < 0.05 * across, "{} u/s for {}", moved / 2.0, across);
+}
+
+#[test]
+fn released_at_rest_in_open_space_it_goes_on_at_its_old_pace() {
+    let mut scene = scene();
+    let pace = scene.orbit_speeds[1] * scene.orbits[1].radius;
+    let mut ship = scene.bodies[1].position + Vec3::new(0.0, 1.0, 0.0);
+    scene.capture(1, ship);
+    tow(&mut scene, &mut ship, Vec3::new(0.0, 0.0, 3.0), 60);
+    tow(&mut scene, &mut ship, Vec3::zeros(), 2);
+    let released_at = scene.bodies[1].position;
+
+    scene.release();
+    assert_eq!(scene.parent(1), scene.bodies.iter().position(|body| body.shader_type == PlanetType::Sun));
+    let path = scene.orbit_path(1);
+    assert!((path.position(path.angle) - released_at).magnitude() < 1e-3);
+    assert!((path.orbit.radius - released_at.magnitude()).abs() < 1e-3);
+    scene.update(DT);
+    assert!((scene.velocity(1).magnitude() - pace).abs() < 0.05 * pace, "{} for {}", scene.velocity(1).magnitude(), pace);
+}
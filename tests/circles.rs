@@ -0,0 +1,85 @@
+use nalgebra_glm::Vec2;
+use space_travel::framebuffer::Framebuffer;
+
+const SIZE: usize = 32;
+const WHITE: u32 = 0xFFFFFF;
+
+fn blank() -> Framebuffer {
+    Framebuffer::new(SIZE, SIZE)
+}
+
+// How much white each pixel got, 0 to 255, from the blue channel
+fn coverage(framebuffer: &Framebuffer, x: usize, y: usize) -> u32 {
+    framebuffer.pixel(x, y).unwrap() & 0xFF
+}
+
+fn touched(framebuffer: &Framebuffer) -> usize {
+    framebuffer.buffer.iter().filter(|&&pixel| pixel != 0).count()
+}
+
+#[test]
+fn discs_are_solid_inside_with_soft_symmetric_edges() {
+    let mut framebuffer = blank();
+    // Centered on a pixel corner, so the disc is symmetric about both axes through it
+    framebuffer.filled_circle(Vec2::new(16.0, 16.0), 6.0, WHITE);
+
+    assert_eq!(coverage(&framebuffer, 16, 16), 255);
+    assert_eq!(coverage(&framebuffer, 12, 15), 255);
+    assert_eq!(coverage(&framebuffer, 16, 25), 0);
+    assert_eq!(coverage(&framebuffer, 0, 0), 0);
+    let soft = framebuffer.buffer.iter().filter(|&&pixel| pixel != 0 && pixel != WHITE).count();
+    assert!(soft > 0, "no anti-aliased edge");
+
+    for y in 0..16 {
+        for x in 0..16 {
+            let c = coverage(&framebuffer, x, y);
+            assert_eq!(c, coverage(&framebuffer, 31 - x, y), "({}, {})", x, y);
+            assert_eq!(c, coverage(&framebuffer, x, 31 - y), "({}, {})", x, y);
+            assert_eq!(c, coverage(&framebuffer, y, x), "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn outlines_are_hollow() {
+    let mut framebuffer = blank();
+    framebuffer.circle(Vec2::new(16.0, 16.0), 8.0, WHITE);
+    assert_eq!(coverage(&framebuffer, 16, 16), 0);
+    assert_eq!(coverage(&framebuffer, 20, 16), 0);
+    // The pixels straddling the ring on the axes, 7.5 and 8.5 from the center
+    assert!(coverage(&framebuffer, 23, 16) > 100 && coverage(&framebuffer, 24, 16) > 100);
+    assert_eq!(coverage(&framebuffer, 26, 16), 0);
+    assert_eq!(framebuffer.zbuffer.iter().filter(|depth| depth.is_finite()).count(), 0);
+}
+
+#[test]
+fn circles_smaller_than_a_pixel_are_one_faint_point() {
+    let mut framebuffer = blank();
+    framebuffer.filled_circle(Vec2::new(5.5, 7.5), 0.3, WHITE);
+    assert_eq!(touched(&framebuffer), 1);
+    let c = coverage(&framebuffer, 5, 7);
+    assert!(c > 0 && c < 255, "{}", c);
+
+    // Smaller still is fainter, and nothing at all is nothing
+    let mut smaller = blank();
+    smaller.circle(Vec2::new(5.5, 7.5), 0.1, WHITE);
+    assert!(coverage(&smaller, 5, 7) < c);
+    let mut empty = blank();
+    empty.filled_circle(Vec2::new(5.5, 7.5), 0.0, WHITE);
+    assert_eq!(touched(&empty), 0);
+}
+
+#[test]
+fn circles_off_the_edge_are_clipped() {
+    let mut framebuffer = blank();
+    framebuffer.filled_circle(Vec2::new(-3.0, 2.0), 6.0, WHITE);
+    framebuffer.circle(Vec2::new(SIZE as f32 + 2.0, SIZE as f32 + 2.0), 5.0, WHITE);
+    assert!(coverage(&framebuffer, 0, 2) == 255 && coverage(&framebuffer, SIZE - 1, SIZE - 3) > 0);
+
+    let before = framebuffer.buffer.clone();
+    framebuffer.filled_circle(Vec2::new(-100.0, -100.0), 4.0, WHITE);
+    framebuffer.filled_circle(Vec2::new(f32::NAN, 4.0), 4.0, WHITE);
+    framebuffer.circle(Vec2::new(4.0, 4.0), -1.0, WHITE);
+    framebuffer.filled_circle(Vec2::new(1e9, 4.0), 0.2, WHITE);
+    assert_eq!(framebuffer.buffer, before);
+}
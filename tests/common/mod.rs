@@ -0,0 +1,36 @@
+// Scenes for the integration tests: the scene's own body mesh (the generated sphere unless
+// it names a model), a sky without stars so they don't land on the pixels being checked,
+// and meteors from a fixed seed. Each test file uses some of these
+#![allow(dead_code)]
+
+use space_travel::ambient::Meteors;
+use space_travel::mesh::MeshCache;
+use space_travel::scene::{build_scene, Scene, SceneConfig};
+use space_travel::skybox::{Skybox, SkyboxConfig};
+use space_travel::uniforms::FrameUniforms;
+
+// The scene a scene file with this text describes
+pub fn scene_from(source: &str) -> Scene {
+    scene_from_config(&SceneConfig::parse(source).expect("scene"))
+}
+
+pub fn scene_from_config(config: &SceneConfig) -> Scene {
+    scene_with(config, Skybox::new(SkyboxConfig { star_count: 0, ..SkyboxConfig::default() }), Meteors::new(1))
+}
+
+// Without the milky way band either, for tests that read the background's exact color
+pub fn dark_scene_from_config(config: &SceneConfig) -> Scene {
+    let skybox = Skybox::new(SkyboxConfig { star_count: 0, band_strength: 0.0, ..SkyboxConfig::default() });
+    scene_with(config, skybox, Meteors::new(1))
+}
+
+pub fn scene_with(config: &SceneConfig, skybox: Skybox, meteors: Meteors) -> Scene {
+    let body_mesh = config.body_mesh(&mut MeshCache::new()).expect("body mesh");
+    build_scene(config, body_mesh, skybox, meteors)
+}
+
+// Uniforms of a `width` x `height` frame before the first one is rendered: the view is the
+// identity, eye at the origin looking down -z
+pub fn frame(width: usize, height: usize) -> FrameUniforms {
+    FrameUniforms::first(width, height)
+}
@@ -0,0 +1,88 @@
+use nalgebra_glm::Vec3;
+use space_travel::dof::DepthOfField;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::scene::{Scene, SceneConfig};
+use space_travel::sprite::project;
+use space_travel::uniforms::FrameUniforms;
+
+mod common;
+
+const WIDTH: usize = 40;
+const HEIGHT: usize = 20;
+
+// The first body moved 10 units in front of the renderer's initial view (from the origin
+// down -z), the one the blur focuses on
+fn scene() -> Scene {
+    let mut scene = common::scene_from_config(&SceneConfig::default());
+    scene.bodies[0].position = Vec3::new(0.0, 0.0, -10.0);
+    scene.bodies[0].scale = 1.0;
+    scene
+}
+
+// A black and white checkerboard: the left half at the body's distance, the right half
+// `far` units away, with the top row left as sky
+fn checkerboard(uniforms: &FrameUniforms, far: f32) -> Framebuffer {
+    let depth = |distance: f32| project(uniforms, Vec3::new(0.0, 0.0, -distance)).unwrap().z;
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let index = y * WIDTH + x;
+            framebuffer.buffer[index] = if (x + y) % 2 == 0 { 0xFFFFFF } else { 0x000000 };
+            if y > 0 {
+                framebuffer.zbuffer[index] = depth(if x < WIDTH / 2 { 10.0 } else { far });
+            }
+        }
+    }
+    framebuffer
+}
+
+fn grey_level(color: u32) -> u32 {
+    color & 0xFF
+}
+
+#[test]
+fn the_focused_depth_stays_sharp_and_the_rest_blurs() {
+    let scene = scene();
+    for log_depth in [false, true] {
+        let mut uniforms = common::frame(WIDTH, HEIGHT);
+        uniforms.log_depth = log_depth;
+        let original = checkerboard(&uniforms, 100.0);
+        let mut framebuffer = checkerboard(&uniforms, 100.0);
+        let mut dof = DepthOfField::new();
+        dof.enabled = true;
+        dof.apply(&mut framebuffer, &uniforms, &scene.bodies[0]);
+
+        for y in 1..HEIGHT {
+            for x in 0..WIDTH / 2 - 1 {
+                assert_eq!(framebuffer.pixel(x, y), original.pixel(x, y), "({}, {}) should be sharp", x, y);
+            }
+            // Far from the divide, the checkerboard averages out to grey
+            for x in WIDTH / 2 + 8..WIDTH {
+                let level = grey_level(framebuffer.pixel(x, y).unwrap());
+                assert!((100..=155).contains(&level), "({}, {}) = {} (log {})", x, y, level, log_depth);
+            }
+        }
+        // The sky is at infinity: always blurred, even in front of the in-focus half
+        let sky = grey_level(framebuffer.pixel(4, 0).unwrap());
+        assert!((60..=195).contains(&sky), "sky {}", sky);
+        assert_eq!(framebuffer.zbuffer, original.zbuffer, "depth is left alone");
+    }
+}
+
+#[test]
+fn nothing_changes_while_disabled_or_within_the_range() {
+    let scene = scene();
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    let mut dof = DepthOfField::new();
+
+    let mut framebuffer = checkerboard(&uniforms, 100.0);
+    dof.apply(&mut framebuffer, &uniforms, &scene.bodies[0]);
+    assert_eq!(framebuffer.buffer, checkerboard(&uniforms, 100.0).buffer);
+
+    // The range plus the body's radius, 5 units here, still counts as in focus
+    dof.enabled = true;
+    let mut framebuffer = checkerboard(&uniforms, 14.5);
+    dof.apply(&mut framebuffer, &uniforms, &scene.bodies[0]);
+    let original = checkerboard(&uniforms, 14.5);
+    assert!((1..HEIGHT).all(|y| (0..WIDTH).all(|x| framebuffer.pixel(x, y) == original.pixel(x, y))));
+}
@@ -0,0 +1,50 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use nalgebra_glm::Vec3;
+use space_travel::ambient::Meteors;
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::renderer::Renderer;
+use space_travel::scene::SceneConfig;
+use space_travel::seeds::Seeds;
+use space_travel::skybox::{NebulaConfig, Skybox, SkyboxConfig};
+
+mod common;
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+const DT: f32 = 1.0 / 60.0;
+
+// What main does with --deterministic --seed <root>: build everything from the root seed,
+// step `frame` fixed updates and render that frame, returning a hash of the pixels
+fn render_frame_hash(root: u64, frame: u32) -> u64 {
+    let seeds = Seeds::from_root(root);
+    let config = SceneConfig::default();
+    let skybox = Skybox::new(SkyboxConfig { seed: seeds.sky, ..SkyboxConfig::default() })
+        .with_nebula(Some(NebulaConfig { seed: seeds.nebula, ..NebulaConfig::default() }));
+    let mut scene = common::scene_with(&config, skybox, Meteors::new(seeds.meteors));
+    for _ in 0..frame {
+        scene.update(DT);
+    }
+
+    let camera = Camera::new(Vec3::new(0.0, 40.0, 120.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT).with_seeds(&seeds);
+    renderer.time_from_frame = true;
+    renderer.set_shader_params(config.shader_params());
+    renderer.render_scene(&mut framebuffer, &scene, &camera);
+
+    let mut hasher = DefaultHasher::new();
+    framebuffer.buffer.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn same_frame_renders_byte_identical_twice() {
+    assert_eq!(render_frame_hash(42, 90), render_frame_hash(42, 90));
+}
+
+#[test]
+fn root_seed_changes_the_frame() {
+    assert_ne!(render_frame_hash(42, 90), render_frame_hash(43, 90));
+}
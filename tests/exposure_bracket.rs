@@ -0,0 +1,68 @@
+use nalgebra_glm::Vec3;
+use space_travel::bracket::{render_bracket, save_bracket, BRACKET_STOPS};
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::color::Color;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::renderer::Renderer;
+use space_travel::scene::{Scene, SceneConfig};
+
+mod common;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 60;
+
+// The default system a step in, seen from above
+fn setup() -> (Scene, Camera, Renderer) {
+    let config = SceneConfig::default();
+    let mut scene = common::dark_scene_from_config(&config);
+    scene.update(1.0 / 60.0);
+    let mut camera = Camera::new(Vec3::new(0.0, 8.0, 12.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.set_shader_params(config.shader_params());
+    (scene, camera, renderer)
+}
+
+fn total_luminance(framebuffer: &Framebuffer) -> f32 {
+    framebuffer.buffer.iter().map(|&color| Color::from_hex(color).luminance()).sum()
+}
+
+#[test]
+fn the_shots_differ_only_in_exposure() {
+    let (scene, camera, mut renderer) = setup();
+    renderer.exposure = 1.5;
+    let template = Framebuffer::new(WIDTH, HEIGHT);
+    let mut post_runs = 0;
+    let shots = render_bracket(&mut renderer, &template, &scene, &camera, |_, _| post_runs += 1);
+    assert_eq!(shots.iter().map(|shot| shot.stops).collect::<Vec<_>>(), BRACKET_STOPS);
+    assert_eq!(post_runs, BRACKET_STOPS.len());
+    assert_eq!(renderer.exposure, 1.5, "the live exposure is restored");
+
+    // The middle shot is the frame as it would be drawn anyway
+    let mut live = Framebuffer::new(WIDTH, HEIGHT);
+    renderer.render_scene(&mut live, &scene, &camera);
+    assert_eq!(shots[1].framebuffer.buffer, live.buffer);
+
+    let [under, normal, over] = [0, 1, 2].map(|i| total_luminance(&shots[i].framebuffer));
+    assert!(under < normal && normal < over, "{} {} {}", under, normal, over);
+}
+
+#[test]
+fn brackets_are_numbered_without_overwriting_earlier_ones() {
+    let (scene, camera, mut renderer) = setup();
+    let shots = render_bracket(&mut renderer, &Framebuffer::new(WIDTH, HEIGHT), &scene, &camera, |_, _| {});
+    let dir = std::env::temp_dir().join(format!("space_travel_bracket_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let first = save_bracket(&shots, &dir).expect("first bracket");
+    let second = save_bracket(&shots, &dir).expect("second bracket");
+    let names = |files: &[std::path::PathBuf]| {
+        files.iter().map(|file| file.file_name().unwrap().to_string_lossy().into_owned()).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&first), ["bracket_001_-1ev.png", "bracket_001_+0ev.png", "bracket_001_+1ev.png"]);
+    assert_eq!(names(&second), ["bracket_002_-1ev.png", "bracket_002_+0ev.png", "bracket_002_+1ev.png"]);
+    for file in first.iter().chain(&second) {
+        assert!(std::fs::read(file).expect("written").starts_with(b"\x89PNG"));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,133 @@
+use space_travel::scene::{Scene, SceneConfig};
+use space_travel::timing::{FixedTimestep, MAX_STEPS_PER_FRAME, TIME_SCALE_MAX, TIME_SCALE_MIN};
+
+mod common;
+
+const STEP: f32 = 1.0 / 60.0;
+const EPSILON: f32 = 1e-5;
+
+fn scene() -> Scene {
+    common::scene_from_config(&SceneConfig::default())
+}
+
+#[test]
+fn leftover_time_carries_to_the_next_frame() {
+    let mut timestep = FixedTimestep::new(0.01);
+    assert_eq!(timestep.advance(0.004), 0);
+    assert!((timestep.alpha() - 0.4).abs() < EPSILON);
+    assert_eq!(timestep.advance(0.021), 2);
+    assert!((timestep.alpha() - 0.5).abs() < EPSILON, "{}", timestep.alpha());
+    assert_eq!(timestep.advance(0.005), 1);
+    assert!(timestep.alpha() < EPSILON);
+}
+
+#[test]
+fn a_long_stall_runs_at_most_a_few_steps() {
+    let mut timestep = FixedTimestep::new(STEP);
+    assert_eq!(timestep.advance(2.0), MAX_STEPS_PER_FRAME);
+    // The rest of the backlog is gone, not queued for the next frames
+    assert!(timestep.alpha() < 1.0);
+    assert_eq!(timestep.advance(STEP * 0.5), 0);
+}
+
+#[test]
+fn the_simulation_does_not_depend_on_the_frame_rate() {
+    // About two seconds at an uneven frame rate, against the same steps taken one by one
+    let mut timestep = FixedTimestep::new(STEP);
+    let mut uneven = scene();
+    let mut steps = 0;
+    for frame in 0..90 {
+        let dt = [0.007, 0.031, 0.016, 0.044, 0.002][frame % 5];
+        for _ in 0..timestep.advance(dt) {
+            uneven.update(STEP);
+            steps += 1;
+        }
+        uneven.interpolate(timestep.alpha());
+    }
+
+    let mut even = scene();
+    for _ in 0..steps {
+        even.update(STEP);
+    }
+    assert_eq!(uneven.frame, even.frame);
+    assert_eq!(uneven.angles, even.angles);
+    uneven.interpolate(1.0);
+    for (a, b) in uneven.bodies.iter().zip(&even.bodies) {
+        assert!((a.position - b.position).magnitude() < EPSILON, "{}", a.name);
+        assert_eq!(a.trail.particles.len(), b.trail.particles.len(), "{}", a.name);
+    }
+}
+
+#[test]
+fn bodies_are_drawn_between_the_last_two_steps() {
+    let mut scene = scene();
+    scene.update(STEP);
+    let previous: Vec<_> = scene.bodies.iter().map(|body| body.position).collect();
+    let previous_time = scene.time;
+    scene.update(STEP);
+    let latest: Vec<_> = scene.bodies.iter().map(|body| body.position).collect();
+
+    scene.interpolate(0.0);
+    assert_eq!(scene.time, previous_time);
+    for (body, expected) in scene.bodies.iter().zip(&previous) {
+        assert!((body.position - expected).magnitude() < EPSILON, "{}", body.name);
+    }
+    scene.interpolate(0.25);
+    assert!((scene.time - (previous_time + STEP * 0.25)).abs() < EPSILON);
+    for ((body, a), b) in scene.bodies.iter().zip(&previous).zip(&latest) {
+        assert!((body.position - (a + (b - a) * 0.25)).magnitude() < EPSILON, "{}", body.name);
+    }
+
+    // Drawing in between doesn't change where the next step starts from
+    let mut untouched = self::scene();
+    untouched.update(STEP);
+    untouched.update(STEP);
+    scene.update(STEP);
+    untouched.update(STEP);
+    assert_eq!(scene.time, untouched.time);
+    for (a, b) in scene.bodies.iter().zip(&untouched.bodies) {
+        assert_eq!(a.position, b.position, "{}", a.name);
+    }
+}
+
+#[test]
+fn the_time_scale_stretches_real_time_into_more_or_fewer_steps() {
+    let mut timestep = FixedTimestep::new(STEP);
+    assert_eq!(timestep.scale_time(2.0), 2.0);
+    assert_eq!(timestep.advance(STEP * 1.5), 3);
+    assert_eq!(timestep.scale_time(0.25), 0.5);
+    assert_eq!(timestep.advance(STEP), 0);
+    assert_eq!(timestep.advance(STEP), 1);
+
+    // Halving or doubling stops at the ends of the range
+    for _ in 0..10 {
+        timestep.scale_time(2.0);
+    }
+    assert_eq!(timestep.time_scale(), TIME_SCALE_MAX);
+    for _ in 0..10 {
+        timestep.scale_time(0.5);
+    }
+    assert_eq!(timestep.time_scale(), TIME_SCALE_MIN);
+}
+
+#[test]
+fn the_time_scale_applies_without_real_time_too() {
+    // Headless, bench and deterministic runs take one frame_step per frame instead of
+    // advancing by the clock: a second of frames at x4 simulates four seconds
+    let mut timestep = FixedTimestep::new(STEP);
+    timestep.scale_time(4.0);
+    let mut fast = scene();
+    for _ in 0..60 {
+        fast.update(timestep.frame_step());
+    }
+    assert!((fast.time - 4.0).abs() < 1e-3, "{}", fast.time);
+
+    let mut normal = scene();
+    for _ in 0..60 {
+        normal.update(FixedTimestep::new(STEP).frame_step());
+    }
+    assert!((normal.time - 1.0).abs() < 1e-3, "{}", normal.time);
+    for (a, b) in fast.angles.iter().zip(&normal.angles) {
+        assert!((a - 4.0 * b).abs() < 1e-3, "{} != 4 * {}", a, b);
+    }
+}
@@ -0,0 +1,86 @@
+use nalgebra_glm::Vec3;
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::gltf::load_gltf;
+use space_travel::mesh::MeshCache;
+use space_travel::planet::PlanetType;
+use space_travel::profile::FrameProfile;
+use space_travel::renderer::Renderer;
+use space_travel::scene::SceneConfig;
+
+mod common;
+
+// An octahedron of radius 1 scaled by 0.5 in its node, under a parent moved to y = 1.
+// Base color factor (0.2, 0.4, 1.0)
+const OCTAHEDRON: &str = "tests/fixtures/octahedron.glb";
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn loads_the_first_mesh_with_node_transforms_baked_in() {
+    let model = load_gltf(OCTAHEDRON).expect("fixture");
+    assert_eq!(model.parts.len(), 1);
+    let mesh = &model.parts[0].mesh;
+    assert_eq!((mesh.vertices.len(), mesh.triangle_count()), (6, 8));
+
+    let center = Vec3::new(0.0, 1.0, 0.0);
+    for vertex in &mesh.vertices {
+        assert!(((vertex.position - center).magnitude() - 0.5).abs() < EPSILON, "{:?}", vertex.position);
+        assert!((vertex.normal - (vertex.position - center) * 2.0).magnitude() < EPSILON, "{:?}", vertex.normal);
+        assert!((0.0..=1.0).contains(&vertex.tex_coords.x) && (0.0..=1.0).contains(&vertex.tex_coords.y));
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| mesh.vertices[index as usize].position);
+        assert!((b - a).cross(&(c - a)).dot(&(a + b + c - center * 3.0)) > 0.0, "inward triangle");
+    }
+}
+
+#[test]
+fn reads_the_base_color_factor() {
+    let model = load_gltf(OCTAHEDRON).expect("fixture");
+    let material = &model.parts[0].material;
+    assert_eq!(material.name, "Blue");
+    let color = material.diffuse;
+    assert_eq!(color.b(), 255.0);
+    assert!(color.r() < color.g() && color.g() < color.b(), "{}", color);
+}
+
+#[test]
+fn broken_files_are_errors_not_panics() {
+    let missing = load_gltf("tests/fixtures/missing.glb").err().expect("missing file");
+    assert!(missing.to_string().contains("no se encontró"), "{}", missing);
+
+    let bytes = std::fs::read(OCTAHEDRON).unwrap();
+    let dir = std::env::temp_dir();
+    for (name, cut) in [("space_travel_cut_header.glb", 10), ("space_travel_cut_json.glb", 200), ("space_travel_cut_bin.glb", bytes.len() - 20)] {
+        let path = dir.join(name);
+        std::fs::write(&path, &bytes[..cut]).unwrap();
+        let result = load_gltf(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err(), "{} bytes loaded", cut);
+    }
+}
+
+#[test]
+fn scene_bodies_can_be_gltf_models() {
+    let default = SceneConfig::default();
+    let config = SceneConfig {
+        bodies: default.bodies.iter().filter(|body| body.planet_type == PlanetType::Sun).cloned().collect(),
+        body_model: Some(OCTAHEDRON.to_string()),
+        ..default
+    };
+    let body_mesh = config.body_mesh(&mut MeshCache::new()).expect("glTF body model");
+    assert_eq!(body_mesh.mesh.triangle_count(), 8);
+
+    let mut scene = common::dark_scene_from_config(&config);
+    scene.update(1.0 / 60.0);
+    let mut camera = Camera::new(Vec3::new(0.0, 2.0, 8.0), Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+
+    let mut framebuffer = Framebuffer::new(80, 60);
+    let mut renderer = Renderer::new(80, 60);
+    renderer.profile = FrameProfile::new(true);
+    renderer.render_scene(&mut framebuffer, &scene, &camera);
+    renderer.profile.end_frame();
+    let triangles = renderer.profile.counter_averages().into_iter().find(|(name, _)| *name == "triangulos").map_or(0.0, |(_, count)| count);
+    assert!(triangles > 0.0);
+}
@@ -0,0 +1,73 @@
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+use space_travel::mesh::{icosphere, BodyMesh, Lod, LOD_LEVELS};
+
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn vertex_and_triangle_counts_per_level() {
+    let expected = [(12, 20), (42, 80), (162, 320), (642, 1280), (2562, 5120)];
+    for (level, &(vertices, triangles)) in expected.iter().enumerate() {
+        let mesh = icosphere(level as u32);
+        assert_eq!((mesh.vertices.len(), mesh.triangle_count()), (vertices, triangles), "level {}", level);
+        assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+    }
+}
+
+#[test]
+fn vertices_lie_on_the_unit_sphere_with_normals_equal_to_positions() {
+    for vertex in &icosphere(3).vertices {
+        assert!((vertex.position.magnitude() - 1.0).abs() < EPSILON, "{:?}", vertex.position);
+        assert_eq!(vertex.normal, vertex.position);
+    }
+}
+
+#[test]
+fn triangles_wind_outward() {
+    let mesh = icosphere(2);
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| mesh.vertices[index as usize].position);
+        let normal: Vec3 = (b - a).cross(&(c - a));
+        assert!(normal.magnitude() > EPSILON);
+        assert!(normal.dot(&(a + b + c)) > 0.0);
+    }
+}
+
+#[test]
+fn lod_picks_finer_levels_for_bigger_bodies() {
+    let lod = Lod::new();
+    assert_eq!(lod.level_for(0.5), 0);
+    assert_eq!(lod.level_for(10_000.0), LOD_LEVELS as usize - 1);
+    let mut previous = 0;
+    for radius in (1..400).map(|r| r as f32) {
+        let level = lod.level_for(radius);
+        assert!(level >= previous, "level dropped at {} px", radius);
+        previous = level;
+    }
+    assert_eq!(lod.select(10_000.0).triangle_count(), lod.finest().triangle_count());
+}
+
+#[test]
+fn lod_levels_in_use_only_change_past_the_hysteresis() {
+    let lod = Lod::new();
+    // The size where level_for moves from 1 to 2
+    let boundary = (1..4000).map(|r| r as f32 * 0.1).find(|&radius| lod.level_for(radius) == 2).unwrap();
+
+    // Hovering around the boundary keeps whichever level was already in use
+    for radius in [boundary * 0.95, boundary, boundary * 1.05] {
+        assert_eq!(lod.level_from(radius, 1), 1, "at {} px", radius);
+        assert_eq!(lod.level_from(radius, 2), 2, "at {} px", radius);
+    }
+    // Well past it the level follows the size again
+    assert_eq!(lod.level_from(boundary * 1.5, 1), 2);
+    assert_eq!(lod.level_from(boundary * 0.6, 2), 1);
+    assert_eq!(lod.level_from(10_000.0, 0), LOD_LEVELS as usize - 1, "a jump isn't held back");
+
+    let body = BodyMesh { mesh: Arc::clone(lod.finest()), lod: Some(Arc::new(Lod::new())) };
+    let mut level = None;
+    let triangles = |mesh: &space_travel::mesh::Mesh| mesh.triangle_count();
+    assert_eq!(triangles(body.select_from(boundary * 1.05, &mut level)), triangles(lod.select(boundary * 1.05)));
+    assert_eq!(level, Some(2));
+    body.select_from(boundary * 0.95, &mut level);
+    assert_eq!(level, Some(2), "shrinking a little stays on the finer level");
+}
@@ -0,0 +1,63 @@
+use std::path::{Path, PathBuf};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::texture::{Texture, TextureError};
+
+// Where the tests write their images, under the tests' scratch directory
+fn scratch(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+// Write `bytes` where Texture::load can read them
+fn load(name: &str, bytes: &[u8]) -> Result<Texture, TextureError> {
+    let path = scratch(name);
+    std::fs::write(&path, bytes).expect("scratch file");
+    Texture::load(path.to_str().unwrap())
+}
+
+// A 2x2 frame saved as a PNG, as the screenshots are
+fn png() -> Vec<u8> {
+    let mut framebuffer = Framebuffer::new(2, 2);
+    framebuffer.buffer.copy_from_slice(&[0xFF0000, 0x00FF00, 0x0000FF, 0xFFFFFF]);
+    let path = scratch("frame.png");
+    framebuffer.save_png(&path).expect("saved PNG");
+    std::fs::read(path).expect("saved PNG")
+}
+
+#[test]
+fn saved_frames_load_back() {
+    let texture = load("round_trip.png", &png()).expect("valid PNG");
+    assert_eq!((texture.width, texture.height), (2, 2));
+    assert_eq!(texture.texel(1, 0).to_hex(), 0x00FF00);
+    assert_eq!(texture.texel(0, 1).to_hex(), 0x0000FF);
+}
+
+#[test]
+fn missing_files_say_so() {
+    let error = Texture::load("tests/fixtures/no_such_face.png").err().expect("no file");
+    assert!(matches!(error, TextureError::Io(..)), "{}", error);
+    assert_eq!(error.to_string(), "could not find tests/fixtures/no_such_face.png");
+}
+
+#[test]
+fn malformed_headers_are_errors() {
+    // IHDR starts at byte 8: length, "IHDR", then the width and the height
+    for (width, height) in [(0, 0), (0, 2), (2, 0), (u32::MAX, u32::MAX)] {
+        let mut bytes = png();
+        bytes[16..20].copy_from_slice(&width.to_be_bytes());
+        bytes[20..24].copy_from_slice(&height.to_be_bytes());
+        assert!(load("bad_size.png", &bytes).is_err(), "{}x{}", width, height);
+    }
+    assert!(load("empty.ppm", b"P6\n0 0\n255\n").is_err());
+    assert!(load("empty_row.ppm", b"P6\n4 0\n255\n").is_err());
+    let huge = format!("P6\n{} {}\n255\n", usize::MAX, 3);
+    assert!(load("huge.ppm", huge.as_bytes()).is_err());
+}
+
+#[test]
+fn truncated_files_are_errors() {
+    let bytes = png();
+    for cut in [4, 12, 30, bytes.len() / 2, bytes.len() - 13] {
+        assert!(load("short.png", &bytes[..cut]).is_err(), "cut at {}", cut);
+    }
+    assert!(load("short.ppm", b"P6\n2 2\n255\n\x01\x02\x03").is_err());
+}
@@ -0,0 +1,59 @@
+use nalgebra_glm::Vec3;
+use space_travel::color::Color;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::impostor::{render_impostor, Impostor};
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 60;
+
+#[test]
+fn every_body_type_gets_a_sprite_and_its_average_color() {
+    let renderer = Renderer::new(WIDTH, HEIGHT);
+    for planet_type in PlanetType::BODIES {
+        let sprite = renderer.impostor(planet_type);
+        // Opaque in the middle, empty in the corners
+        assert_eq!(sprite.sample(0.5, 0.5, 1.0).w, 1.0, "{:?}", planet_type);
+        assert_eq!(sprite.sample(0.0, 0.0, 1.0).w, 0.0, "{:?}", planet_type);
+        // The single-texel level is the whole disc averaged
+        assert!(sprite.sample(0.5, 0.5, 32.0).w > 0.7, "{:?}", planet_type);
+    }
+    let earth = renderer.impostor(PlanetType::Earth).color;
+    let sun = renderer.impostor(PlanetType::Sun).color;
+    assert!((earth.r() - sun.r()).abs() + (earth.g() - sun.g()).abs() + (earth.b() - sun.b()).abs() > 30.0);
+}
+
+#[test]
+fn sprites_follow_the_shader_parameters_once_updated() {
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    let before = renderer.impostor(PlanetType::Earth).color.luminance();
+
+    renderer.shader_params.get_mut(PlanetType::Earth).brightness = 0.3;
+    assert_eq!(renderer.impostor(PlanetType::Earth).color.luminance(), before, "kept until asked");
+    renderer.update_impostors();
+    let after = renderer.impostor(PlanetType::Earth).color.luminance();
+    assert!(after < before, "{} then {}", before, after);
+}
+
+#[test]
+fn the_impostor_draws_the_sprite_not_a_flat_dot() {
+    let renderer = Renderer::new(WIDTH, HEIGHT);
+    // The renderer's view before its first frame: eye at the origin, looking down -z
+    let uniforms = renderer.frame();
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.clear();
+    let impostor = Impostor { center: Vec3::new(0.0, 0.0, -50.0), projected_radius: 12.0, brightness: 1.0, alpha: 1.0 };
+    render_impostor(&mut framebuffer, uniforms, &impostor, renderer.impostor(PlanetType::Earth));
+
+    let (cx, cy) = (WIDTH / 2, HEIGHT / 2);
+    let disc: Vec<u32> = (cy - 8..cy + 8)
+        .flat_map(|y| (cx - 8..cx + 8).map(move |x| (x, y)))
+        .filter(|&(x, y)| (x as f32 + 0.5 - cx as f32).hypot(y as f32 + 0.5 - cy as f32) < 8.0)
+        .map(|(x, y)| framebuffer.pixel(x, y).unwrap())
+        .collect();
+    assert!(disc.iter().all(|&color| Color::from_hex(color).luminance() > 0.0));
+    let distinct = disc.iter().collect::<std::collections::HashSet<_>>().len();
+    assert!(distinct > 10, "only {} colors on the disc", distinct);
+    assert_eq!(framebuffer.pixel(cx + 14, cy), Some(0), "nothing past the radius");
+}
@@ -0,0 +1,56 @@
+use nalgebra_glm::Vec3;
+use space_travel::intro::{catmull_rom, Intro, INTRO_DURATION};
+
+const STEP: f32 = 1.0 / 60.0;
+
+fn start_pose() -> (Vec3, Vec3) {
+    (Vec3::new(0.0, 0.0, 10.0), Vec3::zeros())
+}
+
+#[test]
+fn the_spline_passes_through_every_point() {
+    let points = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 1.0, 0.0), Vec3::new(5.0, 5.0, 2.0), Vec3::new(0.0, 6.0, 3.0)];
+    for (i, point) in points.iter().enumerate() {
+        let t = i as f32 / (points.len() - 1) as f32;
+        assert!((catmull_rom(&points, t) - point).magnitude() < 1e-5, "point {}", i);
+    }
+    // In between it stays close to the polyline rather than overshooting wildly
+    let middle = catmull_rom(&points, 0.5);
+    assert!((middle - (points[1] + points[2]) * 0.5).magnitude() < 1.0, "{:?}", middle);
+}
+
+#[test]
+fn the_intro_flies_in_from_afar_and_ends_on_the_starting_pose() {
+    let (end_eye, end_center) = start_pose();
+    let mut intro = Intro::new(end_eye, end_center, Vec3::zeros(), 40.0);
+    let (first_eye, first_center) = intro.pose();
+    assert!(first_eye.magnitude() > 80.0, "starts outside the system: {:?}", first_eye);
+    assert!(first_eye.y > 0.0, "and above it");
+    let looking = (first_center - first_eye).normalize();
+    assert!(looking.dot(&(-first_eye).normalize()) > 0.999, "looking at the sun");
+
+    // No frame moves the camera far, and the last ones barely move it
+    let (mut eye, mut steps) = (first_eye, Vec::new());
+    while !intro.finished() {
+        intro.update(STEP);
+        let (next, _) = intro.pose();
+        steps.push((next - eye).magnitude());
+        eye = next;
+    }
+    assert_eq!(steps.len(), (INTRO_DURATION / STEP).round() as usize);
+    let fastest = steps.iter().cloned().fold(0.0, f32::max);
+    assert!(fastest < 1.5, "{} units in one frame", fastest);
+    assert!(steps[steps.len() - 2] < 0.01, "still moving at the end: {}", steps[steps.len() - 2]);
+    assert_eq!(intro.pose(), (end_eye, end_center));
+}
+
+#[test]
+fn skipping_goes_straight_to_the_starting_pose() {
+    let (end_eye, end_center) = start_pose();
+    let mut intro = Intro::new(end_eye, end_center, Vec3::zeros(), 40.0);
+    intro.update(1.0);
+    assert!(!intro.finished());
+    intro.skip();
+    assert!(intro.finished());
+    assert_eq!(intro.pose(), (end_eye, end_center));
+}
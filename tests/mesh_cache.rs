@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use space_travel::ambient::Meteors;
+use space_travel::mesh::{MeshCache, LOD_LEVELS};
+use space_travel::scene::{build_scene, SceneConfig};
+use space_travel::skybox::{Skybox, SkyboxConfig};
+
+#[test]
+fn each_source_is_built_once_and_shared() {
+    let mut meshes = MeshCache::new();
+    let first = meshes.obj("assets/models/smooth_sphere.obj").expect("sphere model");
+    let again = meshes.obj("assets/models/smooth_sphere.obj").expect("sphere model");
+    assert!(Arc::ptr_eq(&first, &again));
+    assert!(Arc::ptr_eq(&meshes.uv_sphere(8, 12), &meshes.uv_sphere(8, 12)));
+    assert!(!Arc::ptr_eq(&meshes.uv_sphere(8, 12), &meshes.uv_sphere(8, 16)));
+    assert_eq!(meshes.len(), 3);
+
+    // The LOD levels are the cached icospheres
+    let lod = meshes.lod();
+    assert!(Arc::ptr_eq(lod.finest(), &meshes.icosphere(LOD_LEVELS - 1)));
+    assert_eq!(meshes.len(), 3 + LOD_LEVELS as usize);
+}
+
+#[test]
+fn failed_loads_are_not_cached() {
+    let mut meshes = MeshCache::new();
+    assert!(meshes.obj("tests/fixtures/missing.obj").is_err());
+    assert!(meshes.is_empty());
+}
+
+#[test]
+fn every_body_shares_the_scene_mesh() {
+    let config = SceneConfig::default();
+    let mut meshes = MeshCache::new();
+    let body_mesh = config.body_mesh(&mut meshes).expect("generated sphere");
+    let skybox = Skybox::new(SkyboxConfig { star_count: 0, ..SkyboxConfig::default() });
+    let scene = build_scene(&config, body_mesh.clone(), skybox, Meteors::new(1));
+    assert!(scene.bodies.iter().all(|body| Arc::ptr_eq(&body.mesh.mesh, &body_mesh.mesh)));
+}
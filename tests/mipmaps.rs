@@ -0,0 +1,93 @@
+use space_travel::color::Color;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::renderer::Renderer;
+use space_travel::skybox::Skybox;
+use space_travel::texture::Texture;
+
+const FACE: usize = 64;
+
+// Black and white texels, alternating in both directions
+fn checkerboard(width: usize, height: usize) -> Vec<Color> {
+    (0..width * height)
+        .map(|i| if (i % width + i / width).is_multiple_of(2) { Color::new(255, 255, 255) } else { Color::black() })
+        .collect()
+}
+
+fn channels(color: Color) -> [u32; 3] {
+    let hex = color.to_hex();
+    [hex >> 16 & 0xFF, hex >> 8 & 0xFF, hex & 0xFF]
+}
+
+#[test]
+fn each_level_halves_the_last_down_to_one_texel() {
+    let texture = Texture::from_pixels(5, 3, checkerboard(5, 3));
+    assert_eq!(texture.mip_levels(), 3);
+    assert_eq!((0..3).map(|level| texture.level_size(level)).collect::<Vec<_>>(), [(5, 3), (2, 1), (1, 1)]);
+
+    let square = Texture::from_pixels(8, 8, checkerboard(8, 8));
+    assert_eq!(square.mip_levels(), 4);
+    // Every 2x2 block of a checkerboard averages to the same gray
+    for [r, g, b] in [square.level_texel(1, 0, 0), square.level_texel(3, 0, 0)].map(channels) {
+        assert!((r as i32 - 128).abs() <= 1 && r == g && g == b, "{} {} {}", r, g, b);
+    }
+}
+
+#[test]
+fn the_footprint_picks_the_level() {
+    let texture = Texture::from_pixels(8, 8, checkerboard(8, 8));
+    assert_eq!(texture.mip_level(1.0), 0.0);
+    assert_eq!(texture.mip_level(0.25), 0.0, "magnified reads the full image");
+    assert_eq!(texture.mip_level(4.0), 2.0);
+    assert!((texture.mip_level(2.0_f32.powf(1.5)) - 1.5).abs() < 1e-6);
+    assert_eq!(texture.mip_level(1000.0), 3.0, "clamped at the 1x1 level");
+
+    // At a texel's center the full image gives the texel itself; far enough, the average
+    let (u, v) = (0.5 / 8.0, 0.5 / 8.0);
+    assert_eq!(channels(texture.sample(u, v, 1.0)), [255, 255, 255]);
+    let [r, _, _] = channels(texture.sample(u, v, 8.0));
+    assert!((r as i32 - 128).abs() <= 1, "{}", r);
+}
+
+#[test]
+fn empty_textures_sample_black() {
+    for (width, height) in [(0, 0), (4, 0)] {
+        let texture = Texture::from_pixels(width, height, Vec::new());
+        assert_eq!(texture.texel(1, 1).to_hex(), 0);
+        assert_eq!(texture.sample(0.5, 0.5, 1.0).to_hex(), 0);
+        assert_eq!(texture.sample(0.5, 0.5, 16.0).to_hex(), 0);
+    }
+}
+
+// The checkerboard on all six faces, seen straight ahead in a square frame `size` pixels wide
+fn cubemap_frame(size: usize) -> Framebuffer {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("checker_cubemap");
+    std::fs::create_dir_all(&dir).expect("scratch directory");
+    let mut ppm = format!("P6\n{} {}\n255\n", FACE, FACE).into_bytes();
+    for texel in checkerboard(FACE, FACE) {
+        ppm.extend_from_slice(&channels(texel).map(|c| c as u8));
+    }
+    let paths = ["px", "nx", "py", "ny", "pz", "nz"].map(|face| {
+        let path = dir.join(format!("{}.ppm", face));
+        std::fs::write(&path, &ppm).expect("face");
+        path.to_str().unwrap().to_string()
+    });
+    let skybox = Skybox::from_cubemap(paths.each_ref().map(String::as_str)).expect("cubemap");
+
+    let mut framebuffer = Framebuffer::new(size, size);
+    framebuffer.clear();
+    skybox.render(&mut framebuffer, Renderer::new(size, size).frame());
+    framebuffer
+}
+
+#[test]
+fn a_distant_cubemap_reads_its_averaged_levels() {
+    let spread = |framebuffer: &Framebuffer| {
+        let reds = framebuffer.buffer.iter().map(|&pixel| pixel >> 16 & 0xFF);
+        reds.clone().max().unwrap() - reds.min().unwrap()
+    };
+    // Up close a face's texels are several pixels wide, and both colors show
+    assert!(spread(&cubemap_frame(256)) > 200);
+    // Squeezed into a few pixels, each pixel covers several texels: the level it reads
+    // from is already gray instead of whichever texel the pixel happens to land on
+    assert!(spread(&cubemap_frame(8)) <= 2, "{}", spread(&cubemap_frame(8)));
+}
@@ -0,0 +1,106 @@
+use nalgebra_glm::{Vec2, Vec3};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::motion_overlay::MotionOverlay;
+use space_travel::scene::Scene;
+use space_travel::sprite::project;
+use space_travel::transform::create_view_matrix;
+use space_travel::uniforms::FrameUniforms;
+
+mod common;
+
+const SIZE: usize = 96;
+const DT: f32 = 1.0 / 60.0;
+
+// The sun and one planet on a circular orbit of radius 10
+fn scene() -> Scene {
+    let source = "[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"rocky\"\norbit_radius = 10\n";
+    common::scene_from(source)
+}
+
+// Straight down on the orbital plane, as the bird's eye view looks
+fn bird_eye() -> FrameUniforms {
+    let eye = Vec3::new(0.0, 50.0, 0.0);
+    FrameUniforms {
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0)),
+        camera_position: eye,
+        ..common::frame(SIZE, SIZE)
+    }
+}
+
+fn draw(overlay: &MotionOverlay, scene: &Scene, orbits: bool) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    overlay.draw(&mut framebuffer, &bird_eye(), scene, orbits);
+    framebuffer
+}
+
+fn drawn(framebuffer: &Framebuffer) -> Vec<Vec2> {
+    (0..SIZE)
+        .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+        .filter(|&(x, y)| framebuffer.pixel(x, y) != Some(0))
+        .map(|(x, y)| Vec2::new(x as f32, y as f32))
+        .collect()
+}
+
+fn screen(point: Vec3) -> Vec2 {
+    let projected = project(&bird_eye(), point).expect("in view");
+    Vec2::new(projected.x, projected.y)
+}
+
+#[test]
+fn velocity_is_the_motion_over_the_last_step() {
+    let mut scene = scene();
+    assert_eq!(scene.velocity(1), Vec3::zeros(), "nothing has moved yet");
+    let before = scene.bodies[1].position;
+    scene.update(DT);
+    let velocity = scene.velocity(1);
+    let moved = (scene.bodies[1].position - before) / DT;
+    assert!((velocity - moved).magnitude() < 1e-3, "{:?} {:?}", velocity, moved);
+    assert_eq!(scene.orbital_speed(1), velocity.magnitude());
+    assert_eq!(scene.velocity(0), Vec3::zeros(), "the sun stays put");
+}
+
+#[test]
+fn arrows_point_along_the_velocity() {
+    let mut scene = scene();
+    let overlay = MotionOverlay::new();
+    assert!(drawn(&draw(&overlay, &scene, true)).is_empty(), "no arrows before the first step");
+
+    scene.update(DT);
+    let pixels = drawn(&draw(&overlay, &scene, true));
+    assert!(!pixels.is_empty());
+    let body = scene.bodies[1].position;
+    let along = screen(body + scene.velocity(1)) - screen(body);
+    let from_body = pixels.iter().fold(Vec2::zeros(), |sum, pixel| sum + pixel) / pixels.len() as f32 - screen(body);
+    assert!(from_body.dot(&along) > 0.0, "{:?} {:?}", from_body, along);
+
+    // Hidden bodies get none
+    scene.bodies[1].visibility.body = false;
+    assert!(drawn(&draw(&overlay, &scene, true)).is_empty());
+}
+
+#[test]
+fn ticks_mark_the_orbit_every_tick_degrees() {
+    let scene = scene();
+    let mut overlay = MotionOverlay::new();
+    overlay.ticks = true;
+    overlay.tick_degrees = 45.0;
+    let framebuffer = draw(&overlay, &scene, true);
+    let path = scene.orbit_path(1);
+    // Anything drawn within a pixel of where the orbit is at `angle`
+    let lit = |angle: f32| {
+        let at = screen(path.position(angle.to_radians()));
+        let (x, y) = (at.x.round() as usize, at.y.round() as usize);
+        (x - 1..=x + 1).any(|x| (y - 1..=y + 1).any(|y| framebuffer.pixel(x, y) != Some(0)))
+    };
+    for tick in 0..8 {
+        let angle = tick as f32 * 45.0;
+        assert!(lit(angle), "tick at {}°", angle);
+        assert!(!lit(angle + 22.5), "nothing between ticks at {}°", angle + 22.5);
+    }
+
+    // Not with the orbits off, nor unless asked for
+    assert!(drawn(&draw(&overlay, &scene, false)).is_empty());
+    overlay.ticks = false;
+    assert!(drawn(&draw(&overlay, &scene, true)).is_empty());
+}
@@ -0,0 +1,59 @@
+use nalgebra_glm::Vec3;
+use space_travel::obj::Obj;
+
+const MIXED_FACES: &str = "tests/fixtures/mixed_faces.obj";
+
+fn positions(path: &str) -> Vec<Vec3> {
+    Obj::load(path).expect("fixture").get_vertex_array().iter().map(|v| v.position).collect()
+}
+
+#[test]
+fn quads_and_polygons_are_fanned_into_triangles() {
+    let obj = Obj::load(MIXED_FACES).expect("fixture");
+    // 1 + 2 + 3 triangles
+    assert_eq!(obj.get_vertex_array().len(), 18);
+    assert_eq!(obj.triangulated_faces(), 2);
+}
+
+#[test]
+fn fans_start_at_the_first_corner() {
+    let p = positions(MIXED_FACES);
+    let v = |x: f32, y: f32| Vec3::new(x, y, 0.0);
+    let expected = [
+        // Triangle as written
+        v(0.0, 0.0), v(1.0, 0.0), v(0.0, 1.0),
+        // Quad 4 5 6 7 -> (4 5 6) (4 6 7)
+        v(2.0, 0.0), v(3.0, 0.0), v(3.0, 1.0),
+        v(2.0, 0.0), v(3.0, 1.0), v(2.0, 1.0),
+        // Pentagon 8..12 -> (8 9 10) (8 10 11) (8 11 12)
+        v(5.0, 0.0), v(6.0, 0.0), v(6.5, 1.0),
+        v(5.0, 0.0), v(6.5, 1.0), v(5.5, 2.0),
+        v(5.0, 0.0), v(5.5, 2.0), v(4.5, 1.0),
+    ];
+    assert_eq!(p, expected);
+}
+
+#[test]
+fn triangles_keep_the_face_winding() {
+    for triangle in positions(MIXED_FACES).chunks_exact(3) {
+        let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+        assert!(normal.z > 0.0, "clockwise triangle {:?}", triangle);
+    }
+}
+
+#[test]
+fn corners_keep_their_own_uvs_and_normals() {
+    let vertices = Obj::load(MIXED_FACES).expect("fixture").get_vertex_array();
+    // Second triangle of the quad: corners 4, 6 and 7 with UVs 1, 3 and 4
+    let uvs: Vec<_> = vertices[6..9].iter().map(|v| (v.tex_coords.x, v.tex_coords.y)).collect();
+    // V is flipped on load
+    assert_eq!(uvs, [(0.0, 1.0), (1.0, 0.0), (0.0, 0.0)]);
+    assert!(vertices.iter().all(|v| v.normal == Vec3::new(0.0, 0.0, 1.0)));
+}
+
+#[test]
+fn triangle_only_models_load_unchanged() {
+    let obj = Obj::load("assets/models/smooth_sphere.obj").expect("sphere model");
+    assert_eq!(obj.triangulated_faces(), 0);
+    assert_eq!(obj.get_vertex_array().len() % 3, 0);
+}
@@ -0,0 +1,42 @@
+use space_travel::obj::Obj;
+
+const SPHERE: &str = "assets/models/smooth_sphere.obj";
+
+#[test]
+fn sphere_corners_share_vertices() {
+    let obj = Obj::load(SPHERE).expect("sphere model");
+    let corners = obj.indices().len();
+    let unique = obj.vertices().len();
+    let ratio = corners as f32 / unique as f32;
+    println!("smooth_sphere.obj: {} corners -> {} vertices ({:.2}x)", corners, unique, ratio);
+
+    assert_eq!(corners, 960 * 3);
+    // A closed triangle mesh has about six corners per vertex; UV seams split a few
+    assert!(ratio > 4.5, "only {:.2}x", ratio);
+    assert!(obj.indices().iter().all(|&index| (index as usize) < unique));
+}
+
+#[test]
+fn vertex_array_expands_the_indices() {
+    let obj = Obj::load(SPHERE).expect("sphere model");
+    let expanded = obj.get_vertex_array();
+    assert_eq!(expanded.len(), obj.indices().len());
+    for (vertex, &index) in expanded.iter().zip(obj.indices()) {
+        let shared = &obj.vertices()[index as usize];
+        assert_eq!((vertex.position, vertex.normal, vertex.tex_coords), (shared.position, shared.normal, shared.tex_coords));
+    }
+}
+
+#[test]
+fn corners_only_merge_when_all_indices_match() {
+    // The same position with two UVs (a seam) stays two vertices; repeats of one triple merge
+    let obj = Obj::load("tests/fixtures/uv_indices.obj").expect("fixture");
+    // v//vn: 1//1 2//1 3//1, then 1/1/2 2/2/2 3/3/2, then 1/1 3/3 4/4
+    assert_eq!(obj.vertices().len(), 9);
+    let path = std::env::temp_dir().join("space_travel_shared_quad.obj");
+    std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4\n").unwrap();
+    let quad = Obj::load(path.to_str().unwrap()).expect("quad");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(quad.vertices().len(), 4);
+    assert_eq!(quad.indices(), [0, 1, 2, 0, 2, 3]);
+}
@@ -0,0 +1,59 @@
+use space_travel::mesh::Model;
+use space_travel::mtl::Material;
+use space_travel::obj::Obj;
+
+const MATERIALS: &str = "tests/fixtures/materials.obj";
+
+fn rgb(material: &Material) -> (f32, f32, f32) {
+    (material.diffuse.r(), material.diffuse.g(), material.diffuse.b())
+}
+
+#[test]
+fn triangles_are_gathered_by_material() {
+    let obj = Obj::load(MATERIALS).expect("fixture");
+    let names: Vec<&str> = obj.groups().iter().map(|group| group.material.name.as_str()).collect();
+    assert_eq!(names, ["", "Hull Paint", "Glass", "Chrome"]);
+    let ranges: Vec<_> = obj.groups().iter().map(|group| group.indices.clone()).collect();
+    assert_eq!(ranges, [0..3, 3..9, 9..12, 12..15]);
+    assert_eq!(obj.indices().len(), 15);
+}
+
+#[test]
+fn diffuse_colors_and_maps_come_from_the_library() {
+    let obj = Obj::load(MATERIALS).expect("fixture");
+    let [none, hull, glass, chrome] = [0, 1, 2, 3].map(|i| &obj.groups()[i].material);
+    assert_eq!(rgb(hull), (204.0, 51.0, 51.0));
+    assert_eq!(rgb(glass), (0.0, 128.0, 255.0));
+    assert_eq!(hull.diffuse_map.as_deref(), Some("tests/fixtures/textures/hull plates.png"));
+    assert_eq!(glass.diffuse_map.as_deref(), Some("tests/fixtures/glass.png"));
+    // No material and an unknown one both fall back to grey
+    assert_eq!(rgb(none), rgb(&Material::fallback()));
+    assert_eq!(rgb(chrome), rgb(&Material::fallback()));
+}
+
+#[test]
+fn a_missing_library_still_loads_the_model_in_grey() {
+    let path = std::env::temp_dir().join("space_travel_missing_mtl.obj");
+    std::fs::write(&path, "mtllib nowhere.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl Hull\nf 1 2 3\n").unwrap();
+    let obj = Obj::load(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    let obj = obj.expect("model without its MTL");
+    assert_eq!(obj.groups().len(), 1);
+    assert_eq!(obj.groups()[0].material.name, "Hull");
+    assert_eq!(rgb(&obj.groups()[0].material), rgb(&Material::fallback()));
+}
+
+#[test]
+fn model_parts_keep_only_their_own_vertices() {
+    let obj = Obj::load(MATERIALS).expect("fixture");
+    let model = Model::from(&obj);
+    let triangles: Vec<usize> = model.parts.iter().map(|part| part.mesh.triangle_count()).collect();
+    assert_eq!(triangles, [1, 2, 1, 1]);
+    for (part, group) in model.parts.iter().zip(obj.groups()) {
+        assert!(part.mesh.indices.iter().all(|&index| (index as usize) < part.mesh.vertices.len()));
+        let positions: Vec<_> = part.mesh.corners().map(|vertex| vertex.position).collect();
+        let expected: Vec<_> = obj.indices()[group.indices.clone()].iter().map(|&i| obj.vertices()[i as usize].position).collect();
+        assert_eq!(positions, expected);
+    }
+    assert_eq!(model.parts[1].mesh.vertices.len(), 4);
+}
@@ -0,0 +1,61 @@
+use nalgebra_glm::{Vec2, Vec3};
+use space_travel::obj::{LoadOptions, Obj};
+
+const UV_INDICES: &str = "tests/fixtures/uv_indices.obj";
+
+fn uvs(options: &LoadOptions) -> Vec<Vec2> {
+    let obj = Obj::load_with(UV_INDICES, options).expect("fixture");
+    obj.get_vertex_array().iter().map(|v| v.tex_coords).collect()
+}
+
+#[test]
+fn corners_get_the_uv_their_face_names() {
+    let vertices = Obj::load(UV_INDICES).expect("fixture").get_vertex_array();
+    assert_eq!(vertices.len(), 9);
+
+    // V is flipped by default
+    let second: Vec<_> = vertices[3..6].iter().map(|v| (v.position, v.tex_coords)).collect();
+    assert_eq!(second, [
+        (Vec3::new(0.0, 0.0, 0.0), Vec2::new(0.25, 1.0)),
+        (Vec3::new(1.0, 0.0, 0.0), Vec2::new(0.75, 1.0)),
+        (Vec3::new(1.0, 1.0, 0.0), Vec2::new(0.75, 0.5)),
+    ]);
+
+    let third: Vec<_> = vertices[6..9].iter().map(|v| (v.position, v.tex_coords)).collect();
+    assert_eq!(third, [
+        (Vec3::new(0.0, 0.0, 0.0), Vec2::new(0.25, 1.0)),
+        (Vec3::new(1.0, 1.0, 0.0), Vec2::new(0.75, 0.5)),
+        (Vec3::new(0.0, 1.0, 0.0), Vec2::new(0.25, 0.5)),
+    ]);
+}
+
+#[test]
+fn missing_components_fall_back_per_corner() {
+    let vertices = Obj::load(UV_INDICES).expect("fixture").get_vertex_array();
+    // `v//vn`: its own normal, no UV
+    assert!(vertices[..3].iter().all(|v| v.tex_coords == Vec2::zeros() && v.normal == Vec3::new(0.0, 0.0, 1.0)));
+    // `v/vt/vn` with a relative normal index
+    let normals: Vec<_> = vertices[3..6].iter().map(|v| v.normal).collect();
+    assert_eq!(normals, [Vec3::new(0.0, 0.0, -1.0); 3]);
+    // `v/vt`: no normal, +Y
+    assert!(vertices[6..].iter().all(|v| v.normal == Vec3::new(0.0, 1.0, 0.0)));
+}
+
+#[test]
+fn v_is_kept_as_written_without_flip() {
+    let raw = uvs(&LoadOptions { flip_v: false });
+    let flipped = uvs(&LoadOptions::default());
+    assert_eq!(raw[3..6], [Vec2::new(0.25, 0.0), Vec2::new(0.75, 0.0), Vec2::new(0.75, 0.5)]);
+    for (raw, flipped) in raw.iter().zip(&flipped).skip(3) {
+        assert_eq!((raw.x, raw.y), (flipped.x, 1.0 - flipped.y));
+    }
+}
+
+#[test]
+fn out_of_range_indices_are_reported_with_their_line() {
+    let path = std::env::temp_dir().join("space_travel_bad_uv_index.obj");
+    std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nf 1/1 2/2 3/1\n").unwrap();
+    let err = Obj::load(path.to_str().unwrap()).err().expect("UV 2 doesn't exist");
+    std::fs::remove_file(&path).ok();
+    assert!(err.to_string().contains("línea 5"), "{}", err);
+}
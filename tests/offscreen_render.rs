@@ -0,0 +1,100 @@
+use nalgebra_glm::Vec3;
+use space_travel::ambient::Meteors;
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::color::Color;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::mesh::{BodyMesh, Mesh};
+use space_travel::obj::Obj;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+use space_travel::scene::{build_scene, Scene, SceneConfig};
+use space_travel::skybox::{Skybox, SkyboxConfig};
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 60;
+const BACKGROUND: u32 = 0x102030;
+
+// The sun alone under an empty sky, seen head-on from `distance`
+fn render_sun(distance: f32) -> Framebuffer {
+    let sphere = Obj::load("assets/models/smooth_sphere.obj").expect("sphere model");
+    let default = SceneConfig::default();
+    let config = SceneConfig {
+        bodies: default.bodies.iter().filter(|body| body.planet_type == PlanetType::Sun).cloned().collect(),
+        ..default
+    };
+    let skybox = Skybox::new(SkyboxConfig { star_count: 0, band_strength: 0.0, ..SkyboxConfig::default() });
+    let mut scene = build_scene(&config, BodyMesh::from(Mesh::from(&sphere)), skybox, Meteors::new(1));
+    scene.update(1.0 / 60.0);
+
+    let mut camera = Camera::new(Vec3::new(0.0, 0.0, distance), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(BACKGROUND);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.set_shader_params(config.shader_params());
+    renderer.render_scene(&mut framebuffer, &scene, &camera);
+    framebuffer
+}
+
+#[test]
+fn sun_covers_the_center_and_leaves_the_corners_clear() {
+    let framebuffer = render_sun(10.0);
+
+    let center = Color::from_hex(framebuffer.pixel(WIDTH / 2, HEIGHT / 2).unwrap());
+    assert!(center.r() > center.b(), "the sun should look warm, got {}", center);
+    assert!(framebuffer.depth(WIDTH / 2, HEIGHT / 2).unwrap() <= 1.0);
+
+    for (x, y) in [(0, 0), (WIDTH - 1, 0), (0, HEIGHT - 1), (WIDTH - 1, HEIGHT - 1)] {
+        assert_eq!(framebuffer.pixel(x, y), Some(BACKGROUND), "corner ({}, {})", x, y);
+        assert_eq!(framebuffer.depth(x, y), Some(f32::INFINITY));
+    }
+}
+
+#[test]
+fn sun_shrinks_with_distance() {
+    let covered = |framebuffer: &Framebuffer| framebuffer.zbuffer.iter().filter(|depth| depth.is_finite()).count();
+    let near = covered(&render_sun(8.0));
+    let far = covered(&render_sun(16.0));
+    assert!(far > 0 && far < near, "near {} px, far {} px", near, far);
+}
+
+#[test]
+fn hidden_bodies_keep_orbiting_and_reappear() {
+    let sphere = Obj::load("assets/models/smooth_sphere.obj").expect("sphere model");
+    let config = SceneConfig::default();
+    let new_scene = || {
+        let skybox = Skybox::new(SkyboxConfig { star_count: 0, band_strength: 0.0, ..SkyboxConfig::default() });
+        build_scene(&config, BodyMesh::from(Mesh::from(&sphere)), skybox, Meteors::new(1))
+    };
+    let (mut shown, mut hidden) = (new_scene(), new_scene());
+    for body in &mut hidden.bodies {
+        body.visibility.body = false;
+    }
+    for _ in 0..30 {
+        shown.update(1.0 / 60.0);
+        hidden.update(1.0 / 60.0);
+    }
+    for (a, b) in shown.bodies.iter().zip(&hidden.bodies) {
+        assert_eq!(a.position, b.position, "{}", a.name);
+        assert_eq!(a.trail.particles.len(), b.trail.particles.len(), "{}", a.name);
+    }
+
+    let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.orbits = false;
+    renderer.trails = false;
+    let mut covered = |scene: &Scene| {
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+        renderer.render_scene(&mut framebuffer, scene, &camera);
+        framebuffer.zbuffer.iter().filter(|depth| depth.is_finite()).count()
+    };
+    assert_eq!(covered(&hidden), 0);
+
+    for body in &mut hidden.bodies {
+        body.visibility.body = true;
+    }
+    assert!(covered(&hidden) > 0);
+    assert_eq!(covered(&hidden), covered(&shown));
+}
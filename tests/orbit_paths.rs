@@ -0,0 +1,72 @@
+use nalgebra_glm::Vec3;
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::{orbit_segments, Renderer};
+
+mod common;
+
+const EPSILON: f32 = 1e-4;
+
+#[test]
+fn every_body_stays_on_its_drawn_path() {
+    let source = "[[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 20\neccentricity = 0.3\ninclination = 25\nascending_node = 40\n\
+        [[body]]\ntype = \"moon\"\norbit_radius = 5\n";
+    let mut scene = common::scene_from(source);
+    for _ in 0..200 {
+        scene.update(1.0 / 60.0);
+        for i in 1..scene.bodies.len() {
+            let path = scene.orbit_path(i);
+            let on_path = path.position(path.angle);
+            assert!((on_path - scene.bodies[i].position).magnitude() < EPSILON, "{} is off its path", scene.bodies[i].name);
+        }
+    }
+
+    // The moon's path goes around the earth wherever it is, not around the sun
+    let moon = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Moon).unwrap();
+    let path = scene.orbit_path(moon);
+    assert_eq!(path.center, scene.bodies[1].position);
+    assert!(path.center.y.abs() > 0.1, "the earth should be off the plane on an inclined orbit");
+}
+
+#[test]
+fn close_orbits_get_more_segments() {
+    let scene = common::scene_from("[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"rocky\"\norbit_radius = 30\n");
+    let path = scene.orbit_path(1);
+    let segments_from = |eye: Vec3| {
+        let mut camera = Camera::new(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        camera.mode = CameraMode::FirstPerson;
+        let mut renderer = Renderer::new(400, 300);
+        renderer.render_scene(&mut Framebuffer::new(400, 300), &scene, &camera);
+        orbit_segments(renderer.frame(), 300, &path)
+    };
+    let far = segments_from(Vec3::new(0.0, 500.0, 500.0));
+    let near = segments_from(Vec3::new(30.5, 1.0, 0.0));
+    assert!(far >= 24 && near > 2 * far, "far {}, near {}", far, near);
+}
+
+#[test]
+fn the_moon_follows_the_earth_wherever_it_is_listed() {
+    // Planets before and after the earth, and the moon before it, with no orbit_radius
+    let layouts = [
+        "[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"earth\"\norbit_radius = 20\n[[body]]\ntype = \"moon\"\n",
+        "[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"moon\"\n[[body]]\ntype = \"rocky\"\norbit_radius = 10\n\
+            [[body]]\ntype = \"earth\"\norbit_radius = 20\n[[body]]\ntype = \"cloud\"\norbit_radius = 30\n",
+    ];
+    for source in layouts {
+        let mut scene = common::scene_from(&format!("moon_orbit_radius = 1.5\n{}", source));
+        let earth = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Earth).unwrap();
+        let moon = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Moon).unwrap();
+        assert_eq!(scene.parents[moon], Some(earth));
+        for _ in 0..120 {
+            scene.update(1.0 / 60.0);
+            let distance = (scene.bodies[moon].position - scene.bodies[earth].position).magnitude();
+            assert!((distance - 1.5).abs() < EPSILON, "{}", distance);
+        }
+        // Only the moon's own orbit moves it: the planets keep the angles their speeds give
+        for i in (0..scene.bodies.len()).filter(|&i| i != moon) {
+            assert!((scene.angles[i] - scene.orbit_speeds[i] * 2.0).abs() < EPSILON);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::{PI, TAU};
+use space_travel::planet::PlanetType;
+use space_travel::scene::{OrbitSpeeds, SceneConfig};
+
+mod common;
+
+const STEP: f32 = 1.0 / 60.0;
+
+// A planet three times as far from the sun at aphelion as at perihelion
+const ECCENTRIC: &str = "[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"earth\"\norbit_radius = 20\neccentricity = 0.5\n";
+
+// A sun, planets at four radii (one sped up on its own) and a moon around the earth
+fn source(preset: &str) -> String {
+    format!(
+        "orbit_speeds = \"{}\"\n\
+        [[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"rocky\"\norbit_radius = 8\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 20\n\
+        [[body]]\ntype = \"moon\"\norbit_radius = 4\n\
+        [[body]]\ntype = \"water\"\norbit_radius = 35\n\
+        [[body]]\ntype = \"cloud\"\norbit_radius = 60\norbit_speed_multiplier = 2\n",
+        preset
+    )
+}
+
+// Orbital period of each body going around the sun, with its orbit's radius. A body's own
+// orbit_speed_multiplier is taken out, to compare the laws alone
+fn periods(config: &SceneConfig) -> Vec<(f32, f32)> {
+    config.bodies.iter()
+        .filter(|body| !matches!(body.planet_type, PlanetType::Sun | PlanetType::Moon))
+        .map(|body| (body.orbit_radius, TAU / config.angular_speed(body) * body.orbit_speed_multiplier))
+        .collect()
+}
+
+#[test]
+fn realistic_periods_follow_keplers_third_law() {
+    let config = SceneConfig::parse(&source("realistic")).unwrap();
+    let realistic = periods(&config);
+    let (a0, t0) = realistic[0];
+    for &(a, t) in &realistic[1..] {
+        // T^2 / a^3 is the same for every planet
+        let ratio = (t / t0).powi(2) / (a / a0).powi(3);
+        assert!((ratio - 1.0).abs() < 1e-4, "radius {}: {}", a, ratio);
+    }
+
+    // The classic law goes as T proportional to a instead
+    let config = SceneConfig::parse(&source("classic")).unwrap();
+    let classic = periods(&config);
+    let (a0, t0) = classic[0];
+    for &(a, t) in &classic[1..] {
+        assert!(((t / t0) / (a / a0) - 1.0).abs() < 1e-4, "radius {}", a);
+    }
+}
+
+#[test]
+fn switching_preset_keeps_every_body_where_it_is() {
+    let mut scene = common::scene_from(&source("classic"));
+    let realistic = common::scene_from(&source("realistic"));
+    for _ in 0..90 {
+        scene.update(STEP);
+    }
+    let angles = scene.angles.clone();
+    let positions: Vec<_> = scene.bodies.iter().map(|body| body.position).collect();
+
+    scene.set_speed_preset(OrbitSpeeds::Realistic);
+    assert_eq!(scene.speed_preset, OrbitSpeeds::Realistic);
+    assert_eq!(scene.angles, angles);
+    // The speeds are the ones the realistic scene starts with, the moon's included
+    for (i, (&speed, &expected)) in scene.orbit_speeds.iter().zip(&realistic.orbit_speeds).enumerate() {
+        assert!((speed - expected).abs() <= expected.abs() * 1e-5, "{}: {} != {}", scene.bodies[i].name, speed, expected);
+    }
+
+    // And the next step goes on from the same phase at the new speed
+    scene.update(STEP);
+    for i in 0..scene.bodies.len() {
+        let expected = angles[i] + scene.orbit_speeds[i] * STEP;
+        assert!((scene.angles[i] - expected).abs() < 1e-4, "{} jumped", scene.bodies[i].name);
+        let moved = (scene.bodies[i].position - positions[i]).magnitude();
+        assert!(moved < 1.0, "{} moved {} in one step", scene.bodies[i].name, moved);
+    }
+
+    // Back to the classic speeds
+    let classic = common::scene_from(&source("classic"));
+    scene.set_speed_preset(OrbitSpeeds::Classic);
+    for (&speed, &expected) in scene.orbit_speeds.iter().zip(&classic.orbit_speeds) {
+        assert!((speed - expected).abs() <= expected.abs() * 1e-5, "{} != {}", speed, expected);
+    }
+}
+
+#[test]
+fn eccentric_anomalies_solve_keplers_equation() {
+    let mut orbit = common::scene_from(ECCENTRIC).orbits[1];
+    for e in [0.0, 0.3, 0.5, 0.9, 0.99] {
+        orbit.eccentricity = e;
+        for mean in [-7.0, -PI, -1.0, 0.0, 0.01, 1.0, 3.0, PI, 10.0] {
+            let anomaly = orbit.eccentric_anomaly(mean);
+            assert!((anomaly - e * anomaly.sin() - mean).abs() < 1e-4, "e {} M {}: E {}", e, mean, anomaly);
+        }
+    }
+}
+
+#[test]
+fn bodies_move_faster_at_perihelion_than_at_aphelion() {
+    // Distance covered in one step starting from mean anomaly `mean`
+    let moved_from = |mean: f32| {
+        let mut scene = common::scene_from(ECCENTRIC);
+        scene.angles[1] = mean;
+        scene.update(0.0);
+        let before = scene.bodies[1].position;
+        scene.update(STEP);
+        (scene.bodies[1].position - before).magnitude()
+    };
+    let perihelion = moved_from(0.0);
+    let aphelion = moved_from(PI);
+    // The orbital speed goes as sqrt((1 + e) / (1 - e)) at perihelion over the circular
+    // one and the inverse at aphelion, so their ratio is (1 + e) / (1 - e) = 3
+    assert!((perihelion / aphelion - 3.0).abs() < 0.05, "{} vs {}", perihelion, aphelion);
+}
@@ -0,0 +1,77 @@
+use nalgebra_glm::Vec3;
+use space_travel::renderer::{trail_stride, TRAIL_THINNING_DISTANCE, TRAIL_THINNING_STRIDE};
+use space_travel::scene::{SceneConfig, DEFAULT_PARTICLE_BUDGET};
+
+mod common;
+
+const TWO_PLANETS: &str = "particle_budget = 30\n\
+    [[body]]\ntype = \"sun\"\ntrail = 0\n\
+    [[body]]\ntype = \"earth\"\norbit_radius = 20\ntrail = 40\n\
+    [[body]]\ntype = \"rocky\"\norbit_radius = 30\ntrail = 20\n";
+
+#[test]
+fn trails_share_the_budget_in_proportion_and_keep_their_newest_particles() {
+    let mut scene = common::scene_from(TWO_PLANETS);
+    assert_eq!(scene.bodies[1].trail.retention(), 20);
+    assert_eq!(scene.bodies[2].trail.retention(), 10);
+    for _ in 0..100 {
+        scene.update(1.0 / 60.0);
+    }
+    assert_eq!(scene.particle_count(), 30);
+    for body in &scene.bodies[1..] {
+        let newest = body.trail.particles.last().unwrap().position;
+        assert!((newest - body.position).magnitude() < 1e-4, "the newest particle is where the body is");
+    }
+
+    // Lowering the budget drops the oldest particles right away
+    let newest = scene.bodies[1].trail.particles.last().unwrap().position;
+    scene.set_particle_budget(6);
+    assert_eq!(scene.particle_count(), 6);
+    assert_eq!(scene.bodies[1].trail.particles.len(), 4);
+    assert_eq!(scene.bodies[1].trail.particles.last().unwrap().position, newest);
+    // And raising it never lets a trail past its own length
+    scene.set_particle_budget(1000);
+    assert_eq!(scene.bodies[1].trail.retention(), 40);
+}
+
+#[test]
+fn the_default_system_stays_within_the_default_budget() {
+    let config = SceneConfig::default();
+    assert_eq!(config.particle_budget, DEFAULT_PARTICLE_BUDGET);
+    // Its trails fit as they are
+    let scene = common::scene_from_config(&config);
+    assert!(scene.bodies.iter().zip(&config.bodies).all(|(body, body_config)| body.trail.retention() == config.trail_length(body_config)));
+
+    // Trails around whole orbits don't, and get cut down to it
+    let config = SceneConfig { trail_arc: 1.0, ..SceneConfig::default() };
+    let lengths: usize = config.bodies.iter().map(|body| config.trail_length(body)).sum();
+    assert!(lengths > DEFAULT_PARTICLE_BUDGET, "nothing to enforce: {}", lengths);
+    let scene = common::scene_from_config(&config);
+    let retained: usize = scene.bodies.iter().map(|body| body.trail.retention()).sum();
+    assert!(retained <= DEFAULT_PARTICLE_BUDGET && retained > DEFAULT_PARTICLE_BUDGET - scene.bodies.len());
+
+    let error = SceneConfig::parse("particle_budget = lots\n").expect_err("bad budget");
+    assert!(error.contains("particle_budget"), "{}", error);
+}
+
+#[test]
+fn far_trails_draw_the_same_every_nth_particle_as_they_move_on() {
+    let mut scene = common::scene_from(TWO_PLANETS);
+    for _ in 0..50 {
+        scene.update(1.0 / 60.0);
+    }
+    let earth = &scene.bodies[1];
+    assert_eq!(trail_stride(earth, earth.position + Vec3::new(0.0, 0.0, 5.0)), 1);
+    let far = earth.position + Vec3::new(0.0, 0.0, TRAIL_THINNING_DISTANCE + 1.0);
+    assert_eq!(trail_stride(earth, far), TRAIL_THINNING_STRIDE);
+
+    let before: Vec<Vec3> = earth.trail.thinned(TRAIL_THINNING_STRIDE).map(|particle| particle.position).collect();
+    assert_eq!(before.len(), 20 / TRAIL_THINNING_STRIDE);
+    assert_eq!(earth.trail.thinned(1).count(), earth.trail.particles.len());
+    scene.update(1.0 / 60.0);
+    let after: Vec<Vec3> = scene.bodies[1].trail.thinned(TRAIL_THINNING_STRIDE).map(|particle| particle.position).collect();
+    // One step moves the trail on by a single particle: the picked ones that are still
+    // there are still picked
+    let kept = before.iter().filter(|position| after.contains(position)).count();
+    assert!(kept >= before.len() - 1, "{} of {} kept", kept, before.len());
+}
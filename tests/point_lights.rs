@@ -0,0 +1,81 @@
+use nalgebra_glm::Vec3;
+use space_travel::lights::{compress_light, PointLight, PointLights, LIGHT_HEADROOM, MAX_LIGHTS};
+use space_travel::scene::SceneConfig;
+
+const EPSILON: f32 = 1e-5;
+
+fn light(position: Vec3, color: Vec3) -> PointLight {
+    PointLight { position, color, intensity: 16.0, range: 20.0 }
+}
+
+#[test]
+fn attenuation_is_inverse_square_and_ends_at_the_range() {
+    let light = PointLight { range: 1000.0, ..PointLight::default() };
+    let ratio = light.attenuation(4.0) / light.attenuation(2.0);
+    assert!((ratio - 0.25).abs() < 1e-3, "{}", ratio);
+    assert_eq!(light.attenuation(1000.0), 0.0);
+    assert_eq!(light.attenuation(5000.0), 0.0);
+    assert!(light.attenuation(999.0) > 0.0);
+    let mut previous = f32::INFINITY;
+    for distance in (0..100).map(|d| d as f32 * 10.0) {
+        assert!(light.attenuation(distance) <= previous);
+        previous = light.attenuation(distance);
+    }
+}
+
+#[test]
+fn contributions_add_up_per_channel_and_only_on_the_facing_side() {
+    let red = light(Vec3::new(0.0, 0.0, 4.0), Vec3::new(1.0, 0.0, 0.0));
+    let blue = light(Vec3::new(4.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    let lights = PointLights::new(&[red, blue]);
+    let (position, normal) = (Vec3::zeros(), Vec3::new(1.0, 0.0, 1.0).normalize());
+
+    let sum = lights.illuminate(&position, &normal);
+    let expected = red.illuminate(&position, &normal) + blue.illuminate(&position, &normal);
+    assert!((sum - expected).magnitude() < EPSILON);
+    assert!(sum.x > 0.0 && sum.z > 0.0 && sum.y == 0.0);
+    assert_eq!(lights.illuminate(&position, &-normal), Vec3::zeros());
+}
+
+#[test]
+fn no_more_than_max_lights_are_kept() {
+    let many = vec![PointLight::default(); MAX_LIGHTS + 3];
+    assert_eq!(PointLights::new(&many).as_slice().len(), MAX_LIGHTS);
+    assert!(PointLights::default().is_empty());
+}
+
+#[test]
+fn bright_light_is_compressed_but_keeps_its_order() {
+    for light in [0.0, 0.3, 1.0] {
+        assert_eq!(compress_light(light), light);
+    }
+    let mut previous = 1.0;
+    for light in [1.5, 2.0, 4.0, 8.0, 100.0] {
+        let compressed = compress_light(light);
+        assert!(compressed > previous && compressed < 1.0 + LIGHT_HEADROOM, "{} -> {}", light, compressed);
+        previous = compressed;
+    }
+}
+
+#[test]
+fn scene_files_describe_lights() {
+    let body = "[[body]]\ntype = \"sun\"\n";
+    let config = SceneConfig::parse(&format!(
+        "{}[[light]]\nposition = [1.0, -2, 3.5]\ncolor = \"#3366FF\"\nintensity = 40\nrange = 25\n",
+        body
+    ))
+    .expect("scene with a light");
+    assert_eq!(config.lights.len(), 1);
+    let light = config.lights[0];
+    assert_eq!(light.position, Vec3::new(1.0, -2.0, 3.5));
+    assert!((light.color - Vec3::new(0.2, 0.4, 1.0)).magnitude() < EPSILON);
+    assert_eq!((light.intensity, light.range), (40.0, 25.0));
+
+    assert!(SceneConfig::parse(&format!("{}[[light]]\ncolor = \"#FFFFFF\"\n", body)).is_err());
+    assert!(SceneConfig::parse(&format!("{}[[light]]\nposition = [1, 2]\n", body)).is_err());
+    assert!(SceneConfig::parse(&format!("{}[[light]]\nposition = [0, 0, 5]\nrange = 0\n", body)).is_err());
+
+    let too_many = "[[light]]\nposition = [0, 0, 5]\n".repeat(MAX_LIGHTS + 1);
+    assert!(SceneConfig::parse(&format!("{}{}", body, "[[light]]\nposition = [0, 0, 5]\n".repeat(MAX_LIGHTS))).is_ok());
+    assert!(SceneConfig::parse(&format!("{}{}", body, too_many)).is_err());
+}
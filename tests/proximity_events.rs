@@ -0,0 +1,62 @@
+use nalgebra_glm::Vec3;
+use space_travel::events::{ProximityTracker, SpaceEvent};
+use space_travel::scene::{CelestialBody, SceneConfig};
+
+mod common;
+
+// The default scene's first body, at the origin with a scale of 1, so distances are in
+// body scales
+fn body() -> CelestialBody {
+    let mut scene = common::scene_from_config(&SceneConfig::default());
+    let mut body = scene.bodies.remove(0);
+    body.position = Vec3::zeros();
+    body.scale = 1.0;
+    body
+}
+
+// Every event the tracker sends for the camera at each of `distances` from the body, along +x
+fn events(tracker: &mut ProximityTracker, bodies: &[CelestialBody], distances: &[f32]) -> Vec<SpaceEvent> {
+    distances.iter().flat_map(|&distance| tracker.update(bodies, Vec3::new(distance, 0.0, 0.0))).collect()
+}
+
+#[test]
+fn hovering_at_the_entry_radius_enters_once() {
+    let bodies = [body()];
+    let mut tracker = ProximityTracker::new();
+    let hovering: Vec<f32> = (0..200u32).map(|i| if i.is_multiple_of(2) { 5.05 } else { 4.95 }).collect();
+    assert_eq!(events(&mut tracker, &bodies, &hovering), [SpaceEvent::EnteredOrbit(0)]);
+}
+
+#[test]
+fn leaving_takes_going_past_the_leave_radius() {
+    let bodies = [body()];
+    let mut tracker = ProximityTracker::new();
+    assert_eq!(events(&mut tracker, &bodies, &[4.0]), [SpaceEvent::EnteredOrbit(0)]);
+    // Receding reports the approach, but the camera is still in orbit up to 6.5
+    assert_eq!(events(&mut tracker, &bodies, &[6.0]), [SpaceEvent::ClosestApproach(0, 4.0)]);
+    assert!(events(&mut tracker, &bodies, &[5.5, 6.4, 6.5, 6.2, 6.5]).is_empty());
+    assert_eq!(events(&mut tracker, &bodies, &[6.6]), [SpaceEvent::LeftOrbit(0)]);
+    // And it's out until it comes back under 5
+    assert!(events(&mut tracker, &bodies, &[6.0, 5.2, 6.6]).is_empty());
+    assert_eq!(events(&mut tracker, &bodies, &[4.9]), [SpaceEvent::EnteredOrbit(0)]);
+}
+
+#[test]
+fn each_flyby_reports_one_closest_approach() {
+    let bodies = [body()];
+    let mut tracker = ProximityTracker::new();
+    // Straight past the body, 2 scales from its center at the nearest, there and back
+    let flyby: Vec<Vec3> = (-100..=100).chain((-100..=100).rev()).map(|i| Vec3::new(i as f32 * 0.1, 2.0, 0.0)).collect();
+    let events: Vec<SpaceEvent> = flyby.iter().flat_map(|&eye| tracker.update(&bodies, eye)).collect();
+    let approaches: Vec<f32> = events
+        .iter()
+        .filter_map(|event| match event {
+            SpaceEvent::ClosestApproach(0, distance) => Some(*distance),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(approaches.len(), 2, "{:?}", events);
+    assert!(approaches.iter().all(|&distance| (distance - 2.0).abs() < 1e-4), "{:?}", approaches);
+    assert_eq!(events.iter().filter(|event| matches!(event, SpaceEvent::EnteredOrbit(0))).count(), 2);
+    assert_eq!(events.iter().filter(|event| matches!(event, SpaceEvent::LeftOrbit(0))).count(), 2);
+}
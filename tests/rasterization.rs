@@ -0,0 +1,73 @@
+use nalgebra_glm::{Vec2, Vec3};
+use space_travel::shaders::Shading;
+use space_travel::triangle::triangle;
+use space_travel::vertex::Vertex;
+
+const SIZE: usize = 16;
+
+fn vertex(x: f32, y: f32) -> Vertex {
+    Vertex::new(Vec3::new(x, y, 0.5), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros())
+}
+
+// How many fragments each pixel of a SIZE x SIZE grid gets from `triangles`
+fn coverage(triangles: &[[(f32, f32); 3]]) -> Vec<u32> {
+    let mut counts = vec![0; SIZE * SIZE];
+    let light = Vec3::new(0.0, 0.0, 1.0);
+    for &[a, b, c] in triangles {
+        for fragment in triangle(&vertex(a.0, a.1), &vertex(b.0, b.1), &vertex(c.0, c.1), Shading::Phong, &light) {
+            let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+            assert!(x < SIZE && y < SIZE, "fragment outside the triangles at ({}, {})", x, y);
+            counts[y * SIZE + x] += 1;
+        }
+    }
+    counts
+}
+
+#[test]
+fn two_triangles_sharing_an_edge_cover_each_pixel_once() {
+    // A square split along its diagonal, which runs exactly through the pixel centers on
+    // it; the second triangle is wound the other way
+    let square = [[(2.0, 2.0), (14.0, 2.0), (14.0, 14.0)], [(2.0, 2.0), (2.0, 14.0), (14.0, 14.0)]];
+    let counts = coverage(&square);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let expected = u32::from((2..14).contains(&x) && (2..14).contains(&y));
+            assert_eq!(counts[y * SIZE + x], expected, "pixel ({}, {})", x, y);
+        }
+    }
+
+    // A flat shared edge through a row of pixel centers
+    let flat = [[(1.5, 7.5), (14.5, 7.5), (8.0, 1.0)], [(14.5, 7.5), (1.5, 7.5), (8.0, 14.0)]];
+    let counts = coverage(&flat);
+    assert!(counts.iter().all(|&count| count <= 1), "{:?}", counts);
+    // Every pixel center between the ends of the shared edge is drawn by one of the two
+    assert!((2..14).all(|x| counts[7 * SIZE + x] == 1), "{:?}", &counts[7 * SIZE..8 * SIZE]);
+}
+
+#[test]
+fn a_fan_of_triangles_leaves_no_seams_and_no_overlaps() {
+    // A convex polygon with off-grid corners, cut into triangles around an off-grid point
+    let center = (7.3, 8.1);
+    let corners = [(1.2, 3.7), (6.9, 0.6), (14.4, 2.9), (15.1, 10.3), (9.8, 15.2), (2.1, 13.4)];
+    let fan: Vec<_> = (0..corners.len()).map(|i| [center, corners[i], corners[(i + 1) % corners.len()]]).collect();
+    let counts = coverage(&fan);
+
+    // Pixels whose center is clearly inside the polygon (not right at its outline, where
+    // only the fill rule decides) are each drawn exactly once
+    let inside = |x: f32, y: f32| {
+        (0..corners.len()).all(|i| {
+            let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+            let cross = (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0);
+            cross / (b.0 - a.0).hypot(b.1 - a.1) > 0.01
+        })
+    };
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let count = counts[y * SIZE + x];
+            assert!(count <= 1, "pixel ({}, {}) drawn {} times", x, y, count);
+            if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                assert_eq!(count, 1, "seam at pixel ({}, {})", x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use nalgebra_glm::{Mat4, Vec3};
+use space_travel::camera::{Camera, CameraMode};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::mesh::icosphere;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+use space_travel::scene::Scene;
+use space_travel::shaders::vertex_shader;
+use space_travel::transform::create_view_matrix;
+use space_travel::triangle::is_back_facing;
+use space_travel::uniforms::{DrawUniforms, FrameUniforms};
+
+mod common;
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+// A lone rocky planet at the origin, with the sun hidden
+fn scene() -> Scene {
+    let mut scene = common::scene_from("[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"rocky\"\norbit_radius = 10\nscale = 2\n");
+    scene.bodies[0].visibility.body = false;
+    scene.bodies[1].position = Vec3::zeros();
+    scene
+}
+
+fn camera(center: Vec3) -> Camera {
+    let mut camera = Camera::new(Vec3::new(0.0, 0.0, 8.0), center, Vec3::new(0.0, 1.0, 0.0));
+    camera.mode = CameraMode::FirstPerson;
+    camera
+}
+
+#[test]
+fn the_counts_describe_the_last_frame_only() {
+    let scene = scene();
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.trails = false;
+
+    renderer.render_scene(&mut framebuffer, &scene, &camera(Vec3::zeros()));
+    let stats = renderer.stats;
+    assert!(stats.triangles > 0 && stats.fragments > 0, "{:?}", stats);
+    assert_eq!(stats.frustum_culled, 0);
+    // A closed sphere seen from nearby shows the camera a bit over half its triangles from
+    // behind, and their fragments land behind the front ones
+    let share = stats.back_facing as f32 / stats.triangles as f32;
+    assert!((0.5..0.75).contains(&share), "{:?}", stats);
+    assert!(stats.depth_rejected > 0 && stats.depth_rejected < stats.fragments, "{:?}", stats);
+
+    // The same frame again counts the same, not twice as much
+    renderer.render_scene(&mut framebuffer, &scene, &camera(Vec3::zeros()));
+    assert_eq!(renderer.stats, stats);
+
+    // Looking away, the planet is culled whole
+    renderer.render_scene(&mut framebuffer, &scene, &camera(Vec3::new(0.0, 0.0, 16.0)));
+    assert_eq!(renderer.stats.triangles, 0);
+    assert_eq!(renderer.stats.fragments, 0);
+    assert_eq!(renderer.stats.frustum_culled, stats.triangles);
+}
+
+#[test]
+fn bands_count_and_draw_the_same_as_one_thread() {
+    let scene = scene();
+    let mut single = Framebuffer::new(WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.render_scene(&mut single, &scene, &camera(Vec3::zeros()));
+    let single_stats = renderer.stats;
+
+    let mut banded = Framebuffer::new(WIDTH, HEIGHT);
+    renderer.threads = 4;
+    renderer.render_scene(&mut banded, &scene, &camera(Vec3::zeros()));
+    assert_eq!(renderer.stats, single_stats);
+    let differing = single.buffer.iter().zip(&banded.buffer).filter(|(a, b)| a != b).count();
+    assert_eq!(differing, 0, "{} of {} pixels differ", differing, WIDTH * HEIGHT);
+}
+
+#[test]
+fn back_facing_triangles_are_the_ones_turned_away_from_the_eye() {
+    let renderer = Renderer::new(WIDTH, HEIGHT);
+    let eye = Vec3::new(1.0, 2.0, 6.0);
+    let frame = FrameUniforms { view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)), ..renderer.frame().clone() };
+    let uniforms = DrawUniforms {
+        frame: &frame,
+        model_matrix: Mat4::identity(),
+        shader: PlanetType::Moon,
+        params: renderer.shader_params.get(PlanetType::Moon),
+        noise: &renderer.noise,
+        seed: 0,
+        base_color: None,
+        ambient: 0.0,
+        bump_strength: 0.0,
+    };
+    let mesh = icosphere(2);
+    let vertices: Vec<_> = mesh.vertices.iter().map(|vertex| vertex_shader(vertex, &uniforms)).collect();
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| &vertices[index as usize]);
+        let center = (a.world_position + b.world_position + c.world_position) / 3.0;
+        let facing = center.normalize().dot(&(eye - center).normalize());
+        // Triangles seen nearly edge-on can go either way once snapped to the subpixel grid
+        if facing.abs() > 0.05 {
+            assert_eq!(is_back_facing(a, b, c), facing < 0.0, "triangle at {:?}", center);
+        }
+    }
+}
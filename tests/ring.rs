@@ -0,0 +1,78 @@
+use nalgebra_glm::Vec3;
+use space_travel::mesh::{ring, Mesh};
+
+const EPSILON: f32 = 1e-5;
+const UP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+// The face normal of every triangle, unnormalized, from its winding
+fn face_normals(mesh: &Mesh) -> Vec<Vec3> {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| mesh.vertices[index as usize].position);
+            (b - a).cross(&(c - a))
+        })
+        .collect()
+}
+
+#[test]
+fn vertex_and_triangle_counts() {
+    for segments in [3, 16, 64] {
+        let mesh = ring(1.5, 2.5, segments);
+        // An inner and an outer vertex per segment, with the wrap column twice
+        assert_eq!(mesh.vertices.len(), 2 * (segments as usize + 1));
+        assert_eq!(mesh.triangle_count(), 2 * segments as usize);
+        assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+
+        let both = mesh.clone().double_sided();
+        assert_eq!(both.vertices.len(), 2 * mesh.vertices.len());
+        assert_eq!(both.triangle_count(), 2 * mesh.triangle_count());
+    }
+    assert_eq!(ring(1.0, 2.0, 1).triangle_count(), 6);
+}
+
+#[test]
+fn vertices_lie_between_the_radii_on_the_plane() {
+    let (inner, outer) = (1.2, 2.3);
+    for vertex in &ring(inner, outer, 48).vertices {
+        let p = vertex.position;
+        let radius = p.magnitude();
+        assert_eq!(p.y, 0.0);
+        assert!(radius > inner - EPSILON && radius < outer + EPSILON, "{:?}", p);
+        // u is the radial fraction, v the angle from +X
+        let u = vertex.tex_coords.x;
+        assert!((radius - (inner + (outer - inner) * u)).abs() < EPSILON, "u {} at radius {}", u, radius);
+        let angle = (-p.z).atan2(p.x).rem_euclid(std::f32::consts::TAU);
+        let v = vertex.tex_coords.y;
+        assert!((angle - v * std::f32::consts::TAU).abs() < 1e-4 || (v - 1.0).abs() < EPSILON, "v {} at {:?}", v, p);
+        assert_eq!(vertex.normal, UP);
+    }
+}
+
+#[test]
+fn every_triangle_faces_up_and_the_back_faces_face_down() {
+    let mesh = ring(1.0, 3.0, 32);
+    for normal in face_normals(&mesh) {
+        assert!(normal.magnitude() > EPSILON, "zero-area triangle");
+        assert!((normal.normalize() - UP).magnitude() < EPSILON, "{:?}", normal);
+    }
+
+    let both = mesh.clone().double_sided();
+    let normals = face_normals(&both);
+    let (front, back) = normals.split_at(mesh.triangle_count());
+    assert!(front.iter().all(|normal| normal.dot(&UP) > 0.0));
+    assert!(back.iter().all(|normal| (normal.normalize() + UP).magnitude() < EPSILON));
+    let (_, back_vertices) = both.vertices.split_at(mesh.vertices.len());
+    assert!(back_vertices.iter().all(|vertex| vertex.normal == -UP));
+}
+
+#[test]
+fn no_triangle_wraps_around_the_texture() {
+    let segments = 24;
+    let mesh = ring(1.0, 2.0, segments);
+    for triangle in mesh.indices.chunks_exact(3) {
+        let vs: Vec<f32> = triangle.iter().map(|&index| mesh.vertices[index as usize].tex_coords.y).collect();
+        let span = vs.iter().cloned().fold(f32::MIN, f32::max) - vs.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(span <= 1.0 / segments as f32 + EPSILON, "v spans {}", span);
+    }
+}
@@ -0,0 +1,97 @@
+use space_travel::compare::ShaderComparison;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::planet::PlanetType;
+use space_travel::renderer::Renderer;
+use space_travel::scene::{Scene, SceneConfig};
+use space_travel::text::GLYPH_HEIGHT;
+
+mod common;
+
+// Two 40-pixel halves around a 2-pixel divider
+const WIDTH: usize = 82;
+const HALF: usize = 40;
+const HEIGHT: usize = 60;
+// Below the captions, where both halves show only the scene
+const FIRST_SCENE_ROW: usize = 12 + GLYPH_HEIGHT;
+
+fn scene() -> Scene {
+    let mut scene = common::dark_scene_from_config(&SceneConfig::default());
+    scene.update(1.0 / 60.0);
+    scene
+}
+
+// Rows of the left and right halves below the captions
+fn halves(framebuffer: &Framebuffer) -> (Vec<u32>, Vec<u32>) {
+    let rows = FIRST_SCENE_ROW..HEIGHT;
+    let half = |left: usize| rows.clone().flat_map(|y| (left..left + HALF).map(move |x| framebuffer.pixel(x, y).unwrap())).collect();
+    (half(0), half(WIDTH - HALF))
+}
+
+#[test]
+fn cycles_through_the_scene_types_then_turns_off() {
+    let scene = scene();
+    let mut types: Vec<PlanetType> = Vec::new();
+    for body in &scene.bodies {
+        if !types.contains(&body.shader_type) {
+            types.push(body.shader_type);
+        }
+    }
+
+    let mut comparison = ShaderComparison::new();
+    assert!(!comparison.active());
+    let renderer = Renderer::new(WIDTH, HEIGHT);
+    for &planet_type in &types {
+        comparison.cycle(&scene.bodies, &renderer.shader_params);
+        assert_eq!(comparison.planet(), Some(planet_type));
+    }
+    comparison.cycle(&scene.bodies, &renderer.shader_params);
+    assert_eq!(comparison.planet(), None);
+}
+
+#[test]
+fn halves_differ_only_in_the_compared_parameters() {
+    let scene = scene();
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    let mut comparison = ShaderComparison::new();
+    comparison.cycle(&scene.bodies, &renderer.shader_params);
+    let planet_type = comparison.planet().expect("a body type");
+
+    // Same parameters on both sides: the same picture, with a solid divider between them
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    comparison.render(&mut renderer, &mut framebuffer, &scene);
+    let (left, right) = halves(&framebuffer);
+    assert_eq!(left, right);
+    assert!(left.iter().any(|&pixel| pixel != 0), "the body is framed in both halves");
+    for y in 0..HEIGHT {
+        assert_eq!(framebuffer.pixel(HALF, y), framebuffer.pixel(HALF + 1, y));
+        assert_ne!(framebuffer.pixel(HALF, y), framebuffer.pixel(HALF - 1, y));
+    }
+
+    // Only the right half follows the current parameters
+    renderer.shader_params.get_mut(planet_type).brightness *= 0.5;
+    comparison.render(&mut renderer, &mut framebuffer, &scene);
+    let (changed_left, changed_right) = halves(&framebuffer);
+    assert_eq!(changed_left, left);
+    assert_ne!(changed_right, right);
+
+    // The renderer is left sized for the whole framebuffer
+    assert_eq!(renderer.frame().viewport_matrix[(0, 3)], WIDTH as f32 / 2.0);
+}
+
+#[test]
+fn blit_copies_color_and_depth_and_clips() {
+    let mut source = Framebuffer::new(3, 2);
+    source.set_current_color(0x123456);
+    source.point(2, 1, 0.5);
+
+    let mut target = Framebuffer::new(4, 4);
+    target.blit(&source, 2, 3);
+    assert_eq!(target.pixel(2, 3), Some(0));
+    assert_eq!(target.depth(2, 3), Some(f32::INFINITY));
+    // (2, 1) of the source lands at (4, 4), outside
+    assert!(target.buffer.iter().all(|&pixel| pixel == 0));
+
+    target.blit(&source, 1, 1);
+    assert_eq!(target.pixel(3, 2), Some(0x123456));
+    assert_eq!(target.depth(3, 2), Some(0.5));
+}
@@ -0,0 +1,62 @@
+use nalgebra_glm::Vec3;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::renderer::Renderer;
+use space_travel::sprite::{project, render_billboard, Billboard, DEFAULT_PARTICLE_SOFTNESS};
+use space_travel::transform::create_view_matrix;
+use space_travel::uniforms::FrameUniforms;
+
+const SIZE: usize = 64;
+
+// Looking down -z from 5 units away at an opaque wall through the origin
+fn wall(log_depth: bool) -> (Framebuffer, FrameUniforms) {
+    let renderer = Renderer::new(SIZE, SIZE);
+    let eye = Vec3::new(0.0, 0.0, 5.0);
+    let frame = FrameUniforms {
+        view_matrix: create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+        camera_position: eye,
+        log_depth,
+        ..renderer.frame().clone()
+    };
+    let depth = project(&frame, Vec3::zeros()).expect("in front of the camera").z;
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.set_current_color(0x000000);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            framebuffer.point(x, y, depth);
+        }
+    }
+    (framebuffer, frame)
+}
+
+// Red channel at the center of a particle drawn `z` units in front of the wall
+fn particle_over_wall(z: f32, softness: f32, log_depth: bool) -> u32 {
+    let (mut framebuffer, frame) = wall(log_depth);
+    let before = framebuffer.depth(SIZE / 2, SIZE / 2);
+    let billboard = Billboard { center: Vec3::new(0.0, 0.0, z), half_size: 0.5, color: 0xFF0000, alpha: 1.0, softness };
+    render_billboard(&mut framebuffer, &frame, &billboard, Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert_eq!(framebuffer.depth(SIZE / 2, SIZE / 2), before, "particles only read the depth buffer");
+    framebuffer.pixel(SIZE / 2, SIZE / 2).unwrap() >> 16
+}
+
+#[test]
+fn particles_fade_out_as_they_reach_the_surface_behind_them() {
+    for log_depth in [false, true] {
+        let clear = particle_over_wall(1.0, DEFAULT_PARTICLE_SOFTNESS, log_depth);
+        let near = particle_over_wall(DEFAULT_PARTICLE_SOFTNESS * 0.5, DEFAULT_PARTICLE_SOFTNESS, log_depth);
+        let touching = particle_over_wall(0.01, DEFAULT_PARTICLE_SOFTNESS, log_depth);
+        assert!(clear > 200, "log_depth {}: {}", log_depth, clear);
+        assert!(near < clear && near > touching, "log_depth {}: {} {} {}", log_depth, clear, near, touching);
+        assert!(touching < 20, "log_depth {}: {}", log_depth, touching);
+        // Behind the wall nothing shows either way
+        assert_eq!(particle_over_wall(-0.5, DEFAULT_PARTICLE_SOFTNESS, log_depth), 0);
+    }
+}
+
+#[test]
+fn zero_softness_keeps_the_hard_edge() {
+    // Right up against the wall the particle still shows whole, and cuts off behind it
+    assert!(particle_over_wall(0.01, 0.0, false) > 200);
+    assert_eq!(particle_over_wall(-0.5, 0.0, false), 0);
+    // Far from any surface softness changes nothing
+    assert_eq!(particle_over_wall(1.0, DEFAULT_PARTICLE_SOFTNESS, false), particle_over_wall(1.0, 0.0, false));
+}
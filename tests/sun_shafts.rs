@@ -0,0 +1,98 @@
+use nalgebra_glm::Vec3;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::impostor::projected_radius;
+use space_travel::scene::{CelestialBody, SceneConfig};
+use space_travel::sprite::project;
+use space_travel::sun_shafts::SunShafts;
+use space_travel::uniforms::FrameUniforms;
+
+mod common;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 60;
+const SUN_COLOR: u32 = 0xFFC040;
+
+// The default scene's sun, moved to `position` with a radius of 2
+fn sun(position: Vec3) -> CelestialBody {
+    let mut scene = common::scene_from_config(&SceneConfig::default());
+    let mut sun = scene.bodies.remove(0);
+    sun.position = position;
+    sun.scale = 2.0;
+    sun
+}
+
+// A black sky with the sun's disc drawn at its depth, and black pixels `covered` by
+// something a unit in front of the camera
+fn frame_with_sun(uniforms: &FrameUniforms, sun: &CelestialBody, covered: impl Fn(usize, usize) -> bool) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.clear();
+    let center = project(uniforms, sun.position).expect("sun in front of the camera");
+    let radius = projected_radius(uniforms, &framebuffer, sun.position, sun.scale);
+    let occluder = project(uniforms, Vec3::new(0.0, 0.0, -1.0)).unwrap().z;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let index = y * WIDTH + x;
+            if covered(x, y) {
+                framebuffer.zbuffer[index] = occluder;
+            } else if (x as f32 + 0.5 - center.x).hypot(y as f32 + 0.5 - center.y) <= radius {
+                framebuffer.buffer[index] = SUN_COLOR;
+                framebuffer.zbuffer[index] = center.z;
+            }
+        }
+    }
+    framebuffer
+}
+
+fn brightness(framebuffer: &Framebuffer, x: usize, y: usize) -> u32 {
+    let color = framebuffer.pixel(x, y).unwrap();
+    (color >> 16 & 0xFF) + (color >> 8 & 0xFF) + (color & 0xFF)
+}
+
+#[test]
+fn light_streams_past_the_edge_of_an_occluder() {
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    let sun = sun(Vec3::new(0.0, 0.0, -20.0));
+    // Everything left of the middle column is covered
+    let covered = |x: usize, _: usize| x < WIDTH / 2;
+    let original = frame_with_sun(&uniforms, &sun, covered);
+    let mut framebuffer = frame_with_sun(&uniforms, &sun, covered);
+    SunShafts::new().apply(&mut framebuffer, &uniforms, &sun);
+
+    // Sky just outside the disc lights up on the uncovered side
+    let radius = projected_radius(&uniforms, &framebuffer, sun.position, sun.scale) as usize;
+    let (x, y) = (WIDTH / 2 + radius + 2, HEIGHT / 2);
+    assert_eq!(brightness(&original, x, y), 0);
+    assert!(brightness(&framebuffer, x, y) > 0, "no shaft at ({}, {})", x, y);
+    // and fades with the distance from the sun
+    assert!(brightness(&framebuffer, x, y) > brightness(&framebuffer, WIDTH - 1, y));
+    // The covered side only gets the light scattered in front of the occluder, less than
+    // the open sky at the same distance
+    assert!(brightness(&framebuffer, WIDTH - 1 - x, y) < brightness(&framebuffer, x, y));
+    assert_eq!(framebuffer.zbuffer, original.zbuffer, "depth is left alone");
+}
+
+#[test]
+fn nothing_is_added_when_the_sun_is_covered_off_screen_or_disabled() {
+    let uniforms = common::frame(WIDTH, HEIGHT);
+    let visible = sun(Vec3::new(0.0, 0.0, -20.0));
+
+    let covered = |_: usize, _: usize| true;
+    let mut framebuffer = frame_with_sun(&uniforms, &visible, covered);
+    SunShafts::new().apply(&mut framebuffer, &uniforms, &visible);
+    assert_eq!(framebuffer.buffer, frame_with_sun(&uniforms, &visible, covered).buffer, "fully covered");
+
+    let open = |_: usize, _: usize| false;
+    let mut disabled = SunShafts::new();
+    disabled.enabled = false;
+    let mut framebuffer = frame_with_sun(&uniforms, &visible, open);
+    disabled.apply(&mut framebuffer, &uniforms, &visible);
+    assert_eq!(framebuffer.buffer, frame_with_sun(&uniforms, &visible, open).buffer, "disabled");
+
+    // Behind the camera, and far to the side: the sky stays black
+    for position in [Vec3::new(0.0, 0.0, 20.0), Vec3::new(200.0, 0.0, -20.0)] {
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+        framebuffer.clear();
+        SunShafts::new().apply(&mut framebuffer, &uniforms, &sun(position));
+        assert!(framebuffer.buffer.iter().all(|&color| color == 0), "sun at {:?}", position);
+    }
+}
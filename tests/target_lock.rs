@@ -0,0 +1,102 @@
+use nalgebra_glm::Vec3;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::impostor::projected_radius;
+use space_travel::reticle::{draw_crosshair, Reticle};
+use space_travel::scene::{Scene, SceneConfig};
+
+mod common;
+
+const SIZE: usize = 200;
+const DT: f32 = 1.0 / 60.0;
+
+// The default scene with its first body moved to `position`
+fn scene(position: Vec3) -> Scene {
+    let mut scene = common::scene_from_config(&SceneConfig::default());
+    scene.bodies[0].position = position;
+    scene.bodies[0].scale = 1.0;
+    scene
+}
+
+// Columns the reticle drew in, leftmost and rightmost
+fn drawn_columns(reticle: &Reticle, scene: &Scene) -> Option<(usize, usize)> {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    reticle.draw(&mut framebuffer, &common::frame(SIZE, SIZE), &scene.bodies[0]);
+    let columns: Vec<usize> = (0..SIZE).filter(|&x| (0..SIZE).any(|y| framebuffer.pixel(x, y) != Some(0))).collect();
+    Some((*columns.first()?, *columns.last()?))
+}
+
+fn locked() -> Reticle {
+    let mut reticle = Reticle::new();
+    reticle.update(Some(0), DT);
+    reticle.update(Some(0), 1.0);
+    reticle
+}
+
+#[test]
+fn the_bracket_tracks_the_bodys_size_on_screen() {
+    let reticle = locked();
+    assert_eq!(reticle.lock(), 1.0);
+    let mut widths = vec![];
+    for distance in [20.0, 8.0, 4.0] {
+        let scene = scene(Vec3::new(0.0, 0.0, -distance));
+        let (left, right) = drawn_columns(&reticle, &scene).expect("bracket drawn");
+        let framebuffer = Framebuffer::new(SIZE, SIZE);
+        let disc = projected_radius(&common::frame(SIZE, SIZE), &framebuffer, scene.bodies[0].position, 1.0);
+        let half = reticle.bracket_half_size(disc);
+        assert!(half > disc, "outside the disc");
+        assert!(((right - left) as f32 - 2.0 * half).abs() <= 2.0, "distance {}: {}..{} for {}", distance, left, right, half);
+        widths.push(right - left);
+    }
+    assert!(widths[0] < widths[1] && widths[1] < widths[2], "{:?}", widths);
+}
+
+#[test]
+fn a_new_target_locks_on_by_shrinking_the_bracket() {
+    let scene = scene(Vec3::new(0.0, 0.0, -6.0));
+    let mut reticle = locked();
+    let settled = drawn_columns(&reticle, &scene).unwrap();
+
+    reticle.update(Some(1), DT);
+    assert_eq!(reticle.lock(), 0.0);
+    let mut previous = drawn_columns(&reticle, &scene).unwrap();
+    assert!(previous.1 - previous.0 > settled.1 - settled.0);
+    for _ in 0..30 {
+        reticle.update(Some(1), DT);
+        let now = drawn_columns(&reticle, &scene).unwrap();
+        assert!(now.1 - now.0 <= previous.1 - previous.0, "{:?} after {:?}", now, previous);
+        previous = now;
+    }
+    assert_eq!(previous, settled);
+    // Keeping the same target doesn't start it again
+    reticle.update(Some(1), DT);
+    assert_eq!(reticle.lock(), 1.0);
+}
+
+#[test]
+fn no_bracket_for_targets_behind_the_camera() {
+    let reticle = locked();
+    let scene = scene(Vec3::new(0.0, 0.0, 6.0));
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    reticle.draw(&mut framebuffer, &common::frame(SIZE, SIZE), &scene.bodies[0]);
+    // Only the arrow at the bottom edge, pointing the way to turn
+    for y in 0..SIZE * 3 / 4 {
+        for x in 0..SIZE {
+            assert_eq!(framebuffer.pixel(x, y), Some(0), "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn the_crosshair_sits_in_the_middle() {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    draw_crosshair(&mut framebuffer);
+    let center = SIZE / 2;
+    assert_ne!(framebuffer.pixel(center + 6, center), Some(0));
+    assert_ne!(framebuffer.pixel(center, center - 6), Some(0));
+    // With a gap around the dot, and nothing far from it
+    assert_eq!(framebuffer.pixel(center + 2, center + 2), Some(0));
+    assert_eq!(framebuffer.pixel(center + 20, center), Some(0));
+}
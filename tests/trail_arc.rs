@@ -0,0 +1,60 @@
+use space_travel::planet::PlanetType;
+use space_travel::scene::{Scene, SceneConfig, DEFAULT_TRAIL_ARC, TRAIL_SPAWN_INTERVAL};
+
+mod common;
+
+// The fraction of its orbit body `i`'s trail covers once it's full
+fn arc(scene: &Scene, i: usize) -> f32 {
+    let period = scene.orbital_period(i).expect("an orbiting body");
+    scene.bodies[i].trail.retention() as f32 * TRAIL_SPAWN_INTERVAL / period
+}
+
+#[test]
+fn every_trail_covers_the_same_share_of_its_orbit() {
+    let config = SceneConfig { particle_budget: usize::MAX, ..SceneConfig::default() };
+    assert_eq!(config.trail_arc, DEFAULT_TRAIL_ARC);
+    let scene = common::scene_from_config(&config);
+    for i in 0..scene.bodies.len() {
+        if scene.bodies[i].shader_type == PlanetType::Sun {
+            assert_eq!(scene.bodies[i].trail.retention(), 0, "the sun doesn't move");
+            continue;
+        }
+        assert!((arc(&scene, i) - DEFAULT_TRAIL_ARC).abs() < 0.01, "{}: {}", scene.bodies[i].name, arc(&scene, i));
+    }
+    // Wider orbits take longer, so they need more particles for the same arc
+    let lengths: Vec<usize> = scene.bodies[1..8].iter().map(|body| body.trail.retention()).collect();
+    assert!(lengths.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", lengths);
+
+    // One setting changes them all alike
+    let half = common::scene_from_config(&SceneConfig { trail_arc: DEFAULT_TRAIL_ARC / 2.0, ..config });
+    for (body, halved) in scene.bodies.iter().zip(&half.bodies) {
+        assert!(halved.trail.retention().abs_diff(body.trail.retention() / 2) <= 1, "{}", body.name);
+    }
+}
+
+#[test]
+fn a_full_trail_spans_the_arc_it_was_sized_for() {
+    let mut scene = common::scene_from("trail_arc = 0.25\n[[body]]\ntype = \"sun\"\n[[body]]\ntype = \"earth\"\norbit_radius = 20\n");
+    let period = scene.orbital_period(1).unwrap();
+    for _ in 0..(period / TRAIL_SPAWN_INTERVAL) as usize {
+        scene.update(TRAIL_SPAWN_INTERVAL);
+    }
+    // From the oldest particle to the newest the earth turned a quarter of the way around
+    let particles = &scene.bodies[1].trail.particles;
+    let (oldest, newest) = (particles.first().unwrap().position, particles.last().unwrap().position);
+    let angle = oldest.normalize().dot(&newest.normalize()).acos();
+    assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 0.01, "{}", angle);
+}
+
+#[test]
+fn a_body_can_still_set_its_own_length() {
+    let source = "trail_arc = 0.5\n[[body]]\ntype = \"sun\"\n\
+        [[body]]\ntype = \"earth\"\norbit_radius = 20\ntrail = 40\n\
+        [[body]]\ntype = \"rocky\"\norbit_radius = 30\n";
+    let config = SceneConfig::parse(source).expect("scene");
+    assert_eq!(config.trail_length(&config.bodies[1]), 40);
+    assert!(config.trail_length(&config.bodies[2]) > 40);
+
+    let error = SceneConfig::parse("trail_arc = -1\n[[body]]\ntype = \"sun\"\n").expect_err("negative arc");
+    assert!(error.contains("trail_arc"), "{}", error);
+}
@@ -0,0 +1,62 @@
+use nalgebra_glm::Vec3;
+use space_travel::planet::PlanetType;
+use space_travel::scene::{Scene, SceneConfig, TrailSpace};
+
+mod common;
+
+const EPSILON: f32 = 1e-3;
+
+// Where each of body `i`'s particles is drawn this frame
+fn drawn_particles(scene: &Scene, i: usize) -> Vec<Vec3> {
+    let origin = scene.trail_origin(i);
+    scene.bodies[i].trail.particles.iter().map(|particle| origin + particle.position).collect()
+}
+
+const SYSTEM: &str = "moon_orbit_radius = 2\ntrail_arc = 1\n\
+    [[body]]\ntype = \"sun\"\n\
+    [[body]]\ntype = \"earth\"\norbit_radius = 20\n\
+    [[body]]\ntype = \"moon\"\n";
+
+#[test]
+fn a_parent_relative_trail_circles_the_parent_where_it_is_now() {
+    let mut scene = common::scene_from(SYSTEM);
+    let moon = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Moon).unwrap();
+    assert_eq!(scene.bodies[moon].trail.space, TrailSpace::Parent, "the moon's default");
+    for _ in 0..100 {
+        scene.update(1.0 / 60.0);
+    }
+
+    // Every particle sits on the moon's orbit around the earth's current position, even
+    // though the earth has moved on since most of them were left
+    let earth = scene.bodies[1].position;
+    assert_eq!(scene.trail_origin(moon), earth);
+    let particles = drawn_particles(&scene, moon);
+    assert!(particles.len() > 50);
+    for particle in &particles {
+        assert!(((particle - earth).magnitude() - 2.0).abs() < EPSILON, "{:?} off the circle", particle);
+    }
+    // The newest one is where the moon is
+    assert!((particles.last().unwrap() - scene.bodies[moon].position).magnitude() < EPSILON);
+}
+
+#[test]
+fn world_space_trails_stay_where_they_were_left() {
+    let source = SYSTEM.replace("type = \"moon\"\n", "type = \"moon\"\ntrail_space = \"world\"\n");
+    let mut scene = common::scene_from(&source);
+    let moon = scene.bodies.iter().position(|body| body.shader_type == PlanetType::Moon).unwrap();
+    assert_eq!(scene.bodies[moon].trail.space, TrailSpace::World);
+    assert_eq!(scene.bodies[1].trail.space, TrailSpace::World, "planets default to world space");
+    for _ in 0..100 {
+        scene.update(1.0 / 60.0);
+    }
+    let earth = scene.bodies[1].position;
+    let farthest = drawn_particles(&scene, moon).iter().map(|particle| (particle - earth).magnitude()).fold(0.0, f32::max);
+    assert!(farthest > 2.5, "the oldest particles were left behind along the earth's orbit");
+    // The earth's own trail lies on its orbit around the sun
+    for particle in drawn_particles(&scene, 1) {
+        assert!((particle.magnitude() - 20.0).abs() < EPSILON);
+    }
+
+    let error = SceneConfig::parse("[[body]]\ntype = \"sun\"\ntrail_space = \"local\"\n").expect_err("bad space");
+    assert!(error.contains("trail_space"), "{}", error);
+}
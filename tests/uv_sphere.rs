@@ -0,0 +1,52 @@
+use nalgebra_glm::Vec3;
+use space_travel::mesh::{uv_sphere, uv_sphere_indexed};
+
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn vertex_and_triangle_counts() {
+    for (stacks, slices) in [(2, 3), (16, 32), (7, 10)] {
+        let mesh = uv_sphere_indexed(stacks, slices);
+        // Rings between the poles with the wrap column twice, plus one pole vertex per slice
+        let expected = (stacks - 1) * (slices + 1) + 2 * slices;
+        assert_eq!(mesh.vertices.len(), expected as usize, "{}x{}", stacks, slices);
+        assert_eq!(mesh.triangle_count(), (2 * slices * (stacks - 1)) as usize);
+        assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+        assert_eq!(uv_sphere(stacks, slices).len(), mesh.indices.len());
+    }
+    // The default tessellation draws as many triangles as smooth_sphere.obj
+    assert_eq!(uv_sphere_indexed(16, 32).triangle_count(), 960);
+}
+
+#[test]
+fn vertices_lie_on_the_unit_sphere_with_outward_unit_normals() {
+    for vertex in &uv_sphere_indexed(16, 32).vertices {
+        assert!((vertex.position.magnitude() - 1.0).abs() < EPSILON, "{:?}", vertex.position);
+        assert!((vertex.normal.magnitude() - 1.0).abs() < EPSILON, "{:?}", vertex.normal);
+        assert!((vertex.normal - vertex.position).magnitude() < EPSILON);
+    }
+}
+
+#[test]
+fn no_degenerate_triangles_and_outward_winding() {
+    let vertices = uv_sphere(16, 32);
+    for triangle in vertices.chunks(3) {
+        let (a, b, c) = (triangle[0].position, triangle[1].position, triangle[2].position);
+        let normal: Vec3 = (b - a).cross(&(c - a));
+        assert!(normal.magnitude() > EPSILON, "zero-area triangle at {:?}", a);
+        // Counter-clockwise from outside: the face normal points away from the center
+        assert!(normal.dot(&(a + b + c)) > 0.0);
+    }
+}
+
+#[test]
+fn no_triangle_wraps_across_the_texture() {
+    // Every triangle spans one slice of u, so none stretches back over the seam
+    let slices = 32;
+    for triangle in uv_sphere(16, slices).chunks(3) {
+        let us: Vec<f32> = triangle.iter().map(|v| v.tex_coords.x).collect();
+        let span = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(span <= 1.0 / slices as f32 + EPSILON, "u spans {}", span);
+        assert!(us.iter().all(|u| (0.0..=1.0).contains(u)));
+    }
+}
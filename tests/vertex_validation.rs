@@ -0,0 +1,59 @@
+use nalgebra_glm::{Vec2, Vec3};
+use space_travel::obj::{LoadOptions, Obj, ObjError};
+use space_travel::vertex::{Vertex, VertexError};
+
+const UP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+fn parse(source: &str) -> Result<Obj, ObjError> {
+    Obj::parse(source, "degenerate.obj", &LoadOptions { flip_v: false })
+}
+
+#[test]
+fn normals_come_out_unit_length() {
+    let vertex = Vertex::validated(Vec3::zeros(), Vec3::new(0.0, 0.0, 3.0), Vec2::zeros()).unwrap();
+    assert_eq!(vertex.normal, Vec3::new(0.0, 0.0, 1.0));
+    // Without a direction to keep, the normal a corner without one gets
+    for degenerate in [Vec3::zeros(), Vec3::new(f32::NAN, 0.0, 1.0), Vec3::new(f32::INFINITY, 0.0, 0.0)] {
+        let vertex = Vertex::validated(Vec3::zeros(), degenerate, Vec2::zeros()).unwrap();
+        assert_eq!(vertex.normal, UP, "{:?}", degenerate);
+    }
+}
+
+#[test]
+fn uvs_are_made_finite_and_v_stays_on_the_texture() {
+    let uv = |tex_coords: Vec2| Vertex::validated(Vec3::zeros(), UP, tex_coords).unwrap().tex_coords;
+    assert_eq!(uv(Vec2::new(f32::NAN, f32::NEG_INFINITY)), Vec2::zeros());
+    assert_eq!(uv(Vec2::new(0.25, 1.5)), Vec2::new(0.25, 1.0));
+    assert_eq!(uv(Vec2::new(0.25, -0.5)), Vec2::new(0.25, 0.0));
+    // u wraps when sampled, so a triangle across the seam keeps its 0.9 to 1.1
+    assert_eq!(uv(Vec2::new(1.1, 0.5)), Vec2::new(1.1, 0.5));
+}
+
+#[test]
+fn positions_that_are_not_finite_are_errors() {
+    for position in [Vec3::new(f32::NAN, 0.0, 0.0), Vec3::new(0.0, f32::INFINITY, 0.0)] {
+        let error = Vertex::validated(position, UP, Vec2::zeros()).expect_err("bad position");
+        assert!(matches!(error, VertexError::Position(_)));
+    }
+}
+
+#[test]
+fn obj_files_go_through_the_same_checks() {
+    let obj = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.5 2\nvn 0 0 0\nvn 0 0 5\nf 1/1/1 2/1/2 3//2\n").expect("fixable");
+    let vertices = obj.get_vertex_array();
+    assert_eq!(vertices[0].normal, UP);
+    assert_eq!(vertices[1].normal, Vec3::new(0.0, 0.0, 1.0));
+    assert_eq!(vertices[0].tex_coords, Vec2::new(0.5, 1.0));
+
+    // A NaN position is reported at the line it was read from
+    match parse("v 0 0 0\nv nan 0 0\nv 0 1 0\nf 1 2 3\n") {
+        Err(ObjError::Parse(_, line, message)) => {
+            assert_eq!(line, 2);
+            assert!(message.contains("posición"), "{}", message);
+        }
+        Err(other) => panic!("wrong error: {}", other),
+        Ok(_) => panic!("a NaN position loaded"),
+    }
+    // Unless no face uses it
+    assert!(parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nv inf 0 0\nf 1 2 3\n").is_ok());
+}